@@ -0,0 +1,36 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_engine::{blame, SpecChunkEvent};
+use std::io::{stdout, Write};
+use std::process;
+
+pub const SUBCOMMAND_NAME: &'static str = "blame";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Lists the events that touched a path, request, response, shape or field, with the batch commits and authors that made them")
+    .arg(
+      Arg::with_name("entity-id")
+        .value_name("ENTITY_ID")
+        .required(true)
+        .help("The id of the path, request, response, shape or field"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let entity_id = command_matches
+    .value_of("entity-id")
+    .expect("entity-id is required");
+
+  let events = events_from_chunks(spec_chunks).await;
+  let blame = blame(events, entity_id);
+
+  let mut stdout = stdout();
+  let written = serde_json::to_writer_pretty(&mut stdout, &blame)
+    .map_err(|err| err.to_string())
+    .and_then(|_| writeln!(stdout).map_err(|err| err.to_string()));
+  if let Err(err) = written {
+    eprintln!("Could not write blame: {}", err);
+    process::exit(1);
+  }
+}
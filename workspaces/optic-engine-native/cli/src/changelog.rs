@@ -0,0 +1,65 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_engine::{changelog, SpecChunkEvent};
+use std::io::{stdout, Write};
+use std::process;
+
+pub const SUBCOMMAND_NAME: &'static str = "changelog";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Lists the changes to endpoints and their bodies made by each batch commit")
+    .arg(
+      Arg::with_name("from")
+        .long("from")
+        .value_name("BATCH_ID")
+        .takes_value(true)
+        .help("List the batch commits after this one, instead of all since the root"),
+    )
+    .arg(
+      Arg::with_name("to")
+        .long("to")
+        .value_name("BATCH_ID")
+        .takes_value(true)
+        .help("List the batch commits up to and including this one, instead of the latest"),
+    )
+    .arg(
+      Arg::with_name("format")
+        .long("format")
+        .required(false)
+        .value_name("FORMAT")
+        .takes_value(true)
+        .possible_values(&["markdown", "json"])
+        .default_value("markdown")
+        .help("The format of the changelog"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let from_batch_id = command_matches.value_of("from").map(String::from);
+  let to_batch_id = command_matches.value_of("to").map(String::from);
+  let format = command_matches
+    .value_of("format")
+    .expect("format has a default value");
+
+  let events = events_from_chunks(spec_chunks).await;
+  let changelog = match changelog(events, from_batch_id.as_ref(), to_batch_id.as_ref()) {
+    Some(changelog) => changelog,
+    None => {
+      eprintln!("Could not find the batch commits in the specification");
+      process::exit(1);
+    }
+  };
+
+  let mut stdout = stdout();
+  let written = match format {
+    "json" => serde_json::to_writer_pretty(&mut stdout, &changelog)
+      .map_err(|err| err.to_string())
+      .and_then(|_| writeln!(stdout).map_err(|err| err.to_string())),
+    _ => write!(stdout, "{}", changelog.to_markdown()).map_err(|err| err.to_string()),
+  };
+  if let Err(err) = written {
+    eprintln!("Could not write changelog: {}", err);
+    process::exit(1);
+  }
+}
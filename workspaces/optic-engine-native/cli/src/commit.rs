@@ -5,6 +5,7 @@ use futures::StreamExt;
 use optic_engine::append_batch_to_spec;
 use optic_engine::streams;
 use optic_engine::CommandContext;
+use optic_engine::{EndpointQueries, ShapeQueries};
 use optic_engine::{SpecChunkEvent, SpecEvent};
 use optic_engine::{SpecCommand, SpecProjection};
 use std::path::Path;
@@ -32,6 +33,13 @@ pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
         .takes_value(false)
         .help("Append new batch commit to the root spec file, instead of a new spec change file"),
     )
+    .arg(
+      Arg::with_name("remove-unreachable-shapes")
+        .long("remove-unreachable-shapes")
+        .required(false)
+        .takes_value(false)
+        .help("Remove shapes no longer used by any endpoint, after applying the commands received"),
+    )
     .arg(
       Arg::with_name("client-session-id")
         .long("client-session-id")
@@ -62,16 +70,12 @@ pub async fn main<'a>(
     .expect("commit-message is required");
 
   let append_to_root = command_matches.is_present("append-to-root");
-
-  if append_to_root
-    && !spec_chunks
-      .iter()
-      .all(|chunk| matches!(chunk, SpecChunkEvent::Root(_)))
-  {
-    eprintln!("Commits cannot be appended to the root when non-root chunks exist");
-    process::exit(1);
+  if append_to_root {
+    exit_unless_root_chunks_only(&spec_chunks);
   }
 
+  let remove_unreachable_shapes = command_matches.is_present("remove-unreachable-shapes");
+
   let client_session_id = command_matches
     .value_of("client-session-id")
     .expect("client-session-id is required");
@@ -85,6 +89,7 @@ pub async fn main<'a>(
     &spec_path,
     commit_message,
     append_to_root,
+    remove_unreachable_shapes,
     client_id,
     client_session_id,
   )
@@ -96,6 +101,7 @@ async fn commit(
   spec_dir_path: impl AsRef<Path>,
   commit_message: &str,
   append_to_root: bool,
+  remove_unreachable_shapes: bool,
   client_id: &str,
   client_session_id: &str,
 ) {
@@ -127,8 +133,49 @@ async fn commit(
       .expect("command could not be applied");
   }
 
-  let mut new_events = batch.commit();
+  if remove_unreachable_shapes {
+    let spec_projection = batch.spec_projection();
+    let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+    let remove_commands = ShapeQueries::new(spec_projection.shape())
+      .remove_unreachable_shapes_commands(&endpoint_queries)
+      .collect::<Vec<_>>();
+
+    for command in remove_commands {
+      batch
+        .with_command(SpecCommand::from(command))
+        .expect("unreachable shape could not be removed");
+    }
+  }
 
+  write_batch(
+    spec_events,
+    batch.commit(),
+    batch_id,
+    spec_dir_path,
+    append_to_root,
+  )
+  .await;
+}
+
+pub fn exit_unless_root_chunks_only(spec_chunks: &[SpecChunkEvent]) {
+  if !spec_chunks
+    .iter()
+    .all(|chunk| matches!(chunk, SpecChunkEvent::Root(_)))
+  {
+    eprintln!("Commits cannot be appended to the root when non-root chunks exist");
+    process::exit(1);
+  }
+}
+
+/// Writes the events of a new batch commit to the spec dir, as a new spec change file or appended
+/// to the root spec file, and echoes them to stdout.
+pub async fn write_batch(
+  spec_events: Vec<SpecEvent>,
+  mut new_events: Vec<SpecEvent>,
+  batch_id: String,
+  spec_dir_path: impl AsRef<Path>,
+  append_to_root: bool,
+) {
   let spec_chunk_event = if append_to_root {
     let mut all_events = spec_events;
     all_events.append(&mut new_events);
@@ -0,0 +1,77 @@
+use super::{events_at_commit, events_from_chunks, spec_chunks_from_path, SpecPathType};
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
+use optic_engine::streams;
+use optic_engine::{compare_specs, SpecChunkEvent, SpecProjection};
+use std::process;
+use tokio::io::stdout;
+
+pub const SUBCOMMAND_NAME: &'static str = "compare";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Reports changes to endpoints and shapes since a base version of the API spec, and whether they break clients")
+    .arg(
+      Arg::with_name("base-spec")
+        .long("base-spec")
+        .value_name("BASE_SPEC_PATH")
+        .takes_value(true)
+        .help("The path to the base version of the specification, of the same type as SPEC_PATH"),
+    )
+    .arg(
+      Arg::with_name("base-commit")
+        .long("base-commit")
+        .value_name("BATCH_ID")
+        .takes_value(true)
+        .help("The batch commit of the base version of the specification"),
+    )
+    .group(
+      ArgGroup::with_name("base")
+        .args(&["base-spec", "base-commit"])
+        .multiple(true)
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("fail-on-breaking")
+        .long("fail-on-breaking")
+        .takes_value(false)
+        .help("Exit with a non-zero status when any of the changes are breaking"),
+    )
+}
+
+pub async fn main<'a>(
+  command_matches: &'a ArgMatches<'a>,
+  spec_chunks: Vec<SpecChunkEvent>,
+  spec_path_type: &SpecPathType,
+) {
+  let head_events = events_from_chunks(spec_chunks).await;
+  let base_events = match command_matches.value_of("base-spec") {
+    Some(base_spec_path) => {
+      events_from_chunks(spec_chunks_from_path(base_spec_path, spec_path_type).await).await
+    }
+    None => head_events.clone(),
+  };
+
+  let base_events = match command_matches.value_of("base-commit") {
+    Some(batch_id) => events_at_commit(base_events, batch_id),
+    None => base_events,
+  };
+
+  let comparison = compare_specs(
+    &SpecProjection::from(base_events),
+    &SpecProjection::from(head_events),
+  );
+  let breaking_count = comparison.breaking_changes().count();
+  eprintln!(
+    "{} changes, of which {} breaking",
+    comparison.changes.len(),
+    breaking_count
+  );
+
+  streams::write_to_json_lines(stdout(), comparison.changes.iter())
+    .await
+    .expect("could not write changes to stdout");
+
+  if breaking_count > 0 && command_matches.is_present("fail-on-breaking") {
+    process::exit(1);
+  }
+}
@@ -0,0 +1,53 @@
+use super::events_from_chunks;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use optic_engine::{export_openapi, SpecChunkEvent, SpecProjection};
+use std::io::{stdout, Write};
+use std::process;
+
+pub const SUBCOMMAND_NAME: &'static str = "export";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Exports the API spec to other formats")
+    .setting(AppSettings::SubcommandRequiredElseHelp)
+    .subcommand(
+      SubCommand::with_name("openapi")
+        .about("Exports the API spec as an OpenAPI 3 document")
+        .arg(
+          Arg::with_name("format")
+            .long("format")
+            .required(false)
+            .value_name("FORMAT")
+            .takes_value(true)
+            .possible_values(&["yaml", "json"])
+            .default_value("yaml")
+            .help("The format of the OpenAPI document"),
+        ),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  match command_matches.subcommand() {
+    ("openapi", Some(openapi_matches)) => {
+      let format = openapi_matches
+        .value_of("format")
+        .expect("format has a default value");
+
+      let spec_projection = SpecProjection::from(events_from_chunks(spec_chunks).await);
+      let openapi = export_openapi(&spec_projection);
+
+      let mut stdout = stdout();
+      let written = match format {
+        "json" => {
+          serde_json::to_writer_pretty(&mut stdout, &openapi).map_err(|err| err.to_string())
+        }
+        _ => serde_yaml::to_writer(&mut stdout, &openapi).map_err(|err| err.to_string()),
+      };
+      if let Err(err) = written.and_then(|_| writeln!(stdout).map_err(|err| err.to_string())) {
+        eprintln!("Could not write OpenAPI document: {}", err);
+        process::exit(1);
+      }
+    }
+    _ => unreachable!("export requires a subcommand"),
+  }
+}
@@ -0,0 +1,66 @@
+use super::events_from_chunks;
+use super::learn::IdGenerator;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use optic_engine::openapi::OpenApi;
+use optic_engine::streams;
+use optic_engine::{import_openapi, SpecChunkEvent, SpecProjection};
+use std::convert::TryFrom;
+use std::process;
+use tokio::io::stdout;
+
+pub const SUBCOMMAND_NAME: &'static str = "import";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about(
+      "Generates spec commands from documents in other formats, for review before committing them",
+    )
+    .setting(AppSettings::SubcommandRequiredElseHelp)
+    .subcommand(
+      SubCommand::with_name("openapi")
+        .about("Generates spec commands adding the endpoints and schemas of an OpenAPI 3 document")
+        .arg(
+          Arg::with_name("document")
+            .required(true)
+            .value_name("DOCUMENT")
+            .help("Path to the OpenAPI document, as YAML or JSON"),
+        ),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  match command_matches.subcommand() {
+    ("openapi", Some(openapi_matches)) => {
+      let document_path = openapi_matches
+        .value_of("document")
+        .expect("document is required");
+
+      // YAML is a superset of JSON, so both can be read as YAML
+      let document = std::fs::read_to_string(document_path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| {
+          serde_yaml::from_str::<serde_json::Value>(&contents).map_err(|err| err.to_string())
+        })
+        .and_then(|value| OpenApi::try_from(value).map_err(|err| err.to_string()));
+      let document = match document {
+        Ok(document) => document,
+        Err(err) => {
+          eprintln!("Could not read OpenAPI document: {}", err);
+          process::exit(1);
+        }
+      };
+
+      let spec_projection = SpecProjection::from(events_from_chunks(spec_chunks).await);
+      let mut id_generator = IdGenerator::default();
+      let import = import_openapi(&spec_projection, &document, &mut id_generator);
+      for unsupported in &import.unsupported {
+        eprintln!("not imported: {}", unsupported);
+      }
+
+      streams::write_to_json_lines(stdout(), import.commands.iter())
+        .await
+        .expect("could not write imported commands to stdout");
+    }
+    _ => unreachable!("import requires a subcommand"),
+  }
+}
@@ -1,4 +1,4 @@
-use super::events_from_chunks;
+use super::{config_arg, config_from_file, events_from_chunks};
 
 use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
 use futures::{try_join, SinkExt, Stream, StreamExt, TryStreamExt};
@@ -12,9 +12,9 @@ use tokio_stream::wrappers::ReceiverStream;
 use optic_engine::streams;
 use optic_engine::Aggregate;
 use optic_engine::{
-  analyze_documented_bodies, analyze_undocumented_bodies, AnalyzeUndocumentedBodiesConfig,
-  InteractionDiffResult, LearnedShapeDiffAffordancesProjection,
-  LearnedUndocumentedBodiesProjection,
+  analyze_documented_bodies, analyze_undocumented_bodies, analyze_undocumented_headers,
+  AnalyzeUndocumentedBodiesConfig, InteractionDiffResult, LearnedShapeDiffAffordancesProjection,
+  LearnedUndocumentedBodiesProjection, LearnedUndocumentedHeadersProjection, ShapeQueries,
 };
 use optic_engine::{
   HttpInteraction, SpecChunkEvent, SpecEvent, SpecIdGenerator, SpecProjection, TaggedInput,
@@ -31,6 +31,12 @@ pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
         .takes_value(false)
         .help("Learn shapes of undocumented bodies from interactions piped to stdin"),
     )
+    .arg(
+      Arg::with_name("undocumented-headers")
+        .long("undocumented-headers")
+        .takes_value(false)
+        .help("Learn undocumented request and response headers from interactions piped to stdin"),
+    )
     .arg(
       Arg::with_name("shape-diffs-affordances")
         .long("shape-diffs-affordances")
@@ -44,9 +50,14 @@ pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
         .takes_value(true)
         .help("Path to file containing diff results for which to learn affordances"),
     )
+    .arg(config_arg())
     .group(
       ArgGroup::with_name("subject")
-        .args(&["undocumented-bodies", "shape-diffs-affordances"])
+        .args(&[
+          "undocumented-bodies",
+          "undocumented-headers",
+          "shape-diffs-affordances",
+        ])
         .multiple(false)
         .required(true),
     )
@@ -64,7 +75,10 @@ pub async fn main<'a>(
     let interaction_lines = streams::http_interaction::json_lines(stdin);
     let sink = stdout();
 
-    let learner_config = AnalyzeUndocumentedBodiesConfig::default();
+    let learner_config: AnalyzeUndocumentedBodiesConfig = match command_matches.value_of("config") {
+      Some(config_path) => config_from_file(config_path).await,
+      None => AnalyzeUndocumentedBodiesConfig::default(),
+    };
 
     learn_undocumented_bodies(
       spec_events,
@@ -74,6 +88,24 @@ pub async fn main<'a>(
       sink,
     )
     .await;
+  } else if command_matches.is_present("undocumented-headers") {
+    let stdin = stdin();
+    let interaction_lines = streams::http_interaction::json_lines(stdin);
+    let sink = stdout();
+
+    let learner_config: AnalyzeUndocumentedBodiesConfig = match command_matches.value_of("config") {
+      Some(config_path) => config_from_file(config_path).await,
+      None => AnalyzeUndocumentedBodiesConfig::default(),
+    };
+
+    learn_undocumented_headers(
+      spec_events,
+      input_queue_size,
+      interaction_lines,
+      learner_config,
+      sink,
+    )
+    .await;
   } else if command_matches.is_present("shape-diffs-affordances") {
     let diffs_path = command_matches
       .value_of("tagged-diff-results")
@@ -89,11 +121,17 @@ pub async fn main<'a>(
 
     let sink = stdout();
 
+    let learner_config: AnalyzeUndocumentedBodiesConfig = match command_matches.value_of("config") {
+      Some(config_path) => config_from_file(config_path).await,
+      None => AnalyzeUndocumentedBodiesConfig::default(),
+    };
+
     learn_shape_diff_affordances(
       spec_events,
       diffs,
       input_queue_size,
       interaction_lines,
+      learner_config,
       sink,
     )
     .await;
@@ -110,6 +148,8 @@ async fn learn_undocumented_bodies<S: 'static + AsyncWrite + Unpin + Send>(
   sink: S,
 ) {
   let spec_projection = Arc::new(SpecProjection::from(spec_events));
+  let reusable_shapes = ShapeQueries::new(spec_projection.shape()).named_shape_structures();
+  let mut learned_undocumented_bodies = LearnedUndocumentedBodiesProjection::new(&learner_config);
   let learner_config = Arc::new(learner_config);
 
   let (analysis_sender, analysis_receiver) = mpsc::channel(32);
@@ -160,14 +200,12 @@ async fn learn_undocumented_bodies<S: 'static + AsyncWrite + Unpin + Send>(
     let mut analysiss = ReceiverStream::new(analysis_receiver);
     let mut id_generator = IdGenerator::default();
 
-    let mut learned_undocumented_bodies = LearnedUndocumentedBodiesProjection::default();
-
     while let Some(analysis) = analysiss.next().await {
       learned_undocumented_bodies.apply(analysis);
     }
 
     let endpoint_bodies = learned_undocumented_bodies
-      .into_endpoint_bodies(&mut id_generator)
+      .into_endpoint_bodies(&mut id_generator, &reusable_shapes)
       .collect::<Vec<_>>();
 
     streams::write_to_json_lines(sink, endpoint_bodies.iter())
@@ -178,15 +216,94 @@ async fn learn_undocumented_bodies<S: 'static + AsyncWrite + Unpin + Send>(
   try_join!(analyzing_bodies, aggregating_results).expect("essential worker task panicked");
 }
 
+async fn learn_undocumented_headers<S: 'static + AsyncWrite + Unpin + Send>(
+  spec_events: Vec<SpecEvent>,
+  input_queue_size: usize,
+  interaction_lines: impl Stream<Item = Result<String, std::io::Error>>,
+  learner_config: AnalyzeUndocumentedBodiesConfig,
+  sink: S,
+) {
+  let spec_projection = Arc::new(SpecProjection::from(spec_events));
+  let learner_config = Arc::new(learner_config);
+
+  let (analysis_sender, analysis_receiver) = mpsc::channel(32);
+
+  let analyzing_headers = async move {
+    let analyze_results = interaction_lines
+      .map(Ok)
+      .try_for_each_concurrent(input_queue_size, |interaction_json_result| {
+        let projection = spec_projection.clone();
+        let analysis_sender = analysis_sender.clone();
+        let learner_config = learner_config.clone();
+
+        let analyze_task = tokio::spawn(async move {
+          let analyze_comp = tokio::task::spawn_blocking(move || {
+            let interaction_json =
+              interaction_json_result.expect("can read interaction json line form stdin");
+
+            let interaction: HttpInteraction =
+              serde_json::from_str(&interaction_json).expect("could not parse interaction json");
+
+            analyze_undocumented_headers(&projection, interaction, &learner_config.filters)
+          });
+
+          match analyze_comp.await {
+            Ok(results) => {
+              for result in results {
+                analysis_sender
+                  .send(result)
+                  .await
+                  .expect("could not send analysis result to aggregation channel")
+              }
+            }
+            Err(err) => {
+              // ignore a single interaction not being able to deserialize
+              eprintln!("interaction ignored: {}", err);
+            }
+          }
+        });
+
+        analyze_task
+      })
+      .await;
+
+    analyze_results
+  };
+
+  let aggregating_results = tokio::spawn(async move {
+    let mut analyses = ReceiverStream::new(analysis_receiver);
+    let mut id_generator = IdGenerator::default();
+
+    let mut learned_undocumented_headers = LearnedUndocumentedHeadersProjection::default();
+
+    while let Some(analysis) = analyses.next().await {
+      learned_undocumented_headers.apply(analysis);
+    }
+
+    let endpoint_headers = learned_undocumented_headers
+      .into_endpoint_headers(&mut id_generator)
+      .collect::<Vec<_>>();
+
+    streams::write_to_json_lines(sink, endpoint_headers.iter())
+      .await
+      .expect("could not write endpoint headers to stdout");
+  });
+
+  try_join!(analyzing_headers, aggregating_results).expect("essential worker task panicked");
+}
+
 async fn learn_shape_diff_affordances<S: 'static + AsyncWrite + Unpin + Send>(
   spec_events: Vec<SpecEvent>,
   diffs: impl Iterator<Item = InteractionDiffResult>,
   input_queue_size: usize,
   interaction_lines: impl Stream<Item = Result<String, std::io::Error>>,
+  learner_config: AnalyzeUndocumentedBodiesConfig,
   sink: S,
 ) {
   let spec_projection = Arc::new(SpecProjection::from(spec_events));
-  let mut learned_shape_diff_affordances: LearnedShapeDiffAffordancesProjection = diffs.collect();
+  let mut learned_shape_diff_affordances = diffs
+    .collect::<LearnedShapeDiffAffordancesProjection>()
+    .with_enum_config(learner_config.enums);
 
   let (analysis_sender, analysis_receiver) = mpsc::channel(32);
 
@@ -261,7 +378,7 @@ async fn learn_shape_diff_affordances<S: 'static + AsyncWrite + Unpin + Send>(
 }
 
 #[derive(Debug, Default)]
-struct IdGenerator;
+pub(crate) struct IdGenerator;
 
 impl SpecIdGenerator for IdGenerator {
   fn generate_id(&mut self, prefix: &str) -> String {
@@ -298,6 +415,48 @@ mod test {
     learn_undocumented_bodies(spec_events, 1, interaction_lines, learner_config, sink).await;
   }
 
+  #[tokio::main]
+  #[test]
+  async fn can_learn_endpoint_headers_from_interactions() {
+    let spec_events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_id_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_id_1","pathId":"path_id_1","httpMethod":"GET"}}
+    ]))
+    .expect("initial spec events should be valid events");
+
+    let interaction = json!({
+      "uuid": "1",
+      "request": {
+        "host": "localhost",
+        "method": "GET",
+        "path": "/todos",
+        "query": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+        "headers": { "asJsonString": "{\"X-Request-Id\":\"abc\"}", "asText": null, "asShapeHashBytes": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+        }
+      },
+      "response": {
+        "statusCode": 200,
+        "headers": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+        }
+      },
+      "tags": []
+    });
+    let interaction_json = format!("{}\n", interaction);
+
+    let interaction_lines = streams::http_interaction::json_lines(interaction_json.as_bytes());
+    let sink = tokio::io::sink();
+
+    let learner_config = AnalyzeUndocumentedBodiesConfig::default();
+
+    learn_undocumented_headers(spec_events, 1, interaction_lines, learner_config, sink).await;
+  }
+
   #[tokio::main]
   #[test]
   async fn can_learn_shape_diffs_affordances_from_interactions() {
@@ -329,6 +488,16 @@ mod test {
     let interaction_lines =
       streams::http_interaction::json_lines(fs::File::open(interactions_path).await.unwrap());
 
-    learn_shape_diff_affordances(spec_events, diffs, 1, interaction_lines, tokio::io::sink()).await;
+    let learner_config = AnalyzeUndocumentedBodiesConfig::default();
+
+    learn_shape_diff_affordances(
+      spec_events,
+      diffs,
+      1,
+      interaction_lines,
+      learner_config,
+      tokio::io::sink(),
+    )
+    .await;
   }
 }
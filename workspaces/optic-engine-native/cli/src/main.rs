@@ -4,20 +4,30 @@ use futures::SinkExt;
 use futures::{StreamExt, TryStreamExt};
 use num_cpus;
 use optic_engine::errors;
+use optic_engine::repair_spec_conflicts;
 use optic_engine::streams;
 use optic_engine::HttpInteraction;
 use optic_engine::InteractionDiffResult;
+use optic_engine::SpecAssemblerProjection;
 use optic_engine::SpecProjection;
 use optic_engine::{diff_interaction, DiffInteractionConfig};
 use optic_engine::{SpecChunkEvent, SpecEvent};
+use serde::de::DeserializeOwned;
 use std::cmp;
 use std::process;
 use std::sync::Arc;
 use tokio::io::{stdin, stdout};
 use tokio::sync::mpsc;
 
+mod blame;
+mod changelog;
 mod commit;
+mod compare;
+mod export;
+mod import;
 mod learn;
+mod query;
+mod revert;
 
 fn main() {
   let cli = App::new("Optic Engine CLI")
@@ -49,6 +59,14 @@ fn main() {
         .multiple(false)
         .required(false),
     )
+    .arg(
+      Arg::with_name("at-commit")
+        .long("at-commit")
+        .value_name("BATCH_ID")
+        .takes_value(true)
+        .global(true)
+        .help("Use the specification as it was right after this batch commit"),
+    )
     .arg(
       Arg::with_name("core-threads")
         .long("core-threads")
@@ -63,11 +81,29 @@ fn main() {
       SubCommand::with_name("assemble")
         .about("Assembles a directory of API spec files into a single events stream"),
     )
+    .subcommand(
+      SubCommand::with_name("conflicts")
+        .about("Reports paths and endpoints of the API spec that conflict with each other")
+        .arg(
+          Arg::with_name("repair")
+            .long("repair")
+            .takes_value(false)
+            .help("Outputs spec commands that resolve the conflicts instead, for review before committing them"),
+        ),
+    )
+    .subcommand(blame::create_subcommand())
+    .subcommand(changelog::create_subcommand())
     .subcommand(commit::create_subcommand())
+    .subcommand(compare::create_subcommand())
+    .subcommand(export::create_subcommand())
+    .subcommand(import::create_subcommand())
     .subcommand(learn::create_subcommand())
+    .subcommand(query::create_subcommand())
+    .subcommand(revert::create_subcommand())
     .subcommand(
       SubCommand::with_name("diff")
-        .about("Detects differences between API spec and captured interactions (default)"),
+        .about("Detects differences between API spec and captured interactions (default)")
+        .arg(config_arg()),
     );
 
   let matches = cli.get_matches();
@@ -76,7 +112,9 @@ fn main() {
     .value_of("specification")
     .expect("SPEC_PATH should be required");
   let spec_path_type = match matches.subcommand_name() {
-    Some("assemble") | Some("commit") => SpecPathType::DIR,
+    Some("assemble") | Some(commit::SUBCOMMAND_NAME) | Some(revert::SUBCOMMAND_NAME) => {
+      SpecPathType::DIR
+    }
     _ => {
       if matches.is_present("use-spec-dir") {
         SpecPathType::DIR
@@ -111,25 +149,22 @@ fn main() {
   ) * 4;
 
   runtime.block_on(async {
-    let spec_chunks = match spec_path_type {
-      SpecPathType::FILE => streams::spec_chunks::from_root_api_file(&spec_path)
-        .await
-        .map_err(|err| match err {
-          errors::SpecChunkLoaderError::Io(err) => {
-            eprintln!("Could not read specification file: {}", err);
-            process::exit(1);
-          }
-          errors::SpecChunkLoaderError::Json(err) => {
-            eprintln!("Specification JSON file could not be parsed: {}", err);
-            process::exit(1);
-          }
-          _ => unreachable!("Specification file not currently serialized as any other but JSON"),
-        })
-        .unwrap(),
-
-      SpecPathType::DIR => streams::spec_chunks::from_api_dir(&spec_path)
-        .await
-        .expect("should be able to find spec event chunks in a folder"),
+    let spec_chunks = spec_chunks_from_path(spec_path, &spec_path_type).await;
+    let spec_chunks = match matches.value_of("at-commit") {
+      Some(_)
+        if matches.subcommand_name() == Some(commit::SUBCOMMAND_NAME)
+          || matches.subcommand_name() == Some(revert::SUBCOMMAND_NAME) =>
+      {
+        eprintln!("Commits can only be made to the latest version of the specification");
+        process::exit(1);
+      }
+      Some(batch_id) => {
+        let events = events_from_chunks(spec_chunks).await;
+        vec![SpecChunkEvent::root_from_events(events_at_commit(
+          events, batch_id,
+        ))]
+      }
+      None => spec_chunks,
     };
 
     match matches.subcommand() {
@@ -137,17 +172,52 @@ fn main() {
         // eprintln!("assembling spec folder into spec");
         assemble(spec_chunks).await;
       }
+      ("conflicts", Some(subcommand_matches)) => {
+        let events = events_from_chunks(spec_chunks).await;
+        if subcommand_matches.is_present("repair") {
+          repair_conflicts(events).await;
+        } else {
+          conflicts(events).await;
+        }
+      }
+      (blame::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        blame::main(subcommand_matches, spec_chunks).await
+      }
+      (changelog::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        changelog::main(subcommand_matches, spec_chunks).await
+      }
       (commit::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
         commit::main(subcommand_matches, spec_chunks, spec_path).await
       }
+      (compare::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        compare::main(subcommand_matches, spec_chunks, &spec_path_type).await
+      }
+      (export::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        export::main(subcommand_matches, spec_chunks).await
+      }
+      (import::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        import::main(subcommand_matches, spec_chunks).await
+      }
       (learn::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
         learn::main(subcommand_matches, spec_chunks, input_queue_size).await
       }
+      (query::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        query::main(subcommand_matches, spec_chunks).await
+      }
+      (revert::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        revert::main(subcommand_matches, spec_chunks, spec_path).await
+      }
       _ => {
         eprintln!("diffing interations against a spec");
         eprintln!("using input queue size {}", input_queue_size);
 
-        let diff_config = DiffInteractionConfig::default();
+        let diff_config: DiffInteractionConfig = match matches
+          .subcommand_matches("diff")
+          .and_then(|diff_matches| diff_matches.value_of("config"))
+        {
+          Some(config_path) => config_from_file(config_path).await,
+          None => DiffInteractionConfig::default(),
+        };
 
         diff(
           events_from_chunks(spec_chunks).await,
@@ -247,8 +317,53 @@ async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize, diff_config: DiffI
   try_join!(diffing_interactions, results_manager).expect("essential worker task panicked");
 }
 
+async fn spec_chunks_from_path(
+  spec_path: &str,
+  spec_path_type: &SpecPathType,
+) -> Vec<SpecChunkEvent> {
+  match spec_path_type {
+    SpecPathType::FILE => streams::spec_chunks::from_root_api_file(spec_path)
+      .await
+      .map_err(|err| match err {
+        errors::SpecChunkLoaderError::Io(err) => {
+          eprintln!("Could not read specification file: {}", err);
+          process::exit(1);
+        }
+        errors::SpecChunkLoaderError::Json(err) => {
+          eprintln!("Specification JSON file could not be parsed: {}", err);
+          process::exit(1);
+        }
+        _ => unreachable!("Specification file not currently serialized as any other but JSON"),
+      })
+      .unwrap(),
+
+    SpecPathType::DIR => streams::spec_chunks::from_api_dir(spec_path)
+      .await
+      .expect("should be able to find spec event chunks in a folder"),
+  }
+}
+
+// the events of the spec as of a batch commit
+fn events_at_commit(events: Vec<SpecEvent>, batch_id: &str) -> Vec<SpecEvent> {
+  SpecProjection::events_at_commit(events, &String::from(batch_id)).unwrap_or_else(|| {
+    eprintln!(
+      "Could not find batch commit {} in the specification",
+      batch_id
+    );
+    process::exit(1);
+  })
+}
+
 async fn assemble(spec_chunks: Vec<SpecChunkEvent>) {
-  let spec_events = events_from_chunks(spec_chunks).await;
+  let (spec_events, report) =
+    match SpecAssemblerProjection::from(spec_chunks).into_events_with_report() {
+      Ok(assembled) => assembled,
+      Err(err) => {
+        eprintln!("{}", err);
+        process::exit(1);
+      }
+    };
+  eprint!("{}", report);
 
   let stdout = stdout();
 
@@ -257,6 +372,58 @@ async fn assemble(spec_chunks: Vec<SpecChunkEvent>) {
     .unwrap_or_else(|err| panic!("could not write new events to stdout: {}", err));
 }
 
+async fn conflicts(events: Vec<SpecEvent>) {
+  let spec_projection = SpecProjection::from(events);
+  let conflicts = spec_projection.conflicts().spec_conflicts();
+
+  streams::write_to_json_lines(stdout(), conflicts.iter())
+    .await
+    .expect("could not write conflicts to stdout");
+}
+
+async fn repair_conflicts(events: Vec<SpecEvent>) {
+  let spec_projection = SpecProjection::from(events);
+  let mut id_generator = learn::IdGenerator::default();
+
+  let repair = match repair_spec_conflicts(&spec_projection, &mut id_generator) {
+    Ok(repair) => repair,
+    Err(err) => {
+      eprintln!("Could not repair conflicts: {:?}", err);
+      process::exit(1);
+    }
+  };
+  for merged_paths in &repair.merged_paths {
+    eprintln!(
+      "merging paths {} into {}",
+      merged_paths.merged_path_ids.join(", "),
+      merged_paths.surviving_path_id
+    );
+  }
+  for merged_shapes in &repair.merged_shapes {
+    eprintln!(
+      "merging shapes {} into {}",
+      merged_shapes.merged_shape_ids.join(", "),
+      merged_shapes.surviving_shape_id
+    );
+  }
+  if !repair.removed_path_ids.is_empty() {
+    eprintln!(
+      "removing unused paths {}",
+      repair.removed_path_ids.join(", ")
+    );
+  }
+  if !repair.removed_shape_ids.is_empty() {
+    eprintln!(
+      "removing unreachable shapes {}",
+      repair.removed_shape_ids.join(", ")
+    );
+  }
+
+  streams::write_to_json_lines(stdout(), repair.commands.iter())
+    .await
+    .expect("could not write repair commands to stdout");
+}
+
 enum SpecPathType {
   FILE,
   DIR,
@@ -275,6 +442,28 @@ impl From<(InteractionDiffResult, &Tags)> for ResultContainer<InteractionDiffRes
   }
 }
 
+fn config_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("config")
+    .long("config")
+    .takes_value(true)
+    .value_name("CONFIG_PATH")
+    .help("Path to a JSON file configuring which interactions, bodies and trails are considered")
+}
+
+async fn config_from_file<T: DeserializeOwned>(config_path: &str) -> T {
+  let config_json = tokio::fs::read_to_string(config_path)
+    .await
+    .unwrap_or_else(|err| {
+      eprintln!("Could not read config file: {}", err);
+      process::exit(1);
+    });
+
+  serde_json::from_str(&config_json).unwrap_or_else(|err| {
+    eprintln!("Config file could not be parsed: {}", err);
+    process::exit(1);
+  })
+}
+
 async fn events_from_chunks(chunks: Vec<SpecChunkEvent>) -> Vec<SpecEvent> {
   streams::spec_events::from_spec_chunks(chunks)
     .await
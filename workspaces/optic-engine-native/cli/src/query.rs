@@ -0,0 +1,39 @@
+use super::events_from_chunks;
+use clap::{App, AppSettings, ArgMatches, SubCommand};
+use optic_engine::{EndpointQueries, ShapeQueries, SpecChunkEvent, SpecProjection};
+use std::io::{stdout, Write};
+use std::process;
+
+pub const SUBCOMMAND_NAME: &'static str = "query";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Queries the API spec")
+    .setting(AppSettings::SubcommandRequiredElseHelp)
+    .subcommand(
+      SubCommand::with_name("shapes")
+        .about("Lists the named shapes of the API spec, with everywhere they're used"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  match command_matches.subcommand() {
+    ("shapes", Some(_)) => {
+      let spec_projection = SpecProjection::from(events_from_chunks(spec_chunks).await);
+      let shape_queries = ShapeQueries::new(spec_projection.shape());
+      let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+
+      let named_shapes = shape_queries.named_shapes(&endpoint_queries);
+
+      let mut stdout = stdout();
+      let written = serde_json::to_writer_pretty(&mut stdout, &named_shapes)
+        .map_err(|err| err.to_string())
+        .and_then(|_| writeln!(stdout).map_err(|err| err.to_string()));
+      if let Err(err) = written {
+        eprintln!("Could not write named shapes: {}", err);
+        process::exit(1);
+      }
+    }
+    _ => unreachable!("query requires a subcommand"),
+  }
+}
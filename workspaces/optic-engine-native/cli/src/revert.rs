@@ -0,0 +1,96 @@
+use super::commit::{exit_unless_root_chunks_only, write_batch};
+use super::events_from_chunks;
+use chrono::Utc;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_engine::{revert_batch, CommandContext, SpecChunkEvent};
+use std::path::Path;
+use std::process;
+use uuid::Uuid;
+
+pub const SUBCOMMAND_NAME: &'static str = "revert";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about(
+      "Undoes the changes of a batch commit, by committing their inverse as a new batch commit",
+    )
+    .arg(
+      Arg::with_name("batch-id")
+        .required(true)
+        .value_name("BATCH_ID")
+        .help("The batch commit to revert"),
+    )
+    .arg(
+      Arg::with_name("append-to-root")
+        .long("append-to-root")
+        .required(false)
+        .takes_value(false)
+        .help("Append new batch commit to the root spec file, instead of a new spec change file"),
+    )
+    .arg(
+      Arg::with_name("client-session-id")
+        .long("client-session-id")
+        .required(false)
+        .value_name("CLIENT_SESSION_ID")
+        .takes_value(true)
+        .default_value("unknown-session")
+        .help("The session id of the client requesting the batch commit to be reverted"),
+    )
+    .arg(
+      Arg::with_name("client-id")
+        .long("client-id")
+        .required(false)
+        .value_name("CLIENT_ID")
+        .takes_value(true)
+        .default_value("anonymous")
+        .help("Unique id of the client requesting the batch commit to be reverted"),
+    )
+}
+
+pub async fn main<'a>(
+  command_matches: &'a ArgMatches<'a>,
+  spec_chunks: Vec<SpecChunkEvent>,
+  spec_path: impl AsRef<Path>,
+) {
+  let reverted_batch_id = command_matches
+    .value_of("batch-id")
+    .expect("batch-id is required");
+
+  let append_to_root = command_matches.is_present("append-to-root");
+  if append_to_root {
+    exit_unless_root_chunks_only(&spec_chunks);
+  }
+
+  let client_session_id = command_matches
+    .value_of("client-session-id")
+    .expect("client-session-id is required");
+
+  let client_id = command_matches
+    .value_of("client-id")
+    .expect("client-id is required");
+
+  let spec_events = events_from_chunks(spec_chunks).await;
+
+  let batch_id = Uuid::new_v4().to_hyphenated().to_string();
+  let batch_command_context = CommandContext::new(
+    batch_id.clone(),
+    String::from(client_id),
+    String::from(client_session_id),
+    Utc::now(),
+  );
+
+  let new_events = revert_batch(
+    spec_events.clone(),
+    &String::from(reverted_batch_id),
+    batch_command_context,
+  )
+  .unwrap_or_else(|err| {
+    eprintln!(
+      "Could not revert batch commit {}: {}",
+      reverted_batch_id, err
+    );
+    process::exit(1);
+  });
+
+  write_batch(spec_events, new_events, batch_id, spec_path, append_to_root).await;
+}
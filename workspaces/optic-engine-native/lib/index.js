@@ -14,13 +14,17 @@ const Config = require('./config');
 
 const fetchBinary = Bent(Config.prebuilt.baseUrl, 'GET', 200, 404);
 
-function diffInteractions({ specPath, interactionsStream }) {
+function diffInteractions({ specPath, interactionsStream, configPath }) {
   const input = Readable.from(HttpInteractions.intoJSONL(interactionsStream));
   const output = new PassThrough();
 
   const binPath = getBinPath();
 
-  const diffProcess = Execa(binPath, [specPath], {
+  const args = configPath
+    ? [specPath, 'diff', '--config', configPath]
+    : [specPath];
+
+  const diffProcess = Execa(binPath, args, {
     input,
     stdio: ['pipe', 'pipe', 'inherit'],
   });
@@ -59,6 +63,94 @@ function readSpec({ specDirPath }) {
   return output;
 }
 
+function readConflicts({ specPath }) {
+  const output = new PassThrough();
+
+  const binPath = getBinPath();
+
+  const conflictsProcess = Execa(binPath, [specPath, 'conflicts'], {
+    stdio: ['ignore', 'pipe', 'inherit'],
+  });
+
+  conflictsProcess.stdout.pipe(output);
+
+  conflictsProcess.then(
+    (childResult) => {},
+    (childResult) => {
+      output.emit('error', new DiffEngineError(childResult));
+    }
+  );
+  return output;
+}
+
+function readConflictRepairCommands({ specPath }) {
+  const output = new PassThrough();
+
+  const binPath = getBinPath();
+
+  const repairProcess = Execa(binPath, [specPath, 'conflicts', '--repair'], {
+    stdio: ['ignore', 'pipe', 'inherit'],
+  });
+
+  repairProcess.stdout.pipe(output);
+
+  repairProcess.then(
+    (childResult) => {},
+    (childResult) => {
+      output.emit('error', new DiffEngineError(childResult));
+    }
+  );
+  return output;
+}
+
+function exportOpenApi({ specPath, format = 'yaml' }) {
+  const output = new PassThrough();
+
+  const binPath = getBinPath();
+
+  const exportProcess = Execa(
+    binPath,
+    [specPath, 'export', 'openapi', '--format', format],
+    {
+      stdio: ['ignore', 'pipe', 'inherit'],
+    }
+  );
+
+  exportProcess.stdout.pipe(output);
+
+  exportProcess.then(
+    (childResult) => {},
+    (childResult) => {
+      output.emit('error', new DiffEngineError(childResult));
+    }
+  );
+  return output;
+}
+
+function importOpenApi({ specPath, documentPath }) {
+  const output = new PassThrough();
+
+  const binPath = getBinPath();
+
+  const importProcess = Execa(
+    binPath,
+    [specPath, 'import', 'openapi', documentPath],
+    {
+      stdio: ['ignore', 'pipe', 'inherit'],
+    }
+  );
+
+  importProcess.stdout.pipe(output);
+
+  importProcess.then(
+    (childResult) => {},
+    (childResult) => {
+      output.emit('error', new DiffEngineError(childResult));
+    }
+  );
+  return output;
+}
+
 function commit(
   commands,
   { commitMessage, specDirPath, appendToRoot, clientSessionId, clientId }
@@ -112,7 +204,7 @@ function commit(
   return output;
 }
 
-function learnUndocumentedBodies(interactions, { specPath }) {
+function learnUndocumentedBodies(interactions, { specPath, configPath }) {
   if (!interactions || typeof interactions[Symbol.asyncIterator] !== 'function')
     throw new Error(
       'interactionsStream must be AsyncIterator to learn undocumented bodies'
@@ -125,7 +217,45 @@ function learnUndocumentedBodies(interactions, { specPath }) {
 
   const learnProcess = Execa(
     binPath,
-    [specPath, '-f', 'learn', '--undocumented-bodies'],
+    [
+      specPath,
+      '-f',
+      'learn',
+      '--undocumented-bodies',
+      ...(configPath ? ['--config', configPath] : []),
+    ],
+    {
+      stdio: ['pipe', 'pipe', 'inherit'],
+    }
+  );
+
+  input.pipe(learnProcess.stdin);
+  learnProcess.stdout.pipe(output);
+
+  learnProcess.then(
+    (childResult) => {},
+    (childResult) => {
+      output.emit('error', new DiffEngineError(childResult));
+    }
+  );
+
+  return output;
+}
+
+function learnUndocumentedHeaders(interactions, { specPath }) {
+  if (!interactions || typeof interactions[Symbol.asyncIterator] !== 'function')
+    throw new Error(
+      'interactionsStream must be AsyncIterator to learn undocumented headers'
+    );
+
+  const input = Readable.from(HttpInteractions.intoJSONL(interactions));
+  const output = new PassThrough();
+
+  const binPath = getBinPath();
+
+  const learnProcess = Execa(
+    binPath,
+    [specPath, '-f', 'learn', '--undocumented-headers'],
     {
       stdio: ['pipe', 'pipe', 'inherit'],
     }
@@ -360,9 +490,14 @@ class DiffEngineError extends Error {
 
 exports.diffInteractions = diffInteractions;
 exports.readSpec = readSpec;
+exports.readConflicts = readConflicts;
+exports.readConflictRepairCommands = readConflictRepairCommands;
 exports.commit = commit;
+exports.exportOpenApi = exportOpenApi;
+exports.importOpenApi = importOpenApi;
 exports.learnShapeDiffAffordances = learnShapeDiffAffordances;
 exports.learnUndocumentedBodies = learnUndocumentedBodies;
+exports.learnUndocumentedHeaders = learnUndocumentedHeaders;
 exports.install = install;
 exports.uninstall = uninstall;
 exports.DiffEngineError = DiffEngineError;
@@ -3,12 +3,14 @@
 use chrono::Utc;
 use nanoid::nanoid;
 use optic_engine::{
-  analyze_undocumented_bodies, Aggregate, AnalyzeUndocumentedBodiesConfig, Body,
-  BodyAnalysisResult, CommandContext, DiffInteractionConfig, EndpointQueries, HttpInteraction,
-  InteractionDiffResult, JsonTrail, JsonType, LearnedShapeDiffAffordancesProjection,
-  LearnedUndocumentedBodiesProjection, ResponseBodyDescriptor, ResponseId, ShapeChoiceQueries,
-  ShapeQueries, SpecCommand, SpecEvent, SpecIdGenerator, SpecProjection, TaggedInput,
-  TrailObservationsResult, TrailValues,
+  analyze_undocumented_bodies, blame, export_openapi, import_openapi, openapi::OpenApi,
+  repair_spec_conflicts, Aggregate, AnalyzeUndocumentedBodiesConfig, Body, BodyAnalysisResult,
+  CommandContext, DiffInteractionConfig, EndpointQueries, HeaderAnalysisResult, HttpInteraction,
+  InteractionDiffResult, InteractionFilters, JsonTrail, JsonType,
+  LearnedShapeDiffAffordancesProjection, LearnedUndocumentedBodiesProjection,
+  LearnedUndocumentedHeadersProjection, ResponseBodyDescriptor, ResponseId, ShapeChoiceQueries,
+  ShapeQueries, ShapeStructureIndex, SpecCommand, SpecEvent, SpecIdGenerator, SpecProjection,
+  TaggedInput, TrailObservationsResult, TrailValues,
 };
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -124,6 +126,36 @@ pub fn affordances_to_commands(
   json_affordances_json: String,
   json_trail_json: String,
   id_generator_strategy: String,
+) -> Result<String, JsValue> {
+  trail_values_to_commands(
+    json_affordances_json,
+    json_trail_json,
+    id_generator_strategy,
+    &ShapeStructureIndex::default(),
+  )
+}
+
+// Learned shapes structurally equivalent to one of the spec's named shapes refer to it instead
+#[wasm_bindgen]
+pub fn affordances_to_commands_reusing(
+  spec: &WasmSpecProjection,
+  json_affordances_json: String,
+  json_trail_json: String,
+  id_generator_strategy: String,
+) -> Result<String, JsValue> {
+  trail_values_to_commands(
+    json_affordances_json,
+    json_trail_json,
+    id_generator_strategy,
+    &spec.shape_queries().named_shape_structures(),
+  )
+}
+
+fn trail_values_to_commands(
+  json_affordances_json: String,
+  json_trail_json: String,
+  id_generator_strategy: String,
+  reusable_shapes: &ShapeStructureIndex,
 ) -> Result<String, JsValue> {
   let values_by_trail_vec: Vec<TrailValues> = serde_json::from_str(&json_affordances_json).unwrap();
   let json_trail: JsonTrail = serde_json::from_str(&json_trail_json).unwrap();
@@ -131,21 +163,20 @@ pub fn affordances_to_commands(
     .into_iter()
     .map(|item| (item.trail.clone(), item))
     .collect();
-  let trail_observation_results: TrailObservationsResult = TrailObservationsResult {
-    values_by_trail: values_by_trail_map,
-  };
-
-  let result: (Vec<SpecCommand>, String) = if id_generator_strategy == "sequential" {
-    let mut sequential_id_generator = SequentialIdGenerator { next_id: 9999 };
-    let (root_shape_id_option, commands_iter) =
-      trail_observation_results.into_commands(&mut sequential_id_generator, &json_trail);
-    (commands_iter.collect(), root_shape_id_option.unwrap())
-  } else {
-    let mut nano_id_generator = NanoIdGenerator::default();
-    let (root_shape_id_option, commands_iter) =
-      trail_observation_results.into_commands(&mut nano_id_generator, &json_trail);
-    (commands_iter.collect(), root_shape_id_option.unwrap())
-  };
+  let trail_observation_results = TrailObservationsResult::from(values_by_trail_map);
+
+  let result: (Vec<SpecCommand>, String) =
+    if id_generator_strategy == "sequential" {
+      let mut sequential_id_generator = SequentialIdGenerator { next_id: 9999 };
+      let (root_shape_id_option, commands_iter, _) = trail_observation_results
+        .into_commands_reusing(&mut sequential_id_generator, &json_trail, reusable_shapes);
+      (commands_iter.collect(), root_shape_id_option.unwrap())
+    } else {
+      let mut nano_id_generator = NanoIdGenerator::default();
+      let (root_shape_id_option, commands_iter, _) = trail_observation_results
+        .into_commands_reusing(&mut nano_id_generator, &json_trail, reusable_shapes);
+      (commands_iter.collect(), root_shape_id_option.unwrap())
+    };
 
   serde_json::to_string(&result)
     .map_err(|err| JsValue::from(format!("new commands could not be serialized: {:?}", err)))
@@ -161,7 +192,7 @@ pub fn learn_undocumented_bodies(
 
   let learner_config = AnalyzeUndocumentedBodiesConfig::default();
 
-  let mut learned_undocumented_bodies = LearnedUndocumentedBodiesProjection::default();
+  let mut learned_undocumented_bodies = LearnedUndocumentedBodiesProjection::new(&learner_config);
   for interaction_parse_result in interactions {
     let interaction: HttpInteraction = interaction_parse_result
       .map_err(|err| JsValue::from(format!("could not parse interaction json: {}", err)))?;
@@ -173,15 +204,16 @@ pub fn learn_undocumented_bodies(
     }
   }
 
+  let reusable_shapes = spec.shape_queries().named_shape_structures();
   let mut sequential_id_generator = SequentialIdGenerator { next_id: 6666 };
   let mut nano_id_generator = NanoIdGenerator::default();
   let endpoint_bodies = if id_generator_strategy == "sequential" {
     learned_undocumented_bodies
-      .into_endpoint_bodies(&mut sequential_id_generator)
+      .into_endpoint_bodies(&mut sequential_id_generator, &reusable_shapes)
       .collect::<Vec<_>>()
   } else {
     learned_undocumented_bodies
-      .into_endpoint_bodies(&mut nano_id_generator)
+      .into_endpoint_bodies(&mut nano_id_generator, &reusable_shapes)
       .collect::<Vec<_>>()
   };
 
@@ -193,6 +225,47 @@ pub fn learn_undocumented_bodies(
   })
 }
 
+#[wasm_bindgen]
+pub fn learn_undocumented_headers(
+  spec: &WasmSpecProjection,
+  interactions_json: String,
+  id_generator_strategy: String,
+) -> Result<String, JsValue> {
+  let interactions = serde_json::Deserializer::from_str(&interactions_json).into_iter();
+
+  let learner_config = AnalyzeUndocumentedBodiesConfig::default();
+  let mut learned_undocumented_headers = LearnedUndocumentedHeadersProjection::default();
+  for interaction_parse_result in interactions {
+    let interaction: HttpInteraction = interaction_parse_result
+      .map_err(|err| JsValue::from(format!("could not parse interaction json: {}", err)))?;
+
+    let results = spec.analyze_undocumented_headers(interaction, &learner_config.filters);
+
+    for result in results {
+      learned_undocumented_headers.apply(result)
+    }
+  }
+
+  let mut sequential_id_generator = SequentialIdGenerator { next_id: 6666 };
+  let mut nano_id_generator = NanoIdGenerator::default();
+  let endpoint_headers = if id_generator_strategy == "sequential" {
+    learned_undocumented_headers
+      .into_endpoint_headers(&mut sequential_id_generator)
+      .collect::<Vec<_>>()
+  } else {
+    learned_undocumented_headers
+      .into_endpoint_headers(&mut nano_id_generator)
+      .collect::<Vec<_>>()
+  };
+
+  serde_json::to_string(&endpoint_headers).map_err(|err| {
+    JsValue::from(format!(
+      "endpoint headers could not be serialized: {:?}",
+      err
+    ))
+  })
+}
+
 // TODO: consider whether to accept 1 interaction at the time and return a control flow struct,
 // to allow for unbound amount of interactions
 #[wasm_bindgen]
@@ -255,6 +328,14 @@ impl WasmSpecProjection {
     optic_engine::analyze_undocumented_bodies(&self.projection, interaction, config)
   }
 
+  fn analyze_undocumented_headers(
+    &self,
+    interaction: HttpInteraction,
+    filters: &InteractionFilters,
+  ) -> impl Iterator<Item = HeaderAnalysisResult> {
+    optic_engine::analyze_undocumented_headers(&self.projection, interaction, filters)
+  }
+
   fn analyze_documented_bodies(
     &self,
     interaction: HttpInteraction,
@@ -341,6 +422,20 @@ pub fn append_batch_to_spec(
     .map_err(|err| JsValue::from(format!("new events could not be serialized: {:?}", err)))
 }
 
+// Spec History
+// ------------
+
+#[wasm_bindgen]
+pub fn spec_blame(events_json: String, entity_id: String) -> Result<String, JsValue> {
+  let spec_events: Vec<SpecEvent> = serde_json::from_str(&events_json)
+    .map_err(|err| JsValue::from(format!("spec events could not be parsed: {:?}", err)))?;
+
+  let blame = blame(spec_events, &entity_id);
+
+  serde_json::to_string(&blame)
+    .map_err(|err| JsValue::from(format!("blame could not be serialized: {:?}", err)))
+}
+
 // Spec Queries
 // ------------
 #[wasm_bindgen]
@@ -424,6 +519,62 @@ pub fn spec_resolve_response(
     .map_err(|err| JsValue::from(format!("responses could not be serialized: {:?}", err)))
 }
 
+#[wasm_bindgen]
+pub fn spec_conflicts(spec: &WasmSpecProjection) -> Result<String, JsValue> {
+  let conflicts = spec.projection.conflicts().spec_conflicts();
+
+  serde_json::to_string(&conflicts)
+    .map_err(|err| JsValue::from(format!("conflicts could not be serialized: {:?}", err)))
+}
+
+#[wasm_bindgen]
+pub fn spec_openapi(spec: &WasmSpecProjection) -> Result<String, JsValue> {
+  let openapi = export_openapi(&spec.projection);
+
+  serde_json::to_string(&openapi).map_err(|err| {
+    JsValue::from(format!(
+      "openapi document could not be serialized: {:?}",
+      err
+    ))
+  })
+}
+
+#[wasm_bindgen]
+pub fn spec_openapi_import(
+  spec: &WasmSpecProjection,
+  openapi_json: String,
+) -> Result<String, JsValue> {
+  let document = serde_json::from_str::<serde_json::Value>(&openapi_json)
+    .map_err(|err| err.to_string())
+    .and_then(|value| OpenApi::try_from(value).map_err(|err| err.to_string()))
+    .map_err(|err| JsValue::from(format!("openapi document could not be read: {}", err)))?;
+  let mut nano_id_generator = NanoIdGenerator::default();
+
+  let import = import_openapi(&spec.projection, &document, &mut nano_id_generator);
+
+  serde_json::to_string(&import).map_err(|err| {
+    JsValue::from(format!(
+      "imported commands could not be serialized: {:?}",
+      err
+    ))
+  })
+}
+
+#[wasm_bindgen]
+pub fn spec_conflicts_repair_commands(spec: &WasmSpecProjection) -> Result<String, JsValue> {
+  let mut nano_id_generator = NanoIdGenerator::default();
+
+  let repair = repair_spec_conflicts(&spec.projection, &mut nano_id_generator)
+    .map_err(|err| JsValue::from(format!("conflicts could not be repaired: {:?}", err)))?;
+
+  serde_json::to_string(&repair).map_err(|err| {
+    JsValue::from(format!(
+      "conflicts repair commands could not be serialized: {:?}",
+      err
+    ))
+  })
+}
+
 #[wasm_bindgen]
 pub fn spec_endpoint_delete_commands(
   spec: &WasmSpecProjection,
@@ -446,6 +597,30 @@ pub fn spec_endpoint_delete_commands(
   })
 }
 
+#[wasm_bindgen]
+pub fn spec_shape_json_schema(
+  spec: &WasmSpecProjection,
+  shape_id: String,
+) -> Result<String, JsValue> {
+  let shape_queries = spec.shape_queries();
+
+  let json_schema = shape_queries.json_schema(&shape_id);
+
+  serde_json::to_string(&json_schema)
+    .map_err(|err| JsValue::from(format!("json schema could not be serialized: {:?}", err)))
+}
+
+#[wasm_bindgen]
+pub fn spec_named_shapes(spec: &WasmSpecProjection) -> Result<String, JsValue> {
+  let shape_queries = spec.shape_queries();
+  let endpoint_queries = spec.endpoint_queries();
+
+  let named_shapes = shape_queries.named_shapes(&endpoint_queries);
+
+  serde_json::to_string(&named_shapes)
+    .map_err(|err| JsValue::from(format!("named shapes could not be serialized: {:?}", err)))
+}
+
 #[wasm_bindgen]
 pub fn spec_field_remove_commands(
   spec: &WasmSpecProjection,
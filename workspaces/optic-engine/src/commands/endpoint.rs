@@ -1,4 +1,4 @@
-use super::{CommandContext, SpecCommand, SpecCommandError};
+use super::{CommandContext, ShapeCommand, SpecCommand, SpecCommandError};
 use serde::{Deserialize, Serialize};
 
 use crate::events::EndpointEvent;
@@ -54,9 +54,22 @@ pub enum EndpointCommand {
   RenameHeaderParameter(RenameHeaderParameter),
   UnsetHeaderParameterShape(UnsetHeaderParameterShape),
   RemoveHeaderParameter(RemoveHeaderParameter),
+  AddResponseHeaderParameter(AddResponseHeaderParameter),
 }
 
 impl EndpointCommand {
+  pub fn add_path_component(
+    path_id: PathComponentId,
+    parent_path_id: PathComponentId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddPathComponent(AddPathComponent {
+      path_id,
+      parent_path_id,
+      name,
+    })
+  }
+
   pub fn remove_path_component(path_id: PathComponentId) -> EndpointCommand {
     EndpointCommand::RemovePathComponent(RemovePathComponent { path_id })
   }
@@ -71,6 +84,18 @@ impl EndpointCommand {
     })
   }
 
+  pub fn add_path_parameter(
+    path_id: PathComponentId,
+    parent_path_id: PathComponentId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddPathParameter(AddPathParameter {
+      path_id,
+      parent_path_id,
+      name,
+    })
+  }
+
   pub fn remove_path_parameter(path_id: PathComponentId) -> EndpointCommand {
     EndpointCommand::RemovePathParameter(RemovePathParameter { path_id })
   }
@@ -141,6 +166,10 @@ impl EndpointCommand {
     })
   }
 
+  pub fn unset_request_body_shape(request_id: RequestId) -> EndpointCommand {
+    EndpointCommand::UnsetRequestBodyShape(UnsetRequestBodyShape { request_id })
+  }
+
   pub fn remove_request(request_id: RequestId) -> EndpointCommand {
     EndpointCommand::RemoveRequest(RemoveRequest { request_id })
   }
@@ -178,9 +207,68 @@ impl EndpointCommand {
     })
   }
 
+  pub fn unset_response_body_shape(response_id: ResponseId) -> EndpointCommand {
+    EndpointCommand::UnsetResponseBodyShape(UnsetResponseBodyShape { response_id })
+  }
+
   pub fn remove_response(response_id: ResponseId) -> EndpointCommand {
     EndpointCommand::RemoveResponse(RemoveResponse { response_id })
   }
+
+  // Headers
+  // -------
+
+  pub fn add_header_parameter(
+    parameter_id: RequestParameterId,
+    request_id: RequestId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddHeaderParameter(AddHeaderParameter {
+      parameter_id,
+      request_id,
+      name,
+    })
+  }
+
+  pub fn set_header_parameter_shape(
+    parameter_id: RequestParameterId,
+    shape_id: ShapeId,
+  ) -> EndpointCommand {
+    EndpointCommand::SetHeaderParameterShape(SetHeaderParameterShape {
+      parameter_id,
+      parameter_descriptor: ShapedRequestParameterShapeDescriptor {
+        shape_id,
+        is_removed: false,
+      },
+    })
+  }
+
+  pub fn rename_header_parameter(
+    parameter_id: RequestParameterId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::RenameHeaderParameter(RenameHeaderParameter { parameter_id, name })
+  }
+
+  pub fn unset_header_parameter_shape(parameter_id: RequestParameterId) -> EndpointCommand {
+    EndpointCommand::UnsetHeaderParameterShape(UnsetHeaderParameterShape { parameter_id })
+  }
+
+  pub fn remove_header_parameter(parameter_id: RequestParameterId) -> EndpointCommand {
+    EndpointCommand::RemoveHeaderParameter(RemoveHeaderParameter { parameter_id })
+  }
+
+  pub fn add_response_header_parameter(
+    parameter_id: RequestParameterId,
+    response_id: ResponseId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddResponseHeaderParameter(AddResponseHeaderParameter {
+      parameter_id,
+      response_id,
+      name,
+    })
+  }
 }
 
 // Path components
@@ -273,7 +361,7 @@ pub struct SetRequestBodyShape {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnsetRequestBodyShape {
-  request_id: RequestId,
+  pub request_id: RequestId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -330,7 +418,7 @@ pub struct SetResponseBodyShape {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnsetResponseBodyShape {
-  response_id: ResponseId,
+  pub response_id: ResponseId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -369,35 +457,43 @@ pub struct RemoveQueryParameters {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AddHeaderParameter {
-  parameter_id: RequestParameterId,
-  request_id: RequestId,
-  name: String,
+  pub parameter_id: RequestParameterId,
+  pub request_id: RequestId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetHeaderParameterShape {
-  parameter_id: RequestParameterId,
-  parameter_descriptor: ShapedRequestParameterShapeDescriptor,
+  pub parameter_id: RequestParameterId,
+  pub parameter_descriptor: ShapedRequestParameterShapeDescriptor,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameHeaderParameter {
-  parameter_id: RequestParameterId,
-  name: String,
+  pub parameter_id: RequestParameterId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnsetHeaderParameterShape {
-  parameter_id: RequestParameterId,
+  pub parameter_id: RequestParameterId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveHeaderParameter {
-  parameter_id: RequestParameterId,
+  pub parameter_id: RequestParameterId,
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddResponseHeaderParameter {
+  pub parameter_id: RequestParameterId,
+  pub response_id: ResponseId,
+  pub name: String,
 }
 
 // Command handling
@@ -628,6 +724,17 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
         ))]
       }
 
+      EndpointCommand::UnsetRequestBodyShape(command) => {
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to unset request body shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestBodyUnset::from(command),
+        )]
+      }
+
       EndpointCommand::RemoveRequest(command) => {
         validation.require(
           validation.request_exists(&command.request_id),
@@ -667,6 +774,17 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
         ))]
       }
 
+      EndpointCommand::UnsetResponseBodyShape(command) => {
+        validation.require(
+          validation.response_exists(&command.response_id),
+          "response must exist to unset response body shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::ResponseBodyUnset::from(command),
+        )]
+      }
+
       EndpointCommand::RemoveResponse(command) => {
         validation.require(
           validation.response_exists(&command.response_id),
@@ -678,6 +796,94 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
         ))]
       }
 
+      // Headers
+      // -------
+      EndpointCommand::AddHeaderParameter(command) => {
+        validation.require(
+          !validation.request_parameter_id_exists(&command.parameter_id),
+          "parameter id must be assignable to add header parameter",
+        )?;
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to add header parameter",
+        )?;
+        validation.require(
+          !validation.request_has_header_parameter_named(&command.request_id, &command.name),
+          "endpoint must not have an existing header parameter by that name to add header parameter",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterAdded::from(command),
+        )]
+      }
+
+      EndpointCommand::SetHeaderParameterShape(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to set header parameter shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterShapeSet::from(command),
+        )]
+      }
+
+      EndpointCommand::RenameHeaderParameter(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to rename header parameter",
+        )?;
+        validation.require(
+          !validation.header_parameter_name_conflicts(&command.parameter_id, &command.name),
+          "endpoint must not have another header parameter by that name to rename header parameter",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterRenamed::from(command),
+        )]
+      }
+
+      EndpointCommand::UnsetHeaderParameterShape(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to unset header parameter shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterShapeUnset::from(command),
+        )]
+      }
+
+      EndpointCommand::RemoveHeaderParameter(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to be removed",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterRemoved::from(command),
+        )]
+      }
+
+      EndpointCommand::AddResponseHeaderParameter(command) => {
+        validation.require(
+          !validation.request_parameter_id_exists(&command.parameter_id),
+          "parameter id must be assignable to add response header parameter",
+        )?;
+        validation.require(
+          validation.response_exists(&command.response_id),
+          "response must exist to add response header parameter",
+        )?;
+        validation.require(
+          !validation.response_has_header_parameter_named(&command.response_id, &command.name),
+          "response must not have an existing header parameter by that name to add response header parameter",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::ResponseParameterAdded::from(command),
+        )]
+      }
+
       _ => Err(SpecCommandError::Unimplemented(
         "endpoint command not implemented for endpoint projection",
         SpecCommand::EndpointCommand(self),
@@ -688,6 +894,34 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
   }
 }
 
+impl AggregateCommand<EndpointProjection> for ShapeCommand {
+  type Error = SpecCommandError;
+  type Event = EndpointEvent;
+  type Events = Vec<EndpointEvent>;
+
+  fn execute_on(self, projection: &EndpointProjection) -> Result<Self::Events, Self::Error> {
+    let validation = CommandValidationQueries::from((projection, &self));
+
+    let events = match self {
+      ShapeCommand::RemoveShape(command) => {
+        validation.require(
+          !validation.shape_id_in_use(&command.shape_id),
+          "shape must not be used by an endpoint to remove shape",
+        )?;
+
+        vec![] // validation only
+      }
+
+      _ => Err(SpecCommandError::Unimplemented(
+        "shape command not implemented for endpoint projection",
+        SpecCommand::ShapeCommand(self),
+      ))?,
+    };
+
+    Ok(events)
+  }
+}
+
 struct CommandValidationQueries<'a> {
   command_description: String,
   endpoint_projection: &'a EndpointProjection,
@@ -776,6 +1010,58 @@ impl<'a> CommandValidationQueries<'a> {
       .get_response_node_index(response_id)
       .is_some()
   }
+
+  pub fn request_parameter_id_exists(&self, parameter_id: &RequestParameterId) -> bool {
+    self
+      .endpoint_projection
+      .get_request_parameter_node_index(parameter_id)
+      .is_some()
+  }
+
+  pub fn header_parameter_exists(&self, parameter_id: &RequestParameterId) -> bool {
+    self
+      .endpoint_projection
+      .get_request_parameter_descriptor(parameter_id)
+      .map(|descriptor| descriptor.is_header())
+      .unwrap_or(false)
+  }
+
+  pub fn request_has_header_parameter_named(&self, request_id: &RequestId, name: &str) -> bool {
+    self
+      .endpoint_projection
+      .get_request_header_parameter_nodes(request_id)
+      .map(|mut headers| headers.any(|(_, descriptor)| descriptor.is_named(name)))
+      .unwrap_or(false)
+  }
+
+  pub fn response_has_header_parameter_named(&self, response_id: &ResponseId, name: &str) -> bool {
+    self
+      .endpoint_projection
+      .get_response_header_parameter_nodes(response_id)
+      .map(|mut headers| headers.any(|(_, descriptor)| descriptor.is_named(name)))
+      .unwrap_or(false)
+  }
+
+  pub fn header_parameter_name_conflicts(
+    &self,
+    parameter_id: &RequestParameterId,
+    name: &str,
+  ) -> bool {
+    self
+      .endpoint_projection
+      .get_sibling_header_parameter_nodes(parameter_id)
+      .map(|mut headers| {
+        headers
+          .any(|(sibling_id, descriptor)| sibling_id != parameter_id && descriptor.is_named(name))
+      })
+      .unwrap_or(false)
+  }
+
+  pub fn shape_id_in_use(&self, shape_id: &ShapeId) -> bool {
+    EndpointQueries::new(self.endpoint_projection)
+      .resolve_root_shape_ids()
+      .contains(shape_id)
+  }
 }
 
 impl<'a> From<(&'a EndpointProjection, &EndpointCommand)> for CommandValidationQueries<'a> {
@@ -789,6 +1075,15 @@ impl<'a> From<(&'a EndpointProjection, &EndpointCommand)> for CommandValidationQ
   }
 }
 
+impl<'a> From<(&'a EndpointProjection, &ShapeCommand)> for CommandValidationQueries<'a> {
+  fn from((endpoint_projection, shape_command): (&'a EndpointProjection, &ShapeCommand)) -> Self {
+    Self {
+      command_description: format!("{:?}", shape_command),
+      endpoint_projection,
+    }
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -1519,4 +1814,319 @@ mod test {
       projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
     }
   }
+
+  #[test]
+  pub fn can_handle_add_header_parameter_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "Authorization"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "header_2", "requestId": "request_1", "name": "X-Request-Id"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__new_events",
+      new_events
+    );
+
+    let unassignable_id: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "header_1", "requestId": "request_1", "name": "X-Request-Id"}}
+    ))
+    .unwrap();
+    let unassignable_id_result = projection.execute(unassignable_id);
+    assert!(unassignable_id_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__unassignable_id_result",
+      unassignable_id_result.unwrap_err()
+    );
+
+    let unexisting_request: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "header_2", "requestId": "not-a-request", "name": "X-Request-Id"}}
+    ))
+    .unwrap();
+    let unexisting_request_result = projection.execute(unexisting_request);
+    assert!(unexisting_request_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__unexisting_request_result",
+      unexisting_request_result.unwrap_err()
+    );
+
+    let existing_name: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "header_2", "requestId": "request_1", "name": "authorization"}}
+    ))
+    .unwrap();
+    let existing_name_result = projection.execute(existing_name);
+    assert!(existing_name_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__existing_name_result",
+      existing_name_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_set_header_parameter_shape_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "Authorization"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "query_1", "pathId": "path_1", "httpMethod": "POST", "parameterLocation": "query", "name": "queryString"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"SetHeaderParameterShape": {"parameterId": "header_1", "parameterDescriptor": { "shapeId": "shape_1", "isRemoved": false }}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_header_parameter_shape_command__new_events",
+      new_events
+    );
+
+    let unexisting_header: EndpointCommand = serde_json::from_value(json!(
+      {"SetHeaderParameterShape": {"parameterId": "not-a-header", "parameterDescriptor": { "shapeId": "shape_1", "isRemoved": false }}}
+    ))
+    .unwrap();
+    let unexisting_header_result = projection.execute(unexisting_header);
+    assert!(unexisting_header_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_header_parameter_shape_command__unexisting_header_result",
+      unexisting_header_result.unwrap_err()
+    );
+
+    let non_header_parameter: EndpointCommand = serde_json::from_value(json!(
+      {"SetHeaderParameterShape": {"parameterId": "query_1", "parameterDescriptor": { "shapeId": "shape_1", "isRemoved": false }}}
+    ))
+    .unwrap();
+    let non_header_parameter_result = projection.execute(non_header_parameter);
+    assert!(non_header_parameter_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_header_parameter_shape_command__non_header_parameter_result",
+      non_header_parameter_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_rename_header_parameter_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "Authorization"}},
+      {"RequestParameterAdded": {"parameterId": "header_2", "requestId": "request_1", "parameterLocation": "header", "name": "X-Request-Id"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"RenameHeaderParameter": {"parameterId": "header_2", "name": "X-Trace-Id"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_rename_header_parameter_command__new_events",
+      new_events
+    );
+
+    let unexisting_header: EndpointCommand = serde_json::from_value(json!(
+      {"RenameHeaderParameter": {"parameterId": "not-a-header", "name": "X-Trace-Id"}}
+    ))
+    .unwrap();
+    let unexisting_header_result = projection.execute(unexisting_header);
+    assert!(unexisting_header_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_header_parameter_command__unexisting_header_result",
+      unexisting_header_result.unwrap_err()
+    );
+
+    let conflicting_name: EndpointCommand = serde_json::from_value(json!(
+      {"RenameHeaderParameter": {"parameterId": "header_2", "name": "AUTHORIZATION"}}
+    ))
+    .unwrap();
+    let conflicting_name_result = projection.execute(conflicting_name);
+    assert!(conflicting_name_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_header_parameter_command__conflicting_name_result",
+      conflicting_name_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_unset_header_parameter_shape_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "Authorization"}},
+      {"RequestParameterShapeSet": {"parameterId": "header_1", "parameterDescriptor": { "shapeId": "shape_1", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"UnsetHeaderParameterShape": {"parameterId": "header_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_unset_header_parameter_shape_command__new_events",
+      new_events
+    );
+
+    let unexisting_header: EndpointCommand = serde_json::from_value(json!(
+      {"UnsetHeaderParameterShape": {"parameterId": "not-a-header"}}
+    ))
+    .unwrap();
+    let unexisting_header_result = projection.execute(unexisting_header);
+    assert!(unexisting_header_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_unset_header_parameter_shape_command__unexisting_header_result",
+      unexisting_header_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_remove_header_parameter_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "Authorization"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveHeaderParameter": {"parameterId": "header_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_remove_header_parameter_command__new_events",
+      new_events
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+
+    let removed_header: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveHeaderParameter": {"parameterId": "header_1"}}
+    ))
+    .unwrap();
+    let removed_header_result = projection.execute(removed_header);
+    assert!(removed_header_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_header_parameter_command__removed_header_result",
+      removed_header_result.unwrap_err()
+    );
+  }
+
+  #[test]
+  pub fn can_handle_add_response_header_parameter_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "X-RateLimit-Remaining"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+      {"ResponseParameterAdded": {"parameterId": "header_2", "responseId": "response_1", "parameterLocation": "header", "name": "X-Request-Id"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"AddResponseHeaderParameter": {"parameterId": "header_3", "responseId": "response_1", "name": "X-RateLimit-Remaining"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_add_response_header_parameter_command__new_events",
+      new_events
+    );
+
+    let unexisting_response: EndpointCommand = serde_json::from_value(json!(
+      {"AddResponseHeaderParameter": {"parameterId": "header_3", "responseId": "not-a-response", "name": "X-RateLimit-Remaining"}}
+    ))
+    .unwrap();
+    let unexisting_response_result = projection.execute(unexisting_response);
+    assert!(unexisting_response_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_response_header_parameter_command__unexisting_response_result",
+      unexisting_response_result.unwrap_err()
+    );
+
+    let existing_name: EndpointCommand = serde_json::from_value(json!(
+      {"AddResponseHeaderParameter": {"parameterId": "header_3", "responseId": "response_1", "name": "x-request-id"}}
+    ))
+    .unwrap();
+    let existing_name_result = projection.execute(existing_name);
+    assert!(existing_name_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_response_header_parameter_command__existing_name_result",
+      existing_name_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+
+    let renamed_to_request_header_name: EndpointCommand = serde_json::from_value(json!(
+      {"RenameHeaderParameter": {"parameterId": "header_2", "name": "x-ratelimit-remaining"}}
+    ))
+    .unwrap();
+    assert!(
+      projection.execute(renamed_to_request_header_name).is_err(),
+      "response header names should conflict with other headers of the same response"
+    );
+  }
 }
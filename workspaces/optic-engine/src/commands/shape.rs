@@ -1,14 +1,19 @@
 use super::{EndpointCommand, SpecCommand, SpecCommandError};
 use crate::events::ShapeEvent;
+use crate::projections::shape::{Edge, Node};
 use crate::projections::ShapeProjection;
+use crate::queries::shape::ShapeQueries;
 use crate::state::shape::{
   FieldId, FieldShapeDescriptor, FieldShapeFromShape, ParameterShapeDescriptor, ProviderDescriptor,
-  ProviderInShape, ShapeId, ShapeKind, ShapeParameterId, ShapeParametersDescriptor,
+  ProviderInShape, ShapeConstraintDescriptor, ShapeId, ShapeKind, ShapeParameterId,
+  ShapeParametersDescriptor,
 };
 use crate::{
   events::shape as shape_events, shapehash::ShapeDescriptor, state::shape::ShapeProvider,
 };
 use cqrs_core::AggregateCommand;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -17,6 +22,7 @@ pub enum ShapeCommand {
   SetBaseShape(SetBaseShape),
   RenameShape(RenameShape),
   RemoveShape(RemoveShape),
+  SetShapeConstraint(SetShapeConstraint),
 
   // Shape parameters
   AddShapeParameter(AddShapeParameter),
@@ -40,6 +46,31 @@ impl ShapeCommand {
     ))
   }
 
+  pub fn set_base_shape(shape_id: ShapeId, base_shape_id: ShapeId) -> Self {
+    Self::SetBaseShape(SetBaseShape {
+      shape_id,
+      base_shape_id,
+    })
+  }
+
+  pub fn rename_shape(shape_id: ShapeId, name: String) -> Self {
+    Self::RenameShape(RenameShape { shape_id, name })
+  }
+
+  pub fn remove_shape(shape_id: ShapeId) -> Self {
+    Self::RemoveShape(RemoveShape { shape_id })
+  }
+
+  pub fn set_shape_constraint(
+    shape_id: ShapeId,
+    constraint_descriptor: ShapeConstraintDescriptor,
+  ) -> Self {
+    Self::SetShapeConstraint(SetShapeConstraint {
+      shape_id,
+      constraint_descriptor,
+    })
+  }
+
   pub fn add_shape_parameter(
     shape_parameter_id: ShapeParameterId,
     shape_id: ShapeId,
@@ -52,6 +83,17 @@ impl ShapeCommand {
     })
   }
 
+  pub fn rename_shape_parameter(shape_parameter_id: ShapeParameterId, name: String) -> Self {
+    Self::RenameShapeParameter(RenameShapeParameter {
+      shape_parameter_id,
+      name,
+    })
+  }
+
+  pub fn remove_shape_parameter(shape_parameter_id: ShapeParameterId) -> Self {
+    Self::RemoveShapeParameter(RemoveShapeParameter { shape_parameter_id })
+  }
+
   pub fn set_parameter_shape(
     shape_id: ShapeId,
     consuming_parameter_id: ShapeParameterId,
@@ -96,6 +138,10 @@ impl ShapeCommand {
     })
   }
 
+  pub fn rename_field(field_id: FieldId, name: String) -> Self {
+    Self::RenameField(RenameField { field_id, name })
+  }
+
   pub fn remove_field(field_id: FieldId) -> Self {
     Self::RemoveField(RemoveField { field_id })
   }
@@ -129,14 +175,21 @@ pub struct SetBaseShape {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameShape {
-  shape_id: ShapeId,
-  name: String,
+  pub shape_id: ShapeId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveShape {
-  shape_id: ShapeId,
+  pub shape_id: ShapeId,
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetShapeConstraint {
+  pub shape_id: ShapeId,
+  pub constraint_descriptor: ShapeConstraintDescriptor,
 }
 
 // Shape parameters
@@ -153,14 +206,14 @@ pub struct AddShapeParameter {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveShapeParameter {
-  shape_parameter_id: ShapeParameterId,
+  pub shape_parameter_id: ShapeParameterId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameShapeParameter {
-  shape_parameter_id: ShapeParameterId,
-  name: String,
+  pub shape_parameter_id: ShapeParameterId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -184,8 +237,8 @@ pub struct AddField {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameField {
-  field_id: FieldId,
-  name: String,
+  pub field_id: FieldId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -230,6 +283,15 @@ impl AggregateCommand<ShapeProjection> for EndpointCommand {
         vec![] // validation only
       }
 
+      EndpointCommand::SetHeaderParameterShape(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.parameter_descriptor.shape_id),
+          "shape must exist to set the header parameter shape",
+        )?;
+
+        vec![] // validation only
+      }
+
       EndpointCommand::SetRequestBodyShape(command) => {
         validation.require(
           validation.shape_id_exists(&command.body_descriptor.shape_id),
@@ -274,8 +336,9 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
           !validation.shape_id_exists(&command.shape_id),
           "shape id must be assignable to add shape",
         )?;
+        // shapes descending from other shapes can bind the parameters those declare
         validation.require(
-          validation.base_shape_id_exists(&command.base_shape_id),
+          validation.shape_id_exists(&command.base_shape_id),
           "base shape id must exist to add shape",
         )?;
 
@@ -299,6 +362,53 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         vec![ShapeEvent::from(shape_events::BaseShapeSet::from(command))]
       }
 
+      ShapeCommand::RenameShape(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to rename shape",
+        )?;
+        validation.require(
+          !validation.base_shape_id_exists(&command.shape_id),
+          "shape must not be base shape to rename shape",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeRenamed::from(command))]
+      }
+
+      ShapeCommand::RemoveShape(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to remove shape",
+        )?;
+        validation.require(
+          !validation.base_shape_id_exists(&command.shape_id),
+          "shape must not be base shape to remove shape",
+        )?;
+        validation.require(
+          !validation.shape_is_referenced(&command.shape_id),
+          "shape must not be referenced by other shapes or fields to remove shape",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeRemoved::from(command))]
+      }
+
+      ShapeCommand::SetShapeConstraint(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to set shape constraint",
+        )?;
+        if let Some(constrained_kind) = command.constraint_descriptor.constrained_kind() {
+          validation.require(
+            validation.shape_is_of_kind(&command.shape_id, &constrained_kind),
+            "shape must be of the constrained kind to set shape constraint",
+          )?;
+        }
+
+        vec![ShapeEvent::from(shape_events::ShapeConstraintSet::from(
+          command,
+        ))]
+      }
+
       // Fields
       // ------
       ShapeCommand::AddField(command) => {
@@ -317,10 +427,11 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
 
         let events = match &command.shape_descriptor {
           FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => {
-            Err(SpecCommandError::Unimplemented(
-              "FieldShapeFromParameter shape descriptor not implemented for add field command",
-              SpecCommand::ShapeCommand(ShapeCommand::AddField(command)),
-            ))?
+            validation.require(
+              validation.shape_parameter_id_exists(&descriptor.shape_parameter_id),
+              "shape parameter of shape descriptor must exist to add field",
+            )?;
+            vec![ShapeEvent::from(shape_events::FieldAdded::from(command))]
           }
           FieldShapeDescriptor::FieldShapeFromShape(descriptor) => {
             validation.require(
@@ -336,10 +447,16 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
 
       ShapeCommand::SetFieldShape(command) => match &command.shape_descriptor {
         FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => {
-          Err(SpecCommandError::Unimplemented(
-            "FieldShapeFromParameter shape descriptor not implemented for set field shape",
-            SpecCommand::ShapeCommand(ShapeCommand::SetFieldShape(command)),
-          ))?
+          validation.require(
+            validation.field_id_exists(&descriptor.field_id),
+            "field of shape descriptor must exist to set field shape",
+          )?;
+          validation.require(
+            validation.shape_parameter_id_exists(&descriptor.shape_parameter_id),
+            "shape parameter of shape descriptor must exist to set field shape",
+          )?;
+
+          vec![ShapeEvent::from(shape_events::FieldShapeSet::from(command))]
         }
         FieldShapeDescriptor::FieldShapeFromShape(descriptor) => {
           validation.require(
@@ -355,6 +472,15 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         }
       },
 
+      ShapeCommand::RenameField(command) => {
+        validation.require(
+          validation.field_id_exists(&command.field_id),
+          "field must exist to rename field",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::FieldRenamed::from(command))]
+      }
+
       ShapeCommand::RemoveField(command) => {
         validation.require(
           validation.field_id_exists(&command.field_id),
@@ -383,46 +509,84 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         ))]
       }
 
-      ShapeCommand::SetParameterShape(command) => match &command.shape_descriptor {
-        ParameterShapeDescriptor::ProviderInShape(descriptor) => {
-          validation.require(
-            validation.shape_id_exists(&descriptor.shape_id),
-            "shape must exist to set parameter shape",
-          )?;
-          validation.require(
-            validation.shape_parameter_id_exists(&descriptor.consuming_parameter_id),
-            "consuming parameter must exist to set shape parameter shape",
-          )?;
+      ShapeCommand::RenameShapeParameter(command) => {
+        validation.require(
+          validation.shape_parameter_id_exists(&command.shape_parameter_id),
+          "shape parameter must exist to rename shape parameter",
+        )?;
+        validation.require(
+          !validation.shape_parameter_is_of_base_shape(&command.shape_parameter_id),
+          "shape parameter must not be of a base shape to rename shape parameter",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeParameterRenamed::from(
+          command,
+        ))]
+      }
+
+      ShapeCommand::RemoveShapeParameter(command) => {
+        validation.require(
+          validation.shape_parameter_id_exists(&command.shape_parameter_id),
+          "shape parameter must exist to remove shape parameter",
+        )?;
+        validation.require(
+          !validation.shape_parameter_is_of_base_shape(&command.shape_parameter_id),
+          "shape parameter must not be of a base shape to remove shape parameter",
+        )?;
+        validation.require(
+          !validation.shape_parameter_is_referenced(&command.shape_parameter_id),
+          "shape parameter must not provide the shape of fields or parameters to remove shape parameter",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeParameterRemoved::from(
+          command,
+        ))]
+      }
 
-          match &descriptor.provider_descriptor {
-            ProviderDescriptor::NoProvider(_) | ProviderDescriptor::ParameterProvider(_) => {
-              Err(SpecCommandError::Unimplemented(
-                "only ShapeProvider provider descriptor is implemented for set shape parameter shape",
-                SpecCommand::ShapeCommand(ShapeCommand::SetParameterShape(command)),
-              ))?
-            }
-            ProviderDescriptor::ShapeProvider(provider) => {
-              validation.require(
-                validation.shape_id_exists(&provider.shape_id),
-                "provided shape must exist to set shape parameter shape",
-              )?;
-
-              vec![ShapeEvent::from(shape_events::ShapeParameterShapeSet::from(command))]
-            }
+      ShapeCommand::SetParameterShape(command) => {
+        let (consuming_parameter_id, provider_descriptor) = match &command.shape_descriptor {
+          ParameterShapeDescriptor::ProviderInShape(descriptor) => {
+            validation.require(
+              validation.shape_id_exists(&descriptor.shape_id),
+              "shape must exist to set parameter shape",
+            )?;
+            (
+              &descriptor.consuming_parameter_id,
+              &descriptor.provider_descriptor,
+            )
           }
-        }
-        ParameterShapeDescriptor::ProviderInField(provider) => {
-          Err(SpecCommandError::Unimplemented(
-            "ProviderInFIeld shape descriptor not implemented for set parameter shape",
-            SpecCommand::ShapeCommand(ShapeCommand::SetParameterShape(command)),
-          ))?
-        }
-      },
+          ParameterShapeDescriptor::ProviderInField(descriptor) => {
+            validation.require(
+              validation.field_id_exists(&descriptor.field_id),
+              "field must exist to set parameter shape",
+            )?;
+            (
+              &descriptor.consuming_parameter_id,
+              &descriptor.provider_descriptor,
+            )
+          }
+        };
+        validation.require(
+          validation.shape_parameter_id_exists(consuming_parameter_id),
+          "consuming parameter must exist to set shape parameter shape",
+        )?;
 
-      _ => Err(SpecCommandError::Unimplemented(
-        "shape command not implemented for shape projection",
-        SpecCommand::ShapeCommand(self),
-      ))?,
+        match provider_descriptor {
+          ProviderDescriptor::ShapeProvider(provider) => validation.require(
+            validation.shape_id_exists(&provider.shape_id),
+            "provided shape must exist to set shape parameter shape",
+          )?,
+          ProviderDescriptor::ParameterProvider(provider) => validation.require(
+            validation.shape_parameter_id_exists(&provider.shape_parameter_id),
+            "provided shape parameter must exist to set shape parameter shape",
+          )?,
+          ProviderDescriptor::NoProvider(_) => {} // unbinds the parameter
+        };
+
+        vec![ShapeEvent::from(
+          shape_events::ShapeParameterShapeSet::from(command),
+        )]
+      }
     };
 
     Ok(events)
@@ -474,6 +638,95 @@ impl<'a> CommandValidationQueries<'a> {
       .is_some()
   }
 
+  // shapes are referenced by the fields they're the shape of, the shapes descending from them and
+  // the parameters they're bound to, for as long as those haven't been removed
+  fn shape_is_referenced(&self, shape_id: &ShapeId) -> bool {
+    let projection = self.shape_projection;
+    let shape_node_index = match projection.get_shape_node_index(shape_id) {
+      Some(node_index) => *node_index,
+      None => return false,
+    };
+
+    let is_referenced_by_fields = projection
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Outgoing)
+      .any(|edge| matches!(edge.weight(), Edge::BelongsTo) && self.node_exists(edge.target()));
+    let is_referenced_by_descendants = projection
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      .any(|edge| matches!(edge.weight(), Edge::IsDescendantOf) && self.node_exists(edge.source()));
+    let is_referenced_by_bindings = projection.graph.edge_references().any(|edge| {
+      matches!(edge.weight(), Edge::HasBinding(binding) if binding.shape_id == *shape_id)
+        && self.node_exists(edge.source())
+    });
+
+    is_referenced_by_fields || is_referenced_by_descendants || is_referenced_by_bindings
+  }
+
+  fn shape_parameter_is_of_base_shape(&self, shape_param_id: &ShapeParameterId) -> bool {
+    let projection = self.shape_projection;
+    let shape_parameter_node_index = match projection.get_shape_parameter_node_index(shape_param_id)
+    {
+      Some(node_index) => *node_index,
+      None => return false,
+    };
+
+    projection
+      .graph
+      .edges_directed(shape_parameter_node_index, petgraph::Direction::Outgoing)
+      .filter(|edge| matches!(edge.weight(), Edge::IsParameterOf))
+      .any(|edge| {
+        matches!(
+          projection.get_node_by_index(&edge.target()),
+          Some(Node::CoreShape(_))
+        )
+      })
+  }
+
+  // parameters are referenced by the fields they provide the shape for and the parameters they're
+  // bound to
+  fn shape_parameter_is_referenced(&self, shape_param_id: &ShapeParameterId) -> bool {
+    let projection = self.shape_projection;
+    let shape_parameter_node_index = match projection.get_shape_parameter_node_index(shape_param_id)
+    {
+      Some(node_index) => *node_index,
+      None => return false,
+    };
+
+    let is_referenced_by_fields = projection
+      .graph
+      .edges_directed(shape_parameter_node_index, petgraph::Direction::Outgoing)
+      .any(|edge| matches!(edge.weight(), Edge::BelongsTo) && self.node_exists(edge.target()));
+    let is_referenced_by_bindings = projection.graph.edge_references().any(|edge| {
+      matches!(
+        edge.weight(),
+        Edge::HasParameterBinding(binding) if binding.shape_parameter_id == *shape_param_id
+      ) && self.node_exists(edge.source())
+    });
+
+    is_referenced_by_fields || is_referenced_by_bindings
+  }
+
+  // removed nodes are kept in the graph, but can no longer be looked up by their id
+  fn node_exists(&self, node_index: NodeIndex) -> bool {
+    let projection = self.shape_projection;
+    let node_id = match projection.get_node_by_index(&node_index) {
+      Some(Node::Shape(node)) => &node.shape_id,
+      Some(Node::CoreShape(node)) => &node.shape_id,
+      Some(Node::Field(node)) => &node.field_id,
+      Some(Node::ShapeParameter(node)) => &node.parameter_id,
+      _ => return false,
+    };
+
+    projection.node_id_to_index.get(node_id) == Some(&node_index)
+  }
+
+  fn shape_is_of_kind(&self, shape_id: &ShapeId, shape_kind: &ShapeKind) -> bool {
+    // shapes can descend from other shapes, so resolve the core shape through its ancestors
+    self.shape_id_exists(shape_id)
+      && ShapeQueries::new(self.shape_projection).resolve_to_core_shape(shape_id) == shape_kind
+  }
+
   fn shape_can_have_fields(&self, shape_id: &ShapeId) -> bool {
     let shape_node_index = self.shape_projection.get_shape_node_index(shape_id);
 
@@ -609,6 +862,60 @@ mod test {
     }
   }
 
+  #[test]
+  pub fn can_handle_set_shape_constraint_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"shape_2","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}}
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"SetShapeConstraint":{"shapeId":"shape_1","constraintDescriptor":{"FormatConstraint":{"format":"date-time"}}}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_shape_constraint_command__new_events",
+      new_events
+    );
+
+    let unexisting_shape: ShapeCommand = serde_json::from_value(json!(
+      {"SetShapeConstraint":{"shapeId":"not-a-shape","constraintDescriptor":{"IntegerConstraint":{}}}}
+    ))
+    .unwrap();
+    let unexisting_shape_result = projection.execute(unexisting_shape);
+    assert!(unexisting_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_shape_constraint_command__unexisting_shape_result",
+      unexisting_shape_result.unwrap_err()
+    );
+
+    let mismatching_kind: ShapeCommand = serde_json::from_value(json!(
+      {"SetShapeConstraint":{"shapeId":"shape_2","constraintDescriptor":{"EnumConstraint":{"values":["a","b"]}}}}
+    ))
+    .unwrap();
+    let mismatching_kind_result = projection.execute(mismatching_kind);
+    assert!(mismatching_kind_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_shape_constraint_command__mismatching_kind_result",
+      mismatching_kind_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert!(projection
+      .get_shape_constraint(&String::from("shape_1"))
+      .is_some());
+  }
+
   #[test]
   pub fn can_handle_add_field_command() {
     let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
@@ -783,6 +1090,202 @@ mod test {
     }
   }
 
+  #[test]
+  pub fn can_handle_rename_field_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object", "name": "" }},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string", "name": "" }},
+      {"FieldAdded":{"fieldId": "field_1", "shapeId": "object_shape_1", "name": "firstName", "shapeDescriptor":{ "FieldShapeFromShape": { "shapeId": "string_shape_1", "fieldId": "field_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command =
+      ShapeCommand::rename_field(String::from("field_1"), String::from("givenName"));
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_rename_field_command__new_events", new_events);
+
+    let unexisting_field =
+      ShapeCommand::rename_field(String::from("not_a_field"), String::from("givenName"));
+    let unexisting_field_result = projection.execute(unexisting_field);
+    assert!(unexisting_field_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_field_command__unexisting_field_result",
+      unexisting_field_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event);
+    }
+    let field_names = ShapeQueries::new(&projection)
+      .resolve_shape_field_id_and_names(&String::from("object_shape_1"))
+      .map(|(_, name)| name.clone())
+      .collect::<Vec<_>>();
+    assert_eq!(field_names, vec![String::from("givenName")]);
+  }
+
+  #[test]
+  pub fn can_handle_rename_shape_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object", "name": "Author" }},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RenameShape":{ "shapeId": "object_shape_1", "name": "User" }}
+    ))
+    .expect("example command should be a valid command");
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_rename_shape_command__new_events", new_events);
+
+    let base_shape = ShapeCommand::rename_shape(String::from("$object"), String::from("User"));
+    let base_shape_result = projection.execute(base_shape);
+    assert!(base_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_shape_command__base_shape_result",
+      base_shape_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event);
+    }
+    assert_eq!(
+      projection.get_shape_name(&String::from("object_shape_1")),
+      Some(&String::from("User"))
+    );
+  }
+
+  #[test]
+  pub fn can_handle_remove_shape_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object", "name": "User" }},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string", "name": "" }},
+      {"ShapeAdded":{"shapeId":"string_shape_2","baseShapeId":"$string", "name": "" }},
+      {"FieldAdded":{"fieldId": "field_1", "shapeId": "object_shape_1", "name": "firstName", "shapeDescriptor":{ "FieldShapeFromShape": { "shapeId": "string_shape_1", "fieldId": "field_1"}}}},
+      {"FieldAdded":{"fieldId": "field_2", "shapeId": "object_shape_1", "name": "lastName", "shapeDescriptor":{ "FieldShapeFromShape": { "shapeId": "string_shape_2", "fieldId": "field_2"}}}},
+      {"FieldRemoved":{"fieldId": "field_2" }},
+      {"ShapeAdded":{"shapeId":"list_shape_1","baseShapeId":"$list", "name": "" }},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"list_shape_1","providerDescriptor":{"ShapeProvider":{"shapeId":"object_shape_1"}},"consumingParameterId":"$listItem"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let field_shape = ShapeCommand::remove_shape(String::from("string_shape_1"));
+    let field_shape_result = projection.execute(field_shape);
+    assert!(field_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_command__field_shape_result",
+      field_shape_result.unwrap_err()
+    );
+
+    let bound_shape = ShapeCommand::remove_shape(String::from("object_shape_1"));
+    let bound_shape_result = projection.execute(bound_shape);
+    assert!(bound_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_command__bound_shape_result",
+      bound_shape_result.unwrap_err()
+    );
+
+    let base_shape = ShapeCommand::remove_shape(String::from("$string"));
+    assert!(projection.execute(base_shape).is_err());
+
+    // shapes of removed fields are no longer referenced
+    let valid_command = ShapeCommand::remove_shape(String::from("string_shape_2"));
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_remove_shape_command__new_events", new_events);
+
+    // removing the list releases the object, which takes its fields along
+    for shape_id in vec!["list_shape_1", "object_shape_1", "string_shape_1"] {
+      let events = projection
+        .execute(ShapeCommand::remove_shape(String::from(shape_id)))
+        .expect("unreferenced shapes should be removable");
+      for event in events {
+        projection.apply(event);
+      }
+    }
+    for event in new_events {
+      projection.apply(event);
+    }
+
+    for shape_id in vec![
+      "list_shape_1",
+      "object_shape_1",
+      "string_shape_1",
+      "string_shape_2",
+    ] {
+      assert!(projection
+        .get_shape_node_index(&String::from(shape_id))
+        .is_none());
+    }
+    assert!(projection
+      .get_field_node_index(&String::from("field_1"))
+      .is_none());
+    assert!(projection
+      .get_shape_name(&String::from("object_shape_1"))
+      .is_none());
+  }
+
+  #[test]
+  pub fn can_handle_remove_shape_parameter_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object", "name": "Page" }},
+      {"ShapeParameterAdded":{"shapeParameterId":"parameter_1","shapeId":"object_shape_1","name":"T","shapeDescriptor":{"ProviderInShape":{"shapeId":"object_shape_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"parameter_1"}}}},
+      {"ShapeParameterAdded":{"shapeParameterId":"parameter_2","shapeId":"object_shape_1","name":"U","shapeDescriptor":{"ProviderInShape":{"shapeId":"object_shape_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"parameter_2"}}}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"items","shapeDescriptor":{"FieldShapeFromParameter":{"fieldId":"field_1","shapeParameterId":"parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let field_parameter = ShapeCommand::remove_shape_parameter(String::from("parameter_1"));
+    let field_parameter_result = projection.execute(field_parameter);
+    assert!(field_parameter_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_parameter_command__field_parameter_result",
+      field_parameter_result.unwrap_err()
+    );
+
+    let base_shape_parameter = ShapeCommand::remove_shape_parameter(String::from("$listItem"));
+    assert!(projection.execute(base_shape_parameter).is_err());
+
+    let rename_command =
+      ShapeCommand::rename_shape_parameter(String::from("parameter_2"), String::from("V"));
+    let rename_events = projection
+      .execute(rename_command)
+      .expect("valid command should yield new events");
+    assert_eq!(rename_events.len(), 1);
+
+    let valid_command = ShapeCommand::remove_shape_parameter(String::from("parameter_2"));
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_parameter_command__new_events",
+      new_events
+    );
+
+    for event in rename_events.into_iter().chain(new_events) {
+      projection.apply(event);
+    }
+    assert!(projection
+      .get_shape_parameter_node_index(&String::from("parameter_2"))
+      .is_none());
+  }
+
   #[test]
   pub fn can_handle_add_shape_parameter() {
     let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
@@ -893,14 +1396,82 @@ mod test {
     ))
     .unwrap();
     let no_shape_provider_result = projection.execute(no_shape_provider);
-    assert!(no_shape_provider_result.is_err());
+    assert!(
+      no_shape_provider_result.is_ok(),
+      "parameters can be unbound by setting no provider"
+    );
+
+    let unexisting_provided_parameter_id: ShapeCommand = serde_json::from_value(json!(
+      {"SetParameterShape": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "nullable_shape_1","providerDescriptor": {"ParameterProvider": { "shapeParameterId": "not-a-shape-parameter-id" }},"consumingParameterId": "$nullableInner"}}}}
+    ))
+    .unwrap();
+    let unexisting_provided_parameter_id_result =
+      projection.execute(unexisting_provided_parameter_id);
+    assert!(unexisting_provided_parameter_id_result.is_err());
     assert_debug_snapshot!(
-      "can_handle_set_parameter_shape_command__no_shape_provider_result",
-      no_shape_provider_result.unwrap_err()
+      "can_handle_set_parameter_shape_command__unexisting_provided_parameter_id_result",
+      unexisting_provided_parameter_id_result.unwrap_err()
     );
 
     for event in new_events {
       projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
     }
   }
+
+  #[test]
+  pub fn can_handle_generic_shape_commands() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"page_shape_1","baseShapeId":"$object","name":"Page"}},
+      {"ShapeParameterAdded":{"shapeParameterId":"page_item_1","shapeId":"page_shape_1","name":"T","shapeDescriptor":{"ProviderInShape":{"shapeId":"page_shape_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"page_item_1"}}}},
+      {"ShapeAdded":{"shapeId":"envelope_shape_1","baseShapeId":"$object","name":"Envelope"}},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","name":""}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_commands: Vec<ShapeCommand> = serde_json::from_value(json!([
+      {"AddField":{"fieldId": "field_1", "shapeId": "page_shape_1", "name": "items", "shapeDescriptor":{ "FieldShapeFromParameter": { "shapeParameterId": "page_item_1", "fieldId": "field_1"}}}},
+      {"AddShape":{"shapeId":"page_shape_2","baseShapeId":"page_shape_1","name":""}},
+      {"SetParameterShape": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "page_shape_2","providerDescriptor": {"ShapeProvider": { "shapeId": "string_shape_1" }},"consumingParameterId": "page_item_1"}}}},
+      {"AddField":{"fieldId": "field_2", "shapeId": "envelope_shape_1", "name": "page", "shapeDescriptor":{ "FieldShapeFromShape": { "shapeId": "page_shape_1", "fieldId": "field_2"}}}},
+      {"SetParameterShape": {"shapeDescriptor": {"ProviderInField": {"fieldId": "field_2","providerDescriptor": {"ShapeProvider": { "shapeId": "string_shape_1" }},"consumingParameterId": "page_item_1"}}}},
+    ]))
+    .expect("example commands should be valid commands");
+
+    let mut new_events = vec![];
+    for command in valid_commands {
+      let events = projection
+        .execute(command)
+        .expect("valid command should yield new events");
+      for event in events {
+        new_events.push(event.clone());
+        projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+      }
+    }
+    assert_eq!(new_events.len(), 5);
+    assert_debug_snapshot!("can_handle_generic_shape_commands__new_events", new_events);
+
+    let unexisting_field_parameter: ShapeCommand = serde_json::from_value(json!(
+      {"AddField":{"fieldId": "field_3", "shapeId": "page_shape_1", "name": "next", "shapeDescriptor":{ "FieldShapeFromParameter": { "shapeParameterId": "not-a-shape-parameter-id", "fieldId": "field_3"}}}}
+    ))
+    .unwrap();
+    let unexisting_field_parameter_result = projection.execute(unexisting_field_parameter);
+    assert!(unexisting_field_parameter_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_generic_shape_commands__unexisting_field_parameter_result",
+      unexisting_field_parameter_result.unwrap_err()
+    );
+
+    let unexisting_providing_field: ShapeCommand = serde_json::from_value(json!(
+      {"SetParameterShape": {"shapeDescriptor": {"ProviderInField": {"fieldId": "not-a-field","providerDescriptor": {"ShapeProvider": { "shapeId": "string_shape_1" }},"consumingParameterId": "page_item_1"}}}}
+    ))
+    .unwrap();
+    let unexisting_providing_field_result = projection.execute(unexisting_providing_field);
+    assert!(unexisting_providing_field_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_generic_shape_commands__unexisting_providing_field_result",
+      unexisting_providing_field_result.unwrap_err()
+    );
+  }
 }
@@ -0,0 +1,454 @@
+use super::{compare_specs, ChangeKind, ChangeLocation, SpecChange};
+use crate::events::{RfcEvent, ShapeEvent, SpecEvent};
+use crate::projections::{CommitId, HistoryProjection, SpecProjection};
+use crate::queries::history::HistoryQueries;
+use cqrs_core::Aggregate;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// List what changed to endpoints and their bodies in every batch commit after `from_batch_id`
+/// (or the root), up to and including `to_batch_id` (or the latest). `None` when either batch
+/// commit can't be found.
+pub fn changelog<I>(
+  events: I,
+  from_batch_id: Option<&CommitId>,
+  to_batch_id: Option<&CommitId>,
+) -> Option<Changelog>
+where
+  I: IntoIterator<Item = SpecEvent>,
+{
+  let events = events.into_iter().collect::<Vec<_>>();
+  let history = HistoryProjection::from(events.iter().cloned());
+  let history_queries = HistoryQueries::from(&history);
+
+  let to_batch_id = to_batch_id
+    .cloned()
+    .unwrap_or_else(|| history_queries.resolve_latest_batch_commit_id());
+  let mut batch_ids = history_queries.resolve_commit_ancestry(&to_batch_id)?;
+  if let Some(from_batch_id) = from_batch_id {
+    for batch_id in history_queries.resolve_commit_ancestry(from_batch_id)? {
+      batch_ids.remove(&batch_id);
+    }
+  }
+
+  let mut commits = vec![];
+  let mut spec_projection = SpecProjection::default();
+  let mut current_commit: Option<(CommitChangelog, SpecProjection, Vec<SpecEvent>)> = None;
+  for event in SpecProjection::events_at_commit(events, &to_batch_id)? {
+    spec_projection.apply(event.clone());
+
+    match event {
+      SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(e)) if batch_ids.contains(&e.batch_id) => {
+        current_commit = Some((
+          CommitChangelog::new(e.batch_id, e.commit_message),
+          spec_projection.clone(),
+          vec![],
+        ));
+      }
+      SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(e)) => {
+        if let Some((mut commit, before, batch_events)) = current_commit.take() {
+          if commit.batch_id == e.batch_id {
+            let comparison = compare_specs(&before, &spec_projection);
+            let renamed_fields = renamed_field_names(&before, &spec_projection, &batch_events);
+            commit.with_changes(comparison.changes, &renamed_fields);
+            commits.push(commit);
+          } else {
+            current_commit = Some((commit, before, batch_events));
+          }
+        }
+      }
+      event => {
+        if let Some((_, _, batch_events)) = &mut current_commit {
+          batch_events.push(event);
+        }
+      }
+    }
+  }
+
+  Some(Changelog { commits })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Changelog {
+  pub commits: Vec<CommitChangelog>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitChangelog {
+  pub batch_id: CommitId,
+  pub commit_message: String,
+  pub endpoints_added: Vec<EndpointChangelog>,
+  pub endpoints_removed: Vec<EndpointChangelog>,
+  pub bodies_changed: Vec<BodyChangelog>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointChangelog {
+  pub absolute_path: String,
+  pub http_method: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodyChangelog {
+  pub absolute_path: String,
+  pub http_method: String,
+  pub location: ChangeLocation,
+  pub change: BodyChange,
+  pub fields: Vec<FieldChangelog>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BodyChange {
+  Added,
+  Removed,
+  Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FieldChangelog {
+  Added {
+    trail: Vec<String>,
+  },
+  Removed {
+    trail: Vec<String>,
+  },
+  Renamed {
+    trail: Vec<String>,
+    name: String,
+  },
+  Retyped {
+    trail: Vec<String>,
+    before: Vec<String>,
+    after: Vec<String>,
+  },
+  MadeOptional {
+    trail: Vec<String>,
+  },
+  MadeRequired {
+    trail: Vec<String>,
+  },
+}
+
+impl CommitChangelog {
+  fn new(batch_id: CommitId, commit_message: String) -> Self {
+    Self {
+      batch_id,
+      commit_message,
+      endpoints_added: vec![],
+      endpoints_removed: vec![],
+      bodies_changed: vec![],
+    }
+  }
+
+  fn with_changes(&mut self, changes: Vec<SpecChange>, renamed_fields: &[(String, String)]) {
+    let changes = with_renamed_fields(changes, renamed_fields);
+
+    for (change, renamed_to) in changes {
+      let endpoint = EndpointChangelog {
+        absolute_path: change.absolute_path.clone(),
+        http_method: change.http_method.clone(),
+      };
+      let (body_change, field_change) = match (&change.change, renamed_to) {
+        (ChangeKind::EndpointAdded, _) => {
+          self.endpoints_added.push(endpoint);
+          continue;
+        }
+        (ChangeKind::EndpointRemoved, _) => {
+          self.endpoints_removed.push(endpoint);
+          continue;
+        }
+        (ChangeKind::ResponseAdded, _)
+        | (ChangeKind::RequestBodyAdded { .. }, _)
+        | (ChangeKind::ContentTypeAdded, _) => (BodyChange::Added, None),
+        (ChangeKind::ResponseRemoved, _)
+        | (ChangeKind::RequestBodyRemoved, _)
+        | (ChangeKind::ContentTypeRemoved, _) => (BodyChange::Removed, None),
+        (ChangeKind::FieldRemoved { .. }, Some(name)) => (
+          BodyChange::Changed,
+          Some(FieldChangelog::Renamed {
+            trail: change.trail.clone(),
+            name,
+          }),
+        ),
+        (ChangeKind::FieldAdded { .. }, _) => (
+          BodyChange::Changed,
+          Some(FieldChangelog::Added {
+            trail: change.trail.clone(),
+          }),
+        ),
+        (ChangeKind::FieldRemoved { .. }, None) => (
+          BodyChange::Changed,
+          Some(FieldChangelog::Removed {
+            trail: change.trail.clone(),
+          }),
+        ),
+        (ChangeKind::FieldMadeOptional, _) => (
+          BodyChange::Changed,
+          Some(FieldChangelog::MadeOptional {
+            trail: change.trail.clone(),
+          }),
+        ),
+        (ChangeKind::FieldMadeRequired, _) => (
+          BodyChange::Changed,
+          Some(FieldChangelog::MadeRequired {
+            trail: change.trail.clone(),
+          }),
+        ),
+        (ChangeKind::TypeNarrowed { before, after }, _)
+        | (ChangeKind::TypeWidened { before, after }, _)
+        | (ChangeKind::TypeChanged { before, after }, _) => (
+          BodyChange::Changed,
+          Some(FieldChangelog::Retyped {
+            trail: change.trail.clone(),
+            before: before.clone(),
+            after: after.clone(),
+          }),
+        ),
+        _ => continue,
+      };
+
+      // only bodies are listed, leaving out parameters and headers
+      if !matches!(
+        change.location,
+        ChangeLocation::RequestBody { .. }
+          | ChangeLocation::ResponseBody { .. }
+          | ChangeLocation::Response { .. }
+      ) {
+        continue;
+      }
+
+      let existing_body = self.bodies_changed.iter_mut().find(|body| {
+        body.absolute_path == change.absolute_path
+          && body.http_method == change.http_method
+          && body.location == change.location
+      });
+      let body = match existing_body {
+        Some(body) => body,
+        None => {
+          self.bodies_changed.push(BodyChangelog {
+            absolute_path: change.absolute_path.clone(),
+            http_method: change.http_method.clone(),
+            location: change.location.clone(),
+            change: body_change,
+            fields: vec![],
+          });
+          self.bodies_changed.last_mut().unwrap()
+        }
+      };
+      body.fields.extend(field_change);
+    }
+  }
+}
+
+impl Changelog {
+  pub fn to_markdown(&self) -> String {
+    let mut markdown = String::new();
+    for commit in &self.commits {
+      // the first line of a commit message is its title, the rest its description
+      let commit_message = commit.commit_message.trim();
+      let (title, description) = match commit_message.find('\n') {
+        Some(index) => (&commit_message[..index], commit_message[index..].trim()),
+        None => (commit_message, ""),
+      };
+      writeln!(markdown, "## {} (`{}`)\n", title.trim(), commit.batch_id).unwrap();
+      if !description.is_empty() {
+        writeln!(markdown, "{}\n", description).unwrap();
+      }
+
+      if commit.endpoints_added.is_empty()
+        && commit.endpoints_removed.is_empty()
+        && commit.bodies_changed.is_empty()
+      {
+        writeln!(markdown, "No changes to endpoints\n").unwrap();
+        continue;
+      }
+
+      for (title, endpoints) in &[
+        ("Endpoints added", &commit.endpoints_added),
+        ("Endpoints removed", &commit.endpoints_removed),
+      ] {
+        if endpoints.is_empty() {
+          continue;
+        }
+        writeln!(markdown, "### {}\n", title).unwrap();
+        for endpoint in endpoints.iter() {
+          writeln!(
+            markdown,
+            "- `{} {}`",
+            endpoint.http_method, endpoint.absolute_path
+          )
+          .unwrap();
+        }
+        writeln!(markdown).unwrap();
+      }
+
+      if !commit.bodies_changed.is_empty() {
+        writeln!(markdown, "### Bodies changed\n").unwrap();
+        for body in &commit.bodies_changed {
+          let change = match body.change {
+            BodyChange::Added => " added",
+            BodyChange::Removed => " removed",
+            BodyChange::Changed => "",
+          };
+          writeln!(
+            markdown,
+            "- `{} {}` {}{}",
+            body.http_method,
+            body.absolute_path,
+            location_markdown(&body.location),
+            change
+          )
+          .unwrap();
+          for field in &body.fields {
+            writeln!(markdown, "  - {}", field_markdown(field)).unwrap();
+          }
+        }
+        writeln!(markdown).unwrap();
+      }
+    }
+    markdown
+  }
+}
+
+fn location_markdown(location: &ChangeLocation) -> String {
+  match location {
+    ChangeLocation::RequestBody {
+      content_type: Some(content_type),
+    } => format!("request body ({})", content_type),
+    ChangeLocation::RequestBody { content_type: None } => String::from("request body"),
+    ChangeLocation::Response { status_code } => format!("response {}", status_code),
+    ChangeLocation::ResponseBody {
+      status_code,
+      content_type,
+    } => format!("response {} body ({})", status_code, content_type),
+    _ => String::new(),
+  }
+}
+
+fn field_markdown(field: &FieldChangelog) -> String {
+  match field {
+    FieldChangelog::Added { trail } => format!("field `{}` added", trail_markdown(trail)),
+    FieldChangelog::Removed { trail } => format!("field `{}` removed", trail_markdown(trail)),
+    FieldChangelog::Renamed { trail, name } => {
+      format!("field `{}` renamed to `{}`", trail_markdown(trail), name)
+    }
+    FieldChangelog::Retyped {
+      trail,
+      before,
+      after,
+    } => format!(
+      "field `{}` retyped from {} to {}",
+      trail_markdown(trail),
+      types_markdown(before),
+      types_markdown(after)
+    ),
+    FieldChangelog::MadeOptional { trail } => {
+      format!("field `{}` made optional", trail_markdown(trail))
+    }
+    FieldChangelog::MadeRequired { trail } => {
+      format!("field `{}` made required", trail_markdown(trail))
+    }
+  }
+}
+
+fn trail_markdown(trail: &[String]) -> String {
+  let mut markdown = String::new();
+  for component in trail {
+    if !markdown.is_empty() && component != "[]" && component != "{}" {
+      markdown.push('.');
+    }
+    markdown.push_str(component);
+  }
+  if markdown.is_empty() {
+    markdown.push_str("(root)");
+  }
+  markdown
+}
+
+fn types_markdown(types: &[String]) -> String {
+  if types.is_empty() {
+    String::from("any")
+  } else {
+    types.join(" or ")
+  }
+}
+
+// (old name, new name) of the fields renamed by the events of a batch
+fn renamed_field_names(
+  before: &SpecProjection,
+  after: &SpecProjection,
+  batch_events: &[SpecEvent],
+) -> Vec<(String, String)> {
+  let field_name = |spec: &SpecProjection, field_id: &String| {
+    spec
+      .shape()
+      .get_field_node(field_id)
+      .map(|(_, field_node)| field_node.descriptor.name.clone())
+  };
+
+  let renamed_field_ids = batch_events
+    .iter()
+    .filter_map(|event| match event {
+      SpecEvent::ShapeEvent(ShapeEvent::FieldRenamed(e)) => Some(&e.field_id),
+      _ => None,
+    })
+    .collect::<HashSet<_>>();
+
+  renamed_field_ids
+    .into_iter()
+    .filter_map(|field_id| {
+      let old_name = field_name(before, field_id)?;
+      let new_name = field_name(after, field_id)?;
+      Some((old_name, new_name)).filter(|(old_name, new_name)| old_name != new_name)
+    })
+    .collect()
+}
+
+// Pairs up removed and added fields at the same trail that are a renamed field, returning every
+// change along with the new name of the renamed fields (in place of their removal).
+fn with_renamed_fields(
+  changes: Vec<SpecChange>,
+  renamed_fields: &[(String, String)],
+) -> Vec<(SpecChange, Option<String>)> {
+  let mut renamed_additions = HashSet::new();
+  let mut renames = vec![None; changes.len()];
+
+  for (removal_index, removal) in changes.iter().enumerate() {
+    let (parent_trail, old_name) = match (&removal.change, removal.trail.split_last()) {
+      (ChangeKind::FieldRemoved { .. }, Some((old_name, parent_trail))) => (parent_trail, old_name),
+      _ => continue,
+    };
+    for (_, new_name) in renamed_fields
+      .iter()
+      .filter(|(renamed_from, _)| renamed_from == old_name)
+    {
+      let addition_index = changes.iter().position(|addition| {
+        matches!(addition.change, ChangeKind::FieldAdded { .. })
+          && addition.absolute_path == removal.absolute_path
+          && addition.http_method == removal.http_method
+          && addition.location == removal.location
+          && addition.trail.split_last() == Some((new_name, parent_trail))
+      });
+      if let Some(addition_index) = addition_index {
+        renamed_additions.insert(addition_index);
+        renames[removal_index] = Some(new_name.clone());
+        break;
+      }
+    }
+  }
+
+  changes
+    .into_iter()
+    .zip(renames)
+    .enumerate()
+    .filter(|(index, _)| !renamed_additions.contains(index))
+    .map(|(_, change)| change)
+    .collect()
+}
@@ -0,0 +1,560 @@
+use crate::openapi::export::export_openapi;
+use crate::openapi::{OpenApi, Operation, Parameter, PathItem, Schema};
+use crate::projections::SpecProjection;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub mod changelog;
+mod schema;
+
+use schema::SchemaComparator;
+
+/// Compare two versions of a spec, classifying every change to its endpoints and shapes as
+/// either breaking or not for clients of the `before` version.
+pub fn compare_specs(before: &SpecProjection, after: &SpecProjection) -> SpecComparison {
+  compare_openapi(&export_openapi(before), &export_openapi(after))
+}
+
+/// Compare two OpenAPI documents, as exported from a spec. Endpoints are matched by their path
+/// pattern and method, ignoring the names of their path parameters.
+pub fn compare_openapi(before: &OpenApi, after: &OpenApi) -> SpecComparison {
+  let mut comparator = SpecComparator {
+    before,
+    after,
+    changes: vec![],
+  };
+
+  let before_operations = operations_by_endpoint(before);
+  let after_operations = operations_by_endpoint(after);
+  let endpoints = before_operations
+    .keys()
+    .chain(after_operations.keys())
+    .collect::<BTreeSet<_>>();
+
+  for endpoint in endpoints {
+    match (
+      before_operations.get(endpoint),
+      after_operations.get(endpoint),
+    ) {
+      (Some((path, _)), None) => comparator.push(
+        &Site::endpoint(path, &endpoint.1),
+        vec![],
+        ChangeKind::EndpointRemoved,
+      ),
+      (None, Some((path, _))) => comparator.push(
+        &Site::endpoint(path, &endpoint.1),
+        vec![],
+        ChangeKind::EndpointAdded,
+      ),
+      (Some((_, before_operation)), Some((path, after_operation))) => {
+        comparator.compare_operations(path, &endpoint.1, before_operation, after_operation)
+      }
+      (None, None) => unreachable!("endpoint should come from either document"),
+    }
+  }
+
+  SpecComparison {
+    changes: comparator.changes,
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecComparison {
+  pub changes: Vec<SpecChange>,
+}
+
+impl SpecComparison {
+  pub fn is_breaking(&self) -> bool {
+    self.changes.iter().any(|change| change.is_breaking)
+  }
+
+  pub fn breaking_changes(&self) -> impl Iterator<Item = &SpecChange> {
+    self.changes.iter().filter(|change| change.is_breaking)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecChange {
+  pub is_breaking: bool,
+  pub absolute_path: String,
+  pub http_method: String,
+  pub location: ChangeLocation,
+  // names of the fields leading to the change within a schema, `[]` for list items and `{}` for
+  // map values
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub trail: Vec<String>,
+  pub change: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "in", rename_all = "camelCase")]
+pub enum ChangeLocation {
+  Endpoint,
+  #[serde(rename_all = "camelCase")]
+  Parameter {
+    location: String,
+    name: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  RequestBody {
+    content_type: Option<String>,
+  },
+  #[serde(rename_all = "camelCase")]
+  Response {
+    status_code: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  ResponseHeader {
+    status_code: String,
+    name: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  ResponseBody {
+    status_code: String,
+    content_type: String,
+  },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ChangeKind {
+  EndpointAdded,
+  EndpointRemoved,
+  ResponseAdded,
+  ResponseRemoved,
+  ContentTypeAdded,
+  ContentTypeRemoved,
+  ParameterAdded {
+    required: bool,
+  },
+  ParameterRemoved {
+    required: bool,
+  },
+  ParameterMadeRequired,
+  ParameterMadeOptional,
+  RequestBodyAdded {
+    required: bool,
+  },
+  RequestBodyRemoved,
+  RequestBodyMadeRequired,
+  RequestBodyMadeOptional,
+  FieldAdded {
+    required: bool,
+  },
+  FieldRemoved {
+    required: bool,
+  },
+  FieldMadeRequired,
+  FieldMadeOptional,
+  // json types of a schema, where no types means any value is allowed
+  TypeNarrowed {
+    before: Vec<String>,
+    after: Vec<String>,
+  },
+  TypeWidened {
+    before: Vec<String>,
+    after: Vec<String>,
+  },
+  TypeChanged {
+    before: Vec<String>,
+    after: Vec<String>,
+  },
+  EnumAdded {
+    values: Vec<serde_json::Value>,
+  },
+  EnumRemoved {
+    values: Vec<serde_json::Value>,
+  },
+  EnumValuesAdded {
+    values: Vec<serde_json::Value>,
+  },
+  EnumValuesRemoved {
+    values: Vec<serde_json::Value>,
+  },
+  FormatChanged {
+    before: Option<String>,
+    after: Option<String>,
+  },
+}
+
+// Which way values flow: clients send requests and receive responses. Making what's sent
+// stricter or what's received looser breaks clients, the opposite of which is compatible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+  Request,
+  Response,
+}
+
+impl ChangeKind {
+  fn is_breaking(&self, direction: Direction) -> bool {
+    let is_request = direction == Direction::Request;
+    match self {
+      ChangeKind::EndpointAdded | ChangeKind::ResponseAdded => false,
+      ChangeKind::EndpointRemoved | ChangeKind::ResponseRemoved => true,
+      ChangeKind::ContentTypeAdded => false,
+      ChangeKind::ContentTypeRemoved => true,
+      ChangeKind::ParameterAdded { required } | ChangeKind::FieldAdded { required } => {
+        is_request && *required
+      }
+      ChangeKind::ParameterRemoved { required } | ChangeKind::FieldRemoved { required } => {
+        !is_request && *required
+      }
+      ChangeKind::RequestBodyAdded { required } => *required,
+      ChangeKind::RequestBodyRemoved | ChangeKind::RequestBodyMadeOptional => false,
+      ChangeKind::RequestBodyMadeRequired => true,
+      ChangeKind::ParameterMadeRequired | ChangeKind::FieldMadeRequired => is_request,
+      ChangeKind::ParameterMadeOptional | ChangeKind::FieldMadeOptional => !is_request,
+      ChangeKind::TypeNarrowed { .. } | ChangeKind::EnumAdded { .. } => is_request,
+      ChangeKind::EnumValuesRemoved { .. } => is_request,
+      ChangeKind::TypeWidened { .. } | ChangeKind::EnumRemoved { .. } => !is_request,
+      ChangeKind::EnumValuesAdded { .. } => !is_request,
+      ChangeKind::TypeChanged { .. } => true,
+      ChangeKind::FormatChanged { before, after } => match (before, after) {
+        (None, Some(_)) => is_request,
+        (Some(_), None) => !is_request,
+        _ => true,
+      },
+    }
+  }
+}
+
+// Where in an endpoint a change happened
+#[derive(Debug, Clone)]
+struct Site {
+  absolute_path: String,
+  http_method: String,
+  location: ChangeLocation,
+  direction: Direction,
+}
+
+impl Site {
+  fn endpoint(absolute_path: &str, http_method: &str) -> Self {
+    Self {
+      absolute_path: String::from(absolute_path),
+      http_method: http_method.to_uppercase(),
+      location: ChangeLocation::Endpoint,
+      direction: Direction::Response,
+    }
+  }
+
+  fn at(&self, location: ChangeLocation, direction: Direction) -> Self {
+    Self {
+      location,
+      direction,
+      ..self.clone()
+    }
+  }
+}
+
+// operations keyed by their path pattern (without parameter names) and method, along with the
+// absolute path they were documented at
+fn operations_by_endpoint(document: &OpenApi) -> BTreeMap<(String, String), (String, Operation)> {
+  let mut operations = BTreeMap::new();
+  for (path, path_item) in &document.paths {
+    let path_pattern = path
+      .split('/')
+      .map(|component| {
+        if component.starts_with('{') && component.ends_with('}') {
+          "{}"
+        } else {
+          component
+        }
+      })
+      .collect::<Vec<_>>()
+      .join("/");
+    for (method, operation) in &path_item.operations {
+      operations
+        .entry((path_pattern.clone(), method.clone()))
+        .or_insert_with(|| {
+          (
+            path.clone(),
+            with_path_item_parameters(path_item, operation),
+          )
+        });
+    }
+  }
+  operations
+}
+
+fn with_path_item_parameters(path_item: &PathItem, operation: &Operation) -> Operation {
+  let mut operation = operation.clone();
+  let path_item_parameters = path_item
+    .parameters
+    .iter()
+    .filter(|parameter| {
+      !operation
+        .parameters
+        .iter()
+        .any(|existing| existing.location == parameter.location && existing.name == parameter.name)
+    })
+    .cloned()
+    .collect::<Vec<_>>();
+  operation.parameters.extend(path_item_parameters);
+  operation
+}
+
+// parameters keyed by their location and name
+fn parameters_by_name(parameters: &[Parameter]) -> BTreeMap<(String, String), &Parameter> {
+  parameters
+    .iter()
+    .filter(|parameter| parameter.location != "path")
+    .map(|parameter| {
+      (
+        (parameter.location.clone(), parameter.name.clone()),
+        parameter,
+      )
+    })
+    .collect()
+}
+
+struct SpecComparator<'a> {
+  before: &'a OpenApi,
+  after: &'a OpenApi,
+  changes: Vec<SpecChange>,
+}
+
+impl<'a> SpecComparator<'a> {
+  fn push(&mut self, site: &Site, trail: Vec<String>, change: ChangeKind) {
+    self.changes.push(SpecChange {
+      is_breaking: change.is_breaking(site.direction),
+      absolute_path: site.absolute_path.clone(),
+      http_method: site.http_method.clone(),
+      location: site.location.clone(),
+      trail,
+      change,
+    });
+  }
+
+  fn compare_schemas(&mut self, site: &Site, before: &Schema, after: &Schema) {
+    let changes = SchemaComparator::new(self.before, self.after).compare(before, after);
+    for (trail, change) in changes {
+      self.push(site, trail, change);
+    }
+  }
+
+  fn compare_operations(
+    &mut self,
+    absolute_path: &str,
+    http_method: &str,
+    before: &Operation,
+    after: &Operation,
+  ) {
+    let endpoint_site = Site::endpoint(absolute_path, http_method);
+
+    self.compare_parameters(&endpoint_site, &before.parameters, &after.parameters);
+
+    let request_site = endpoint_site.at(
+      ChangeLocation::RequestBody { content_type: None },
+      Direction::Request,
+    );
+    match (&before.request_body, &after.request_body) {
+      (None, None) => {}
+      (Some(_), None) => self.push(&request_site, vec![], ChangeKind::RequestBodyRemoved),
+      (None, Some(after_body)) => self.push(
+        &request_site,
+        vec![],
+        ChangeKind::RequestBodyAdded {
+          required: after_body.required,
+        },
+      ),
+      (Some(before_body), Some(after_body)) => {
+        match (before_body.required, after_body.required) {
+          (false, true) => self.push(&request_site, vec![], ChangeKind::RequestBodyMadeRequired),
+          (true, false) => self.push(&request_site, vec![], ChangeKind::RequestBodyMadeOptional),
+          _ => {}
+        }
+        let content_types = before_body
+          .content
+          .keys()
+          .chain(after_body.content.keys())
+          .collect::<BTreeSet<_>>();
+        for content_type in content_types {
+          let site = endpoint_site.at(
+            ChangeLocation::RequestBody {
+              content_type: Some(content_type.clone()),
+            },
+            Direction::Request,
+          );
+          match (
+            before_body.content.get(content_type),
+            after_body.content.get(content_type),
+          ) {
+            (Some(_), None) => self.push(&site, vec![], ChangeKind::ContentTypeRemoved),
+            (None, Some(_)) => self.push(&site, vec![], ChangeKind::ContentTypeAdded),
+            (Some(before_media), Some(after_media)) => {
+              if let (Some(before_schema), Some(after_schema)) =
+                (&before_media.schema, &after_media.schema)
+              {
+                self.compare_schemas(&site, before_schema, after_schema);
+              }
+            }
+            (None, None) => {}
+          }
+        }
+      }
+    }
+
+    let status_codes = before
+      .responses
+      .keys()
+      .chain(after.responses.keys())
+      .collect::<BTreeSet<_>>();
+    for status_code in status_codes {
+      let response_site = endpoint_site.at(
+        ChangeLocation::Response {
+          status_code: status_code.clone(),
+        },
+        Direction::Response,
+      );
+      let (before_response, after_response) = match (
+        before.responses.get(status_code),
+        after.responses.get(status_code),
+      ) {
+        (Some(_), None) => {
+          self.push(&response_site, vec![], ChangeKind::ResponseRemoved);
+          continue;
+        }
+        (None, Some(_)) => {
+          self.push(&response_site, vec![], ChangeKind::ResponseAdded);
+          continue;
+        }
+        (Some(before_response), Some(after_response)) => (before_response, after_response),
+        (None, None) => continue,
+      };
+
+      let header_names = before_response
+        .headers
+        .keys()
+        .chain(after_response.headers.keys())
+        .collect::<BTreeSet<_>>();
+      for name in header_names {
+        let site = endpoint_site.at(
+          ChangeLocation::ResponseHeader {
+            status_code: status_code.clone(),
+            name: name.clone(),
+          },
+          Direction::Response,
+        );
+        match (
+          before_response.headers.get(name),
+          after_response.headers.get(name),
+        ) {
+          (Some(before_header), None) => self.push(
+            &site,
+            vec![],
+            ChangeKind::ParameterRemoved {
+              required: before_header.required,
+            },
+          ),
+          (None, Some(after_header)) => self.push(
+            &site,
+            vec![],
+            ChangeKind::ParameterAdded {
+              required: after_header.required,
+            },
+          ),
+          (Some(before_header), Some(after_header)) => {
+            self.compare_requirement(&site, before_header.required, after_header.required);
+            if let (Some(before_schema), Some(after_schema)) =
+              (&before_header.schema, &after_header.schema)
+            {
+              self.compare_schemas(&site, before_schema, after_schema);
+            }
+          }
+          (None, None) => {}
+        }
+      }
+
+      let content_types = before_response
+        .content
+        .keys()
+        .chain(after_response.content.keys())
+        .collect::<BTreeSet<_>>();
+      for content_type in content_types {
+        let site = endpoint_site.at(
+          ChangeLocation::ResponseBody {
+            status_code: status_code.clone(),
+            content_type: content_type.clone(),
+          },
+          Direction::Response,
+        );
+        match (
+          before_response.content.get(content_type),
+          after_response.content.get(content_type),
+        ) {
+          (Some(_), None) => self.push(&site, vec![], ChangeKind::ContentTypeRemoved),
+          (None, Some(_)) => self.push(&site, vec![], ChangeKind::ContentTypeAdded),
+          (Some(before_media), Some(after_media)) => {
+            if let (Some(before_schema), Some(after_schema)) =
+              (&before_media.schema, &after_media.schema)
+            {
+              self.compare_schemas(&site, before_schema, after_schema);
+            }
+          }
+          (None, None) => {}
+        }
+      }
+    }
+  }
+
+  // path parameters are left out, as endpoints are matched on them already
+  fn compare_parameters(
+    &mut self,
+    endpoint_site: &Site,
+    before: &[Parameter],
+    after: &[Parameter],
+  ) {
+    let before_parameters = parameters_by_name(before);
+    let after_parameters = parameters_by_name(after);
+    let keys = before_parameters
+      .keys()
+      .chain(after_parameters.keys())
+      .collect::<BTreeSet<_>>();
+
+    for key in keys {
+      let (location, name) = key;
+      let site = endpoint_site.at(
+        ChangeLocation::Parameter {
+          location: location.clone(),
+          name: name.clone(),
+        },
+        Direction::Request,
+      );
+      match (before_parameters.get(key), after_parameters.get(key)) {
+        (Some(before_parameter), None) => self.push(
+          &site,
+          vec![],
+          ChangeKind::ParameterRemoved {
+            required: before_parameter.required,
+          },
+        ),
+        (None, Some(after_parameter)) => self.push(
+          &site,
+          vec![],
+          ChangeKind::ParameterAdded {
+            required: after_parameter.required,
+          },
+        ),
+        (Some(before_parameter), Some(after_parameter)) => {
+          self.compare_requirement(&site, before_parameter.required, after_parameter.required);
+          if let (Some(before_schema), Some(after_schema)) =
+            (&before_parameter.schema, &after_parameter.schema)
+          {
+            self.compare_schemas(&site, before_schema, after_schema);
+          }
+        }
+        (None, None) => {}
+      }
+    }
+  }
+
+  fn compare_requirement(&mut self, site: &Site, before_required: bool, after_required: bool) {
+    match (before_required, after_required) {
+      (false, true) => self.push(site, vec![], ChangeKind::ParameterMadeRequired),
+      (true, false) => self.push(site, vec![], ChangeKind::ParameterMadeOptional),
+      _ => {}
+    }
+  }
+}
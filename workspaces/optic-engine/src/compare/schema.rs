@@ -0,0 +1,286 @@
+use super::ChangeKind;
+use crate::openapi::{AdditionalProperties, OpenApi, Schema};
+use std::collections::{BTreeSet, HashSet};
+
+const COMPONENT_REFERENCE_PREFIX: &str = "#/components/schemas/";
+
+// Compares the schemas of the same location in two documents, resolving the references to their
+// components.
+pub struct SchemaComparator<'a> {
+  before: &'a OpenApi,
+  after: &'a OpenApi,
+  visiting_schemas: HashSet<(*const Schema, *const Schema)>,
+  changes: Vec<(Vec<String>, ChangeKind)>,
+}
+
+impl<'a> SchemaComparator<'a> {
+  pub fn new(before: &'a OpenApi, after: &'a OpenApi) -> Self {
+    Self {
+      before,
+      after,
+      visiting_schemas: HashSet::new(),
+      changes: vec![],
+    }
+  }
+
+  pub fn compare(mut self, before: &Schema, after: &Schema) -> Vec<(Vec<String>, ChangeKind)> {
+    self.compare_schemas(before, after, &mut vec![]);
+    self.changes
+  }
+
+  fn compare_schemas(&mut self, before: &Schema, after: &Schema, trail: &mut Vec<String>) {
+    let before = resolve(self.before, before);
+    let after = resolve(self.after, after);
+
+    // recursive schemas are compared until they recurse
+    let visiting = (before as *const Schema, after as *const Schema);
+    if !self.visiting_schemas.insert(visiting) {
+      return;
+    }
+
+    let before_types = json_types(self.before, before);
+    let after_types = json_types(self.after, after);
+    if before_types != after_types {
+      let change = match (&before_types, &after_types) {
+        (None, Some(_)) => Some(TypeVariance::Narrowed),
+        (Some(_), None) => Some(TypeVariance::Widened),
+        (Some(before_types), Some(after_types)) => {
+          if covers(before_types, after_types) {
+            Some(TypeVariance::Narrowed)
+          } else if covers(after_types, before_types) {
+            Some(TypeVariance::Widened)
+          } else {
+            Some(TypeVariance::Changed)
+          }
+        }
+        (None, None) => None,
+      };
+      if let Some(variance) = change {
+        let before = before_types
+          .clone()
+          .unwrap_or_default()
+          .into_iter()
+          .collect();
+        let after = after_types
+          .clone()
+          .unwrap_or_default()
+          .into_iter()
+          .collect();
+        let change = match variance {
+          TypeVariance::Narrowed => ChangeKind::TypeNarrowed { before, after },
+          TypeVariance::Widened => ChangeKind::TypeWidened { before, after },
+          TypeVariance::Changed => ChangeKind::TypeChanged { before, after },
+        };
+        self.changes.push((trail.clone(), change));
+      }
+    }
+
+    // compare the structure of the types both allow
+    for json_type in &["object", "array", "string", "number"] {
+      let before_branch = branch_of_type(self.before, before, json_type);
+      let after_branch = branch_of_type(self.after, after, json_type);
+      if let (Some(before_branch), Some(after_branch)) = (before_branch, after_branch) {
+        match *json_type {
+          "object" => self.compare_objects(before_branch, after_branch, trail),
+          "array" => {
+            if let (Some(before_items), Some(after_items)) =
+              (&before_branch.items, &after_branch.items)
+            {
+              trail.push(String::from("[]"));
+              self.compare_schemas(before_items, after_items, trail);
+              trail.pop();
+            }
+          }
+          _ => self.compare_constraints(before_branch, after_branch, trail),
+        }
+      }
+    }
+
+    self.visiting_schemas.remove(&visiting);
+  }
+
+  fn compare_objects(&mut self, before: &Schema, after: &Schema, trail: &mut Vec<String>) {
+    let field_names = before
+      .properties
+      .keys()
+      .chain(after.properties.keys())
+      .collect::<BTreeSet<_>>();
+
+    for name in field_names {
+      let before_required = before.required.contains(name);
+      let after_required = after.required.contains(name);
+      trail.push(name.clone());
+      match (before.properties.get(name), after.properties.get(name)) {
+        (Some(_), None) => self.changes.push((
+          trail.clone(),
+          ChangeKind::FieldRemoved {
+            required: before_required,
+          },
+        )),
+        (None, Some(_)) => self.changes.push((
+          trail.clone(),
+          ChangeKind::FieldAdded {
+            required: after_required,
+          },
+        )),
+        (Some(before_field), Some(after_field)) => {
+          match (before_required, after_required) {
+            (false, true) => self
+              .changes
+              .push((trail.clone(), ChangeKind::FieldMadeRequired)),
+            (true, false) => self
+              .changes
+              .push((trail.clone(), ChangeKind::FieldMadeOptional)),
+            _ => {}
+          }
+          self.compare_schemas(before_field, after_field, trail);
+        }
+        (None, None) => {}
+      }
+      trail.pop();
+    }
+
+    if let (
+      Some(AdditionalProperties::Schema(before_values)),
+      Some(AdditionalProperties::Schema(after_values)),
+    ) = (&before.additional_properties, &after.additional_properties)
+    {
+      trail.push(String::from("{}"));
+      self.compare_schemas(before_values, after_values, trail);
+      trail.pop();
+    }
+  }
+
+  fn compare_constraints(&mut self, before: &Schema, after: &Schema, trail: &[String]) {
+    match (enum_values(before), enum_values(after)) {
+      (None, Some(values)) => self
+        .changes
+        .push((trail.to_vec(), ChangeKind::EnumAdded { values })),
+      (Some(values), None) => self
+        .changes
+        .push((trail.to_vec(), ChangeKind::EnumRemoved { values })),
+      (Some(before_values), Some(after_values)) => {
+        let added = after_values
+          .iter()
+          .filter(|value| !before_values.contains(value))
+          .cloned()
+          .collect::<Vec<_>>();
+        let removed = before_values
+          .iter()
+          .filter(|value| !after_values.contains(value))
+          .cloned()
+          .collect::<Vec<_>>();
+        if !added.is_empty() {
+          self.changes.push((
+            trail.to_vec(),
+            ChangeKind::EnumValuesAdded { values: added },
+          ));
+        }
+        if !removed.is_empty() {
+          self.changes.push((
+            trail.to_vec(),
+            ChangeKind::EnumValuesRemoved { values: removed },
+          ));
+        }
+      }
+      (None, None) => {}
+    }
+
+    let before_format = format(before);
+    let after_format = format(after);
+    if before_format != after_format {
+      self.changes.push((
+        trail.to_vec(),
+        ChangeKind::FormatChanged {
+          before: before_format,
+          after: after_format,
+        },
+      ));
+    }
+  }
+}
+
+enum TypeVariance {
+  Narrowed,
+  Widened,
+  Changed,
+}
+
+fn resolve<'a>(document: &'a OpenApi, schema: &'a Schema) -> &'a Schema {
+  schema
+    .reference
+    .as_ref()
+    .and_then(|reference| reference.strip_prefix(COMPONENT_REFERENCE_PREFIX))
+    .and_then(|name| document.components.schemas.get(name))
+    .unwrap_or(schema)
+}
+
+// The json types allowed by a schema, or `None` when it allows any value
+fn json_types(document: &OpenApi, schema: &Schema) -> Option<BTreeSet<String>> {
+  let schema = resolve(document, schema);
+  let mut types = if !schema.one_of.is_empty() {
+    let mut types = BTreeSet::new();
+    for branch in &schema.one_of {
+      types.extend(json_types(document, branch)?);
+    }
+    types
+  } else {
+    let mut types = BTreeSet::new();
+    types.insert(schema.schema_type.clone()?);
+    types
+  };
+  if schema.nullable {
+    types.insert(String::from("null"));
+  }
+  Some(types)
+}
+
+// whether every one of the types is allowed by the allowed types, integers being numbers
+fn covers(allowed_types: &BTreeSet<String>, types: &BTreeSet<String>) -> bool {
+  types.iter().all(|json_type| {
+    allowed_types.contains(json_type)
+      || (json_type == "integer" && allowed_types.contains("number"))
+  })
+}
+
+// The (branch of a) schema describing values of a json type, numbers including integers
+fn branch_of_type<'a>(
+  document: &'a OpenApi,
+  schema: &'a Schema,
+  json_type: &str,
+) -> Option<&'a Schema> {
+  let schema = resolve(document, schema);
+  if !schema.one_of.is_empty() {
+    let mut branches = schema
+      .one_of
+      .iter()
+      .filter_map(|branch| branch_of_type(document, branch, json_type));
+    return match (branches.next(), branches.next()) {
+      (Some(branch), None) => Some(branch),
+      // ambiguous which branches correspond
+      _ => None,
+    };
+  }
+
+  match schema.schema_type.as_deref() {
+    Some(schema_type) if schema_type == json_type => Some(schema),
+    Some("integer") if json_type == "number" => Some(schema),
+    _ => None,
+  }
+}
+
+fn enum_values(schema: &Schema) -> Option<Vec<serde_json::Value>> {
+  schema
+    .other_keywords
+    .get("enum")
+    .and_then(|values| values.as_array())
+    .cloned()
+}
+
+fn format(schema: &Schema) -> Option<String> {
+  schema
+    .other_keywords
+    .get("format")
+    .and_then(|format| format.as_str())
+    .map(String::from)
+}
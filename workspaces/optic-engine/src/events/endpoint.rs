@@ -8,6 +8,7 @@ use crate::commands::EndpointCommand;
 use crate::state::endpoint::{
   PathComponentId, QueryParametersId, QueryParametersShapeDescriptor, RequestId,
   RequestParameterId, ResponseId, ShapedBodyDescriptor, ShapedRequestParameterShapeDescriptor,
+  HEADER_PARAMETER_LOCATION,
 };
 
 #[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
@@ -29,12 +30,16 @@ pub enum EndpointEvent {
   QueryParametersRemoved(QueryParametersRemoved),
 
   // request parameters
+  RequestParameterAdded(RequestParameterAdded),
   RequestParameterAddedByPathAndMethod(RequestParameterAddedByPathAndMethod),
   RequestParameterRenamed(RequestParameterRenamed),
   RequestParameterShapeSet(RequestParameterShapeSet),
   RequestParameterShapeUnset(RequestParameterShapeUnset),
   RequestParameterRemoved(RequestParameterRemoved),
 
+  // response parameters
+  ResponseParameterAdded(ResponseParameterAdded),
+
   // Request events
   RequestAdded(RequestAdded),
   RequestContentTypeSet(RequestContentTypeSet),
@@ -134,6 +139,26 @@ pub struct QueryParametersRemoved {
 
 #[derive(Deserialize, Debug, PartialEq, Serialize, Clone)] // request parameters
 #[serde(rename_all = "camelCase")]
+pub struct RequestParameterAdded {
+  pub parameter_id: RequestParameterId,
+  pub request_id: RequestId,
+  pub parameter_location: String,
+  pub name: String,
+  pub event_context: Option<EventContext>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseParameterAdded {
+  pub parameter_id: RequestParameterId,
+  pub response_id: ResponseId,
+  pub parameter_location: String,
+  pub name: String,
+  pub event_context: Option<EventContext>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct RequestParameterAddedByPathAndMethod {
   pub parameter_id: RequestParameterId,
   pub path_id: PathComponentId,
@@ -279,12 +304,16 @@ impl Event for EndpointEvent {
       EndpointEvent::QueryParametersRemoved(evt) => evt.event_type(),
 
       // request parameters
+      EndpointEvent::RequestParameterAdded(evt) => evt.event_type(),
       EndpointEvent::RequestParameterAddedByPathAndMethod(evt) => evt.event_type(),
       EndpointEvent::RequestParameterRenamed(evt) => evt.event_type(),
       EndpointEvent::RequestParameterShapeSet(evt) => evt.event_type(),
       EndpointEvent::RequestParameterShapeUnset(evt) => evt.event_type(),
       EndpointEvent::RequestParameterRemoved(evt) => evt.event_type(),
 
+      // response parameters
+      EndpointEvent::ResponseParameterAdded(evt) => evt.event_type(),
+
       // Request events
       EndpointEvent::RequestAdded(evt) => evt.event_type(),
       EndpointEvent::RequestContentTypeSet(evt) => evt.event_type(),
@@ -322,6 +351,7 @@ impl WithEventContext for EndpointEvent {
       EndpointEvent::QueryParametersRemoved(evt) => evt.event_context.replace(event_context),
 
       // request parameters
+      EndpointEvent::RequestParameterAdded(evt) => evt.event_context.replace(event_context),
       EndpointEvent::RequestParameterAddedByPathAndMethod(evt) => {
         evt.event_context.replace(event_context)
       }
@@ -330,6 +360,9 @@ impl WithEventContext for EndpointEvent {
       EndpointEvent::RequestParameterShapeUnset(evt) => evt.event_context.replace(event_context),
       EndpointEvent::RequestParameterRemoved(evt) => evt.event_context.replace(event_context),
 
+      // response parameters
+      EndpointEvent::ResponseParameterAdded(evt) => evt.event_context.replace(event_context),
+
       // Request events
       EndpointEvent::RequestAdded(evt) => evt.event_context.replace(event_context),
       EndpointEvent::RequestContentTypeSet(evt) => evt.event_context.replace(event_context),
@@ -346,6 +379,51 @@ impl WithEventContext for EndpointEvent {
       EndpointEvent::ResponseRemoved(evt) => evt.event_context.replace(event_context),
     };
   }
+
+  fn event_context(&self) -> Option<&EventContext> {
+    match self {
+      EndpointEvent::PathComponentAdded(evt) => evt.event_context.as_ref(),
+      EndpointEvent::PathComponentRenamed(evt) => evt.event_context.as_ref(),
+      EndpointEvent::PathComponentRemoved(evt) => evt.event_context.as_ref(),
+
+      // path parameters
+      EndpointEvent::PathParameterAdded(evt) => evt.event_context.as_ref(),
+      EndpointEvent::PathParameterShapeSet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::PathParameterRenamed(evt) => evt.event_context.as_ref(),
+      EndpointEvent::PathParameterRemoved(evt) => evt.event_context.as_ref(),
+
+      // query parameters
+      EndpointEvent::QueryParametersAdded(evt) => evt.event_context.as_ref(),
+      EndpointEvent::QueryParametersShapeSet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::QueryParametersRemoved(evt) => evt.event_context.as_ref(),
+
+      // request parameters
+      EndpointEvent::RequestParameterAdded(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestParameterAddedByPathAndMethod(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestParameterRenamed(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestParameterShapeSet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestParameterShapeUnset(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestParameterRemoved(evt) => evt.event_context.as_ref(),
+
+      // response parameters
+      EndpointEvent::ResponseParameterAdded(evt) => evt.event_context.as_ref(),
+
+      // Request events
+      EndpointEvent::RequestAdded(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestContentTypeSet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestBodySet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestBodyUnset(evt) => evt.event_context.as_ref(),
+      EndpointEvent::RequestRemoved(evt) => evt.event_context.as_ref(),
+
+      // Response events
+      EndpointEvent::ResponseAddedByPathAndMethod(evt) => evt.event_context.as_ref(),
+      EndpointEvent::ResponseStatusCodeSet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::ResponseContentTypeSet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::ResponseBodySet(evt) => evt.event_context.as_ref(),
+      EndpointEvent::ResponseBodyUnset(evt) => evt.event_context.as_ref(),
+      EndpointEvent::ResponseRemoved(evt) => evt.event_context.as_ref(),
+    }
+  }
 }
 
 impl Event for PathComponentAdded {
@@ -408,6 +486,12 @@ impl Event for QueryParametersRemoved {
   }
 }
 
+impl Event for RequestParameterAdded {
+  fn event_type(&self) -> &'static str {
+    "RequestParameterAdded"
+  }
+}
+
 impl Event for RequestParameterAddedByPathAndMethod {
   fn event_type(&self) -> &'static str {
     "RequestParameterAddedByPathAndMethod"
@@ -438,6 +522,12 @@ impl Event for RequestParameterRemoved {
   }
 }
 
+impl Event for ResponseParameterAdded {
+  fn event_type(&self) -> &'static str {
+    "ResponseParameterAdded"
+  }
+}
+
 impl Event for RequestAdded {
   fn event_type(&self) -> &'static str {
     "RequestAdded"
@@ -564,6 +654,42 @@ impl From<QueryParametersRemoved> for EndpointEvent {
   }
 }
 
+impl From<RequestParameterAdded> for EndpointEvent {
+  fn from(event: RequestParameterAdded) -> Self {
+    Self::RequestParameterAdded(event)
+  }
+}
+
+impl From<RequestParameterRenamed> for EndpointEvent {
+  fn from(event: RequestParameterRenamed) -> Self {
+    Self::RequestParameterRenamed(event)
+  }
+}
+
+impl From<RequestParameterShapeSet> for EndpointEvent {
+  fn from(event: RequestParameterShapeSet) -> Self {
+    Self::RequestParameterShapeSet(event)
+  }
+}
+
+impl From<RequestParameterShapeUnset> for EndpointEvent {
+  fn from(event: RequestParameterShapeUnset) -> Self {
+    Self::RequestParameterShapeUnset(event)
+  }
+}
+
+impl From<RequestParameterRemoved> for EndpointEvent {
+  fn from(event: RequestParameterRemoved) -> Self {
+    Self::RequestParameterRemoved(event)
+  }
+}
+
+impl From<ResponseParameterAdded> for EndpointEvent {
+  fn from(event: ResponseParameterAdded) -> Self {
+    Self::ResponseParameterAdded(event)
+  }
+}
+
 impl From<RequestAdded> for EndpointEvent {
   fn from(event: RequestAdded) -> Self {
     Self::RequestAdded(event)
@@ -576,6 +702,12 @@ impl From<RequestBodySet> for EndpointEvent {
   }
 }
 
+impl From<RequestBodyUnset> for EndpointEvent {
+  fn from(event: RequestBodyUnset) -> Self {
+    Self::RequestBodyUnset(event)
+  }
+}
+
 impl From<RequestRemoved> for EndpointEvent {
   fn from(event: RequestRemoved) -> Self {
     Self::RequestRemoved(event)
@@ -594,6 +726,12 @@ impl From<ResponseBodySet> for EndpointEvent {
   }
 }
 
+impl From<ResponseBodyUnset> for EndpointEvent {
+  fn from(event: ResponseBodyUnset) -> Self {
+    Self::ResponseBodyUnset(event)
+  }
+}
+
 impl From<ResponseRemoved> for EndpointEvent {
   fn from(event: ResponseRemoved) -> Self {
     Self::ResponseRemoved(event)
@@ -636,6 +774,24 @@ impl From<EndpointCommand> for EndpointEvent {
       EndpointCommand::RemoveQueryParameters(command) => {
         EndpointEvent::from(QueryParametersRemoved::from(command))
       }
+      EndpointCommand::AddHeaderParameter(command) => {
+        EndpointEvent::from(RequestParameterAdded::from(command))
+      }
+      EndpointCommand::SetHeaderParameterShape(command) => {
+        EndpointEvent::from(RequestParameterShapeSet::from(command))
+      }
+      EndpointCommand::RenameHeaderParameter(command) => {
+        EndpointEvent::from(RequestParameterRenamed::from(command))
+      }
+      EndpointCommand::UnsetHeaderParameterShape(command) => {
+        EndpointEvent::from(RequestParameterShapeUnset::from(command))
+      }
+      EndpointCommand::RemoveHeaderParameter(command) => {
+        EndpointEvent::from(RequestParameterRemoved::from(command))
+      }
+      EndpointCommand::AddResponseHeaderParameter(command) => {
+        EndpointEvent::from(ResponseParameterAdded::from(command))
+      }
       EndpointCommand::AddRequest(command) => EndpointEvent::from(RequestAdded::from(command)),
       EndpointCommand::SetRequestBodyShape(command) => {
         EndpointEvent::from(RequestBodySet::from(command))
@@ -759,6 +915,68 @@ impl From<endpoint_commands::RemoveQueryParameters> for QueryParametersRemoved {
   }
 }
 
+impl From<endpoint_commands::AddHeaderParameter> for RequestParameterAdded {
+  fn from(command: endpoint_commands::AddHeaderParameter) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      request_id: command.request_id,
+      parameter_location: String::from(HEADER_PARAMETER_LOCATION),
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::SetHeaderParameterShape> for RequestParameterShapeSet {
+  fn from(command: endpoint_commands::SetHeaderParameterShape) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      parameter_descriptor: command.parameter_descriptor,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::RenameHeaderParameter> for RequestParameterRenamed {
+  fn from(command: endpoint_commands::RenameHeaderParameter) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::UnsetHeaderParameterShape> for RequestParameterShapeUnset {
+  fn from(command: endpoint_commands::UnsetHeaderParameterShape) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::RemoveHeaderParameter> for RequestParameterRemoved {
+  fn from(command: endpoint_commands::RemoveHeaderParameter) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::AddResponseHeaderParameter> for ResponseParameterAdded {
+  fn from(command: endpoint_commands::AddResponseHeaderParameter) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      response_id: command.response_id,
+      parameter_location: String::from(HEADER_PARAMETER_LOCATION),
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
 impl From<endpoint_commands::AddRequest> for RequestAdded {
   fn from(command: endpoint_commands::AddRequest) -> Self {
     Self {
@@ -780,6 +998,15 @@ impl From<endpoint_commands::SetRequestBodyShape> for RequestBodySet {
   }
 }
 
+impl From<endpoint_commands::UnsetRequestBodyShape> for RequestBodyUnset {
+  fn from(command: endpoint_commands::UnsetRequestBodyShape) -> Self {
+    Self {
+      request_id: command.request_id,
+      event_context: None,
+    }
+  }
+}
+
 impl From<endpoint_commands::RemoveRequest> for RequestRemoved {
   fn from(command: endpoint_commands::RemoveRequest) -> Self {
     Self {
@@ -811,6 +1038,15 @@ impl From<endpoint_commands::SetResponseBodyShape> for ResponseBodySet {
   }
 }
 
+impl From<endpoint_commands::UnsetResponseBodyShape> for ResponseBodyUnset {
+  fn from(command: endpoint_commands::UnsetResponseBodyShape) -> Self {
+    Self {
+      response_id: command.response_id,
+      event_context: None,
+    }
+  }
+}
+
 impl From<endpoint_commands::RemoveResponse> for ResponseRemoved {
   fn from(command: endpoint_commands::RemoveResponse) -> Self {
     Self {
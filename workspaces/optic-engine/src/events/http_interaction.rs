@@ -1,6 +1,6 @@
 use super::EventLoadingError;
 use crate::shapehash;
-use crate::state::body::{BodyDescriptor, ParsedQueryString};
+use crate::state::body::{BodyDescriptor, ParsedHeaders, ParsedQueryString};
 use base64;
 use cqrs_core::Event;
 use protobuf::Message;
@@ -109,6 +109,16 @@ impl From<&ArbitraryData> for Option<BodyDescriptor> {
   }
 }
 
+impl From<&ArbitraryData> for Option<ParsedHeaders> {
+  fn from(data: &ArbitraryData) -> Self {
+    // headers can only be compared when their values were captured
+    let json_string = data.as_json_string.as_ref()?;
+    let json: serde_json::Value = serde_json::from_str(json_string)
+      .expect("as_json_string of ArbitraryData should always be valid json");
+    ParsedHeaders::from_json(json)
+  }
+}
+
 impl From<&QueryParametersData> for Option<BodyDescriptor> {
   fn from(query_param_data: &QueryParametersData) -> Self {
     let data = &query_param_data.data;
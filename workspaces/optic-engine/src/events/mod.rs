@@ -48,6 +48,14 @@ impl WithEventContext for SpecEvent {
       SpecEvent::ShapeEvent(evt) => evt.with_event_context(event_context),
     };
   }
+
+  fn event_context(&self) -> Option<&EventContext> {
+    match self {
+      SpecEvent::EndpointEvent(evt) => evt.event_context(),
+      SpecEvent::RfcEvent(evt) => evt.event_context(),
+      SpecEvent::ShapeEvent(evt) => evt.event_context(),
+    }
+  }
 }
 
 impl SpecEvent {
@@ -148,4 +156,5 @@ impl From<CommandContext> for EventContext {
 
 pub trait WithEventContext {
   fn with_event_context(&mut self, event_context: EventContext);
+  fn event_context(&self) -> Option<&EventContext>;
 }
@@ -80,6 +80,16 @@ impl WithEventContext for RfcEvent {
       RfcEvent::BatchCommitEnded(evt) => evt.event_context.replace(event_context),
     };
   }
+
+  fn event_context(&self) -> Option<&EventContext> {
+    match self {
+      RfcEvent::ContributionAdded(evt) => evt.event_context.as_ref(),
+      RfcEvent::APINamed(evt) => evt.event_context.as_ref(),
+      RfcEvent::GitStateSet(evt) => evt.event_context.as_ref(),
+      RfcEvent::BatchCommitStarted(evt) => evt.event_context.as_ref(),
+      RfcEvent::BatchCommitEnded(evt) => evt.event_context.as_ref(),
+    }
+  }
 }
 
 impl Event for ContributionAdded {
@@ -1,6 +1,7 @@
 use super::{EventContext, WithEventContext};
 use crate::state::shape::{
-  FieldShapeDescriptor, ParameterShapeDescriptor, ShapeParametersDescriptor,
+  FieldShapeDescriptor, ParameterShapeDescriptor, ShapeConstraintDescriptor,
+  ShapeParametersDescriptor,
 };
 use crate::{
   commands::shape as shape_commands,
@@ -20,6 +21,7 @@ pub enum ShapeEvent {
   BaseShapeSet(BaseShapeSet),
   ShapeRenamed(ShapeRenamed),
   ShapeRemoved(ShapeRemoved),
+  ShapeConstraintSet(ShapeConstraintSet),
   ShapeParameterAdded(ShapeParameterAdded),
   ShapeParameterShapeSet(ShapeParameterShapeSet),
   ShapeParameterRenamed(ShapeParameterRenamed),
@@ -67,6 +69,14 @@ pub struct ShapeRemoved {
   pub event_context: Option<EventContext>,
 }
 
+#[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShapeConstraintSet {
+  pub shape_id: ShapeId,
+  pub constraint_descriptor: ShapeConstraintDescriptor,
+  pub event_context: Option<EventContext>,
+}
+
 #[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ShapeParameterAdded {
@@ -138,6 +148,7 @@ impl Event for ShapeEvent {
       ShapeEvent::BaseShapeSet(evt) => evt.event_type(),
       ShapeEvent::ShapeRenamed(evt) => evt.event_type(),
       ShapeEvent::ShapeRemoved(evt) => evt.event_type(),
+      ShapeEvent::ShapeConstraintSet(evt) => evt.event_type(),
       ShapeEvent::ShapeParameterAdded(evt) => evt.event_type(),
       ShapeEvent::ShapeParameterShapeSet(evt) => evt.event_type(),
       ShapeEvent::ShapeParameterRenamed(evt) => evt.event_type(),
@@ -158,6 +169,7 @@ impl WithEventContext for ShapeEvent {
       ShapeEvent::BaseShapeSet(evt) => evt.event_context.replace(event_context),
       ShapeEvent::ShapeRenamed(evt) => evt.event_context.replace(event_context),
       ShapeEvent::ShapeRemoved(evt) => evt.event_context.replace(event_context),
+      ShapeEvent::ShapeConstraintSet(evt) => evt.event_context.replace(event_context),
       ShapeEvent::ShapeParameterAdded(evt) => evt.event_context.replace(event_context),
       ShapeEvent::ShapeParameterShapeSet(evt) => evt.event_context.replace(event_context),
       ShapeEvent::ShapeParameterRenamed(evt) => evt.event_context.replace(event_context),
@@ -169,6 +181,25 @@ impl WithEventContext for ShapeEvent {
       ShapeEvent::FieldRemoved(evt) => evt.event_context.replace(event_context),
     };
   }
+
+  fn event_context(&self) -> Option<&EventContext> {
+    match self {
+      ShapeEvent::ShapeAdded(evt) => evt.event_context.as_ref(),
+      ShapeEvent::BaseShapeSet(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeRenamed(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeRemoved(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeConstraintSet(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeParameterAdded(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeParameterShapeSet(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeParameterRenamed(evt) => evt.event_context.as_ref(),
+      ShapeEvent::ShapeParameterRemoved(evt) => evt.event_context.as_ref(),
+
+      ShapeEvent::FieldAdded(evt) => evt.event_context.as_ref(),
+      ShapeEvent::FieldShapeSet(evt) => evt.event_context.as_ref(),
+      ShapeEvent::FieldRenamed(evt) => evt.event_context.as_ref(),
+      ShapeEvent::FieldRemoved(evt) => evt.event_context.as_ref(),
+    }
+  }
 }
 
 impl Event for ShapeAdded {
@@ -195,6 +226,12 @@ impl Event for ShapeRemoved {
   }
 }
 
+impl Event for ShapeConstraintSet {
+  fn event_type(&self) -> &'static str {
+    "ShapeConstraintSet"
+  }
+}
+
 impl Event for ShapeParameterAdded {
   fn event_type(&self) -> &'static str {
     "ShapeParameterAdded"
@@ -255,6 +292,24 @@ impl From<BaseShapeSet> for ShapeEvent {
   }
 }
 
+impl From<ShapeRenamed> for ShapeEvent {
+  fn from(event: ShapeRenamed) -> Self {
+    Self::ShapeRenamed(event)
+  }
+}
+
+impl From<ShapeRemoved> for ShapeEvent {
+  fn from(event: ShapeRemoved) -> Self {
+    Self::ShapeRemoved(event)
+  }
+}
+
+impl From<ShapeConstraintSet> for ShapeEvent {
+  fn from(event: ShapeConstraintSet) -> Self {
+    Self::ShapeConstraintSet(event)
+  }
+}
+
 impl From<FieldAdded> for ShapeEvent {
   fn from(event: FieldAdded) -> Self {
     Self::FieldAdded(event)
@@ -267,6 +322,12 @@ impl From<FieldShapeSet> for ShapeEvent {
   }
 }
 
+impl From<FieldRenamed> for ShapeEvent {
+  fn from(event: FieldRenamed) -> Self {
+    Self::FieldRenamed(event)
+  }
+}
+
 impl From<FieldRemoved> for ShapeEvent {
   fn from(event: FieldRemoved) -> Self {
     Self::FieldRemoved(event)
@@ -285,6 +346,18 @@ impl From<ShapeParameterShapeSet> for ShapeEvent {
   }
 }
 
+impl From<ShapeParameterRenamed> for ShapeEvent {
+  fn from(event: ShapeParameterRenamed) -> Self {
+    Self::ShapeParameterRenamed(event)
+  }
+}
+
+impl From<ShapeParameterRemoved> for ShapeEvent {
+  fn from(event: ShapeParameterRemoved) -> Self {
+    Self::ShapeParameterRemoved(event)
+  }
+}
+
 // Conversions from commands
 // -------------------------
 
@@ -293,7 +366,13 @@ impl From<ShapeCommand> for ShapeEvent {
     match shape_command {
       ShapeCommand::AddShape(command) => ShapeEvent::from(ShapeAdded::from(command)),
       ShapeCommand::SetBaseShape(command) => ShapeEvent::from(BaseShapeSet::from(command)),
+      ShapeCommand::RenameShape(command) => ShapeEvent::from(ShapeRenamed::from(command)),
+      ShapeCommand::RemoveShape(command) => ShapeEvent::from(ShapeRemoved::from(command)),
+      ShapeCommand::SetShapeConstraint(command) => {
+        ShapeEvent::from(ShapeConstraintSet::from(command))
+      }
       ShapeCommand::AddField(command) => ShapeEvent::from(FieldAdded::from(command)),
+      ShapeCommand::RenameField(command) => ShapeEvent::from(FieldRenamed::from(command)),
       ShapeCommand::RemoveField(command) => ShapeEvent::from(FieldRemoved::from(command)),
       ShapeCommand::AddShapeParameter(command) => {
         ShapeEvent::from(ShapeParameterAdded::from(command))
@@ -301,10 +380,13 @@ impl From<ShapeCommand> for ShapeEvent {
       ShapeCommand::SetParameterShape(command) => {
         ShapeEvent::from(ShapeParameterShapeSet::from(command))
       }
-      _ => unimplemented!(
-        "conversion from shape command to shape event not implemented for variant: {:?}",
-        shape_command
-      ),
+      ShapeCommand::RenameShapeParameter(command) => {
+        ShapeEvent::from(ShapeParameterRenamed::from(command))
+      }
+      ShapeCommand::RemoveShapeParameter(command) => {
+        ShapeEvent::from(ShapeParameterRemoved::from(command))
+      }
+      ShapeCommand::SetFieldShape(command) => ShapeEvent::from(FieldShapeSet::from(command)),
     }
   }
 }
@@ -331,6 +413,35 @@ impl From<shape_commands::SetBaseShape> for BaseShapeSet {
   }
 }
 
+impl From<shape_commands::RenameShape> for ShapeRenamed {
+  fn from(command: shape_commands::RenameShape) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::RemoveShape> for ShapeRemoved {
+  fn from(command: shape_commands::RemoveShape) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::SetShapeConstraint> for ShapeConstraintSet {
+  fn from(command: shape_commands::SetShapeConstraint) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      constraint_descriptor: command.constraint_descriptor,
+      event_context: None,
+    }
+  }
+}
+
 impl From<shape_commands::AddField> for FieldAdded {
   fn from(command: shape_commands::AddField) -> Self {
     Self {
@@ -343,6 +454,16 @@ impl From<shape_commands::AddField> for FieldAdded {
   }
 }
 
+impl From<shape_commands::RenameField> for FieldRenamed {
+  fn from(command: shape_commands::RenameField) -> Self {
+    Self {
+      field_id: command.field_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
 impl From<shape_commands::RemoveField> for FieldRemoved {
   fn from(command: shape_commands::RemoveField) -> Self {
     Self {
@@ -385,3 +506,22 @@ impl From<shape_commands::SetParameterShape> for ShapeParameterShapeSet {
     }
   }
 }
+
+impl From<shape_commands::RenameShapeParameter> for ShapeParameterRenamed {
+  fn from(command: shape_commands::RenameShapeParameter) -> Self {
+    Self {
+      shape_parameter_id: command.shape_parameter_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::RemoveShapeParameter> for ShapeParameterRemoved {
+  fn from(command: shape_commands::RemoveShapeParameter) -> Self {
+    Self {
+      shape_parameter_id: command.shape_parameter_id,
+      event_context: None,
+    }
+  }
+}
@@ -0,0 +1,468 @@
+use crate::events::http_interaction::HttpInteraction;
+use crate::shapes::{JsonTrail, JsonTrailPathComponent, ShapeDiffResult};
+use regex::Regex;
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use thiserror::Error;
+
+use super::result::InteractionDiffResult;
+
+/// Configures which parts of interactions get diffed and how strictly.
+///
+/// Deserializes from (for example) a config file:
+/// ```json
+/// {
+///   "ignore": [{ "path": "/health/**" }, { "path": { "regex": "^/internal/" }, "methods": ["GET"] }],
+///   "skipContentTypes": ["text/html", "image/*"],
+///   "skipStatusCodes": ["5xx", 404],
+///   "ignoreTrails": ["$.meta.requestedAt", "$.items[].etag"],
+///   "lenient": true
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffConfig {
+  #[serde(flatten)]
+  pub filters: InteractionFilters,
+  /// Don't report object keys the spec doesn't describe
+  pub lenient: bool,
+}
+
+impl DiffConfig {
+  pub fn ignores_result(&self, result: &InteractionDiffResult) -> bool {
+    let json_trail = match result.json_trail() {
+      Some(json_trail) => json_trail.normalized(),
+      None => return false,
+    };
+
+    let is_ignored_trail = self.filters.ignores_trail(&json_trail);
+
+    let is_extra_key = self.lenient
+      && matches!(
+        result.shape_diff_result(),
+        Some(ShapeDiffResult::UnspecifiedShape { .. })
+      )
+      && matches!(
+        json_trail.last_component(),
+        Some(JsonTrailPathComponent::JsonObjectKey { .. })
+      );
+
+    is_ignored_trail || is_extra_key
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalyzeUndocumentedBodiesConfig {
+  #[serde(flatten)]
+  pub filters: InteractionFilters,
+  pub enums: EnumLearningConfig,
+}
+
+/// Decides when the strings observed at a json trail are few enough to be learned as an enum.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnumLearningConfig {
+  /// The most distinct values kept per trail, beyond which its strings are no enum
+  pub max_values: usize,
+  /// How many values have to be observed at a trail before its strings can be an enum
+  pub min_observations: usize,
+  /// How many times each distinct value has to be observed on average, so strings that are
+  /// mostly different from each other aren't an enum
+  pub min_observations_per_value: usize,
+}
+
+impl Default for EnumLearningConfig {
+  fn default() -> Self {
+    Self {
+      max_values: 8,
+      min_observations: 10,
+      min_observations_per_value: 3,
+    }
+  }
+}
+
+impl EnumLearningConfig {
+  pub fn learns_enum(&self, distinct_values: usize, observations: usize) -> bool {
+    distinct_values > 0
+      && distinct_values <= self.max_values
+      && observations >= self.min_observations
+      && observations >= distinct_values * self.min_observations_per_value
+  }
+}
+
+/// Filters shared between diffing and learning, deciding which interactions, bodies and trails are
+/// considered at all.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InteractionFilters {
+  pub ignore: Vec<IgnoreRule>,
+  pub skip_content_types: Vec<Pattern>,
+  pub skip_status_codes: Vec<StatusCodeRange>,
+  /// Shape diffs at (or nested under) any of these trails are dropped, and nothing is learned
+  /// about the values there
+  pub ignore_trails: Vec<IgnoredTrail>,
+}
+
+impl InteractionFilters {
+  pub fn ignores_interaction(&self, interaction: &HttpInteraction) -> bool {
+    let status_code = interaction.response.status_code;
+
+    self.ignore.iter().any(|rule| rule.matches(interaction))
+      || self
+        .skip_status_codes
+        .iter()
+        .any(|range| range.contains(status_code))
+  }
+
+  pub fn skips_content_type(&self, content_type: Option<&String>) -> bool {
+    let content_type = match content_type {
+      // parameters like charset don't change what kind of body it is
+      Some(content_type) => content_type.split(';').next().unwrap_or("").trim(),
+      None => return false,
+    };
+
+    self
+      .skip_content_types
+      .iter()
+      .any(|pattern| pattern.matches(&content_type.to_lowercase()))
+  }
+
+  pub fn ignores_trail(&self, json_trail: &JsonTrail) -> bool {
+    self
+      .ignore_trails
+      .iter()
+      .any(|ignored_trail| ignored_trail.matches(json_trail))
+  }
+}
+
+/// Ignores interactions matching the path pattern (if any) and one of the methods (if any)
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IgnoreRule {
+  pub path: Option<Pattern>,
+  pub methods: Vec<String>,
+}
+
+impl IgnoreRule {
+  pub fn matches(&self, interaction: &HttpInteraction) -> bool {
+    let request = &interaction.request;
+
+    let matches_path = match &self.path {
+      Some(pattern) => pattern.matches(&request.path),
+      None => true,
+    };
+    let matches_method = self.methods.is_empty()
+      || self
+        .methods
+        .iter()
+        .any(|method| method.eq_ignore_ascii_case(&request.method));
+
+    matches_path && matches_method
+  }
+}
+
+/// Either a glob (`*` matches within a path segment, `**` across them, `?` a single character) or
+/// a regular expression, written as `{ "regex": "..." }`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "PatternDescriptor")]
+pub struct Pattern {
+  regex: Regex,
+}
+
+impl Pattern {
+  pub fn glob(glob: &str) -> Result<Self, DiffConfigError> {
+    let mut expression = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '*' if chars.peek() == Some(&'*') => {
+          chars.next();
+          expression.push_str(".*");
+        }
+        '*' => expression.push_str("[^/]*"),
+        '?' => expression.push_str("[^/]"),
+        c => expression.push_str(&regex::escape(&c.to_string())),
+      }
+    }
+    expression.push('$');
+
+    Self::regex(&expression)
+  }
+
+  pub fn regex(expression: &str) -> Result<Self, DiffConfigError> {
+    let regex = Regex::new(expression)
+      .map_err(|err| DiffConfigError::InvalidPattern(String::from(expression), err))?;
+
+    Ok(Self { regex })
+  }
+
+  pub fn matches(&self, subject: &str) -> bool {
+    self.regex.is_match(subject)
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PatternDescriptor {
+  Glob(String),
+  Regex { regex: String },
+}
+
+impl TryFrom<PatternDescriptor> for Pattern {
+  type Error = DiffConfigError;
+
+  fn try_from(descriptor: PatternDescriptor) -> Result<Self, Self::Error> {
+    match descriptor {
+      PatternDescriptor::Glob(glob) => Self::glob(&glob),
+      PatternDescriptor::Regex { regex } => Self::regex(&regex),
+    }
+  }
+}
+
+/// An inclusive range of status codes, written as a single code (`404`, `"404"`), a class
+/// (`"5xx"`) or an explicit range (`"500-504"`).
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "StatusCodeRangeDescriptor")]
+pub struct StatusCodeRange {
+  pub start: u16,
+  pub end: u16,
+}
+
+impl StatusCodeRange {
+  pub fn contains(&self, status_code: u16) -> bool {
+    self.start <= status_code && status_code <= self.end
+  }
+}
+
+impl FromStr for StatusCodeRange {
+  type Err = DiffConfigError;
+
+  fn from_str(range: &str) -> Result<Self, Self::Err> {
+    let invalid = || DiffConfigError::InvalidStatusCodeRange(String::from(range));
+    let range = range.trim();
+
+    if range.is_ascii() && range.len() == 3 && range[1..].eq_ignore_ascii_case("xx") {
+      let class = range[..1].parse::<u16>().map_err(|_| invalid())?;
+      return Ok(Self {
+        start: class * 100,
+        end: class * 100 + 99,
+      });
+    }
+
+    let (start, end) = match range.find('-') {
+      Some(separator) => (&range[..separator], &range[separator + 1..]),
+      None => (range, range),
+    };
+    let start = start.trim().parse::<u16>().map_err(|_| invalid())?;
+    let end = end.trim().parse::<u16>().map_err(|_| invalid())?;
+
+    if start > end {
+      Err(invalid())
+    } else {
+      Ok(Self { start, end })
+    }
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StatusCodeRangeDescriptor {
+  Code(u16),
+  Range(String),
+}
+
+impl TryFrom<StatusCodeRangeDescriptor> for StatusCodeRange {
+  type Error = DiffConfigError;
+
+  fn try_from(descriptor: StatusCodeRangeDescriptor) -> Result<Self, Self::Error> {
+    match descriptor {
+      StatusCodeRangeDescriptor::Code(code) => Ok(Self {
+        start: code,
+        end: code,
+      }),
+      StatusCodeRangeDescriptor::Range(range) => range.parse(),
+    }
+  }
+}
+
+/// A json trail written as a path, like `$.items[].id`. Array items match at any index.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct IgnoredTrail {
+  json_trail: JsonTrail,
+}
+
+impl IgnoredTrail {
+  pub fn matches(&self, json_trail: &JsonTrail) -> bool {
+    let json_trail = json_trail.normalized();
+    json_trail == self.json_trail || json_trail.is_descendant_of(&self.json_trail)
+  }
+}
+
+impl FromStr for IgnoredTrail {
+  type Err = DiffConfigError;
+
+  fn from_str(path: &str) -> Result<Self, Self::Err> {
+    let invalid = || DiffConfigError::InvalidJsonTrail(String::from(path));
+    let mut remaining = path.trim().strip_prefix('$').ok_or_else(invalid)?;
+    let mut json_trail = JsonTrail::empty();
+
+    while !remaining.is_empty() {
+      if let Some(rest) = remaining.strip_prefix('.') {
+        let key_end = rest.find(&['.', '['][..]).unwrap_or(rest.len());
+        if key_end == 0 {
+          return Err(invalid());
+        }
+        json_trail = json_trail.with_object_key(String::from(&rest[..key_end]));
+        remaining = &rest[key_end..];
+      } else if let Some(rest) = remaining.strip_prefix('[') {
+        let index_end = rest.find(']').ok_or_else(invalid)?;
+        let index = &rest[..index_end];
+        if !(index.is_empty() || index == "*" || index.parse::<u32>().is_ok()) {
+          return Err(invalid());
+        }
+        json_trail = json_trail.with_array_item(0);
+        remaining = &rest[index_end + 1..];
+      } else {
+        return Err(invalid());
+      }
+    }
+
+    Ok(Self { json_trail })
+  }
+}
+
+impl TryFrom<String> for IgnoredTrail {
+  type Error = DiffConfigError;
+
+  fn try_from(path: String) -> Result<Self, Self::Error> {
+    path.parse()
+  }
+}
+
+#[derive(Debug, Error)]
+pub enum DiffConfigError {
+  #[error("invalid pattern '{}': {}", .0, .1)]
+  InvalidPattern(String, regex::Error),
+  #[error("invalid status code range '{}'", .0)]
+  InvalidStatusCodeRange(String),
+  #[error("invalid json trail '{}', expected a path like '$.items[].id'", .0)]
+  InvalidJsonTrail(String),
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn diff_config_can_be_deserialized() {
+    let config: DiffConfig = serde_json::from_value(json!({
+      "ignore": [
+        { "path": "/health/**" },
+        { "path": { "regex": "^/internal/" }, "methods": ["get"] },
+      ],
+      "skipContentTypes": ["text/html", "image/*"],
+      "skipStatusCodes": ["5xx", 404, "401-403"],
+      "ignoreTrails": ["$.meta.requestedAt", "$.items[].etag"],
+      "lenient": true
+    }))
+    .expect("example config should deserialize");
+
+    assert_eq!(config.filters.ignore.len(), 2);
+    assert_eq!(
+      config.filters.skip_status_codes,
+      vec![
+        StatusCodeRange {
+          start: 500,
+          end: 599
+        },
+        StatusCodeRange {
+          start: 404,
+          end: 404
+        },
+        StatusCodeRange {
+          start: 401,
+          end: 403
+        },
+      ]
+    );
+    assert_eq!(config.filters.ignore_trails.len(), 2);
+    assert!(config.lenient);
+
+    let empty_config: DiffConfig =
+      serde_json::from_value(json!({})).expect("empty config should deserialize");
+    assert!(empty_config.filters.ignore.is_empty());
+    assert!(!empty_config.lenient);
+
+    assert!(serde_json::from_value::<DiffConfig>(json!({ "skipStatusCodes": ["5x"] })).is_err());
+    assert!(serde_json::from_value::<DiffConfig>(json!({ "ignoreTrails": ["meta"] })).is_err());
+    assert!(serde_json::from_value::<DiffConfig>(
+      json!({ "ignore": [{ "path": { "regex": "(" } }] })
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn learner_config_can_be_deserialized() {
+    let config: AnalyzeUndocumentedBodiesConfig = serde_json::from_value(json!({
+      "skipContentTypes": ["text/html"],
+      "ignoreTrails": ["$.meta"],
+      "enums": { "maxValues": 3 }
+    }))
+    .expect("example config should deserialize");
+
+    assert_eq!(config.filters.skip_content_types.len(), 1);
+    assert_eq!(config.filters.ignore_trails.len(), 1);
+    assert_eq!(config.enums.max_values, 3);
+    assert_eq!(
+      config.enums.min_observations,
+      EnumLearningConfig::default().min_observations
+    );
+    assert!(config.enums.learns_enum(3, 10));
+    assert!(!config.enums.learns_enum(4, 10));
+    assert!(!config.enums.learns_enum(3, 9));
+    assert!(
+      !config.enums.learns_enum(3, 8),
+      "values should be observed repeatedly"
+    );
+    assert!(!EnumLearningConfig::default().learns_enum(8, 10));
+    assert!(EnumLearningConfig::default().learns_enum(8, 24));
+  }
+
+  #[test]
+  fn patterns_match_globs_and_regexes() {
+    let segment_glob = Pattern::glob("/todos/*").unwrap();
+    assert!(segment_glob.matches("/todos/1"));
+    assert!(!segment_glob.matches("/todos/1/comments"));
+
+    let deep_glob = Pattern::glob("/todos/**").unwrap();
+    assert!(deep_glob.matches("/todos/1/comments"));
+    assert!(!deep_glob.matches("/users/1"));
+
+    let literal_glob = Pattern::glob("/v1.0/status").unwrap();
+    assert!(literal_glob.matches("/v1.0/status"));
+    assert!(!literal_glob.matches("/v1x0/status"));
+
+    let regex = Pattern::regex("^/todos/[0-9]+$").unwrap();
+    assert!(regex.matches("/todos/12"));
+    assert!(!regex.matches("/todos/abc"));
+  }
+
+  #[test]
+  fn ignored_trails_match_nested_trails_and_any_array_item() {
+    let ignored_trail: IgnoredTrail = "$.items[].meta".parse().unwrap();
+
+    let items_trail = JsonTrail::empty().with_object_key(String::from("items"));
+    let meta_trail = items_trail
+      .with_array_item(3)
+      .with_object_key(String::from("meta"));
+
+    assert!(ignored_trail.matches(&meta_trail));
+    assert!(ignored_trail.matches(&meta_trail.with_object_key(String::from("updatedAt"))));
+    assert!(!ignored_trail.matches(&items_trail));
+    assert!(!ignored_trail.matches(&items_trail.with_array_item(0)));
+  }
+}
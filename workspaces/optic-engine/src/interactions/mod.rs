@@ -1,19 +1,30 @@
 use crate::events::http_interaction::{Body, HttpInteraction};
 use crate::learn_shape::{observe_body_trails, TrailObservationsResult, TrailValues};
+use crate::projections::endpoint::RequestParameterDescriptor;
 use crate::projections::{EndpointProjection, SpecProjection};
 use crate::protos::shapehash::ShapeDescriptor;
 use crate::queries::endpoint::EndpointQueries;
+use crate::queries::shape::ShapeQueries;
 use crate::shapes::diff as diff_shape;
 use crate::shapes::ShapeDiffResult;
-use crate::state::body::BodyDescriptor;
+use crate::shapes::ShapeTrail;
+use crate::state::body::{BodyDescriptor, ItemsDescriptor, ObjectDescriptor, ParsedHeaders};
+use crate::state::shape::{ShapeId, ShapeKind};
 
+mod config;
 pub mod result;
 mod traverser;
 mod visitors;
 
+pub use config::{
+  AnalyzeUndocumentedBodiesConfig, DiffConfig, DiffConfigError, EnumLearningConfig, IgnoreRule,
+  IgnoredTrail, InteractionFilters, Pattern, StatusCodeRange,
+};
+
 use result::InteractionTrail;
 pub use result::{
-  BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult, UnmatchedQueryParameters,
+  BodyAnalysisLocation, BodyAnalysisResult, HeaderAnalysisLocation, HeaderAnalysisResult,
+  InteractionDiffResult, UnmatchedQueryParameters,
 };
 use visitors::{InteractionVisitors, PathVisitor};
 
@@ -21,12 +32,22 @@ use visitors::{InteractionVisitors, PathVisitor};
 ///
 /// Will first try to match the interaction to a Request + Response pair from the spec. From there
 /// will either produce unmatched results or proceed to diff bodies of the Request and Response
-/// respectively.
+/// respectively. Interactions, bodies and shape diffs excluded by the config are left out.
 pub fn diff(
   spec_projection: &SpecProjection,
   http_interaction: HttpInteraction,
   config: &DiffConfig,
 ) -> Vec<InteractionDiffResult> {
+  if config.filters.ignores_interaction(&http_interaction) {
+    return vec![];
+  }
+  let skips_request_body = config
+    .filters
+    .skips_content_type(http_interaction.request.body.content_type.as_ref());
+  let skips_response_body = config
+    .filters
+    .skips_content_type(http_interaction.response.body.content_type.as_ref());
+
   let endpoint_projection = spec_projection.endpoint();
   let endpoint_queries = EndpointQueries::new(endpoint_projection);
   let interaction_traverser = traverser::Traverser::new(&endpoint_queries);
@@ -54,6 +75,80 @@ pub fn diff(
           })
           .collect()
       }
+      InteractionDiffResult::MatchedRequestHeader(result) => {
+        let headers: Option<ParsedHeaders> = (&http_interaction.request.headers).into();
+        let header_value = headers.and_then(|headers| {
+          header_value_descriptor(
+            spec_projection,
+            headers.values(&result.name),
+            &result.root_shape_id,
+          )
+        });
+        match header_value {
+          Some(header_value) => {
+            let shape_diff_results = diff_shape(
+              spec_projection.shape(),
+              Some(header_value),
+              &result.root_shape_id,
+            );
+            shape_diff_results
+              .into_iter()
+              .map(|shape_diff| {
+                InteractionDiffResult::UnmatchedRequestHeaderShape(
+                  result.clone().into_shape_diff(shape_diff),
+                )
+              })
+              .collect()
+          }
+          None if is_optional_shape(spec_projection, &result.root_shape_id) => vec![],
+          None => vec![InteractionDiffResult::MissingRequiredRequestHeader(
+            result.into_missing(),
+          )],
+        }
+      }
+      InteractionDiffResult::MatchedResponseHeader(result) => {
+        let headers: Option<ParsedHeaders> = (&http_interaction.response.headers).into();
+        let header_value = headers.and_then(|headers| {
+          header_value_descriptor(
+            spec_projection,
+            headers.values(&result.name),
+            &result.root_shape_id,
+          )
+        });
+        match header_value {
+          Some(header_value) => {
+            let shape_diff_results = diff_shape(
+              spec_projection.shape(),
+              Some(header_value),
+              &result.root_shape_id,
+            );
+            shape_diff_results
+              .into_iter()
+              .map(|shape_diff| {
+                InteractionDiffResult::UnmatchedResponseHeaderShape(
+                  result.clone().into_shape_diff(shape_diff),
+                )
+              })
+              .collect()
+          }
+          None if is_optional_shape(spec_projection, &result.root_shape_id) => vec![],
+          None => vec![InteractionDiffResult::MissingRequiredResponseHeader(
+            result.into_missing(),
+          )],
+        }
+      }
+      InteractionDiffResult::UnmatchedRequestBodyContentType(_)
+      | InteractionDiffResult::MatchedRequestBodyContentType(_)
+        if skips_request_body =>
+      {
+        vec![]
+      }
+      InteractionDiffResult::UnmatchedResponseBodyContentType(_)
+      | InteractionDiffResult::MatchedResponseBodyContentType(_)
+        if skips_response_body =>
+      {
+        vec![]
+      }
       InteractionDiffResult::MatchedRequestBodyContentType(result) => {
         // eprintln!("shape diffing for matched a request body content type");
         let body = &http_interaction.request.body.value;
@@ -94,18 +189,55 @@ pub fn diff(
         InteractionDiffResult::UnmatchedQueryParameters(UnmatchedQueryParameters::Unobserved(_)),
       )
     })
+    .filter(|result| !config.ignores_result(result))
     .collect()
 }
 
-#[derive(Clone, Debug)]
-pub struct DiffConfig {}
+/// Header values are always captured as text, so describe them as the kind of primitive that was
+/// documented, as long as the values allow for it.
+fn header_value_descriptor(
+  spec_projection: &SpecProjection,
+  values: Vec<&String>,
+  shape_id: &ShapeId,
+) -> Option<BodyDescriptor> {
+  let shape_queries = ShapeQueries::new(spec_projection.shape());
+  let choices = shape_queries.list_trail_choices(&ShapeTrail::new(shape_id.clone()));
+  let expects_kind = |kind: ShapeKind| choices.iter().any(|choice| choice.core_shape_kind == kind);
+
+  let describe_value = if expects_kind(ShapeKind::StringKind) {
+    |value: &String| BodyDescriptor::from(value)
+  } else if expects_kind(ShapeKind::NumberKind)
+    && values
+      .iter()
+      .all(|value| BodyDescriptor::number_from_text(value).is_some())
+  {
+    |value: &String| BodyDescriptor::number_from_text(value).unwrap()
+  } else if expects_kind(ShapeKind::BooleanKind)
+    && values.iter().all(|value| value.parse::<bool>().is_ok())
+  {
+    |_: &String| BodyDescriptor::Boolean
+  } else {
+    |value: &String| BodyDescriptor::from(value)
+  };
 
-impl Default for DiffConfig {
-  fn default() -> Self {
-    Self {}
+  let mut value_descriptors = values.into_iter().map(describe_value);
+  match value_descriptors.len() {
+    0 => None,
+    1 => value_descriptors.next(),
+    _ => Some(BodyDescriptor::Array(ItemsDescriptor::from(
+      value_descriptors,
+    ))),
   }
 }
 
+fn is_optional_shape(spec_projection: &SpecProjection, shape_id: &ShapeId) -> bool {
+  let shape_queries = ShapeQueries::new(spec_projection.shape());
+  matches!(
+    shape_queries.resolve_to_core_shape(shape_id),
+    ShapeKind::OptionalKind
+  )
+}
+
 /// Analysises the shapes of interactions that have request or response bodies with previously
 /// unseen content types. From the result, observed types per json trail, commands can be generated
 /// applyable to the spec that would make the interaction compliant. Results can also be merged with
@@ -123,9 +255,19 @@ pub fn analyze_undocumented_bodies<'a>(
   let interaction_traverser = traverser::Traverser::new(&endpoint_queries);
   let mut diff_visitors = visitors::diff::DiffVisitors::new();
 
-  interaction_traverser.traverse(&interaction, &mut diff_visitors);
-
-  let results = diff_visitors.take_results().unwrap();
+  let results = if config.filters.ignores_interaction(&interaction) {
+    vec![]
+  } else {
+    interaction_traverser.traverse(&interaction, &mut diff_visitors);
+    diff_visitors.take_results().unwrap()
+  };
+  let skips_request_body = config
+    .filters
+    .skips_content_type(interaction.request.body.content_type.as_ref());
+  let skips_response_body = config
+    .filters
+    .skips_content_type(interaction.response.body.content_type.as_ref());
+  let filters = config.filters.clone();
 
   results.into_iter().flat_map(move |result| match result {
     InteractionDiffResult::UnmatchedQueryParameters(diff) => {
@@ -133,7 +275,7 @@ pub fn analyze_undocumented_bodies<'a>(
         let maybe_query_params: Option<BodyDescriptor> = (&interaction.request.query).into();
         let query_params = maybe_query_params.or_else(|| Some(BodyDescriptor::empty_object()));
 
-        let query_trail_observations = observe_body_trails(query_params);
+        let query_trail_observations = observe_filtered_body_trails(query_params, &filters);
 
         vec![BodyAnalysisResult {
           body_location: BodyAnalysisLocation::from(diff),
@@ -143,9 +285,11 @@ pub fn analyze_undocumented_bodies<'a>(
         vec![]
       }
     }
+    InteractionDiffResult::UnmatchedRequestBodyContentType(_) if skips_request_body => vec![],
+    InteractionDiffResult::UnmatchedResponseBodyContentType(_) if skips_response_body => vec![],
     InteractionDiffResult::UnmatchedRequestBodyContentType(diff) => {
       let body = &interaction.request.body;
-      let body_trail_observations = observe_body_trails(&body.value);
+      let body_trail_observations = observe_filtered_body_trails(&body.value, &filters);
 
       vec![BodyAnalysisResult {
         body_location: BodyAnalysisLocation::from(diff.clone()),
@@ -154,7 +298,7 @@ pub fn analyze_undocumented_bodies<'a>(
     }
     InteractionDiffResult::UnmatchedResponseBodyContentType(diff) => {
       let body = &interaction.response.body;
-      let trail_observations = observe_body_trails(&body.value);
+      let trail_observations = observe_filtered_body_trails(&body.value, &filters);
 
       vec![BodyAnalysisResult {
         body_location: BodyAnalysisLocation::from(diff),
@@ -165,12 +309,142 @@ pub fn analyze_undocumented_bodies<'a>(
   })
 }
 
-#[derive(Clone, Debug)]
-pub struct AnalyzeUndocumentedBodiesConfig {}
+// Nothing is observed about the values at ignored trails, leaving them out of what gets learned
+fn observe_filtered_body_trails(
+  body: impl Into<Option<BodyDescriptor>>,
+  filters: &InteractionFilters,
+) -> TrailObservationsResult {
+  let body = body
+    .into()
+    .and_then(|body| body.without_trails(|trail| filters.ignores_trail(trail)));
+  observe_body_trails(body)
+}
+
+/// Analyses the headers of interactions that match a documented endpoint, but which aren't documented
+/// themselves yet. All undocumented headers of a request or response are observed as the fields of a
+/// single object, so that once results are merged, headers absent from some interactions can be
+/// learned as optional.
+///
+/// Requests are only analysed when their headers were captured and the endpoint has a request to
+/// add the headers to. Responses additionally require a documented response for their status code.
+/// Interactions excluded by the filters aren't analysed at all.
+pub fn analyze_undocumented_headers(
+  spec_projection: &SpecProjection,
+  interaction: HttpInteraction,
+  filters: &InteractionFilters,
+) -> impl Iterator<Item = HeaderAnalysisResult> {
+  let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+  let mut results = vec![];
+
+  if filters.ignores_interaction(&interaction) {
+    return results.into_iter();
+  }
+  let path_id = match endpoint_queries.resolve_interaction_path(&interaction) {
+    Some(path_id) => path_id,
+    None => return results.into_iter(),
+  };
+  let method = &interaction.request.method;
+
+  let request_headers: Option<ParsedHeaders> = (&interaction.request.headers).into();
+  let request_id = endpoint_queries
+    .resolve_requests(path_id, method)
+    .and_then(|mut requests| requests.next())
+    .map(|(request_id, _)| request_id.clone());
+  if let (Some(request_headers), Some(request_id)) = (request_headers, request_id) {
+    let documented_headers: Vec<_> = endpoint_queries
+      .resolve_endpoint_header_params(path_id, method)
+      .into_iter()
+      .flatten()
+      .map(|(_, header_descriptor)| header_descriptor)
+      .collect();
+
+    results.push(HeaderAnalysisResult {
+      header_location: HeaderAnalysisLocation::Request {
+        path_id: path_id.to_owned(),
+        method: method.clone(),
+        request_id,
+      },
+      trail_observations: observe_undocumented_headers(&request_headers, &documented_headers),
+    });
+  }
+
+  let status_code = interaction.response.status_code;
+  let response_headers: Option<ParsedHeaders> = (&interaction.response.headers).into();
+  let response_id = endpoint_queries
+    .resolve_responses_by_method_and_status_code(method, status_code, path_id)
+    .next()
+    .map(|(response_id, _)| response_id.clone());
+  if let (Some(response_headers), Some(response_id)) = (response_headers, response_id) {
+    let documented_headers: Vec<_> = endpoint_queries
+      .resolve_endpoint_response_header_params(path_id, method, status_code)
+      .into_iter()
+      .flatten()
+      .map(|(_, header_descriptor)| header_descriptor)
+      .collect();
+
+    results.push(HeaderAnalysisResult {
+      header_location: HeaderAnalysisLocation::Response {
+        path_id: path_id.to_owned(),
+        method: method.clone(),
+        status_code,
+        response_id,
+      },
+      trail_observations: observe_undocumented_headers(&response_headers, &documented_headers),
+    });
+  }
+
+  results.into_iter()
+}
+
+fn observe_undocumented_headers(
+  headers: &ParsedHeaders,
+  documented_headers: &[&RequestParameterDescriptor],
+) -> TrailObservationsResult {
+  let undocumented_headers = headers
+    .names()
+    .filter(|name| {
+      visitors::diff::is_tracked_header(name)
+        && !documented_headers
+          .iter()
+          .any(|header_descriptor| header_descriptor.is_named(name))
+    })
+    .map(|name| {
+      (
+        name.clone(),
+        observed_header_value_descriptor(headers.values(name)),
+      )
+    });
+
+  let mut trail_observations = observe_body_trails(BodyDescriptor::Object(ObjectDescriptor::from(
+    undocumented_headers,
+  )));
+  // header values can be credentials (like authorization headers, cookies and api keys), so none
+  // of them are kept, leaving header values to never be learned as enums
+  for trail_values in trail_observations.values_by_trail.values_mut() {
+    trail_values.exceed_string_values();
+  }
+  trail_observations
+}
+
+/// Header values are always captured as text, so describe them as the most specific primitive all
+/// of them can be read as.
+fn observed_header_value_descriptor(values: Vec<&String>) -> BodyDescriptor {
+  let describe_value = if values
+    .iter()
+    .all(|value| BodyDescriptor::number_from_text(value).is_some())
+  {
+    |value: &String| BodyDescriptor::number_from_text(value).unwrap()
+  } else if values.iter().all(|value| value.parse::<bool>().is_ok()) {
+    |_: &String| BodyDescriptor::Boolean
+  } else {
+    |value: &String| BodyDescriptor::from(value)
+  };
 
-impl Default for AnalyzeUndocumentedBodiesConfig {
-  fn default() -> Self {
-    Self {}
+  let mut value_descriptors = values.into_iter().map(describe_value);
+  if value_descriptors.len() > 1 {
+    BodyDescriptor::Array(ItemsDescriptor::from(value_descriptors))
+  } else {
+    value_descriptors.next().unwrap()
   }
 }
 
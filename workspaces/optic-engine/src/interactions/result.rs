@@ -1,7 +1,9 @@
 use crate::events::http_interaction::HttpInteraction;
 use crate::learn_shape::TrailObservationsResult;
 use crate::shapes::{JsonTrail, ShapeDiffResult};
-use crate::state::endpoint::{PathComponentId, QueryParametersId, RequestId, ResponseId, ShapeId};
+use crate::state::endpoint::{
+  PathComponentId, QueryParametersId, RequestId, RequestParameterId, ResponseId, ShapeId,
+};
 use seahash::hash;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::{DefaultHasher, HashMap};
@@ -10,6 +12,12 @@ use std::hash::{Hash, Hasher};
 pub enum InteractionDiffResult {
   UnmatchedQueryParameters(UnmatchedQueryParameters),
   UnmatchedQueryParametersShape(UnmatchedQueryParametersShape),
+  UnmatchedRequestHeader(UnmatchedRequestHeader),
+  UnmatchedRequestHeaderShape(UnmatchedRequestHeaderShape),
+  MissingRequiredRequestHeader(MissingRequiredRequestHeader),
+  UnmatchedResponseHeader(UnmatchedResponseHeader),
+  UnmatchedResponseHeaderShape(UnmatchedResponseHeaderShape),
+  MissingRequiredResponseHeader(MissingRequiredResponseHeader),
   UnmatchedRequestUrl(UnmatchedRequestUrl),
   UnmatchedRequestBodyContentType(UnmatchedRequestBodyContentType),
   UnmatchedRequestBodyShape(UnmatchedRequestBodyShape),
@@ -21,6 +29,10 @@ pub enum InteractionDiffResult {
   #[serde(skip)]
   MatchedQueryParameters(MatchedQueryParameters),
   #[serde(skip)]
+  MatchedRequestHeader(MatchedRequestHeader),
+  #[serde(skip)]
+  MatchedResponseHeader(MatchedResponseHeader),
+  #[serde(skip)]
   MatchedRequestBodyContentType(MatchedRequestBodyContentType),
   #[serde(skip)]
   MatchedResponseBodyContentType(MatchedResponseBodyContentType),
@@ -37,12 +49,20 @@ impl InteractionDiffResult {
     match self {
       InteractionDiffResult::UnmatchedQueryParameters(diff) => diff.interaction_trail(),
       InteractionDiffResult::UnmatchedQueryParametersShape(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedRequestHeader(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedRequestHeaderShape(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MissingRequiredRequestHeader(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedResponseHeader(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedResponseHeaderShape(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MissingRequiredResponseHeader(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedRequestUrl(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedRequestBodyContentType(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedRequestBodyShape(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedResponseBodyContentType(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedResponseBodyShape(diff) => &diff.interaction_trail,
       InteractionDiffResult::MatchedQueryParameters(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MatchedRequestHeader(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MatchedResponseHeader(diff) => &diff.interaction_trail,
       InteractionDiffResult::MatchedRequestBodyContentType(diff) => &diff.interaction_trail,
       InteractionDiffResult::MatchedResponseBodyContentType(diff) => &diff.interaction_trail,
     }
@@ -52,28 +72,41 @@ impl InteractionDiffResult {
     match self {
       InteractionDiffResult::UnmatchedQueryParameters(diff) => diff.requests_trail(),
       InteractionDiffResult::UnmatchedQueryParametersShape(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedRequestHeader(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedRequestHeaderShape(diff) => &diff.requests_trail,
+      InteractionDiffResult::MissingRequiredRequestHeader(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedResponseHeader(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedResponseHeaderShape(diff) => &diff.requests_trail,
+      InteractionDiffResult::MissingRequiredResponseHeader(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedRequestUrl(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedRequestBodyContentType(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedRequestBodyShape(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedResponseBodyContentType(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedResponseBodyShape(diff) => &diff.requests_trail,
       InteractionDiffResult::MatchedQueryParameters(diff) => &diff.requests_trail,
+      InteractionDiffResult::MatchedRequestHeader(diff) => &diff.requests_trail,
+      InteractionDiffResult::MatchedResponseHeader(diff) => &diff.requests_trail,
       InteractionDiffResult::MatchedRequestBodyContentType(diff) => &diff.requests_trail,
       InteractionDiffResult::MatchedResponseBodyContentType(diff) => &diff.requests_trail,
     }
   }
 
-  pub fn json_trail(&self) -> Option<&JsonTrail> {
-    let shape_diff_result = match self {
+  pub fn shape_diff_result(&self) -> Option<&ShapeDiffResult> {
+    match self {
       InteractionDiffResult::UnmatchedQueryParametersShape(diff) => Some(&diff.shape_diff_result),
+      InteractionDiffResult::UnmatchedRequestHeaderShape(diff) => Some(&diff.shape_diff_result),
+      InteractionDiffResult::UnmatchedResponseHeaderShape(diff) => Some(&diff.shape_diff_result),
       InteractionDiffResult::UnmatchedRequestBodyShape(diff) => Some(&diff.shape_diff_result),
       InteractionDiffResult::UnmatchedResponseBodyShape(diff) => Some(&diff.shape_diff_result),
       _ => None,
-    }?;
+    }
+  }
 
-    match shape_diff_result {
+  pub fn json_trail(&self) -> Option<&JsonTrail> {
+    match self.shape_diff_result()? {
       ShapeDiffResult::UnmatchedShape { json_trail, .. } => Some(json_trail),
       ShapeDiffResult::UnspecifiedShape { json_trail, .. } => Some(json_trail),
+      ShapeDiffResult::UnmatchedConstraint { json_trail, .. } => Some(json_trail),
     }
   }
 }
@@ -166,6 +199,186 @@ impl UnmatchedQueryParametersShape {
   }
 }
 
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedRequestHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl UnmatchedRequestHeader {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    UnmatchedRequestHeader {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingRequiredRequestHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl MissingRequiredRequestHeader {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    MissingRequiredRequestHeader {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedRequestHeaderShape {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub shape_diff_result: ShapeDiffResult,
+}
+
+impl UnmatchedRequestHeaderShape {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    shape_diff_result: ShapeDiffResult,
+  ) -> Self {
+    UnmatchedRequestHeaderShape {
+      interaction_trail,
+      requests_trail,
+      shape_diff_result,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Hash)]
+pub struct MatchedRequestHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub name: String,
+  pub root_shape_id: ShapeId,
+}
+
+impl MatchedRequestHeader {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    name: String,
+    root_shape_id: ShapeId,
+  ) -> Self {
+    MatchedRequestHeader {
+      interaction_trail,
+      requests_trail,
+      name,
+      root_shape_id,
+    }
+  }
+
+  pub fn into_shape_diff(self, shape_diff_result: ShapeDiffResult) -> UnmatchedRequestHeaderShape {
+    UnmatchedRequestHeaderShape::new(
+      self.interaction_trail,
+      self.requests_trail,
+      shape_diff_result,
+    )
+  }
+
+  pub fn into_missing(self) -> MissingRequiredRequestHeader {
+    MissingRequiredRequestHeader::new(self.interaction_trail, self.requests_trail)
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedResponseHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl UnmatchedResponseHeader {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    UnmatchedResponseHeader {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingRequiredResponseHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl MissingRequiredResponseHeader {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    MissingRequiredResponseHeader {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedResponseHeaderShape {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub shape_diff_result: ShapeDiffResult,
+}
+
+impl UnmatchedResponseHeaderShape {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    shape_diff_result: ShapeDiffResult,
+  ) -> Self {
+    UnmatchedResponseHeaderShape {
+      interaction_trail,
+      requests_trail,
+      shape_diff_result,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Hash)]
+pub struct MatchedResponseHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub name: String,
+  pub root_shape_id: ShapeId,
+}
+
+impl MatchedResponseHeader {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    name: String,
+    root_shape_id: ShapeId,
+  ) -> Self {
+    MatchedResponseHeader {
+      interaction_trail,
+      requests_trail,
+      name,
+      root_shape_id,
+    }
+  }
+
+  pub fn into_shape_diff(self, shape_diff_result: ShapeDiffResult) -> UnmatchedResponseHeaderShape {
+    UnmatchedResponseHeaderShape::new(
+      self.interaction_trail,
+      self.requests_trail,
+      shape_diff_result,
+    )
+  }
+
+  pub fn into_missing(self) -> MissingRequiredResponseHeader {
+    MissingRequiredResponseHeader::new(self.interaction_trail, self.requests_trail)
+  }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct UnmatchedRequestBodyContentType {
@@ -488,6 +701,28 @@ impl From<MatchedResponseBodyContentType> for BodyAnalysisLocation {
   }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+#[derive(Clone, Debug)]
+pub struct HeaderAnalysisResult {
+  pub header_location: HeaderAnalysisLocation,
+  pub trail_observations: TrailObservationsResult,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HeaderAnalysisLocation {
+  Request {
+    path_id: PathComponentId,
+    method: String,
+    request_id: RequestId,
+  },
+  Response {
+    path_id: PathComponentId,
+    method: String,
+    status_code: u16,
+    response_id: ResponseId,
+  },
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Hash)]
 pub struct InteractionTrail {
@@ -521,6 +756,18 @@ impl InteractionTrail {
       .push(InteractionTrailPathComponent::QueryParameters)
   }
 
+  pub fn with_request_header(&mut self, name: String) {
+    self
+      .path
+      .push(InteractionTrailPathComponent::RequestHeader { name })
+  }
+
+  pub fn with_response_header(&mut self, name: String, status_code: u16) {
+    self
+      .path
+      .push(InteractionTrailPathComponent::ResponseHeader { name, status_code })
+  }
+
   pub fn with_request_body(&mut self, content_type: String) {
     self
       .path
@@ -559,8 +806,10 @@ impl InteractionTrail {
     self.path.iter().find_map(|component| match component {
       InteractionTrailPathComponent::ResponseBody { status_code, .. } => Some(*status_code),
       InteractionTrailPathComponent::ResponseStatusCode { status_code } => Some(*status_code),
+      InteractionTrailPathComponent::ResponseHeader { status_code, .. } => Some(*status_code),
       InteractionTrailPathComponent::Method { .. }
       | InteractionTrailPathComponent::QueryParameters
+      | InteractionTrailPathComponent::RequestHeader { .. }
       | InteractionTrailPathComponent::RequestBody { .. }
       | InteractionTrailPathComponent::Url { .. } => None,
     })
@@ -628,6 +877,7 @@ pub enum RequestSpecTrail {
   SpecRoot(SpecRoot),
   SpecPath(SpecPath),
   SpecQueryParameters(SpecQueryParameters),
+  SpecHeaderParameter(SpecHeaderParameter),
   SpecRequestRoot(SpecRequestRoot),
   SpecRequestBody(SpecRequestBody),
   SpecResponseRoot(SpecResponseRoot),
@@ -651,6 +901,13 @@ impl RequestSpecTrail {
     }
   }
 
+  pub fn get_header_parameter_id(&self) -> Option<&String> {
+    match self {
+      RequestSpecTrail::SpecHeaderParameter(header_spec) => Some(&header_spec.parameter_id),
+      _ => None,
+    }
+  }
+
   pub fn get_request_id(&self) -> Option<&String> {
     match self {
       RequestSpecTrail::SpecRequestBody(spec_body) => Some(&spec_body.request_id),
@@ -683,6 +940,12 @@ pub struct SpecQueryParameters {
   pub query_parameters_id: QueryParametersId,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecHeaderParameter {
+  pub parameter_id: RequestParameterId,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct SpecRequestRoot {
@@ -716,6 +979,14 @@ pub enum InteractionTrailPathComponent {
     method: String,
   },
   QueryParameters,
+  RequestHeader {
+    name: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  ResponseHeader {
+    name: String,
+    status_code: u16,
+  },
   #[serde(rename_all = "camelCase")]
   RequestBody {
     content_type: String,
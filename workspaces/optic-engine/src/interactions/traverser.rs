@@ -1,7 +1,7 @@
 use super::visitors::{
-  InteractionVisitors, PathVisitor, PathVisitorContext, QueryParametersVisitor,
-  QueryParametersVisitorContext, RequestBodyVisitor, RequestBodyVisitorContext,
-  ResponseBodyVisitor, ResponseBodyVisitorContext,
+  HeadersVisitor, HeadersVisitorContext, InteractionVisitors, PathVisitor, PathVisitorContext,
+  QueryParametersVisitor, QueryParametersVisitorContext, RequestBodyVisitor,
+  RequestBodyVisitorContext, ResponseBodyVisitor, ResponseBodyVisitorContext,
 };
 use crate::events::HttpInteraction;
 use crate::projections::endpoint::ROOT_PATH_ID;
@@ -49,6 +49,47 @@ impl<'a> Traverser<'a> {
     };
     query_params_visitor.end(interaction, &path_context);
 
+    let headers_visitor = visitors.headers();
+    headers_visitor.begin();
+    if let Some(path_id) = resolved_path {
+      let request_headers = self
+        .endpoint_queries
+        .resolve_endpoint_header_params(path_id, &interaction.request.method)
+        .into_iter()
+        .flatten()
+        .collect();
+      let response = self
+        .endpoint_queries
+        .resolve_responses_by_method_and_status_code(
+          &interaction.request.method,
+          interaction.response.status_code,
+          path_id,
+        )
+        .map(|(response_id, _)| response_id)
+        .next();
+      let response_headers = self
+        .endpoint_queries
+        .resolve_endpoint_response_header_params(
+          path_id,
+          &interaction.request.method,
+          interaction.response.status_code,
+        )
+        .into_iter()
+        .flatten()
+        .collect();
+
+      headers_visitor.visit(
+        interaction,
+        &HeadersVisitorContext {
+          path: path_id,
+          request_headers,
+          response,
+          response_headers,
+        },
+      );
+    }
+    headers_visitor.end(interaction, &path_context);
+
     let request_body_visitor = visitors.request_body();
     request_body_visitor.begin();
     match resolved_path {
@@ -1,24 +1,26 @@
 use super::{
-  InteractionVisitor, InteractionVisitors, PathVisitor, PathVisitorContext, QueryParametersVisitor,
-  QueryParametersVisitorContext, RequestBodyVisitor, RequestBodyVisitorContext,
-  ResponseBodyVisitor, ResponseBodyVisitorContext, VisitorResults,
+  HeadersVisitor, HeadersVisitorContext, InteractionVisitor, InteractionVisitors, PathVisitor,
+  PathVisitorContext, QueryParametersVisitor, QueryParametersVisitorContext, RequestBodyVisitor,
+  RequestBodyVisitorContext, ResponseBodyVisitor, ResponseBodyVisitorContext, VisitorResults,
 };
 use crate::interactions::result::{
   InteractionDiffResult, MatchedQueryParameters, MatchedRequestBodyContentType,
-  MatchedResponseBodyContentType, SpecQueryParameters, SpecRoot, UnmatchedQueryParameters,
-  UnmatchedRequestBodyContentType, UnmatchedRequestUrl, UnmatchedResponseBodyContentType,
+  MatchedRequestHeader, MatchedResponseBodyContentType, MatchedResponseHeader, SpecQueryParameters,
+  SpecRoot, UnmatchedQueryParameters, UnmatchedRequestBodyContentType, UnmatchedRequestHeader,
+  UnmatchedRequestUrl, UnmatchedResponseBodyContentType, UnmatchedResponseHeader,
 };
 use crate::interactions::result::{
-  InteractionTrail, InteractionTrailPathComponent, RequestSpecTrail, SpecPath, SpecRequestBody,
-  SpecResponseBody,
+  InteractionTrail, InteractionTrailPathComponent, RequestSpecTrail, SpecHeaderParameter, SpecPath,
+  SpecRequestBody, SpecResponseBody, SpecResponseRoot,
 };
-use crate::state::body::BodyDescriptor;
+use crate::state::body::{BodyDescriptor, ParsedHeaders};
 use crate::state::endpoint::{HttpContentType, RequestId, ResponseId};
 use crate::HttpInteraction;
 
 pub struct DiffVisitors {
   path: DiffPathVisitor,
   query_params: DiffQueryParametersVisitor,
+  headers: DiffHeadersVisitor,
   request_body: DiffRequestBodyVisitor,
   response_body: DiffResponseBodyVisitor,
 }
@@ -28,6 +30,7 @@ impl DiffVisitors {
     DiffVisitors {
       path: DiffPathVisitor::new(),
       query_params: DiffQueryParametersVisitor::new(),
+      headers: DiffHeadersVisitor::new(),
       request_body: DiffRequestBodyVisitor::new(),
       response_body: DiffResponseBodyVisitor::new(),
     }
@@ -39,6 +42,7 @@ type DiffResults = VisitorResults<InteractionDiffResult>;
 impl InteractionVisitors<InteractionDiffResult> for DiffVisitors {
   type Path = DiffPathVisitor;
   type QueryParameters = DiffQueryParametersVisitor;
+  type Headers = DiffHeadersVisitor;
   type RequestBody = DiffRequestBodyVisitor;
   type ResponseBody = DiffResponseBodyVisitor;
 
@@ -48,6 +52,9 @@ impl InteractionVisitors<InteractionDiffResult> for DiffVisitors {
   fn query_params(&mut self) -> &mut DiffQueryParametersVisitor {
     &mut self.query_params
   }
+  fn headers(&mut self) -> &mut DiffHeadersVisitor {
+    &mut self.headers
+  }
   fn request_body(&mut self) -> &mut DiffRequestBodyVisitor {
     &mut self.request_body
   }
@@ -168,6 +175,146 @@ impl QueryParametersVisitor<InteractionDiffResult> for DiffQueryParametersVisito
 }
 ///////////////////////////////////////////////////////////////////////////////
 
+// Headers that are part of every exchange and only describe the transport, rather than the API
+const UNTRACKED_HEADERS: &[&str] = &[
+  "accept",
+  "accept-encoding",
+  "connection",
+  "content-encoding",
+  "content-length",
+  "content-type",
+  "date",
+  "host",
+  "keep-alive",
+  "transfer-encoding",
+  "user-agent",
+];
+
+pub(crate) fn is_tracked_header(name: &str) -> bool {
+  !UNTRACKED_HEADERS
+    .iter()
+    .any(|untracked| untracked.eq_ignore_ascii_case(name))
+}
+
+pub struct DiffHeadersVisitor {
+  results: DiffResults,
+}
+
+impl DiffHeadersVisitor {
+  fn new() -> Self {
+    Self {
+      results: DiffResults::new(),
+    }
+  }
+}
+
+impl InteractionVisitor<InteractionDiffResult> for DiffHeadersVisitor {
+  fn results(&mut self) -> Option<&mut DiffResults> {
+    Some(&mut self.results)
+  }
+}
+impl HeadersVisitor<InteractionDiffResult> for DiffHeadersVisitor {
+  fn begin(&mut self) {}
+  fn visit(&mut self, interaction: &HttpInteraction, context: &HeadersVisitorContext) {
+    // headers of which the values weren't captured can't be diffed
+    let interaction_request_headers: Option<ParsedHeaders> = (&interaction.request.headers).into();
+    if let Some(request_headers) = interaction_request_headers {
+      let interaction_trail = |name: &String| {
+        let mut trail = InteractionTrail::default();
+        trail.with_url(interaction.request.path.clone());
+        trail.with_method(interaction.request.method.clone());
+        trail.with_request_header(name.clone());
+        trail
+      };
+
+      for (parameter_id, header_descriptor) in &context.request_headers {
+        if let Some(shape_descriptor) = &header_descriptor.shape {
+          let requests_trail = RequestSpecTrail::SpecHeaderParameter(SpecHeaderParameter {
+            parameter_id: String::from(*parameter_id),
+          });
+          self.push(InteractionDiffResult::MatchedRequestHeader(
+            MatchedRequestHeader::new(
+              interaction_trail(&header_descriptor.name),
+              requests_trail,
+              header_descriptor.name.clone(),
+              shape_descriptor.shape_id.clone(),
+            ),
+          ));
+        }
+      }
+
+      // only requests that document headers at all have their other headers reported
+      let undocumented_names = request_headers.names().filter(|name| {
+        !context.request_headers.is_empty()
+          && is_tracked_header(name)
+          && !context
+            .request_headers
+            .iter()
+            .any(|(_, header_descriptor)| header_descriptor.is_named(name))
+      });
+      for name in undocumented_names {
+        let requests_trail = RequestSpecTrail::SpecPath(SpecPath {
+          path_id: String::from(context.path),
+        });
+        self.push(InteractionDiffResult::UnmatchedRequestHeader(
+          UnmatchedRequestHeader::new(interaction_trail(name), requests_trail),
+        ));
+      }
+    }
+
+    // without a documented response there's nothing to document its headers on yet
+    let interaction_response_headers: Option<ParsedHeaders> =
+      (&interaction.response.headers).into();
+    if let (Some(response_headers), Some(response_id)) =
+      (interaction_response_headers, context.response)
+    {
+      let status_code = interaction.response.status_code;
+      let interaction_trail = |name: &String| {
+        let mut trail = InteractionTrail::default();
+        trail.with_url(interaction.request.path.clone());
+        trail.with_method(interaction.request.method.clone());
+        trail.with_response_header(name.clone(), status_code);
+        trail
+      };
+
+      for (parameter_id, header_descriptor) in &context.response_headers {
+        if let Some(shape_descriptor) = &header_descriptor.shape {
+          let requests_trail = RequestSpecTrail::SpecHeaderParameter(SpecHeaderParameter {
+            parameter_id: String::from(*parameter_id),
+          });
+          self.push(InteractionDiffResult::MatchedResponseHeader(
+            MatchedResponseHeader::new(
+              interaction_trail(&header_descriptor.name),
+              requests_trail,
+              header_descriptor.name.clone(),
+              shape_descriptor.shape_id.clone(),
+            ),
+          ));
+        }
+      }
+
+      let undocumented_names = response_headers.names().filter(|name| {
+        !context.response_headers.is_empty()
+          && is_tracked_header(name)
+          && !context
+            .response_headers
+            .iter()
+            .any(|(_, header_descriptor)| header_descriptor.is_named(name))
+      });
+      for name in undocumented_names {
+        let requests_trail = RequestSpecTrail::SpecResponseRoot(SpecResponseRoot {
+          response_id: String::from(response_id),
+        });
+        self.push(InteractionDiffResult::UnmatchedResponseHeader(
+          UnmatchedResponseHeader::new(interaction_trail(name), requests_trail),
+        ));
+      }
+    }
+  }
+  fn end(&mut self, interaction: &HttpInteraction, context: &PathVisitorContext) {}
+}
+///////////////////////////////////////////////////////////////////////////////
+
 pub struct DiffRequestBodyVisitor {
   results: DiffResults,
   visited_with_matched_content_types: std::collections::HashSet<RequestId>,
@@ -1,16 +1,17 @@
 pub mod diff;
 
 use crate::projections::endpoint::{
-  QueryParametersDescriptor, RequestDescriptor, ResponseBodyDescriptor,
+  QueryParametersDescriptor, RequestDescriptor, RequestParameterDescriptor, ResponseBodyDescriptor,
 };
 use crate::state::endpoint::{
-  PathComponentId, PathComponentIdRef, QueryParametersId, RequestId, ResponseId,
+  PathComponentId, PathComponentIdRef, QueryParametersId, RequestId, RequestParameterId, ResponseId,
 };
 use crate::HttpInteraction;
 
 pub trait InteractionVisitors<R> {
   type Path: PathVisitor<R>;
   type QueryParameters: QueryParametersVisitor<R>;
+  type Headers: HeadersVisitor<R>;
   type RequestBody: RequestBodyVisitor<R>;
   type ResponseBody: ResponseBodyVisitor<R>;
 
@@ -18,6 +19,8 @@ pub trait InteractionVisitors<R> {
 
   fn query_params(&mut self) -> &mut Self::QueryParameters;
 
+  fn headers(&mut self) -> &mut Self::Headers;
+
   fn request_body(&mut self) -> &mut Self::RequestBody;
 
   fn response_body(&mut self) -> &mut Self::ResponseBody;
@@ -26,6 +29,7 @@ pub trait InteractionVisitors<R> {
     let flattened = vec![
       self.path().take_results(),
       self.query_params().take_results(),
+      self.headers().take_results(),
       self.request_body().take_results(),
       self.response_body().take_results(),
     ]
@@ -62,6 +66,11 @@ pub trait QueryParametersVisitor<R>: InteractionVisitor<R> {
   fn visit(&mut self, interaction: &HttpInteraction, context: &QueryParametersVisitorContext);
   fn end(&mut self, interaction: &HttpInteraction, context: &PathVisitorContext);
 }
+pub trait HeadersVisitor<R>: InteractionVisitor<R> {
+  fn begin(&mut self);
+  fn visit(&mut self, interaction: &HttpInteraction, context: &HeadersVisitorContext);
+  fn end(&mut self, interaction: &HttpInteraction, context: &PathVisitorContext);
+}
 pub trait RequestBodyVisitor<R>: InteractionVisitor<R> {
   fn begin(&mut self);
   fn visit(&mut self, interaction: &HttpInteraction, context: &RequestBodyVisitorContext);
@@ -84,6 +93,12 @@ pub struct QueryParametersVisitorContext<'a> {
   pub path: PathComponentIdRef<'a>,
   pub query: Option<(&'a QueryParametersId, &'a QueryParametersDescriptor)>,
 }
+pub struct HeadersVisitorContext<'a> {
+  pub path: PathComponentIdRef<'a>,
+  pub request_headers: Vec<(&'a RequestParameterId, &'a RequestParameterDescriptor)>,
+  pub response: Option<&'a ResponseId>,
+  pub response_headers: Vec<(&'a RequestParameterId, &'a RequestParameterDescriptor)>,
+}
 pub struct RequestBodyVisitorContext<'a> {
   pub path: PathComponentIdRef<'a>,
   pub operation: Option<(&'a RequestId, &'a RequestDescriptor)>,
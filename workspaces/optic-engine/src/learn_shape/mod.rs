@@ -7,7 +7,7 @@ mod result;
 mod traverser;
 mod visitors;
 
-pub use result::{TrailObservationsResult, TrailValues};
+pub use result::{LearnedShapeReuse, TrailObservationsResult, TrailValues};
 use traverser::Traverser;
 use visitors::learn_json_values::LearnVisitors;
 use visitors::BodyVisitors;
@@ -1,18 +1,37 @@
 use crate::commands::shape as shape_commands;
 use crate::commands::{ShapeCommand, SpecCommand};
+use crate::interactions::EnumLearningConfig;
+use crate::queries::shape::{ShapeStructure, ShapeStructureIndex, StructuralHash};
 use crate::shapes::JsonTrail;
-use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeKindDescriptor};
+use crate::state::shape::{
+  EnumConstraint, FieldId, FormatConstraint, IntegerConstraint, ShapeConstraintDescriptor, ShapeId,
+  ShapeKind, ShapeKindDescriptor, StringFormat,
+};
 use crate::state::SpecIdGenerator;
 use crate::BodyDescriptor;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+// Objects with at least this many distinct keys, all holding the same kind of values, are learned
+// as maps rather than as objects with a field for every key. The keys have to differ between the
+// observed objects as well, with at least as many distinct keys as objects observed, so objects
+// that merely have many (optional) fields aren't mistaken for maps.
+const MAP_MIN_KEY_COUNT: usize = 10;
 
 #[derive(Clone, Debug, Default)]
 pub struct TrailObservationsResult {
   pub values_by_trail: HashMap<JsonTrail, TrailValues>,
+  enum_config: EnumLearningConfig,
 }
 
 impl TrailObservationsResult {
+  pub fn with_enum_config(enum_config: EnumLearningConfig) -> Self {
+    Self {
+      values_by_trail: HashMap::new(),
+      enum_config,
+    }
+  }
+
   pub fn union(&mut self, new_result: TrailObservationsResult) {
     for (json_trail, new_trail_values) in new_result.values_by_trail {
       let existing_trail_values = self
@@ -21,6 +40,7 @@ impl TrailObservationsResult {
         .or_insert_with_key(|json_trail| TrailValues::new(json_trail));
 
       existing_trail_values.union(new_trail_values);
+      existing_trail_values.limit_string_values(self.enum_config.max_values);
     }
   }
 
@@ -61,10 +81,29 @@ impl TrailObservationsResult {
   }
 
   pub fn into_commands(
-    mut self,
+    self,
     id_generator: &mut impl SpecIdGenerator,
     root_trail: &JsonTrail,
   ) -> (Option<String>, impl Iterator<Item = SpecCommand>) {
+    let (root_shape_id, commands, _) =
+      self.into_commands_reusing(id_generator, root_trail, &ShapeStructureIndex::default());
+    (root_shape_id, commands)
+  }
+
+  // Generates the commands for the observed shapes, referring to structurally equivalent shapes
+  // of the index instead of adding new ones, and reporting the shapes it reused
+  pub fn into_commands_reusing(
+    mut self,
+    id_generator: &mut impl SpecIdGenerator,
+    root_trail: &JsonTrail,
+    reusable_shapes: &ShapeStructureIndex,
+  ) -> (
+    Option<String>,
+    impl Iterator<Item = SpecCommand>,
+    Vec<LearnedShapeReuse>,
+  ) {
+    let map_trails = self.merge_map_values();
+
     let sorted_trails = {
       let mut trails = self
         .values_by_trail
@@ -77,12 +116,21 @@ impl TrailObservationsResult {
 
     let mut shape_prototypes_by_trail = HashMap::new();
     let mut shape_prototypes = Vec::with_capacity(sorted_trails.len());
+    let mut structural_hashes = HashMap::new();
+    let mut shape_reuses = vec![];
 
     for json_trail in sorted_trails.into_iter().rev() {
       let trail_values = self.values_by_trail.remove(&json_trail).unwrap();
 
+      let shape_prototype = trail_values.into_shape_prototype(
+        id_generator,
+        &shape_prototypes_by_trail,
+        &map_trails,
+        &self.enum_config,
+      );
+      shape_prototype.structural_hash(&mut structural_hashes);
       let shape_prototype =
-        trail_values.into_shape_prototype(id_generator, &shape_prototypes_by_trail);
+        shape_prototype.reusing_shapes(reusable_shapes, &mut structural_hashes, &mut shape_reuses);
 
       shape_prototypes_by_trail.insert(json_trail, shape_prototype.clone());
       shape_prototypes.push(shape_prototype);
@@ -94,6 +142,8 @@ impl TrailObservationsResult {
     let included_trails: HashSet<_> =
       shape_prototypes_trails(root_shape, &shape_prototypes_by_trail).collect();
 
+    shape_reuses.retain(|shape_reuse| included_trails.contains(&shape_reuse.json_trail));
+
     let commands = shape_prototypes_to_commands(
       shape_prototypes
         .into_iter()
@@ -101,7 +151,68 @@ impl TrailObservationsResult {
     )
     .map(|command| SpecCommand::from(command));
 
-    (root_shape_id, commands)
+    (root_shape_id, commands, shape_reuses)
+  }
+
+  // Finds the objects that look like maps and merges the values observed for all of their keys,
+  // returning the trails of those maps
+  fn merge_map_values(&mut self) -> HashSet<JsonTrail> {
+    let mut map_trails = HashSet::new();
+
+    // merging can reveal nested maps, so look for the shallowest remaining map after every merge
+    while let Some(map_trail) = self
+      .values_by_trail
+      .values()
+      .filter(|trail_values| !map_trails.contains(&trail_values.trail))
+      .filter(|trail_values| self.is_map(trail_values))
+      .map(|trail_values| trail_values.trail.clone())
+      .min_by_key(|trail| trail.depth())
+    {
+      let key_depth = map_trail.depth();
+      let values_by_trail = std::mem::take(&mut self.values_by_trail);
+      for (json_trail, mut trail_values) in values_by_trail {
+        if json_trail.is_descendant_of(&map_trail) {
+          if let Some(value_trail) = json_trail.with_object_at(key_depth) {
+            trail_values.trail = value_trail.clone();
+            self
+              .values_by_trail
+              .entry(value_trail)
+              .or_insert_with_key(TrailValues::new)
+              .union(trail_values);
+            continue;
+          }
+        }
+        self.values_by_trail.insert(json_trail, trail_values);
+      }
+
+      map_trails.insert(map_trail);
+    }
+
+    map_trails
+  }
+
+  fn is_map(&self, trail_values: &TrailValues) -> bool {
+    if !trail_values.was_object || trail_values.field_sets.len() < 2 {
+      return false;
+    }
+
+    let keys = trail_values
+      .field_sets
+      .iter()
+      .flatten()
+      .collect::<HashSet<_>>();
+    if keys.len() < MAP_MIN_KEY_COUNT || keys.len() < trail_values.object_observations {
+      return false;
+    }
+
+    let mut value_kinds = keys.into_iter().map(|key| {
+      self
+        .values_by_trail
+        .get(&trail_values.trail.with_object_key(key.clone()))
+        .map(TrailValues::kinds)
+    });
+    let first_kinds = value_kinds.next().flatten();
+    first_kinds.is_some() && value_kinds.all(|kinds| kinds == first_kinds)
   }
 }
 
@@ -136,23 +247,35 @@ fn shape_prototypes_trails<'a>(
 
           trails
         }
-        ShapePrototypeDescriptor::ObjectWithFields { ref fields } => {
-          let nested_trails = fields.iter().flat_map(|field_prototype_descriptor| {
-            let field_prototype = prototypes_by_trail.get(
-              &shape_prototype
-                .trail
-                .with_object_key(field_prototype_descriptor.key.clone()),
-            );
+        ShapePrototypeDescriptor::MapOfShape { .. } => {
+          let value_prototype = prototypes_by_trail.get(&shape_prototype.trail.with_object());
+          let nested_trails = shape_prototypes_trails(value_prototype, prototypes_by_trail);
 
-            shape_prototypes_trails(field_prototype, prototypes_by_trail)
-          });
+          let mut trails = vec![shape_prototype.trail.clone()];
+          trails.extend(nested_trails);
+
+          trails
+        }
+        ShapePrototypeDescriptor::ObjectWithFields { ref fields } => {
+          let nested_trails = fields
+            .iter()
+            .filter(|field_prototype_descriptor| field_prototype_descriptor.value_shape.is_none())
+            .flat_map(|field_prototype_descriptor| {
+              let field_prototype = prototypes_by_trail.get(
+                &shape_prototype
+                  .trail
+                  .with_object_key(field_prototype_descriptor.key.clone()),
+              );
+
+              shape_prototypes_trails(field_prototype, prototypes_by_trail)
+            });
 
           let mut trails = vec![shape_prototype.trail.clone()];
           trails.extend(nested_trails);
 
           trails
         }
-        ShapePrototypeDescriptor::Unknown => {
+        ShapePrototypeDescriptor::Unknown | ShapePrototypeDescriptor::Existing => {
           vec![shape_prototype.trail.clone()]
         }
       },
@@ -166,10 +289,22 @@ fn shape_prototypes_to_commands(
     .into_iter()
     .flat_map(
       |shape_prototype| match shape_prototype.prototype_descriptor {
-        ShapePrototypeDescriptor::PrimitiveKind { base_shape_kind } => {
-          let add_command =
-            ShapeCommand::add_shape(shape_prototype.id, base_shape_kind, String::from(""));
-          Some(vec![add_command])
+        ShapePrototypeDescriptor::PrimitiveKind {
+          base_shape_kind,
+          constraint,
+        } => {
+          let mut commands = vec![ShapeCommand::add_shape(
+            shape_prototype.id.clone(),
+            base_shape_kind,
+            String::from(""),
+          )];
+          if let Some(constraint) = constraint {
+            commands.push(ShapeCommand::set_shape_constraint(
+              shape_prototype.id,
+              constraint,
+            ));
+          }
+          Some(commands)
         }
         ShapePrototypeDescriptor::OneOfShape {
           branches,
@@ -266,6 +401,23 @@ fn shape_prototypes_to_commands(
 
           Some(commands)
         }
+        ShapePrototypeDescriptor::MapOfShape { value_shape_id } => Some(vec![
+          ShapeCommand::add_shape(
+            shape_prototype.id.clone(),
+            ShapeKind::MapKind,
+            String::from(""),
+          ),
+          ShapeCommand::set_parameter_shape(
+            shape_prototype.id,
+            String::from(
+              ShapeKind::MapKind
+                .get_parameter_descriptor()
+                .unwrap()
+                .shape_parameter_id,
+            ),
+            value_shape_id,
+          ),
+        ]),
         ShapePrototypeDescriptor::ObjectWithFields { fields } => {
           let mut commands = vec![];
           commands.push(ShapeCommand::add_shape(
@@ -275,6 +427,10 @@ fn shape_prototypes_to_commands(
           ));
 
           for field in fields {
+            if let Some(value_shape) = field.value_shape {
+              commands.extend(shape_prototypes_to_commands(std::iter::once(*value_shape)));
+            }
+
             let field_shape_id = if let Some(optional_shape_id) = field.optional_shape_id {
               commands.push(ShapeCommand::add_shape(
                 optional_shape_id.clone(),
@@ -310,6 +466,7 @@ fn shape_prototypes_to_commands(
             ShapeCommand::add_shape(shape_prototype.id, ShapeKind::UnknownKind, String::from(""));
           Some(vec![add_command])
         }
+        ShapePrototypeDescriptor::Existing => None,
       },
     )
     .flatten()
@@ -317,12 +474,31 @@ fn shape_prototypes_to_commands(
 
 impl From<HashMap<JsonTrail, TrailValues>> for TrailObservationsResult {
   fn from(values_by_trail: HashMap<JsonTrail, TrailValues>) -> Self {
-    Self { values_by_trail }
+    Self {
+      values_by_trail,
+      enum_config: EnumLearningConfig::default(),
+    }
   }
 }
 
+// A shape that was already in the spec and was referred to instead of learning a new one
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedShapeReuse {
+  pub json_trail: JsonTrail,
+  pub shape_id: ShapeId,
+  pub name: String,
+}
+
 pub type FieldSet = HashSet<String>;
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldSetStrings {
+  pub field_set: FieldSet,
+  pub strings: BTreeMap<String, Option<String>>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TrailValues {
@@ -335,8 +511,32 @@ pub struct TrailValues {
   pub was_object: bool,
   pub was_empty_array: bool,
 
+  // Whether every number observed was known to be an integer, `None` when that's unknown, like for
+  // observations from before it was tracked
+  #[serde(default)]
+  pub all_integers: Option<bool>,
+
+  // The formats of the observed strings, `None` standing for strings without any
+  #[serde(default)]
+  pub string_formats: Vec<Option<StringFormat>>,
+
+  // The single string each field held for every distinct field set, `None` for fields that held
+  // anything else, so a field telling the field sets apart can be found
+  #[serde(default)]
+  pub field_set_strings: Vec<FieldSetStrings>,
+
+  // The distinct strings observed, until there were more than an enum can have
+  #[serde(default)]
+  pub string_values: BTreeSet<String>,
+  #[serde(default)]
+  pub string_values_exceeded: bool,
+  #[serde(default)]
+  pub string_observations: usize,
+
   #[serde(rename = "fieldSet")]
   pub field_sets: Vec<FieldSet>,
+  #[serde(default)]
+  pub object_observations: usize,
 }
 
 impl From<JsonTrail> for TrailValues {
@@ -356,7 +556,14 @@ impl TrailValues {
       was_array: false,
       was_object: false,
       was_empty_array: false,
+      all_integers: Some(true),
+      string_formats: Default::default(),
+      field_set_strings: Default::default(),
+      string_values: Default::default(),
+      string_values_exceeded: false,
+      string_observations: 0,
       field_sets: Default::default(),
+      object_observations: 0,
     }
   }
 
@@ -368,10 +575,34 @@ impl TrailValues {
     self.was_array = self.was_array || new_values.was_array;
     self.was_empty_array = self.was_empty_array || new_values.was_empty_array;
     self.was_object = self.was_object || new_values.was_object;
+    self.all_integers = match (self.all_integers, new_values.all_integers) {
+      (Some(all_integers), Some(new_all_integers)) => Some(all_integers && new_all_integers),
+      _ => None,
+    };
+
+    for string_format in new_values.string_formats {
+      self.insert_string_format(string_format);
+    }
+
+    for new_field_set_strings in new_values.field_set_strings {
+      self.insert_field_set_strings(
+        new_field_set_strings.field_set,
+        new_field_set_strings.strings,
+      );
+    }
+
+    self.string_observations += new_values.string_observations;
+    self.string_values_exceeded = self.string_values_exceeded || new_values.string_values_exceeded;
+    if self.string_values_exceeded {
+      self.string_values.clear();
+    } else {
+      self.string_values.extend(new_values.string_values);
+    }
 
     for new_field_set in new_values.field_sets {
       self.insert_field_set(new_field_set);
     }
+    self.object_observations += new_values.object_observations;
   }
 
   pub fn normalize(&mut self) {
@@ -388,6 +619,141 @@ impl TrailValues {
       && !self.was_object
   }
 
+  pub fn insert_string_format(&mut self, string_format: Option<StringFormat>) {
+    if !self.string_formats.contains(&string_format) {
+      self.string_formats.push(string_format);
+    }
+  }
+
+  pub fn insert_string_value(&mut self, value: &str) {
+    self.string_observations += 1;
+    if !self.string_values_exceeded && !self.string_values.contains(value) {
+      self.string_values.insert(String::from(value));
+    }
+  }
+
+  pub fn limit_string_values(&mut self, max_values: usize) {
+    if self.string_values.len() > max_values {
+      self.exceed_string_values();
+    }
+  }
+
+  pub fn exceed_string_values(&mut self) {
+    self.string_values.clear();
+    self.string_values_exceeded = true;
+  }
+
+  // Numbers are only constrained to integers when every one observed was known to be one
+  fn number_constraint(&self) -> Option<ShapeConstraintDescriptor> {
+    if self.all_integers == Some(true) {
+      Some(ShapeConstraintDescriptor::IntegerConstraint(
+        IntegerConstraint {},
+      ))
+    } else {
+      None
+    }
+  }
+
+  // Strings are only constrained to a format when every one observed had it, or otherwise to an
+  // enum when few distinct values were observed often enough
+  fn string_constraint(
+    &self,
+    enum_config: &EnumLearningConfig,
+  ) -> Option<ShapeConstraintDescriptor> {
+    if let [Some(format)] = self.string_formats.as_slice() {
+      return Some(ShapeConstraintDescriptor::FormatConstraint(
+        FormatConstraint { format: *format },
+      ));
+    }
+
+    if !self.string_values_exceeded
+      && enum_config.learns_enum(self.string_values.len(), self.string_observations)
+    {
+      Some(ShapeConstraintDescriptor::EnumConstraint(EnumConstraint {
+        values: self.string_values.iter().cloned().collect(),
+      }))
+    } else {
+      None
+    }
+  }
+
+  pub fn insert_field_set_strings(
+    &mut self,
+    field_set: FieldSet,
+    strings: BTreeMap<String, Option<String>>,
+  ) {
+    match self
+      .field_set_strings
+      .iter_mut()
+      .find(|existing| existing.field_set == field_set)
+    {
+      Some(existing) => {
+        for (key, string) in existing.strings.iter_mut() {
+          if strings.get(key) != Some(string) {
+            *string = None;
+          }
+        }
+      }
+      None => self
+        .field_set_strings
+        .push(FieldSetStrings { field_set, strings }),
+    }
+  }
+
+  // A field of every field set holding a single string for each of them, which tells at least two
+  // of them apart. The field has to have been learned as an enum as well, so fields like ids don't
+  // pass for one after only a few observations.
+  fn discriminator(
+    &self,
+    existing_prototypes: &HashMap<JsonTrail, ShapePrototype>,
+  ) -> Option<(String, BTreeMap<String, Vec<FieldSet>>)> {
+    if self.field_sets.len() < 2 || self.field_set_strings.len() != self.field_sets.len() {
+      return None;
+    }
+
+    let candidate_keys = self.field_set_strings[0]
+      .strings
+      .iter()
+      .filter(|(_, string)| string.is_some())
+      .map(|(key, _)| key)
+      .filter(|key| {
+        let key_trail = self.trail.with_object_key((*key).clone());
+        matches!(
+          existing_prototypes.get(&key_trail),
+          Some(ShapePrototype {
+            prototype_descriptor: ShapePrototypeDescriptor::PrimitiveKind {
+              constraint: Some(ShapeConstraintDescriptor::EnumConstraint(_)),
+              ..
+            },
+            ..
+          })
+        )
+      });
+
+    for key in candidate_keys {
+      let field_sets_by_value = self.field_set_strings.iter().try_fold(
+        BTreeMap::new(),
+        |mut field_sets_by_value: BTreeMap<String, Vec<FieldSet>>, field_set_strings| {
+          let value = field_set_strings.strings.get(key)?.clone()?;
+          field_sets_by_value
+            .entry(value)
+            .or_default()
+            .push(field_set_strings.field_set.clone());
+          Some(field_sets_by_value)
+        },
+      );
+
+      match field_sets_by_value {
+        Some(field_sets_by_value) if field_sets_by_value.len() > 1 => {
+          return Some((key.clone(), field_sets_by_value))
+        }
+        _ => continue,
+      }
+    }
+
+    None
+  }
+
   pub fn insert_field_set(&mut self, field_set: FieldSet) {
     let exists = self.field_sets.iter().any(|existing_set| {
       if let None = existing_set.symmetric_difference(&field_set).next() {
@@ -402,15 +768,99 @@ impl TrailValues {
     }
   }
 
+  // The kinds of (non-null) values observed, as string, number, boolean, array and object
+  fn kinds(&self) -> [bool; 5] {
+    [
+      self.was_string,
+      self.was_number,
+      self.was_boolean,
+      self.was_array || self.was_empty_array,
+      self.was_object,
+    ]
+  }
+
+  // An object with every key of the field sets as a field, optional when missing from any of them.
+  // A literal shape can be given for a key, describing a field only this object has.
+  fn object_prototype_descriptor(
+    &self,
+    field_sets: &[FieldSet],
+    literal_field: Option<(&String, ShapePrototype)>,
+    id_generator: &mut impl SpecIdGenerator,
+    existing_prototypes: &HashMap<JsonTrail, ShapePrototype>,
+  ) -> ShapePrototypeDescriptor {
+    let (field_keys, optional_keys) = {
+      let all_keys_set = field_sets
+        .iter()
+        .fold(HashSet::new(), |all_keys: HashSet<String>, field_set| {
+          all_keys.union(field_set).cloned().collect()
+        });
+
+      let optional_keys_set = field_sets
+        .iter()
+        .fold(HashSet::new(), |optional_keys, field_set| {
+          let missing_keys = all_keys_set.difference(field_set).cloned().collect();
+          optional_keys.union(&missing_keys).cloned().collect()
+        });
+
+      let mut all_keys = all_keys_set.into_iter().collect::<Vec<_>>();
+      all_keys.sort();
+
+      let optional_keys = optional_keys_set.into_iter().collect::<Vec<_>>();
+      (all_keys, optional_keys)
+    };
+
+    let mut literal_field = literal_field;
+    let field_descriptors = field_keys
+      .into_iter()
+      .map(|key| {
+        let value_shape = match &literal_field {
+          Some((literal_key, _)) if **literal_key == key => literal_field
+            .take()
+            .map(|(_, literal_shape)| Box::new(literal_shape)),
+          _ => None,
+        };
+        let value_shape_id = match &value_shape {
+          Some(literal_shape) => literal_shape.id.clone(),
+          None => {
+            let field_trail = self.trail.with_object_key(key.clone());
+            let field_shape_prototype = existing_prototypes.get(&field_trail).expect(
+              "object field shape prototype should have been generated before its parent object",
+            );
+            field_shape_prototype.id.clone()
+          }
+        };
+        let is_optional = optional_keys.contains(&key);
+
+        FieldPrototypeDescriptor {
+          field_id: id_generator.field(),
+          key,
+          optional_shape_id: match is_optional {
+            true => Some(id_generator.shape()),
+            false => None,
+          },
+          value_shape_id,
+          value_shape,
+        }
+      })
+      .collect::<Vec<_>>();
+
+    ShapePrototypeDescriptor::ObjectWithFields {
+      fields: field_descriptors,
+    }
+  }
+
   fn into_shape_prototype(
     self,
     id_generator: &mut impl SpecIdGenerator,
     existing_prototypes: &HashMap<JsonTrail, ShapePrototype>,
+    map_trails: &HashSet<JsonTrail>,
+    enum_config: &EnumLearningConfig,
   ) -> ShapePrototype {
     let mut descriptors: Vec<_> = vec![
       if self.was_string {
         Some(ShapePrototypeDescriptor::PrimitiveKind {
           base_shape_kind: ShapeKind::StringKind,
+          constraint: self.string_constraint(enum_config),
         })
       } else {
         None
@@ -418,6 +868,7 @@ impl TrailValues {
       if self.was_number {
         Some(ShapePrototypeDescriptor::PrimitiveKind {
           base_shape_kind: ShapeKind::NumberKind,
+          constraint: self.number_constraint(),
         })
       } else {
         None
@@ -425,6 +876,7 @@ impl TrailValues {
       if self.was_boolean {
         Some(ShapePrototypeDescriptor::PrimitiveKind {
           base_shape_kind: ShapeKind::BooleanKind,
+          constraint: None,
         })
       } else {
         None
@@ -448,54 +900,14 @@ impl TrailValues {
       } else {
         None
       },
-      if self.was_object {
-        let (field_keys, optional_keys) = {
-          let all_keys_set = self
-            .field_sets
-            .iter()
-            .fold(HashSet::new(), |all_keys: HashSet<String>, field_set| {
-              all_keys.union(&field_set).cloned().collect()
-            });
-
-          let optional_keys_set =
-            self
-              .field_sets
-              .iter()
-              .fold(HashSet::new(), |optional_keys, field_set| {
-                let missing_keys = all_keys_set.difference(&field_set).cloned().collect();
-                optional_keys.union(&missing_keys).cloned().collect()
-              });
-
-          let mut all_keys = all_keys_set.into_iter().collect::<Vec<_>>();
-          all_keys.sort();
-
-          let optional_keys = optional_keys_set.into_iter().collect::<Vec<_>>();
-          (all_keys, optional_keys)
-        };
-
-        let field_descriptors = field_keys
-          .into_iter()
-          .map(|key| {
-            let field_trail = self.trail.with_object_key(key.clone());
-            let field_shape_prototype = existing_prototypes.get(&field_trail).expect(
-              "object field shape prototype should have been generated before its parent object",
-            );
-            let is_optional = optional_keys.contains(&key);
-
-            FieldPrototypeDescriptor {
-              field_id: id_generator.field(),
-              key,
-              optional_shape_id: match is_optional {
-                true => Some(id_generator.shape()),
-                false => None,
-              },
-              value_shape_id: field_shape_prototype.id.clone(),
-            }
-          })
-          .collect::<Vec<_>>();
-
-        Some(ShapePrototypeDescriptor::ObjectWithFields {
-          fields: field_descriptors,
+      if self.was_object && map_trails.contains(&self.trail) {
+        let value_trail = self.trail.with_object();
+        let value_prototype = existing_prototypes
+          .get(&value_trail)
+          .expect("map value shape prototype should have been generated before its parent map");
+
+        Some(ShapePrototypeDescriptor::MapOfShape {
+          value_shape_id: value_prototype.id.clone(),
         })
       } else {
         None
@@ -505,6 +917,40 @@ impl TrailValues {
     .flatten()
     .collect();
 
+    if self.was_object && !map_trails.contains(&self.trail) {
+      match self.discriminator(existing_prototypes) {
+        // every value of a discriminator gets an object of its own
+        Some((discriminator_key, field_sets_by_value)) => {
+          for (value, field_sets) in field_sets_by_value {
+            let literal_shape = ShapePrototype {
+              id: id_generator.shape(),
+              trail: self.trail.with_object_key(discriminator_key.clone()),
+              prototype_descriptor: ShapePrototypeDescriptor::PrimitiveKind {
+                base_shape_kind: ShapeKind::StringKind,
+                constraint: Some(ShapeConstraintDescriptor::EnumConstraint(EnumConstraint {
+                  values: vec![value],
+                })),
+              },
+            };
+            descriptors.push(self.object_prototype_descriptor(
+              &field_sets,
+              Some((&discriminator_key, literal_shape)),
+              id_generator,
+              existing_prototypes,
+            ));
+          }
+        }
+        None => {
+          descriptors.push(self.object_prototype_descriptor(
+            &self.field_sets,
+            None,
+            id_generator,
+            existing_prototypes,
+          ));
+        }
+      }
+    }
+
     let descriptors_count = descriptors.len();
     let shape_id = id_generator.shape();
     let shape_prototype = match descriptors_count {
@@ -574,10 +1020,155 @@ enum ShapePrototypeDescriptor {
     item_shape_id: ShapeId,
     item_is_unknown: bool,
   },
+  MapOfShape {
+    value_shape_id: ShapeId,
+  },
   PrimitiveKind {
     base_shape_kind: ShapeKind,
+    constraint: Option<ShapeConstraintDescriptor>,
   },
   Unknown,
+  // a shape that already exists, so doesn't need adding
+  Existing,
+}
+
+impl ShapePrototype {
+  // Hashes the structure of the prototype the way `ShapeQueries` hashes shapes, recording the
+  // hashes of it and its nested prototypes by their shape ids
+  fn structural_hash(
+    &self,
+    structural_hashes: &mut HashMap<ShapeId, StructuralHash>,
+  ) -> StructuralHash {
+    let known_hash = |structural_hashes: &HashMap<ShapeId, StructuralHash>, shape_id: &ShapeId| {
+      *structural_hashes
+        .get(shape_id)
+        .expect("nested shape prototypes should be hashed before their parents")
+    };
+
+    let structure = match &self.prototype_descriptor {
+      ShapePrototypeDescriptor::NullableShape { shape } => {
+        let inner_hash = shape.structural_hash(structural_hashes);
+        ShapeStructure::parameterized(ShapeKind::NullableKind, inner_hash)
+      }
+      ShapePrototypeDescriptor::OneOfShape { branches, .. } => ShapeStructure::one_of(
+        branches
+          .iter()
+          .map(|branch| branch.structural_hash(structural_hashes))
+          .collect(),
+      ),
+      ShapePrototypeDescriptor::ObjectWithFields { fields } => {
+        let mut field_hashes = BTreeMap::new();
+        for field in fields {
+          let value_hash = match &field.value_shape {
+            Some(value_shape) => value_shape.structural_hash(structural_hashes),
+            None => known_hash(structural_hashes, &field.value_shape_id),
+          };
+          let field_hash = match field.optional_shape_id {
+            Some(_) => {
+              ShapeStructure::parameterized(ShapeKind::OptionalKind, value_hash).structural_hash()
+            }
+            None => value_hash,
+          };
+          field_hashes.insert(field.key.clone(), field_hash);
+        }
+        ShapeStructure::object(field_hashes)
+      }
+      ShapePrototypeDescriptor::ListOfShape {
+        item_shape_id,
+        item_is_unknown,
+      } => {
+        let item_hash = if *item_is_unknown {
+          ShapeStructure::primitive(ShapeKind::UnknownKind, None).structural_hash()
+        } else {
+          known_hash(structural_hashes, item_shape_id)
+        };
+        ShapeStructure::parameterized(ShapeKind::ListKind, item_hash)
+      }
+      ShapePrototypeDescriptor::MapOfShape { value_shape_id } => ShapeStructure::parameterized(
+        ShapeKind::MapKind,
+        known_hash(structural_hashes, value_shape_id),
+      ),
+      ShapePrototypeDescriptor::PrimitiveKind {
+        base_shape_kind,
+        constraint,
+      } => ShapeStructure::primitive(base_shape_kind.clone(), constraint.clone()),
+      ShapePrototypeDescriptor::Unknown => ShapeStructure::primitive(ShapeKind::UnknownKind, None),
+      ShapePrototypeDescriptor::Existing => return known_hash(structural_hashes, &self.id),
+    };
+
+    let structural_hash = structure.structural_hash();
+    structural_hashes.insert(self.id.clone(), structural_hash);
+    structural_hash
+  }
+
+  // Replaces the prototype, or the prototypes nested in it, by equivalent shapes of the index.
+  // Primitives are left alone, as naming a string shouldn't turn every string into it.
+  fn reusing_shapes(
+    self,
+    reusable_shapes: &ShapeStructureIndex,
+    structural_hashes: &mut HashMap<ShapeId, StructuralHash>,
+    shape_reuses: &mut Vec<LearnedShapeReuse>,
+  ) -> ShapePrototype {
+    if reusable_shapes.is_empty() {
+      return self;
+    }
+
+    let is_composite = matches!(
+      self.prototype_descriptor,
+      ShapePrototypeDescriptor::ObjectWithFields { .. }
+        | ShapePrototypeDescriptor::ListOfShape { .. }
+        | ShapePrototypeDescriptor::MapOfShape { .. }
+        | ShapePrototypeDescriptor::OneOfShape { .. }
+    );
+    let reusable_shape = structural_hashes
+      .get(&self.id)
+      .copied()
+      .filter(|_| is_composite)
+      .and_then(|structural_hash| {
+        reusable_shapes
+          .get(structural_hash)
+          .map(|reusable_shape| (reusable_shape, structural_hash))
+      });
+
+    if let Some((reusable_shape, structural_hash)) = reusable_shape {
+      // parents refer to the reused shape from now on
+      structural_hashes.insert(reusable_shape.shape_id.clone(), structural_hash);
+      shape_reuses.push(LearnedShapeReuse {
+        json_trail: self.trail.clone(),
+        shape_id: reusable_shape.shape_id.clone(),
+        name: reusable_shape.name.clone(),
+      });
+      return ShapePrototype {
+        id: reusable_shape.shape_id.clone(),
+        trail: self.trail,
+        prototype_descriptor: ShapePrototypeDescriptor::Existing,
+      };
+    }
+
+    let prototype_descriptor = match self.prototype_descriptor {
+      ShapePrototypeDescriptor::NullableShape { shape } => {
+        ShapePrototypeDescriptor::NullableShape {
+          shape: Box::new(shape.reusing_shapes(reusable_shapes, structural_hashes, shape_reuses)),
+        }
+      }
+      ShapePrototypeDescriptor::OneOfShape {
+        branches,
+        parameter_ids,
+      } => ShapePrototypeDescriptor::OneOfShape {
+        branches: branches
+          .into_iter()
+          .map(|branch| branch.reusing_shapes(reusable_shapes, structural_hashes, shape_reuses))
+          .collect(),
+        parameter_ids,
+      },
+      prototype_descriptor => prototype_descriptor,
+    };
+
+    ShapePrototype {
+      prototype_descriptor,
+      ..self
+    }
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -586,14 +1177,19 @@ struct FieldPrototypeDescriptor {
   key: String,
   optional_shape_id: Option<ShapeId>,
   value_shape_id: ShapeId,
+  // a shape only this field has, generated along with it
+  value_shape: Option<Box<ShapePrototype>>,
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use crate::events::SpecEvent;
   use crate::projections::SpecProjection;
+  use crate::queries::shape::ShapeQueries;
   use crate::shapes::diff as diff_shapes;
   use crate::state::body::BodyDescriptor;
+  use crate::test_helpers::assert_valid_commands;
   use crate::{learn_shape::observe_body_trails, Body};
   use cqrs_core::Aggregate;
   use insta::assert_debug_snapshot;
@@ -616,7 +1212,8 @@ mod test {
     );
     assert!(string_results.0.is_some());
     assert_eq!(string_results.1.len(), 1);
-    let spec_projection = assert_valid_commands(string_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), string_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       string_results.0.as_ref().unwrap(),
@@ -631,8 +1228,9 @@ mod test {
       number_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(number_results.0.is_some());
-    assert_eq!(number_results.1.len(), 1);
-    let spec_projection = assert_valid_commands(number_results.1.clone());
+    assert_eq!(number_results.1.len(), 2); // integral, so constrained to integers
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), number_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       number_results.0.as_ref().unwrap(),
@@ -648,7 +1246,8 @@ mod test {
     );
     assert!(boolean_results.0.is_some());
     assert_eq!(boolean_results.1.len(), 1);
-    let spec_projection = assert_valid_commands(boolean_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), boolean_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       boolean_results.0.as_ref().unwrap(),
@@ -683,7 +1282,8 @@ mod test {
       primitive_array_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(primitive_array_results.0.is_some());
-    let spec_projection = assert_valid_commands(primitive_array_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), primitive_array_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       primitive_array_results.0.as_ref().unwrap(),
@@ -698,7 +1298,8 @@ mod test {
       empty_array_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(empty_array_results.0.is_some());
-    let spec_projection = assert_valid_commands(empty_array_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), empty_array_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       empty_array_results.0.as_ref().unwrap(),
@@ -713,7 +1314,10 @@ mod test {
       polymorphic_array_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(polymorphic_array_results.0.is_some());
-    let spec_projection = assert_valid_commands(polymorphic_array_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      polymorphic_array_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       polymorphic_array_results.0.as_ref().unwrap(),
@@ -729,7 +1333,10 @@ mod test {
         .into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(empty_and_primitive_array_results.0.is_some());
-    let spec_projection = assert_valid_commands(empty_and_primitive_array_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      empty_and_primitive_array_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       empty_and_primitive_array_results.0.as_ref().unwrap(),
@@ -765,7 +1372,10 @@ mod test {
       primitive_object_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(primitive_object_results.0.is_some());
-    let spec_projection = assert_valid_commands(primitive_object_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      primitive_object_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       primitive_object_results.0.as_ref().unwrap(),
@@ -780,7 +1390,8 @@ mod test {
       empty_object_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(empty_object_results.0.is_some());
-    let spec_projection = assert_valid_commands(empty_object_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), empty_object_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       empty_object_results.0.as_ref().unwrap(),
@@ -795,7 +1406,8 @@ mod test {
       nested_object_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(nested_object_results.0.is_some());
-    let spec_projection = assert_valid_commands(nested_object_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), nested_object_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       nested_object_results.0.as_ref().unwrap(),
@@ -880,7 +1492,10 @@ mod test {
       primitive_object_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(primitive_object_results.0.is_some());
-    let spec_projection = assert_valid_commands(primitive_object_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      primitive_object_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       primitive_object_results.0.as_ref().unwrap(),
@@ -895,7 +1510,8 @@ mod test {
       nested_optional_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(nested_optional_results.0.is_some());
-    let spec_projection = assert_valid_commands(nested_optional_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), nested_optional_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       nested_optional_results.0.as_ref().unwrap(),
@@ -911,7 +1527,10 @@ mod test {
     );
 
     assert!(missing_nested_field_results.0.is_some());
-    let spec_projection = assert_valid_commands(missing_nested_field_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      missing_nested_field_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       missing_nested_field_results.0.as_ref().unwrap(),
@@ -987,7 +1606,10 @@ mod test {
       nullable_primitive_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(nullable_primitive_results.0.is_some());
-    let spec_projection = assert_valid_commands(nullable_primitive_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      nullable_primitive_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       nullable_primitive_results.0.as_ref().unwrap(),
@@ -1002,7 +1624,10 @@ mod test {
       nullable_object_field_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(nullable_object_field_results.0.is_some());
-    let spec_projection = assert_valid_commands(nullable_object_field_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      nullable_object_field_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       nullable_object_field_results.0.as_ref().unwrap(),
@@ -1017,7 +1642,10 @@ mod test {
       nullable_array_item_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(nullable_array_item_results.0.is_some());
-    let spec_projection = assert_valid_commands(nullable_array_item_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      nullable_array_item_results.1.clone(),
+    );
     // TODO: debug this
     // assert_no_shape_diffs(
     //   &spec_projection,
@@ -1033,7 +1661,10 @@ mod test {
       nullable_one_off_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(nullable_one_off_results.0.is_some());
-    let spec_projection = assert_valid_commands(nullable_one_off_results.1.clone());
+    let spec_projection = assert_valid_commands(
+      SpecProjection::default(),
+      nullable_one_off_results.1.clone(),
+    );
     assert_no_shape_diffs(
       &spec_projection,
       nullable_one_off_results.0.as_ref().unwrap(),
@@ -1048,7 +1679,8 @@ mod test {
       only_null_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(only_null_results.0.is_some());
-    let spec_projection = assert_valid_commands(only_null_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), only_null_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       only_null_results.0.as_ref().unwrap(),
@@ -1060,6 +1692,377 @@ mod test {
     );
   }
 
+  #[test]
+  fn trail_observations_can_generate_commands_for_integer_numbers() {
+    let integer_bodies = vec![
+      BodyDescriptor::from(json!({ "id": 42 })),
+      BodyDescriptor::from(json!({ "id": 1093 })),
+    ];
+    let mixed_bodies = vec![
+      BodyDescriptor::from(json!({ "id": 42 })),
+      BodyDescriptor::from(json!({ "id": 4.2 })),
+    ];
+
+    let observe_all = |bodies: &Vec<BodyDescriptor>| {
+      bodies.iter().cloned().fold(
+        TrailObservationsResult::default(),
+        |mut observations, body| {
+          observations.union(observe_body_trails(body).normalized());
+          observations
+        },
+      )
+    };
+    let constraints = |commands: &Vec<SpecCommand>| {
+      commands
+        .iter()
+        .filter_map(|command| match command {
+          SpecCommand::ShapeCommand(ShapeCommand::SetShapeConstraint(command)) => {
+            Some(command.constraint_descriptor.clone())
+          }
+          _ => None,
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let mut test_id_generator = TestIdGenerator::default();
+
+    let integer_results = collect_commands(
+      observe_all(&integer_bodies).into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert_eq!(
+      constraints(&integer_results.1),
+      vec![ShapeConstraintDescriptor::IntegerConstraint(
+        IntegerConstraint {}
+      )]
+    );
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), integer_results.1.clone());
+    assert_no_shape_diffs(
+      &spec_projection,
+      integer_results.0.as_ref().unwrap(),
+      integer_bodies.clone(),
+    );
+    let decimal_diffs = diff_shapes(
+      spec_projection.shape(),
+      Some(BodyDescriptor::from(json!({ "id": 4.2 }))),
+      integer_results.0.as_ref().unwrap(),
+    );
+    assert_eq!(decimal_diffs.len(), 1);
+
+    let mixed_results = collect_commands(
+      observe_all(&mixed_bodies).into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      constraints(&mixed_results.1).is_empty(),
+      "numbers observed with decimals should not be constrained to integers"
+    );
+
+    // observations from before integers were tracked don't tell
+    let untold_observations = observe_all(&integer_bodies)
+      .values()
+      .map(|trail_values| {
+        let mut trail_values_json = serde_json::to_value(trail_values).unwrap();
+        trail_values_json
+          .as_object_mut()
+          .unwrap()
+          .remove("allIntegers");
+        let trail_values: TrailValues = serde_json::from_value(trail_values_json).unwrap();
+        (trail_values.trail.clone(), trail_values)
+      })
+      .collect::<HashMap<_, _>>();
+    let untold_results = collect_commands(
+      TrailObservationsResult::from(untold_observations)
+        .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      constraints(&untold_results.1).is_empty(),
+      "numbers not known to be integers should not be constrained to integers"
+    );
+  }
+
+  #[test]
+  fn trail_observations_can_generate_commands_for_formatted_strings() {
+    let uuid_bodies = vec![
+      BodyDescriptor::from(json!({ "id": "0b9dd9c7-7b1c-4a37-9a1e-4a4f4e4c3a2b" })),
+      BodyDescriptor::from(json!({ "id": "6f1c4a4e-2d4f-4c3e-8e7b-1f0b3c2d5e6a" })),
+    ];
+    let mixed_bodies = vec![
+      BodyDescriptor::from(json!({ "id": "0b9dd9c7-7b1c-4a37-9a1e-4a4f4e4c3a2b" })),
+      BodyDescriptor::from(json!({ "id": "not-a-uuid" })),
+    ];
+
+    let observe_all = |bodies: &Vec<BodyDescriptor>| {
+      bodies.iter().cloned().fold(
+        TrailObservationsResult::default(),
+        |mut observations, body| {
+          observations.union(observe_body_trails(body).normalized());
+          observations
+        },
+      )
+    };
+    let is_constraint_command = |command: &SpecCommand| {
+      matches!(
+        command,
+        SpecCommand::ShapeCommand(ShapeCommand::SetShapeConstraint(_))
+      )
+    };
+
+    let mut test_id_generator = TestIdGenerator::default();
+
+    let uuid_results = collect_commands(
+      observe_all(&uuid_bodies).into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert_eq!(
+      uuid_results
+        .1
+        .iter()
+        .filter(|command| is_constraint_command(command))
+        .count(),
+      1
+    );
+    let spec_projection = assert_valid_commands(SpecProjection::default(), uuid_results.1.clone());
+    assert_no_shape_diffs(
+      &spec_projection,
+      uuid_results.0.as_ref().unwrap(),
+      uuid_bodies,
+    );
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_formatted_strings__uuid_results",
+      &uuid_results
+    );
+
+    let mixed_results = collect_commands(
+      observe_all(&mixed_bodies).into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      !mixed_results.1.iter().any(is_constraint_command),
+      "strings observed in different formats should not be constrained"
+    );
+    let spec_projection = assert_valid_commands(SpecProjection::default(), mixed_results.1.clone());
+    assert_no_shape_diffs(
+      &spec_projection,
+      mixed_results.0.as_ref().unwrap(),
+      mixed_bodies,
+    );
+  }
+
+  #[test]
+  fn trail_observations_can_generate_commands_for_string_enums() {
+    let statuses = ["pending", "shipped", "cancelled"];
+    let order_bodies = (0..12)
+      .map(|n| BodyDescriptor::from(json!({ "status": statuses[n % statuses.len()] })))
+      .collect::<Vec<_>>();
+    let named_bodies = (0..12)
+      .map(|n| BodyDescriptor::from(json!({ "name": format!("customer {}", n) })))
+      .collect::<Vec<_>>();
+
+    let observe_all = |bodies: &Vec<BodyDescriptor>, enum_config: EnumLearningConfig| {
+      bodies.iter().cloned().fold(
+        TrailObservationsResult::with_enum_config(enum_config),
+        |mut observations, body| {
+          observations.union(observe_body_trails(body).normalized());
+          observations
+        },
+      )
+    };
+    let enum_constraints = |commands: &Vec<SpecCommand>| {
+      commands
+        .iter()
+        .filter_map(|command| match command {
+          SpecCommand::ShapeCommand(ShapeCommand::SetShapeConstraint(command)) => {
+            Some(command.constraint_descriptor.clone())
+          }
+          _ => None,
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let mut test_id_generator = TestIdGenerator::default();
+
+    let order_results = collect_commands(
+      observe_all(&order_bodies, EnumLearningConfig::default())
+        .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert_eq!(
+      enum_constraints(&order_results.1),
+      vec![ShapeConstraintDescriptor::EnumConstraint(EnumConstraint {
+        values: vec![
+          String::from("cancelled"),
+          String::from("pending"),
+          String::from("shipped")
+        ]
+      })]
+    );
+    let spec_projection = assert_valid_commands(SpecProjection::default(), order_results.1.clone());
+    assert_no_shape_diffs(
+      &spec_projection,
+      order_results.0.as_ref().unwrap(),
+      order_bodies.clone(),
+    );
+
+    let too_few_observations = collect_commands(
+      observe_all(
+        &order_bodies,
+        EnumLearningConfig {
+          min_observations: 20,
+          ..EnumLearningConfig::default()
+        },
+      )
+      .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      enum_constraints(&too_few_observations.1).is_empty(),
+      "strings should only be learned as enums after enough observations"
+    );
+
+    let too_many_values = collect_commands(
+      observe_all(
+        &order_bodies,
+        EnumLearningConfig {
+          max_values: 2,
+          ..EnumLearningConfig::default()
+        },
+      )
+      .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(enum_constraints(&too_many_values.1).is_empty());
+
+    let named_results = collect_commands(
+      observe_all(&named_bodies, EnumLearningConfig::default())
+        .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      enum_constraints(&named_results.1).is_empty(),
+      "strings with many distinct values should not be learned as enums"
+    );
+  }
+
+  #[test]
+  fn trail_observations_can_generate_commands_for_discriminated_unions() {
+    let event_bodies = (0..12)
+      .map(|n| match n % 3 {
+        0 => BodyDescriptor::from(json!({ "type": "created", "id": format!("{}", n), "at": n })),
+        1 => {
+          BodyDescriptor::from(json!({ "type": "renamed", "id": format!("{}", n), "name": "x" }))
+        }
+        _ => BodyDescriptor::from(
+          json!({ "type": "deleted", "id": format!("{}", n), "reason": "spam", "by": "admin" }),
+        ),
+      })
+      .collect::<Vec<_>>();
+
+    let event_observations = event_bodies.iter().cloned().fold(
+      TrailObservationsResult::default(),
+      |mut observations, body| {
+        observations.union(observe_body_trails(body).normalized());
+        observations
+      },
+    );
+
+    let mut test_id_generator = TestIdGenerator::default();
+
+    let event_results = collect_commands(
+      event_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(event_results.0.is_some());
+    let spec_projection = assert_valid_commands(SpecProjection::default(), event_results.1.clone());
+    assert_no_shape_diffs(
+      &spec_projection,
+      event_results.0.as_ref().unwrap(),
+      event_bodies.clone(),
+    );
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_discriminated_unions__event_results",
+      &event_results
+    );
+
+    let mismatched_event =
+      BodyDescriptor::from(json!({ "type": "created", "id": "13", "name": "x" }));
+    let diffs = diff_shapes(
+      spec_projection.shape(),
+      Some(mismatched_event),
+      event_results.0.as_ref().unwrap(),
+    );
+    assert_eq!(
+      diffs.len(),
+      2,
+      "only the branch of the discriminator value should be compared against a body"
+    );
+  }
+
+  #[test]
+  fn trail_observations_can_reuse_named_shapes_of_the_same_structure() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "ShapeAdded": { "shapeId": "user_shape_1", "baseShapeId": "$object", "name": "User" }},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "user_shape_1", "name": "id", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "string_shape_1"}} }},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "user_shape_1", "name": "name", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "string_shape_1"}} }},
+    ]))
+    .expect("should be able to deserialize test events");
+    let mut spec_projection = SpecProjection::from(events);
+    let reusable_shapes = ShapeQueries::new(spec_projection.shape()).named_shape_structures();
+
+    let post_body = BodyDescriptor::from(json!({
+      "author": { "id": "1", "name": "Jane" },
+      "editors": [{ "id": "2", "name": "John" }],
+      "title": "A post"
+    }));
+    let post_observations = observe_body_trails(post_body.clone()).normalized();
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let (root_shape_id, commands, shape_reuses) = post_observations.into_commands_reusing(
+      &mut test_id_generator,
+      &JsonTrail::empty(),
+      &reusable_shapes,
+    );
+    let root_shape_id = root_shape_id.unwrap();
+    let commands = commands.collect::<Vec<_>>();
+
+    let mut reused_trails = shape_reuses
+      .iter()
+      .map(|shape_reuse| {
+        assert_eq!(shape_reuse.shape_id, "user_shape_1");
+        assert_eq!(shape_reuse.name, "User");
+        shape_reuse.json_trail.clone()
+      })
+      .collect::<Vec<_>>();
+    reused_trails.sort();
+    assert_eq!(
+      reused_trails,
+      vec![
+        JsonTrail::empty().with_object_key(String::from("author")),
+        JsonTrail::empty()
+          .with_object_key(String::from("editors"))
+          .with_array_item(0),
+      ]
+    );
+
+    let added_objects_count = commands
+      .iter()
+      .filter(|command| {
+        matches!(
+          command,
+          SpecCommand::ShapeCommand(ShapeCommand::AddShape(add_shape))
+            if add_shape.base_shape_id == "$object"
+        )
+      })
+      .count();
+    assert_eq!(
+      added_objects_count, 1,
+      "only the post object should be added"
+    );
+
+    for command in commands {
+      let events = spec_projection
+        .execute(command)
+        .expect("generated commands must be valid");
+      for event in events {
+        spec_projection.apply(event)
+      }
+    }
+    assert_no_shape_diffs(&spec_projection, &root_shape_id, std::iter::once(post_body));
+  }
+
   #[test]
   fn trail_observations_can_generate_commands_for_one_off_polymorphic_bodies() {
     let primitive_bodies = vec![
@@ -1095,7 +2098,8 @@ mod test {
       primitive_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(primitive_results.0.is_some());
-    let spec_projection = assert_valid_commands(primitive_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), primitive_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       primitive_results.0.as_ref().unwrap(),
@@ -1110,7 +2114,8 @@ mod test {
       collections_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
     );
     assert!(collections_results.0.is_some());
-    let spec_projection = assert_valid_commands(collections_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), collections_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       collections_results.0.as_ref().unwrap(),
@@ -1150,7 +2155,8 @@ mod test {
     let collections_results =
       collect_commands(collections_observations.into_commands(&mut test_id_generator, &json_trail));
     assert!(collections_results.0.is_some());
-    let spec_projection = assert_valid_commands(collections_results.1.clone());
+    let spec_projection =
+      assert_valid_commands(SpecProjection::default(), collections_results.1.clone());
     assert_no_shape_diffs(
       &spec_projection,
       collections_results.0.as_ref().unwrap(),
@@ -1221,7 +2227,7 @@ mod test {
     let root_results =
       collect_commands(root_observations.into_commands(&mut test_id_generator, &root_trail));
     assert!(root_results.0.is_some());
-    assert_valid_commands(root_results.1.clone());
+    assert_valid_commands(SpecProjection::default(), root_results.1.clone());
     assert_eq!(root_results.1.len(), 1);
     assert_debug_snapshot!(
       "trail_observations_does_not_generate_commands_for_orphaned_shapes__root_results",
@@ -1239,7 +2245,7 @@ mod test {
     let nested_results =
       collect_commands(nested_observations.into_commands(&mut test_id_generator, &nested_trail));
     assert!(nested_results.0.is_some());
-    assert_valid_commands(nested_results.1.clone());
+    assert_valid_commands(SpecProjection::default(), nested_results.1.clone());
     assert_eq!(nested_results.1.len(), 1);
     assert_debug_snapshot!(
       "trail_observations_does_not_generate_commands_for_orphaned_shapes__nested_results",
@@ -1259,7 +2265,7 @@ mod test {
     );
 
     assert!(array_item_results.0.is_some());
-    assert_valid_commands(array_item_results.1.clone());
+    assert_valid_commands(SpecProjection::default(), array_item_results.1.clone());
     assert_eq!(array_item_results.1.len(), 1);
     assert_debug_snapshot!(
       "trail_observations_does_not_generate_commands_for_orphaned_shapes__array_item_results",
@@ -1267,25 +2273,104 @@ mod test {
     );
   }
 
-  fn collect_commands(
-    (root_shape_id, commands): (Option<String>, impl Iterator<Item = SpecCommand>),
-  ) -> (Option<String>, Vec<SpecCommand>) {
-    (root_shape_id, commands.collect::<Vec<_>>())
-  }
+  #[test]
+  fn trail_observations_can_generate_commands_for_map_bodies() {
+    let map_bodies = (0..3)
+      .map(|page| {
+        let users = (1..=4)
+          .map(|n| {
+            let n = page * 4 + n;
+            (
+              format!("user_{}", n),
+              json!({ "name": format!("user {}", n), "age": 20 + n }),
+            )
+          })
+          .collect::<serde_json::Map<_, _>>();
+        BodyDescriptor::from(json!({ "users": users }))
+      })
+      .collect::<Vec<_>>();
+    let map_observations = map_bodies.iter().cloned().fold(
+      TrailObservationsResult::default(),
+      |mut observations, body| {
+        observations.union(observe_body_trails(body));
+        observations
+      },
+    );
 
-  fn assert_valid_commands(commands: impl IntoIterator<Item = SpecCommand>) -> SpecProjection {
-    let mut spec_projection = SpecProjection::default();
-    for command in commands {
-      let events = spec_projection
-        .execute(command)
-        .expect("generated commands must be valid");
+    let mut test_id_generator = TestIdGenerator::default();
 
-      for event in events {
-        spec_projection.apply(event)
+    let map_results =
+      collect_commands(map_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()));
+    assert!(map_results.0.is_some());
+    assert!(map_results.1.iter().any(|command| match command {
+      SpecCommand::ShapeCommand(ShapeCommand::AddShape(add_shape)) => {
+        add_shape.base_shape_id == "$map"
       }
+      _ => false,
+    }));
+    let spec_projection = assert_valid_commands(SpecProjection::default(), map_results.1.clone());
+    assert_no_shape_diffs(
+      &spec_projection,
+      map_results.0.as_ref().unwrap(),
+      map_bodies.into_iter().chain(vec![
+        BodyDescriptor::from(json!({
+          "users": { "user_13": { "name": "user 13", "age": 33 } }
+        })),
+        BodyDescriptor::from(json!({
+          "users": { "": { "name": "nameless", "age": 0 } }
+        })),
+      ]),
+    );
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_map_bodies__map_results",
+      &map_results
+    );
+  }
+
+  #[test]
+  fn trail_observations_learns_objects_with_many_fields_as_objects() {
+    let fields = (1..=12)
+      .map(|n| (format!("field_{}", n), json!(n)))
+      .collect::<serde_json::Map<_, _>>();
+    let mut optional_fields = fields.clone();
+    optional_fields.remove("field_12");
+
+    // the same keys every time, or too few distinct keys for the objects observed
+    let repeated_observations =
+      observe_body_trails(BodyDescriptor::from(json!({ "object": fields })));
+    let optional_observations = (0..20)
+      .map(|n| {
+        if n % 2 == 0 {
+          json!({ "object": fields })
+        } else {
+          json!({ "object": optional_fields })
+        }
+      })
+      .fold(
+        TrailObservationsResult::default(),
+        |mut observations, body| {
+          observations.union(observe_body_trails(BodyDescriptor::from(body)));
+          observations
+        },
+      );
+
+    let mut test_id_generator = TestIdGenerator::default();
+    for observations in vec![repeated_observations, optional_observations] {
+      let (_, commands) =
+        collect_commands(observations.into_commands(&mut test_id_generator, &JsonTrail::empty()));
+      assert!(!commands.iter().any(|command| match command {
+        SpecCommand::ShapeCommand(ShapeCommand::AddShape(add_shape)) => {
+          add_shape.base_shape_id == "$map"
+        }
+        _ => false,
+      }));
     }
+  }
 
-    spec_projection
+  fn collect_commands(
+    (root_shape_id, commands): (Option<String>, impl Iterator<Item = SpecCommand>),
+  ) -> (Option<String>, Vec<SpecCommand>) {
+    (root_shape_id, commands.collect::<Vec<_>>())
   }
 
   fn assert_no_shape_diffs(
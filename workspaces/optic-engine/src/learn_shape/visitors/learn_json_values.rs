@@ -112,8 +112,25 @@ impl BodyPrimitiveVisitor<TrailValues> for LearnPrimitiveVisitor {
 
     match body {
       BodyDescriptor::Boolean => trail_values.was_boolean = true,
-      BodyDescriptor::Number => trail_values.was_number = true,
-      BodyDescriptor::String => trail_values.was_string = true,
+      BodyDescriptor::Number(number) => {
+        trail_values.was_number = true;
+        let is_integer = number.is_integer() == Some(true);
+        trail_values.all_integers = trail_values
+          .all_integers
+          .map(|all_integers| all_integers && is_integer);
+      }
+      BodyDescriptor::String(string) => {
+        trail_values.was_string = true;
+        if string.is_described() {
+          trail_values.insert_string_format(string.format());
+        }
+        if string.values_exceeded() {
+          trail_values.exceed_string_values();
+        }
+        for value in string.values() {
+          trail_values.insert_string_value(value);
+        }
+      }
       BodyDescriptor::Null => trail_values.was_null = true,
       _ => unreachable!("should not call primitive visitor without a primitive value"),
     }
@@ -182,10 +199,26 @@ impl BodyObjectVisitor<TrailValues> for LearnObjectVisitor {
 
     if let BodyDescriptor::Object(object_description) = &body {
       trail_values.was_object = true;
+      trail_values.object_observations += 1;
 
       let keys = object_description.keys().map(|x| (*x).clone());
       let keys_set = HashSet::<String>::from_iter(keys);
 
+      let field_strings = object_description
+        .fields()
+        .map(|(key, field_body)| {
+          let string = match field_body {
+            BodyDescriptor::String(string) => match string.values() {
+              [value] => Some(value.clone()),
+              _ => None,
+            },
+            _ => None,
+          };
+          (key.clone(), string)
+        })
+        .collect();
+
+      trail_values.insert_field_set_strings(keys_set.clone(), field_strings);
       trail_values.insert_field_set(keys_set);
     }
   }
@@ -1,41 +1,62 @@
 #![allow(dead_code, unused_imports, unused_variables)]
 
 mod commands;
+pub mod compare;
 mod events;
 mod interactions;
 mod learn_shape;
+pub mod openapi;
 mod projections;
 mod protos;
 mod queries;
 mod shapes;
 mod spec;
 mod state;
+#[cfg(test)]
+mod test_helpers;
 
 #[cfg(feature = "streams")]
 pub mod streams;
 
 pub use commands::{CommandContext, EndpointCommand, RfcCommand, SpecCommand, SpecCommandHandler};
+pub use compare::changelog::{changelog, Changelog};
+pub use compare::{compare_specs, SpecComparison};
 pub use cqrs_core::Aggregate;
 pub use events::{
   http_interaction::{ArbitraryData, Body, HttpInteraction, Request, Response},
   RfcEvent, SpecChunkEvent, SpecEvent,
 };
-pub use interactions::result::{BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult};
+pub use interactions::result::{
+  BodyAnalysisLocation, BodyAnalysisResult, HeaderAnalysisLocation, HeaderAnalysisResult,
+  InteractionDiffResult,
+};
 pub use interactions::{
-  analyze_documented_bodies, analyze_undocumented_bodies, AnalyzeUndocumentedBodiesConfig,
+  analyze_documented_bodies, analyze_undocumented_bodies, analyze_undocumented_headers,
+  AnalyzeUndocumentedBodiesConfig,
 };
 pub use interactions::{diff as diff_interaction, DiffConfig as DiffInteractionConfig};
-pub use learn_shape::{TrailObservationsResult, TrailValues};
+pub use interactions::{
+  DiffConfigError, EnumLearningConfig, IgnoreRule, IgnoredTrail, InteractionFilters, Pattern,
+  StatusCodeRange,
+};
+pub use learn_shape::{LearnedShapeReuse, TrailObservationsResult, TrailValues};
+pub use openapi::export::export_openapi;
+pub use openapi::import::{import_openapi, OpenApiImport, OpenApiImportError};
 pub use projections::{
   EndpointProjection, LearnedShapeDiffAffordancesProjection, LearnedUndocumentedBodiesProjection,
-  ResponseBodyDescriptor, ShapeProjection, SpecAssemblerProjection, SpecProjection,
+  LearnedUndocumentedHeadersProjection, ResponseBodyDescriptor, ShapeProjection,
+  SpecAssemblerError, SpecAssemblerProjection, SpecAssemblyReport, SpecConflict, SpecProjection,
 };
 pub use protos::shapehash;
 pub use queries::endpoint::EndpointQueries;
-pub use queries::shape::ShapeQueries;
+pub use queries::shape::{ShapeQueries, ShapeStructureIndex};
 pub use queries::spectacle::spec_choices::{JsonType, ShapeChoiceQueries};
 pub use shapes::{diff as diff_shape, JsonTrail};
 pub use spec::append_batch as append_batch_to_spec;
+pub use spec::{
+  blame, repair_spec_conflicts, revert_batch, revert_batch_commands, Blame, BlameChange,
+  BlameEntry, RevertError,
+};
 pub use state::endpoint::ResponseId;
 pub use state::{body::BodyDescriptor, SpecIdGenerator, TaggedInput, Tags};
 
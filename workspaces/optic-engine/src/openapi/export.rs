@@ -0,0 +1,571 @@
+use super::{
+  AdditionalProperties, Header, MediaType, OpenApi, Operation, Parameter, PathItem, RequestBody,
+  Response, Schema,
+};
+use crate::projections::endpoint::{Node, ROOT_PATH_ID};
+use crate::projections::{ContributionsProjection, SpecProjection};
+use crate::queries::{EndpointQueries, ShapeQueries};
+use crate::shapes::traverser::{ShapeTrail, ShapeTrailPathComponent};
+use crate::state::endpoint::{HttpMethod, PathComponentId, ShapedRequestParameterShapeDescriptor};
+use crate::state::shape::{FieldId, ShapeConstraintDescriptor, ShapeId, ShapeKind};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashSet};
+
+pub fn export_openapi(spec: &SpecProjection) -> OpenApi {
+  let mut exporter = OpenApiExporter {
+    endpoint_queries: EndpointQueries::new(spec.endpoint()),
+    shape_queries: ShapeQueries::new(spec.shape()),
+    contributions: spec.contributions(),
+    component_schemas: BTreeMap::new(),
+    visiting_shape_ids: vec![],
+    recursive_shape_ids: HashSet::new(),
+  };
+
+  let mut document = OpenApi::default();
+  exporter.export_path(
+    &String::from(ROOT_PATH_ID),
+    String::new(),
+    vec![],
+    &mut document.paths,
+  );
+  document.components.schemas = exporter.component_schemas;
+
+  document
+}
+
+struct OpenApiExporter<'a> {
+  endpoint_queries: EndpointQueries<'a>,
+  shape_queries: ShapeQueries<'a>,
+  contributions: &'a ContributionsProjection,
+  component_schemas: BTreeMap<ShapeId, Schema>,
+  visiting_shape_ids: Vec<ShapeId>,
+  recursive_shape_ids: HashSet<ShapeId>,
+}
+
+impl<'a> OpenApiExporter<'a> {
+  fn export_path(
+    &mut self,
+    path_id: &PathComponentId,
+    absolute_path: String,
+    path_parameters: Vec<Parameter>,
+    paths: &mut BTreeMap<String, PathItem>,
+  ) {
+    let operations = self
+      .endpoint_queries
+      .resolve_path_methods(path_id)
+      .into_iter()
+      .filter_map(|method| {
+        let operation = self.export_operation(path_id, &method)?;
+        Some((method.to_lowercase(), operation))
+      })
+      .collect::<Vec<_>>();
+
+    if !operations.is_empty() {
+      let path = if absolute_path.is_empty() {
+        String::from("/")
+      } else {
+        absolute_path.clone()
+      };
+      // duplicated paths end up at the same absolute path, the first operation for a method wins
+      let path_item = paths.entry(path).or_insert_with(|| PathItem {
+        parameters: path_parameters.clone(),
+        ..PathItem::default()
+      });
+      for (method, operation) in operations {
+        path_item.operations.entry(method).or_insert(operation);
+      }
+    }
+
+    let child_paths = self
+      .endpoint_queries
+      .endpoint_projection
+      .get_child_path_component_nodes(path_id)
+      .into_iter()
+      .flatten()
+      .filter_map(|node| match node {
+        Node::PathComponent(child_path_id, descriptor) => {
+          Some((child_path_id.clone(), descriptor.clone()))
+        }
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+
+    for (child_path_id, descriptor) in child_paths {
+      let mut child_path_parameters = path_parameters.clone();
+      let child_path = if descriptor.is_parameter {
+        child_path_parameters.push(Parameter {
+          name: descriptor.name.clone(),
+          location: String::from("path"),
+          description: self.contribution(&child_path_id, "description"),
+          required: true,
+          schema: Some(Schema::of_type("string")),
+        });
+        format!("{}/{{{}}}", absolute_path, descriptor.name)
+      } else {
+        format!("{}/{}", absolute_path, descriptor.name)
+      };
+
+      self.export_path(&child_path_id, child_path, child_path_parameters, paths);
+    }
+  }
+
+  fn export_operation(
+    &mut self,
+    path_id: &PathComponentId,
+    method: &HttpMethod,
+  ) -> Option<Operation> {
+    let requests = self
+      .endpoint_queries
+      .resolve_requests(path_id, method)
+      .into_iter()
+      .flatten()
+      .map(|(request_id, descriptor)| (request_id.clone(), descriptor.body.clone()))
+      .collect::<Vec<_>>();
+    let responses = self
+      .endpoint_queries
+      .endpoint_projection
+      .get_response_nodes(path_id)
+      .into_iter()
+      .flatten()
+      .filter_map(|(response_method, status_code, node)| match node {
+        Node::Response(response_id, descriptor) if response_method == method => {
+          Some((*status_code, response_id.clone(), descriptor.body.clone()))
+        }
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+
+    if requests.is_empty() && responses.is_empty() {
+      return None;
+    }
+
+    let endpoint_id = format!("{}.{}", path_id, method);
+    let mut operation = Operation {
+      summary: self.contribution(&endpoint_id, "purpose"),
+      description: self.contribution(&endpoint_id, "description"),
+      ..Operation::default()
+    };
+
+    operation.parameters = self.export_query_parameters(path_id, method);
+    let header_parameters = self
+      .endpoint_queries
+      .resolve_endpoint_header_params(path_id, method)
+      .into_iter()
+      .flatten()
+      .map(|(parameter_id, descriptor)| {
+        (
+          parameter_id.clone(),
+          descriptor.name.clone(),
+          descriptor.shape.clone(),
+        )
+      })
+      .collect::<Vec<_>>();
+    for (parameter_id, name, shape) in header_parameters {
+      let (schema, required) = self.parameter_schema(shape.as_ref());
+      operation.parameters.push(Parameter {
+        name,
+        location: String::from("header"),
+        description: self.contribution(&parameter_id, "description"),
+        required,
+        schema,
+      });
+    }
+
+    let request_bodies = requests
+      .iter()
+      .filter_map(|(request_id, body)| Some((request_id, body.as_ref()?)))
+      .collect::<Vec<_>>();
+    if !request_bodies.is_empty() {
+      let mut request_body = RequestBody {
+        required: request_bodies.len() == requests.len(),
+        ..RequestBody::default()
+      };
+      for (request_id, body) in request_bodies {
+        if request_body.description.is_none() {
+          request_body.description = self.contribution(request_id, "description");
+        }
+        let schema = self.body_schema(&body.root_shape_id);
+        request_body.content.insert(
+          body.http_content_type.clone(),
+          MediaType {
+            schema: Some(schema),
+          },
+        );
+      }
+      operation.request_body = Some(request_body);
+    }
+
+    for (status_code, response_id, body) in responses {
+      let description = self.contribution(&response_id, "description");
+      let schema = body
+        .as_ref()
+        .map(|body| self.body_schema(&body.root_shape_id));
+
+      let response = match operation.responses.entry(status_code.to_string()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(Response {
+          headers: self.export_response_headers(path_id, method, status_code),
+          ..Response::default()
+        }),
+      };
+      if let Some(description) = description {
+        if response.description.is_empty() {
+          response.description = description;
+        }
+      }
+      if let (Some(body), Some(schema)) = (body, schema) {
+        response.content.insert(
+          body.http_content_type,
+          MediaType {
+            schema: Some(schema),
+          },
+        );
+      }
+    }
+
+    Some(operation)
+  }
+
+  // query parameters are described by a single object shape, of which every field is a parameter
+  fn export_query_parameters(
+    &mut self,
+    path_id: &PathComponentId,
+    method: &HttpMethod,
+  ) -> Vec<Parameter> {
+    let query_shape_id = self
+      .endpoint_queries
+      .resolve_endpoint_query_params(path_id, method)
+      .and_then(|(_, descriptor)| descriptor.shape.as_ref())
+      .filter(|shape| !shape.is_removed)
+      .map(|shape| shape.shape_id.clone());
+
+    let query_shape_id = match query_shape_id {
+      Some(shape_id) => shape_id,
+      None => return vec![],
+    };
+    if *self.shape_queries.resolve_to_core_shape(&query_shape_id) != ShapeKind::ObjectKind {
+      return vec![];
+    }
+
+    self
+      .object_fields(&query_shape_id, &ShapeTrail::new(query_shape_id.clone()))
+      .into_iter()
+      .map(|(field_id, name, field_shape_id, field_trail)| {
+        let (schema, required) = self.field_schema(&field_shape_id, &field_trail);
+        Parameter {
+          name,
+          location: String::from("query"),
+          description: self.contribution(&field_id, "description"),
+          required,
+          schema: Some(schema),
+        }
+      })
+      .collect()
+  }
+
+  fn export_response_headers(
+    &mut self,
+    path_id: &PathComponentId,
+    method: &HttpMethod,
+    status_code: u16,
+  ) -> BTreeMap<String, Header> {
+    let header_parameters = self
+      .endpoint_queries
+      .resolve_endpoint_response_header_params(path_id, method, status_code)
+      .into_iter()
+      .flatten()
+      .map(|(parameter_id, descriptor)| {
+        (
+          parameter_id.clone(),
+          descriptor.name.clone(),
+          descriptor.shape.clone(),
+        )
+      })
+      .collect::<Vec<_>>();
+
+    header_parameters
+      .into_iter()
+      .map(|(parameter_id, name, shape)| {
+        let (schema, required) = self.parameter_schema(shape.as_ref());
+        let header = Header {
+          description: self.contribution(&parameter_id, "description"),
+          required,
+          schema,
+        };
+        (name, header)
+      })
+      .collect()
+  }
+
+  fn parameter_schema(
+    &mut self,
+    shape: Option<&ShapedRequestParameterShapeDescriptor>,
+  ) -> (Option<Schema>, bool) {
+    match shape.filter(|shape| !shape.is_removed) {
+      Some(shape) => {
+        let shape_trail = ShapeTrail::new(shape.shape_id.clone());
+        let (schema, required) = self.field_schema(&shape.shape_id, &shape_trail);
+        (Some(schema), required)
+      }
+      None => (None, false),
+    }
+  }
+
+  // Optional shapes only make sense as a field (or parameter) of something else, where they make
+  // it not required
+  fn field_schema(&mut self, shape_id: &ShapeId, shape_trail: &ShapeTrail) -> (Schema, bool) {
+    match self.shape_queries.resolve_to_core_shape(shape_id) {
+      ShapeKind::OptionalKind => {
+        let schema = match self.parameter_shape(shape_id, shape_trail, ShapeKind::OptionalKind) {
+          Some((inner_shape_id, inner_trail)) => self.shape_schema(&inner_shape_id, &inner_trail),
+          None => Schema::default(),
+        };
+        (schema, false)
+      }
+      _ => (self.shape_schema(shape_id, shape_trail), true),
+    }
+  }
+
+  // root shapes of bodies are exported as components, unless they resolve to the component of
+  // another shape (like the one they descend from), in which case that's the one referred to
+  fn body_schema(&mut self, shape_id: &ShapeId) -> Schema {
+    if self.component_schemas.contains_key(shape_id) {
+      return Schema::component_reference(shape_id);
+    }
+
+    let schema = self.shape_schema(shape_id, &ShapeTrail::new(shape_id.clone()));
+    if schema.reference.is_some() {
+      return schema;
+    }
+    self.component_schemas.insert(shape_id.clone(), schema);
+    Schema::component_reference(shape_id)
+  }
+
+  // The trail leads to the shape, from the root shape we're exporting. It's what parameters are
+  // resolved through, so generic shapes are described with what they're bound to where they're used.
+  fn shape_schema(&mut self, shape_id: &ShapeId, shape_trail: &ShapeTrail) -> Schema {
+    if self.visiting_shape_ids.contains(shape_id) {
+      self.recursive_shape_ids.insert(shape_id.clone());
+      return Schema::component_reference(shape_id);
+    }
+
+    // shapes descending from another (non-core) shape share its definition, unless it declares
+    // parameters, which the descending shape might bind
+    if let Some(base_shape_id) = self.shape_queries.resolve_base_shape_id(shape_id) {
+      if !self.shape_queries.declares_shape_parameters(&base_shape_id) {
+        return self.body_schema(&base_shape_id);
+      }
+    }
+
+    self.visiting_shape_ids.push(shape_id.clone());
+    let mut schema = match self.shape_queries.resolve_to_core_shape(shape_id) {
+      ShapeKind::ObjectKind => {
+        let mut schema = Schema::of_type("object");
+        for (field_id, name, field_shape_id, field_trail) in
+          self.object_fields(shape_id, shape_trail)
+        {
+          let (mut field_schema, required) = self.field_schema(&field_shape_id, &field_trail);
+          if let Some(description) = self.contribution(&field_id, "description") {
+            field_schema.description = Some(description);
+          }
+          if required {
+            schema.required.push(name.clone());
+          }
+          schema.properties.insert(name, field_schema);
+        }
+        schema.required.sort();
+        schema
+      }
+      ShapeKind::ListKind => {
+        let item_schema = match self.parameter_shape(shape_id, shape_trail, ShapeKind::ListKind) {
+          Some((item_shape_id, item_trail)) => self.shape_schema(&item_shape_id, &item_trail),
+          None => Schema::default(),
+        };
+        Schema {
+          items: Some(Box::new(item_schema)),
+          ..Schema::of_type("array")
+        }
+      }
+      ShapeKind::MapKind => {
+        let value_schema = match self.parameter_shape(shape_id, shape_trail, ShapeKind::MapKind) {
+          Some((value_shape_id, value_trail)) => self.shape_schema(&value_shape_id, &value_trail),
+          None => Schema::default(),
+        };
+        Schema {
+          additional_properties: Some(AdditionalProperties::Schema(Box::new(value_schema))),
+          ..Schema::of_type("object")
+        }
+      }
+      ShapeKind::NullableKind => {
+        let inner_schema =
+          match self.parameter_shape(shape_id, shape_trail, ShapeKind::NullableKind) {
+            Some((inner_shape_id, inner_trail)) => self.shape_schema(&inner_shape_id, &inner_trail),
+            None => Schema::default(),
+          };
+        // OpenAPI 3.0 doesn't allow siblings next to a reference
+        if inner_schema.reference.is_some() {
+          Schema {
+            one_of: vec![inner_schema],
+            nullable: true,
+            ..Schema::default()
+          }
+        } else {
+          Schema {
+            nullable: true,
+            ..inner_schema
+          }
+        }
+      }
+      ShapeKind::OptionalKind => {
+        match self.parameter_shape(shape_id, shape_trail, ShapeKind::OptionalKind) {
+          Some((inner_shape_id, inner_trail)) => self.shape_schema(&inner_shape_id, &inner_trail),
+          None => Schema::default(),
+        }
+      }
+      ShapeKind::OneOfKind => {
+        let mut branch_schemas = self
+          .shape_queries
+          .resolve_parameters_to_shapes(shape_id)
+          .into_iter()
+          .map(|(parameter_id, branch_shape_id)| {
+            let branch_trail = shape_trail
+              .with_component(ShapeTrailPathComponent::OneOfTrail {
+                shape_id: shape_id.clone(),
+              })
+              .with_component(ShapeTrailPathComponent::OneOfItemTrail {
+                one_of_id: shape_id.clone(),
+                parameter_id,
+                item_shape_id: branch_shape_id.clone(),
+              });
+            self.shape_schema(&branch_shape_id, &branch_trail)
+          })
+          .collect::<Vec<_>>();
+        if branch_schemas.len() == 1 {
+          branch_schemas.remove(0)
+        } else {
+          Schema {
+            one_of: branch_schemas,
+            ..Schema::default()
+          }
+        }
+      }
+      ShapeKind::StringKind => self.constrained_schema(shape_id, Schema::of_type("string")),
+      ShapeKind::NumberKind => self.constrained_schema(shape_id, Schema::of_type("number")),
+      ShapeKind::BooleanKind => Schema::of_type("boolean"),
+      _ => Schema::default(),
+    };
+    self.visiting_shape_ids.pop();
+
+    if let Some(description) = self.contribution(shape_id, "description") {
+      schema.description = Some(description);
+    }
+
+    if self.recursive_shape_ids.contains(shape_id) {
+      self.component_schemas.insert(shape_id.clone(), schema);
+      Schema::component_reference(shape_id)
+    } else {
+      schema
+    }
+  }
+
+  fn constrained_schema(&self, shape_id: &ShapeId, mut schema: Schema) -> Schema {
+    match self.shape_queries.resolve_shape_constraint(shape_id) {
+      Some(ShapeConstraintDescriptor::FormatConstraint(constraint)) => {
+        schema.other_keywords.insert(
+          String::from("format"),
+          serde_json::Value::from(constraint.format.name()),
+        );
+      }
+      Some(ShapeConstraintDescriptor::EnumConstraint(constraint)) => {
+        schema.other_keywords.insert(
+          String::from("enum"),
+          serde_json::Value::from(constraint.values.clone()),
+        );
+      }
+      Some(ShapeConstraintDescriptor::IntegerConstraint(_)) => {
+        schema.schema_type = Some(String::from("integer"));
+      }
+      Some(ShapeConstraintDescriptor::NoConstraint(_)) | None => {}
+    }
+    schema
+  }
+
+  // The fields of the object at the end of the trail, with their shapes and the trails to them
+  fn object_fields(
+    &self,
+    shape_id: &ShapeId,
+    shape_trail: &ShapeTrail,
+  ) -> Vec<(FieldId, String, ShapeId, ShapeTrail)> {
+    self
+      .shape_queries
+      .resolve_shape_field_id_and_names(shape_id)
+      .map(|(field_id, name)| {
+        let field_shape_id = self
+          .shape_queries
+          .resolve_trail_field_shape(shape_trail, field_id);
+        let field_trail = shape_trail.with_component(ShapeTrailPathComponent::ObjectFieldTrail {
+          field_id: field_id.clone(),
+          field_shape_id: field_shape_id.clone(),
+          parent_object_shape_id: shape_id.clone(),
+        });
+        (field_id.clone(), name.clone(), field_shape_id, field_trail)
+      })
+      .collect()
+  }
+
+  // The shape the core parameter of the shape at the end of the trail is bound to, with the trail
+  // to it
+  fn parameter_shape(
+    &self,
+    shape_id: &ShapeId,
+    shape_trail: &ShapeTrail,
+    kind: ShapeKind,
+  ) -> Option<(ShapeId, ShapeTrail)> {
+    let parameter_id = String::from(kind.get_parameter_descriptor()?.shape_parameter_id);
+    let parameter_shape_id = self
+      .shape_queries
+      .resolve_trail_parameter_to_shape(shape_trail, &parameter_id);
+
+    let shape_id = shape_id.clone();
+    let components = match kind {
+      ShapeKind::ListKind => vec![ShapeTrailPathComponent::ListItemTrail {
+        list_shape_id: shape_id,
+        item_shape_id: parameter_shape_id.clone(),
+      }],
+      ShapeKind::MapKind => vec![ShapeTrailPathComponent::MapValueTrail {
+        map_shape_id: shape_id,
+        value_shape_id: parameter_shape_id.clone(),
+      }],
+      ShapeKind::NullableKind => vec![
+        ShapeTrailPathComponent::NullableTrail {
+          shape_id: shape_id.clone(),
+        },
+        ShapeTrailPathComponent::NullableItemTrail {
+          shape_id,
+          inner_shape_id: parameter_shape_id.clone(),
+        },
+      ],
+      ShapeKind::OptionalKind => vec![
+        ShapeTrailPathComponent::OptionalTrail {
+          shape_id: shape_id.clone(),
+        },
+        ShapeTrailPathComponent::OptionalItemTrail {
+          shape_id,
+          inner_shape_id: parameter_shape_id.clone(),
+        },
+      ],
+      _ => return None,
+    };
+    let parameter_trail = components
+      .into_iter()
+      .fold(shape_trail.clone(), |trail, component| {
+        trail.with_component(component)
+      });
+
+    Some((parameter_shape_id, parameter_trail))
+  }
+
+  fn contribution(&self, id: &str, key: &str) -> Option<String> {
+    self.contributions.get(id, key).cloned()
+  }
+}
@@ -0,0 +1,935 @@
+use super::{AdditionalProperties, OpenApi, Operation, Parameter, Schema};
+use crate::commands::{EndpointCommand, RfcCommand, ShapeCommand, SpecCommand};
+use crate::projections::endpoint::{Node, ROOT_PATH_ID};
+use crate::projections::SpecProjection;
+use crate::queries::EndpointQueries;
+use crate::state::endpoint::{PathComponentId, RequestId, ResponseId};
+use crate::state::shape::{
+  EnumConstraint, FormatConstraint, IntegerConstraint, ShapeConstraintDescriptor, ShapeId,
+  ShapeKind, StringFormat,
+};
+use crate::state::SpecIdGenerator;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use thiserror::Error;
+
+const HTTP_METHODS: [&str; 8] = [
+  "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+// Schema keywords that only annotate a schema, so dropping them loses no structure
+const ANNOTATION_KEYWORDS: [&str; 5] = ["title", "example", "examples", "externalDocs", "xml"];
+
+// Nested `$ref`s are followed at most this deep when inlining non-schema components
+const MAX_REFERENCE_DEPTH: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum OpenApiImportError {
+  #[error("unsupported OpenAPI version '{0}', expected 3.0.x")]
+  UnsupportedVersion(String),
+  #[error("could not resolve reference '{0}'")]
+  UnresolvedReference(String),
+  #[error("invalid OpenAPI document: {0}")]
+  InvalidDocument(#[from] serde_json::Error),
+}
+
+// Parses a document, inlining any references to reusable parameters, request bodies,
+// responses and headers. References to schemas are left in place, as they become shared shapes.
+impl TryFrom<JsonValue> for OpenApi {
+  type Error = OpenApiImportError;
+
+  fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+    let version = value
+      .get("openapi")
+      .and_then(JsonValue::as_str)
+      .unwrap_or_default();
+    if !version.starts_with("3.0") {
+      return Err(OpenApiImportError::UnsupportedVersion(String::from(
+        version,
+      )));
+    }
+
+    let inlined = inline_references(&value, &value, 0)?;
+    Ok(serde_json::from_value(inlined)?)
+  }
+}
+
+fn inline_references(
+  document: &JsonValue,
+  value: &JsonValue,
+  depth: usize,
+) -> Result<JsonValue, OpenApiImportError> {
+  match value {
+    JsonValue::Object(map) => {
+      if let Some(reference) = map.get("$ref").and_then(JsonValue::as_str) {
+        if reference.starts_with("#/components/") && !reference.starts_with("#/components/schemas/")
+        {
+          let unresolved = || OpenApiImportError::UnresolvedReference(String::from(reference));
+          if depth >= MAX_REFERENCE_DEPTH {
+            return Err(unresolved());
+          }
+          let target = document.pointer(&reference[1..]).ok_or_else(unresolved)?;
+          return inline_references(document, target, depth + 1);
+        }
+      }
+
+      let inlined = map
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), inline_references(document, value, depth)?)))
+        .collect::<Result<_, OpenApiImportError>>()?;
+      Ok(JsonValue::Object(inlined))
+    }
+    JsonValue::Array(items) => {
+      let inlined = items
+        .iter()
+        .map(|item| inline_references(document, item, depth))
+        .collect::<Result<_, _>>()?;
+      Ok(JsonValue::Array(inlined))
+    }
+    _ => Ok(value.clone()),
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApiImport {
+  pub commands: Vec<SpecCommand>,
+  pub unsupported: Vec<UnsupportedOpenApi>,
+}
+
+// A part of the document that couldn't be represented in the spec, located by JSON pointer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedOpenApi {
+  pub location: String,
+  pub reason: String,
+}
+
+impl fmt::Display for UnsupportedOpenApi {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.location, self.reason)
+  }
+}
+
+// Generates the commands that add the paths, endpoints and shapes of an OpenAPI document
+// to a spec, meant to be committed as a single batch. Path components already in the spec are
+// reused, everything else is added.
+pub fn import_openapi(
+  spec: &SpecProjection,
+  document: &OpenApi,
+  id_generator: &mut impl SpecIdGenerator,
+) -> OpenApiImport {
+  let mut importer = OpenApiImporter {
+    document,
+    endpoint_queries: EndpointQueries::new(spec.endpoint()),
+    id_generator,
+    commands: vec![],
+    unsupported: vec![],
+    path_ids: HashMap::new(),
+    component_shape_ids: HashMap::new(),
+    importing_components: HashSet::new(),
+    reserved_shape_id: None,
+    contributed: HashSet::new(),
+  };
+
+  for (path, path_item) in &document.paths {
+    let location = pointer("/paths", path);
+    let path_parameter_ids = importer.import_path(path, &location);
+    let path_id = path_parameter_ids
+      .last()
+      .map(|(_, path_id)| path_id.clone())
+      .unwrap_or_else(|| String::from(ROOT_PATH_ID));
+
+    for (method, operation) in &path_item.operations {
+      let operation_location = pointer(&location, method);
+      if !HTTP_METHODS.contains(&method.as_str()) {
+        importer.report(&operation_location, "only http methods can be imported");
+        continue;
+      }
+
+      // operation parameters override path item parameters with the same name and location
+      let mut parameters = operation.parameters.iter().collect::<Vec<_>>();
+      parameters.extend(path_item.parameters.iter().filter(|parameter| {
+        !operation.parameters.iter().any(|overriding| {
+          overriding.name == parameter.name && overriding.location == parameter.location
+        })
+      }));
+
+      let http_method = method.to_uppercase();
+      let endpoint_id = format!("{}.{}", path_id, http_method);
+      if let Some(summary) = &operation.summary {
+        importer.contribute(&endpoint_id, "purpose", summary);
+      }
+      if let Some(description) = &operation.description {
+        importer.contribute(&endpoint_id, "description", description);
+      }
+
+      for parameter in parameters.iter().filter(|p| p.location == "path") {
+        match path_parameter_ids
+          .iter()
+          .find(|(name, _)| name.as_deref() == Some(parameter.name.as_str()))
+        {
+          Some((_, parameter_path_id)) => {
+            if let Some(description) = &parameter.description {
+              importer.contribute(&parameter_path_id.clone(), "description", description);
+            }
+          }
+          None => importer.report(
+            &operation_location,
+            &format!(
+              "path parameter '{}' is not part of the path",
+              parameter.name
+            ),
+          ),
+        }
+      }
+      for parameter in parameters.iter().filter(|p| p.location == "cookie") {
+        importer.report(
+          &operation_location,
+          &format!("cookie parameter '{}' is not supported", parameter.name),
+        );
+      }
+
+      let request_ids =
+        importer.import_requests(&path_id, &http_method, operation, &operation_location);
+      let header_parameters = parameters
+        .iter()
+        .copied()
+        .filter(|p| p.location == "header")
+        .collect::<Vec<_>>();
+      importer.import_request_headers(&request_ids[0], &header_parameters, &operation_location);
+
+      let query_parameters = parameters
+        .iter()
+        .copied()
+        .filter(|p| p.location == "query")
+        .collect::<Vec<_>>();
+      importer.import_query_parameters(
+        &path_id,
+        &http_method,
+        &query_parameters,
+        &operation_location,
+      );
+
+      importer.import_responses(&path_id, &http_method, operation, &operation_location);
+    }
+  }
+
+  OpenApiImport {
+    commands: importer.commands,
+    unsupported: importer.unsupported,
+  }
+}
+
+struct OpenApiImporter<'a, G: SpecIdGenerator> {
+  document: &'a OpenApi,
+  endpoint_queries: EndpointQueries<'a>,
+  id_generator: &'a mut G,
+  commands: Vec<SpecCommand>,
+  unsupported: Vec<UnsupportedOpenApi>,
+  // keyed by parent path id and segment name, with all parameters sharing the "{}" segment
+  path_ids: HashMap<(PathComponentId, String), PathComponentId>,
+  component_shape_ids: HashMap<String, ShapeId>,
+  importing_components: HashSet<String>,
+  // the id the next added shape takes, so component shapes can be referenced before they're added
+  reserved_shape_id: Option<ShapeId>,
+  contributed: HashSet<(String, String)>,
+}
+
+impl<'a, G: SpecIdGenerator> OpenApiImporter<'a, G> {
+  // Paths
+  // -----
+
+  // Returns the path id of every segment, together with its name when it's a parameter
+  fn import_path(&mut self, path: &str, location: &str) -> Vec<(Option<String>, PathComponentId)> {
+    let mut parent_path_id = String::from(ROOT_PATH_ID);
+    let mut path_ids = vec![];
+
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+      let parameter_name = segment
+        .strip_prefix('{')
+        .and_then(|segment| segment.strip_suffix('}'))
+        .filter(|name| !name.is_empty() && !name.contains(['{', '}']));
+      if parameter_name.is_none() && segment.contains('{') {
+        self.report(
+          location,
+          &format!(
+            "path segment '{}' is partially templated, it is imported as a literal",
+            segment
+          ),
+        );
+      }
+
+      let key = match parameter_name {
+        Some(_) => String::from("{}"),
+        None => String::from(segment),
+      };
+      let path_id = match self
+        .path_ids
+        .get(&(parent_path_id.clone(), key.clone()))
+        .cloned()
+        .or_else(|| self.existing_path_id(&parent_path_id, parameter_name, segment))
+      {
+        Some(path_id) => path_id,
+        None => {
+          let path_id = self.id_generator.path();
+          self.commands.push(
+            match parameter_name {
+              Some(name) => EndpointCommand::add_path_parameter(
+                path_id.clone(),
+                parent_path_id.clone(),
+                String::from(name),
+              ),
+              None => EndpointCommand::add_path_component(
+                path_id.clone(),
+                parent_path_id.clone(),
+                String::from(segment),
+              ),
+            }
+            .into(),
+          );
+          path_id
+        }
+      };
+
+      self.path_ids.insert((parent_path_id, key), path_id.clone());
+      path_ids.push((parameter_name.map(String::from), path_id.clone()));
+      parent_path_id = path_id;
+    }
+
+    path_ids
+  }
+
+  fn existing_path_id(
+    &self,
+    parent_path_id: &PathComponentId,
+    parameter_name: Option<&str>,
+    segment: &str,
+  ) -> Option<PathComponentId> {
+    self
+      .endpoint_queries
+      .endpoint_projection
+      .get_child_path_component_nodes(parent_path_id)?
+      .find_map(|node| match node {
+        Node::PathComponent(path_id, descriptor)
+          if descriptor.is_parameter == parameter_name.is_some()
+            && (descriptor.is_parameter || descriptor.name == segment) =>
+        {
+          Some(path_id.clone())
+        }
+        _ => None,
+      })
+  }
+
+  // Requests and responses
+  // ----------------------
+
+  // Returns the ids of the added requests, of which there's always at least one
+  fn import_requests(
+    &mut self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    operation: &Operation,
+    location: &str,
+  ) -> Vec<RequestId> {
+    let request_body = operation
+      .request_body
+      .as_ref()
+      .filter(|request_body| !request_body.content.is_empty());
+    let request_body = match request_body {
+      Some(request_body) => request_body,
+      None => {
+        let request_id = self.id_generator.request();
+        self.commands.push(
+          EndpointCommand::add_request(
+            request_id.clone(),
+            path_id.clone(),
+            String::from(http_method),
+          )
+          .into(),
+        );
+        return vec![request_id];
+      }
+    };
+
+    let body_location = pointer(location, "requestBody");
+    request_body
+      .content
+      .iter()
+      .map(|(content_type, media_type)| {
+        let request_id = self.id_generator.request();
+        self.commands.push(
+          EndpointCommand::add_request(
+            request_id.clone(),
+            path_id.clone(),
+            String::from(http_method),
+          )
+          .into(),
+        );
+        let schema_location = pointer(
+          &pointer(&pointer(&body_location, "content"), content_type),
+          "schema",
+        );
+        let shape_id = self.import_body_schema(media_type.schema.as_ref(), &schema_location);
+        self.commands.push(
+          EndpointCommand::set_request_body_shape(
+            request_id.clone(),
+            shape_id,
+            content_type.clone(),
+            false,
+          )
+          .into(),
+        );
+        if let Some(description) = &request_body.description {
+          self.contribute(&request_id, "description", description);
+        }
+        request_id
+      })
+      .collect()
+  }
+
+  fn import_responses(
+    &mut self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    operation: &Operation,
+    location: &str,
+  ) {
+    let responses_location = pointer(location, "responses");
+    for (status_code, response) in &operation.responses {
+      let response_location = pointer(&responses_location, status_code);
+      let status_code = match status_code.parse::<u16>() {
+        Ok(status_code) => status_code,
+        Err(_) => {
+          self.report(
+            &response_location,
+            "only responses for a specific status code are supported",
+          );
+          continue;
+        }
+      };
+
+      let add_response = |importer: &mut Self| -> ResponseId {
+        let response_id = importer.id_generator.response();
+        importer.commands.push(
+          EndpointCommand::add_response_by_path_and_method(
+            response_id.clone(),
+            path_id.clone(),
+            String::from(http_method),
+            status_code,
+          )
+          .into(),
+        );
+        if !response.description.is_empty() {
+          importer.contribute(&response_id, "description", &response.description);
+        }
+        response_id
+      };
+
+      let response_ids = if response.content.is_empty() {
+        vec![add_response(self)]
+      } else {
+        response
+          .content
+          .iter()
+          .map(|(content_type, media_type)| {
+            let response_id = add_response(self);
+            let schema_location = pointer(
+              &pointer(&pointer(&response_location, "content"), content_type),
+              "schema",
+            );
+            let shape_id = self.import_body_schema(media_type.schema.as_ref(), &schema_location);
+            self.commands.push(
+              EndpointCommand::set_response_body_shape(
+                response_id.clone(),
+                shape_id,
+                content_type.clone(),
+                false,
+              )
+              .into(),
+            );
+            response_id
+          })
+          .collect::<Vec<_>>()
+      };
+
+      // headers are documented once per status code, on its first response
+      for (name, header) in &response.headers {
+        let header_location = pointer(&pointer(&response_location, "headers"), name);
+        let parameter_id = self.id_generator.request_parameter();
+        self.commands.push(
+          EndpointCommand::add_response_header_parameter(
+            parameter_id.clone(),
+            response_ids[0].clone(),
+            name.clone(),
+          )
+          .into(),
+        );
+        let shape_id =
+          self.import_parameter_schema(header.schema.as_ref(), header.required, &header_location);
+        self
+          .commands
+          .push(EndpointCommand::set_header_parameter_shape(parameter_id.clone(), shape_id).into());
+        if let Some(description) = &header.description {
+          self.contribute(&parameter_id, "description", description);
+        }
+      }
+    }
+  }
+
+  // Parameters
+  // ----------
+
+  fn import_request_headers(
+    &mut self,
+    request_id: &RequestId,
+    parameters: &[&Parameter],
+    location: &str,
+  ) {
+    for parameter in parameters {
+      let parameter_location = pointer(&pointer(location, "parameters"), &parameter.name);
+      let parameter_id = self.id_generator.request_parameter();
+      self.commands.push(
+        EndpointCommand::add_header_parameter(
+          parameter_id.clone(),
+          request_id.clone(),
+          parameter.name.clone(),
+        )
+        .into(),
+      );
+      let shape_id = self.import_parameter_schema(
+        parameter.schema.as_ref(),
+        parameter.required,
+        &parameter_location,
+      );
+      self
+        .commands
+        .push(EndpointCommand::set_header_parameter_shape(parameter_id.clone(), shape_id).into());
+      if let Some(description) = &parameter.description {
+        self.contribute(&parameter_id, "description", description);
+      }
+    }
+  }
+
+  // Query parameters are described by a single object shape, with a field per parameter
+  fn import_query_parameters(
+    &mut self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    parameters: &[&Parameter],
+    location: &str,
+  ) {
+    if parameters.is_empty() {
+      return;
+    }
+
+    let query_shape_id = self.add_shape(ShapeKind::ObjectKind, "");
+    for parameter in parameters {
+      let parameter_location = pointer(&pointer(location, "parameters"), &parameter.name);
+      let field_shape_id = self.import_parameter_schema(
+        parameter.schema.as_ref(),
+        parameter.required,
+        &parameter_location,
+      );
+      let field_id = self.id_generator.field();
+      self.commands.push(
+        ShapeCommand::add_field(
+          parameter.name.clone(),
+          field_id.clone(),
+          query_shape_id.clone(),
+          field_shape_id,
+        )
+        .into(),
+      );
+      if let Some(description) = &parameter.description {
+        self.contribute(&field_id, "description", description);
+      }
+    }
+
+    let query_parameters_id = self.id_generator.query_params();
+    self.commands.push(
+      EndpointCommand::add_query_parameters(
+        query_parameters_id.clone(),
+        path_id.clone(),
+        String::from(http_method),
+      )
+      .into(),
+    );
+    self.commands.push(
+      EndpointCommand::set_query_parameters_shape(query_parameters_id, query_shape_id, false)
+        .into(),
+    );
+  }
+
+  fn import_parameter_schema(
+    &mut self,
+    schema: Option<&Schema>,
+    is_required: bool,
+    location: &str,
+  ) -> ShapeId {
+    let shape_id = match schema {
+      Some(schema) => self.import_schema(schema, &pointer(location, "schema"), ""),
+      None => self.add_shape(ShapeKind::StringKind, ""),
+    };
+    if is_required {
+      shape_id
+    } else {
+      self.wrap_optional(shape_id)
+    }
+  }
+
+  // Shapes
+  // ------
+
+  fn import_body_schema(&mut self, schema: Option<&Schema>, location: &str) -> ShapeId {
+    match schema {
+      Some(schema) => {
+        let shape_id = self.import_schema(schema, location, "");
+        if schema.reference.is_none() {
+          if let Some(description) = &schema.description {
+            self.contribute(&shape_id, "description", description);
+          }
+        }
+        shape_id
+      }
+      None => self.add_shape(ShapeKind::UnknownKind, ""),
+    }
+  }
+
+  fn import_schema(&mut self, schema: &Schema, location: &str, name: &str) -> ShapeId {
+    let constraint_keyword = match schema_constraint(schema) {
+      Some(ShapeConstraintDescriptor::FormatConstraint(_)) => Some("format"),
+      Some(ShapeConstraintDescriptor::EnumConstraint(_)) => Some("enum"),
+      _ => None,
+    };
+    for keyword in schema.other_keywords.keys() {
+      if !keyword.starts_with("x-")
+        && !ANNOTATION_KEYWORDS.contains(&keyword.as_str())
+        && constraint_keyword != Some(keyword.as_str())
+      {
+        self.report(
+          &pointer(location, keyword),
+          &format!("keyword '{}' is not supported", keyword),
+        );
+      }
+    }
+
+    if let Some(reference) = &schema.reference {
+      return match reference.strip_prefix("#/components/schemas/") {
+        Some(component_name) => self.import_component(component_name, location),
+        None => {
+          self.report(
+            location,
+            "only references to schemas in components are supported",
+          );
+          self.add_shape(ShapeKind::UnknownKind, name)
+        }
+      };
+    }
+
+    if !schema.nullable {
+      return self.import_schema_kind(schema, location, name);
+    }
+    let nullable_shape_id = self.add_shape(ShapeKind::NullableKind, name);
+    let inner_shape_id = self.import_schema_kind(schema, location, "");
+    self.commands.push(
+      ShapeCommand::set_parameter_shape(
+        nullable_shape_id.clone(),
+        String::from("$nullableInner"),
+        inner_shape_id,
+      )
+      .into(),
+    );
+    nullable_shape_id
+  }
+
+  fn import_schema_kind(&mut self, schema: &Schema, location: &str, name: &str) -> ShapeId {
+    if !schema.all_of.is_empty() {
+      if let [only_schema] = schema.all_of.as_slice() {
+        if schema.schema_type.is_none() && schema.properties.is_empty() {
+          return self.import_schema(
+            only_schema,
+            &pointer(&pointer(location, "allOf"), "0"),
+            name,
+          );
+        }
+      }
+      self.report(
+        &pointer(location, "allOf"),
+        "combining schemas with allOf is not supported, it is imported as unknown",
+      );
+      return self.add_shape(ShapeKind::UnknownKind, name);
+    }
+
+    let (choices, choices_keyword) = if !schema.one_of.is_empty() {
+      (&schema.one_of, "oneOf")
+    } else {
+      (&schema.any_of, "anyOf")
+    };
+    if !choices.is_empty() {
+      let one_of_shape_id = self.add_shape(ShapeKind::OneOfKind, name);
+      for (index, choice) in choices.iter().enumerate() {
+        let choice_location = pointer(&pointer(location, choices_keyword), &index.to_string());
+        let choice_shape_id = self.import_schema(choice, &choice_location, "");
+        let shape_parameter_id = self.id_generator.shape_param();
+        self.commands.push(
+          ShapeCommand::add_shape_parameter(
+            shape_parameter_id.clone(),
+            one_of_shape_id.clone(),
+            String::new(),
+          )
+          .into(),
+        );
+        self.commands.push(
+          ShapeCommand::set_parameter_shape(
+            one_of_shape_id.clone(),
+            shape_parameter_id,
+            choice_shape_id,
+          )
+          .into(),
+        );
+      }
+      return one_of_shape_id;
+    }
+
+    if let Some(AdditionalProperties::Schema(value_schema)) = &schema.additional_properties {
+      if schema.properties.is_empty() {
+        let map_shape_id = self.add_shape(ShapeKind::MapKind, name);
+        let value_shape_id =
+          self.import_schema(value_schema, &pointer(location, "additionalProperties"), "");
+        self.commands.push(
+          ShapeCommand::set_parameter_shape(
+            map_shape_id.clone(),
+            String::from("$mapValue"),
+            value_shape_id,
+          )
+          .into(),
+        );
+        return map_shape_id;
+      }
+      self.report(
+        &pointer(location, "additionalProperties"),
+        "objects with both properties and additional properties are not supported",
+      );
+    }
+
+    match schema.schema_type.as_deref() {
+      Some("object") => self.import_object_schema(schema, location, name),
+      None if !schema.properties.is_empty() => self.import_object_schema(schema, location, name),
+      Some("array") => {
+        let list_shape_id = self.add_shape(ShapeKind::ListKind, name);
+        let item_shape_id = match &schema.items {
+          Some(items) => self.import_schema(items, &pointer(location, "items"), ""),
+          None => self.add_shape(ShapeKind::UnknownKind, ""),
+        };
+        self.commands.push(
+          ShapeCommand::set_parameter_shape(
+            list_shape_id.clone(),
+            String::from("$listItem"),
+            item_shape_id,
+          )
+          .into(),
+        );
+        list_shape_id
+      }
+      Some("string") => {
+        let string_shape_id = self.add_shape(ShapeKind::StringKind, name);
+        self.constrain_shape(&string_shape_id, schema);
+        string_shape_id
+      }
+      Some("number") | Some("integer") => {
+        let number_shape_id = self.add_shape(ShapeKind::NumberKind, name);
+        self.constrain_shape(&number_shape_id, schema);
+        number_shape_id
+      }
+      Some("boolean") => self.add_shape(ShapeKind::BooleanKind, name),
+      Some(schema_type) => {
+        self.report(
+          &pointer(location, "type"),
+          &format!("type '{}' is not supported", schema_type),
+        );
+        self.add_shape(ShapeKind::UnknownKind, name)
+      }
+      None => self.add_shape(ShapeKind::UnknownKind, name),
+    }
+  }
+
+  fn import_object_schema(&mut self, schema: &Schema, location: &str, name: &str) -> ShapeId {
+    let object_shape_id = self.add_shape(ShapeKind::ObjectKind, name);
+    for (field_name, field_schema) in &schema.properties {
+      let field_location = pointer(&pointer(location, "properties"), field_name);
+      let mut field_shape_id = self.import_schema(field_schema, &field_location, "");
+      if !schema.required.contains(field_name) {
+        field_shape_id = self.wrap_optional(field_shape_id);
+      }
+      let field_id = self.id_generator.field();
+      self.commands.push(
+        ShapeCommand::add_field(
+          field_name.clone(),
+          field_id.clone(),
+          object_shape_id.clone(),
+          field_shape_id,
+        )
+        .into(),
+      );
+      if let Some(description) = &field_schema.description {
+        self.contribute(&field_id, "description", description);
+      }
+    }
+    object_shape_id
+  }
+
+  // Every component schema becomes a single named shape, shared by all references to it
+  fn import_component(&mut self, component_name: &str, location: &str) -> ShapeId {
+    if let Some(shape_id) = self.component_shape_ids.get(component_name) {
+      return shape_id.clone();
+    }
+
+    let document = self.document;
+    let schema = match document.components.schemas.get(component_name) {
+      Some(schema) => schema,
+      None => {
+        self.report(
+          location,
+          &format!("schema component '{}' does not exist", component_name),
+        );
+        return self.add_shape(ShapeKind::UnknownKind, "");
+      }
+    };
+    let component_location = pointer("/components/schemas", component_name);
+
+    // aliases of other components resolve to that component's shape
+    if schema.reference.is_some() {
+      if !self
+        .importing_components
+        .insert(String::from(component_name))
+      {
+        self.report(
+          &component_location,
+          "schema component only references itself",
+        );
+        return self.add_shape(ShapeKind::UnknownKind, "");
+      }
+      let shape_id = self.import_schema(schema, &component_location, component_name);
+      self.importing_components.remove(component_name);
+      self
+        .component_shape_ids
+        .insert(String::from(component_name), shape_id.clone());
+      return shape_id;
+    }
+
+    // the component's shape id is known before its schema is imported, so recursive
+    // references to it resolve to the shape that's being added
+    let shape_id = self.id_generator.shape();
+    self
+      .component_shape_ids
+      .insert(String::from(component_name), shape_id.clone());
+    self.reserved_shape_id = Some(shape_id);
+    let imported_shape_id = self.import_schema(schema, &component_location, component_name);
+    if self.reserved_shape_id.take().is_some() {
+      // the schema resolved to another component's shape without adding one of its own
+      self
+        .component_shape_ids
+        .insert(String::from(component_name), imported_shape_id.clone());
+    }
+    if let Some(description) = &schema.description {
+      self.contribute(&imported_shape_id, "description", description);
+    }
+    imported_shape_id
+  }
+
+  fn add_shape(&mut self, shape_kind: ShapeKind, name: &str) -> ShapeId {
+    let shape_id = match self.reserved_shape_id.take() {
+      Some(shape_id) => shape_id,
+      None => self.id_generator.shape(),
+    };
+    self
+      .commands
+      .push(ShapeCommand::add_shape(shape_id.clone(), shape_kind, String::from(name)).into());
+    shape_id
+  }
+
+  fn constrain_shape(&mut self, shape_id: &ShapeId, schema: &Schema) {
+    if let Some(constraint) = schema_constraint(schema) {
+      self
+        .commands
+        .push(ShapeCommand::set_shape_constraint(shape_id.clone(), constraint).into());
+    }
+  }
+
+  fn wrap_optional(&mut self, shape_id: ShapeId) -> ShapeId {
+    let optional_shape_id = self.add_shape(ShapeKind::OptionalKind, "");
+    self.commands.push(
+      ShapeCommand::set_parameter_shape(
+        optional_shape_id.clone(),
+        String::from("$optionalInner"),
+        shape_id,
+      )
+      .into(),
+    );
+    optional_shape_id
+  }
+
+  // Contributions and reporting
+  // ---------------------------
+
+  // path parameters are shared by operations, so only their first description is kept
+  fn contribute(&mut self, id: &str, key: &str, value: &str) {
+    if !self
+      .contributed
+      .insert((String::from(id), String::from(key)))
+    {
+      return;
+    }
+    self.commands.push(
+      RfcCommand::add_contribution(String::from(id), String::from(key), String::from(value)).into(),
+    );
+  }
+
+  fn report(&mut self, location: &str, reason: &str) {
+    self.unsupported.push(UnsupportedOpenApi {
+      location: String::from(location),
+      reason: String::from(reason),
+    });
+  }
+}
+
+// Appends a reference token to a JSON pointer, escaping it as per RFC 6901
+fn pointer(base: &str, token: &str) -> String {
+  format!("{}/{}", base, token.replace('~', "~0").replace('/', "~1"))
+}
+
+// The constraint a primitive schema puts on its values, if a shape can carry it
+fn schema_constraint(schema: &Schema) -> Option<ShapeConstraintDescriptor> {
+  match schema.schema_type.as_deref()? {
+    "integer" => Some(ShapeConstraintDescriptor::IntegerConstraint(
+      IntegerConstraint {},
+    )),
+    "string" => {
+      let format = schema
+        .other_keywords
+        .get("format")
+        .and_then(JsonValue::as_str)
+        .and_then(StringFormat::from_name);
+      if let Some(format) = format {
+        return Some(ShapeConstraintDescriptor::FormatConstraint(
+          FormatConstraint { format },
+        ));
+      }
+      let values = schema
+        .other_keywords
+        .get("enum")?
+        .as_array()?
+        .iter()
+        .map(|value| value.as_str().map(String::from))
+        .collect::<Option<Vec<_>>>()?;
+      Some(ShapeConstraintDescriptor::EnumConstraint(EnumConstraint {
+        values,
+      }))
+    }
+    _ => None,
+  }
+}
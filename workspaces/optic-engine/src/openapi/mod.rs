@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub mod export;
+pub mod import;
+
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+// Document
+// --------
+// A subset of the OpenAPI 3.0 document structure, covering what can be described by a spec.
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApi {
+  pub openapi: String,
+  pub info: Info,
+  #[serde(default)]
+  pub paths: BTreeMap<String, PathItem>,
+  #[serde(default, skip_serializing_if = "Components::is_empty")]
+  pub components: Components,
+}
+
+impl Default for OpenApi {
+  fn default() -> Self {
+    Self {
+      openapi: String::from(OPENAPI_VERSION),
+      info: Info::default(),
+      paths: BTreeMap::new(),
+      components: Components::default(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+  pub title: String,
+  pub version: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+}
+
+impl Default for Info {
+  fn default() -> Self {
+    Self {
+      title: String::from("Optic Generated OpenAPI"),
+      version: String::from("1.0.0"),
+      description: None,
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathItem {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub summary: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub parameters: Vec<Parameter>,
+  // keyed by the lowercased http method
+  #[serde(flatten)]
+  pub operations: BTreeMap<String, Operation>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub summary: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub parameters: Vec<Parameter>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub request_body: Option<RequestBody>,
+  #[serde(default)]
+  pub responses: BTreeMap<String, Response>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Parameter {
+  pub name: String,
+  #[serde(rename = "in")]
+  pub location: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub required: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub schema: Option<Schema>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestBody {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub content: BTreeMap<String, MediaType>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub required: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+  #[serde(default)]
+  pub description: String,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub headers: BTreeMap<String, Header>,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub content: BTreeMap<String, MediaType>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Header {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub required: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub schema: Option<Schema>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaType {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub schema: Option<Schema>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub schemas: BTreeMap<String, Schema>,
+}
+
+impl Components {
+  pub fn is_empty(&self) -> bool {
+    self.schemas.is_empty()
+  }
+}
+
+// OpenAPI 3.0 flavoured JSON Schema, which marks nullable schemas with `nullable` instead of
+// a `null` type.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+  #[serde(rename = "$ref", default, skip_serializing_if = "Option::is_none")]
+  pub reference: Option<String>,
+  #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+  pub schema_type: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub nullable: bool,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub properties: BTreeMap<String, Schema>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub required: Vec<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub items: Option<Box<Schema>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub additional_properties: Option<AdditionalProperties>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub one_of: Vec<Schema>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub any_of: Vec<Schema>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub all_of: Vec<Schema>,
+  // keywords a spec can't describe (formats, patterns, enums, ...), kept so imports can report them
+  #[serde(flatten)]
+  pub other_keywords: BTreeMap<String, serde_json::Value>,
+}
+
+// Either whether any additional properties are allowed, or the schema they should match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AdditionalProperties {
+  Allowed(bool),
+  Schema(Box<Schema>),
+}
+
+impl Schema {
+  pub fn of_type(schema_type: &str) -> Self {
+    Self {
+      schema_type: Some(String::from(schema_type)),
+      ..Self::default()
+    }
+  }
+
+  pub fn component_reference(name: &str) -> Self {
+    Self {
+      reference: Some(format!("#/components/schemas/{}", name)),
+      ..Self::default()
+    }
+  }
+}
+
+fn is_false(value: &bool) -> bool {
+  !*value
+}
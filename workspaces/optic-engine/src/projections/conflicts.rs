@@ -5,9 +5,10 @@ use crate::state::endpoint::{
 };
 use cqrs_core::{Aggregate, AggregateEvent};
 use petgraph::csr::NodeIndex;
-use petgraph::Direction::Incoming;
+use petgraph::Direction::{Incoming, Outgoing};
 use petgraph::Graph;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct ConflictsProjection {
@@ -18,6 +19,12 @@ pub struct ConflictsProjection {
   // without rebuilding these maps.
   pub node_id_to_index: HashMap<String, petgraph::graph::NodeIndex>,
   pub domain_id_to_index: HashMap<String, petgraph::graph::NodeIndex>,
+
+  // what every id was added as and in which order, so renamed paths can be added again under their
+  // new absolute path, together with everything below them
+  domain_entities: HashMap<String, DomainEntity>,
+  added_domain_ids: Vec<String>,
+  body_content_types: HashMap<String, HttpContentType>,
 }
 
 impl Default for ConflictsProjection {
@@ -30,6 +37,9 @@ impl Default for ConflictsProjection {
       graph,
       node_id_to_index,
       domain_id_to_index,
+      domain_entities: HashMap::new(),
+      added_domain_ids: vec![],
+      body_content_types: HashMap::new(),
     };
 
     projection.with_path_component_node(
@@ -50,7 +60,7 @@ impl Aggregate for ConflictsProjection {
 }
 
 impl AggregateEvent<ConflictsProjection> for ShapeEvent {
-  fn apply_to(self, projection: &mut ConflictsProjection) {}
+  fn apply_to(self, _projection: &mut ConflictsProjection) {}
 }
 
 impl AggregateEvent<ConflictsProjection> for EndpointEvent {
@@ -62,6 +72,12 @@ impl AggregateEvent<ConflictsProjection> for EndpointEvent {
       EndpointEvent::PathParameterAdded(e) => {
         projection.with_path_parameter(e.parent_path_id, e.path_id, e.name);
       }
+      EndpointEvent::PathComponentRenamed(e) => {
+        projection.with_path_renamed(e.path_id, e.name);
+      }
+      EndpointEvent::PathParameterRenamed(e) => {
+        projection.with_path_renamed(e.path_id, e.name);
+      }
       EndpointEvent::RequestAdded(e) => {
         projection.with_request(e.request_id, e.path_id, e.http_method);
       }
@@ -69,10 +85,30 @@ impl AggregateEvent<ConflictsProjection> for EndpointEvent {
         projection.with_response(e.response_id, e.path_id, e.http_method, e.http_status_code);
       }
       EndpointEvent::RequestBodySet(e) => {
-        //@TODO: projection.with_request_body_content_type(...)
+        projection.with_body_content_type(e.request_id, Some(e.body_descriptor.http_content_type));
+      }
+      EndpointEvent::RequestBodyUnset(e) => {
+        projection.with_body_content_type(e.request_id, None);
       }
       EndpointEvent::ResponseBodySet(e) => {
-        //@TODO: projection.with_response_body_content_type(...)
+        projection.with_body_content_type(e.response_id, Some(e.body_descriptor.http_content_type));
+      }
+      EndpointEvent::ResponseBodyUnset(e) => {
+        projection.with_body_content_type(e.response_id, None);
+      }
+      EndpointEvent::PathComponentRemoved(e) => {
+        projection.without_domain_id(e.path_id);
+      }
+      EndpointEvent::PathParameterRemoved(e) => {
+        projection.without_domain_id(e.path_id);
+      }
+      EndpointEvent::RequestRemoved(e) => {
+        projection.with_body_content_type(e.request_id.clone(), None);
+        projection.without_domain_id(e.request_id);
+      }
+      EndpointEvent::ResponseRemoved(e) => {
+        projection.with_body_content_type(e.response_id.clone(), None);
+        projection.without_domain_id(e.response_id);
       }
       _ => eprintln!(
         "Ignoring applying event of type '{}' for ConflictsProjection",
@@ -83,7 +119,13 @@ impl AggregateEvent<ConflictsProjection> for EndpointEvent {
 }
 
 impl AggregateEvent<ConflictsProjection> for SpecEvent {
-  fn apply_to(self, projection: &mut ConflictsProjection) {}
+  fn apply_to(self, projection: &mut ConflictsProjection) {
+    match self {
+      SpecEvent::EndpointEvent(event) => projection.apply(event),
+      SpecEvent::ShapeEvent(event) => projection.apply(event),
+      SpecEvent::RfcEvent(_) => {}
+    }
+  }
 }
 
 impl<I> From<I> for ConflictsProjection
@@ -109,6 +151,14 @@ impl ConflictsProjection {
     path_id: PathComponentId,
     path_name: String,
   ) {
+    self.with_domain_entity(
+      path_id.clone(),
+      DomainEntity::PathComponent {
+        parent_path_id: parent_path_id.clone(),
+        name: path_name.clone(),
+      },
+    );
+
     // build absolute path pattern inductively
     let parent_node_index = self
       .domain_id_to_index
@@ -150,6 +200,14 @@ impl ConflictsProjection {
     path_id: PathComponentId,
     path_name: String,
   ) {
+    self.with_domain_entity(
+      path_id.clone(),
+      DomainEntity::PathParameter {
+        parent_path_id: parent_path_id.clone(),
+        name: path_name,
+      },
+    );
+
     // build absolute path pattern inductively
     let parent_node_index = self
       .domain_id_to_index
@@ -253,6 +311,14 @@ impl ConflictsProjection {
     path_id: PathComponentId,
     http_method: HttpMethod,
   ) {
+    self.with_domain_entity(
+      request_id.clone(),
+      DomainEntity::Request {
+        path_id: path_id.clone(),
+        http_method: http_method.clone(),
+      },
+    );
+
     let method_index = self.with_http_method_node(&path_id, &http_method);
     let node = self
       .graph
@@ -274,6 +340,15 @@ impl ConflictsProjection {
     http_method: HttpMethod,
     http_status_code: HttpStatusCode,
   ) {
+    self.with_domain_entity(
+      response_id.clone(),
+      DomainEntity::Response {
+        path_id: path_id.clone(),
+        http_method: http_method.clone(),
+        http_status_code,
+      },
+    );
+
     let method_index = self.with_http_method_node(&path_id, &http_method);
 
     let existing_status_code_index = self
@@ -322,6 +397,292 @@ impl ConflictsProjection {
       .domain_id_to_index
       .insert(response_id, status_code_index);
   }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  // Requests are children of their method node, responses of their status code node. Either can have
+  // at most one body content type, tracked by a content type node below that node.
+  pub fn with_body_content_type(
+    &mut self,
+    request_or_response_id: String,
+    http_content_type: Option<HttpContentType>,
+  ) {
+    let parent_index = match self.domain_id_to_index.get(&request_or_response_id) {
+      Some(index) => *index,
+      None => return,
+    };
+
+    let content_type_indexes = self
+      .graph
+      .neighbors_directed(parent_index, Incoming)
+      .collect::<Vec<_>>();
+    for content_type_index in &content_type_indexes {
+      if let Some(Node::HttpContentType(_, ids)) = self.graph.node_weight_mut(*content_type_index) {
+        ids.retain(|id| *id != request_or_response_id);
+      }
+    }
+
+    let http_content_type = match http_content_type {
+      Some(content_type) => content_type,
+      None => {
+        self.body_content_types.remove(&request_or_response_id);
+        return;
+      }
+    };
+    self
+      .body_content_types
+      .insert(request_or_response_id.clone(), http_content_type.clone());
+
+    let existing_content_type_index =
+      content_type_indexes
+        .into_iter()
+        .find(|index| match self.graph.node_weight(*index) {
+          Some(Node::HttpContentType(content_type, _)) => *content_type == http_content_type,
+          _ => false,
+        });
+
+    match existing_content_type_index {
+      Some(index) => {
+        if let Some(Node::HttpContentType(_, ids)) = self.graph.node_weight_mut(index) {
+          ids.push(request_or_response_id);
+        }
+      }
+      None => {
+        let content_type_node =
+          Node::HttpContentType(http_content_type, vec![request_or_response_id]);
+        let content_type_index = self.graph.add_node(content_type_node);
+        self
+          .graph
+          .add_edge(content_type_index, parent_index, Edge::IsChildOf);
+      }
+    }
+  }
+
+  pub fn without_domain_id(&mut self, domain_id: String) {
+    self.domain_entities.remove(&domain_id);
+    let node_index = match self.domain_id_to_index.remove(&domain_id) {
+      Some(index) => index,
+      None => return,
+    };
+
+    match self.graph.node_weight_mut(node_index) {
+      Some(Node::Path(_, ids))
+      | Some(Node::HttpMethod(_, ids))
+      | Some(Node::HttpStatusCode(_, ids))
+      | Some(Node::HttpContentType(_, ids)) => ids.retain(|id| *id != domain_id),
+      None => {}
+    }
+  }
+
+  fn with_domain_entity(&mut self, domain_id: String, domain_entity: DomainEntity) {
+    if self
+      .domain_entities
+      .insert(domain_id.clone(), domain_entity)
+      .is_none()
+    {
+      self.added_domain_ids.push(domain_id);
+    }
+  }
+
+  // A renamed path has a new absolute path, as do the paths below it, so all of them are taken out
+  // and added again, together with their requests, responses and body content types
+  pub fn with_path_renamed(&mut self, path_id: PathComponentId, name: String) {
+    match self.domain_entities.get_mut(&path_id) {
+      Some(DomainEntity::PathComponent {
+        name: path_name, ..
+      }) => *path_name = name,
+      // parameters match any name, so their absolute path stays the same
+      Some(DomainEntity::PathParameter {
+        name: path_name, ..
+      }) => {
+        *path_name = name;
+        return;
+      }
+      _ => return,
+    }
+
+    // ids are added after their parents, so a single pass finds everything below the path
+    let mut renamed_ids = HashSet::new();
+    let mut renamed_entities = vec![];
+    for domain_id in &self.added_domain_ids {
+      let domain_entity = match self.domain_entities.get(domain_id) {
+        Some(domain_entity) => domain_entity,
+        None => continue,
+      };
+      if (*domain_id == path_id || renamed_ids.contains(domain_entity.parent_path_id()))
+        && renamed_ids.insert(domain_id.clone())
+      {
+        renamed_entities.push((
+          domain_id.clone(),
+          domain_entity.clone(),
+          self.body_content_types.get(domain_id).cloned(),
+        ));
+      }
+    }
+
+    // children are taken out before their parents, and added again after them
+    for (domain_id, _, _) in renamed_entities.iter().rev() {
+      self.with_body_content_type(domain_id.clone(), None);
+      self.without_domain_id(domain_id.clone());
+    }
+    self
+      .added_domain_ids
+      .retain(|domain_id| !renamed_ids.contains(domain_id));
+
+    for (domain_id, domain_entity, http_content_type) in renamed_entities {
+      match domain_entity {
+        DomainEntity::PathComponent {
+          parent_path_id,
+          name,
+        } => self.with_path_component(parent_path_id, domain_id.clone(), name),
+        DomainEntity::PathParameter {
+          parent_path_id,
+          name,
+        } => self.with_path_parameter(parent_path_id, domain_id.clone(), name),
+        DomainEntity::Request {
+          path_id,
+          http_method,
+        } => self.with_request(domain_id.clone(), path_id, http_method),
+        DomainEntity::Response {
+          path_id,
+          http_method,
+          http_status_code,
+        } => self.with_response(domain_id.clone(), path_id, http_method, http_status_code),
+      }
+      if http_content_type.is_some() {
+        self.with_body_content_type(domain_id, http_content_type);
+      }
+    }
+  }
+
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn spec_conflicts(&self) -> Vec<SpecConflict> {
+    let mut conflicts = vec![];
+
+    for node_index in self.graph.node_indices() {
+      match &self.graph[node_index] {
+        Node::Path(absolute_path, path_ids) => {
+          if path_ids.len() > 1 {
+            conflicts.push(SpecConflict::DuplicatePath {
+              absolute_path: absolute_path.clone(),
+              path_ids: path_ids.clone(),
+            });
+          }
+
+          if !path_ids.is_empty() {
+            if let Some(parent_path) = absolute_path.strip_suffix("{}") {
+              let literal_path_ids = self.literal_sibling_path_ids(parent_path);
+              if !literal_path_ids.is_empty() {
+                conflicts.push(SpecConflict::AmbiguousPathParameter {
+                  absolute_path: absolute_path.clone(),
+                  path_ids: path_ids.clone(),
+                  literal_path_ids,
+                });
+              }
+            }
+          }
+        }
+        Node::HttpMethod(http_method, request_ids) => {
+          let absolute_path = self.parent_absolute_path(node_index);
+          for (http_content_type, request_ids) in self.ids_by_content_type(node_index, request_ids)
+          {
+            if request_ids.len() > 1 {
+              conflicts.push(SpecConflict::DuplicateRequest {
+                absolute_path: absolute_path.clone(),
+                http_method: http_method.clone(),
+                http_content_type,
+                request_ids,
+              });
+            }
+          }
+        }
+        Node::HttpStatusCode(http_status_code, response_ids) => {
+          let method_index = self.parent_index(node_index);
+          let http_method = match method_index.map(|index| &self.graph[index]) {
+            Some(Node::HttpMethod(http_method, _)) => http_method.clone(),
+            _ => continue,
+          };
+          let absolute_path = method_index
+            .map(|index| self.parent_absolute_path(index))
+            .unwrap_or_default();
+
+          for (http_content_type, response_ids) in
+            self.ids_by_content_type(node_index, response_ids)
+          {
+            if response_ids.len() > 1 {
+              conflicts.push(SpecConflict::DuplicateResponse {
+                absolute_path: absolute_path.clone(),
+                http_method: http_method.clone(),
+                http_status_code: *http_status_code,
+                http_content_type,
+                response_ids,
+              });
+            }
+          }
+        }
+        Node::HttpContentType(_, _) => {}
+      }
+    }
+
+    conflicts
+  }
+
+  fn parent_index(
+    &self,
+    node_index: petgraph::graph::NodeIndex,
+  ) -> Option<petgraph::graph::NodeIndex> {
+    self.graph.neighbors_directed(node_index, Outgoing).next()
+  }
+
+  fn parent_absolute_path(&self, node_index: petgraph::graph::NodeIndex) -> AbsolutePathPattern {
+    match self
+      .parent_index(node_index)
+      .map(|index| &self.graph[index])
+    {
+      Some(Node::Path(absolute_path, _)) => absolute_path.clone(),
+      _ => AbsolutePathPattern::new(),
+    }
+  }
+
+  // literal paths sharing a parent with a path parameter, which would match the same urls
+  fn literal_sibling_path_ids(&self, parent_path: &str) -> Vec<PathComponentId> {
+    self
+      .graph
+      .raw_nodes()
+      .iter()
+      .filter_map(|node| match &node.weight {
+        Node::Path(absolute_path, ids) => absolute_path
+          .strip_prefix(parent_path)
+          .filter(|name| !name.is_empty() && *name != "{}" && !name.contains('/'))
+          .map(|_| ids.clone()),
+        _ => None,
+      })
+      .flatten()
+      .collect()
+  }
+
+  // groups the requests or responses of a node by the content type of their body, if they have one
+  fn ids_by_content_type(
+    &self,
+    node_index: petgraph::graph::NodeIndex,
+    ids: &[String],
+  ) -> Vec<(Option<HttpContentType>, Vec<String>)> {
+    let mut grouped: Vec<(Option<HttpContentType>, Vec<String>)> = self
+      .graph
+      .neighbors_directed(node_index, Incoming)
+      .filter_map(|index| match &self.graph[index] {
+        Node::HttpContentType(content_type, ids) => Some((Some(content_type.clone()), ids.clone())),
+        _ => None,
+      })
+      .collect();
+
+    let without_body = ids
+      .iter()
+      .filter(|id| !grouped.iter().any(|(_, with_body)| with_body.contains(id)))
+      .cloned()
+      .collect();
+    grouped.push((None, without_body));
+
+    grouped
+  }
 }
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
@@ -340,4 +701,69 @@ pub enum Node {
 pub enum Edge {
   IsChildOf,
 }
+
+#[derive(Debug, Clone)]
+enum DomainEntity {
+  PathComponent {
+    parent_path_id: PathComponentId,
+    name: String,
+  },
+  PathParameter {
+    parent_path_id: PathComponentId,
+    name: String,
+  },
+  Request {
+    path_id: PathComponentId,
+    http_method: HttpMethod,
+  },
+  Response {
+    path_id: PathComponentId,
+    http_method: HttpMethod,
+    http_status_code: HttpStatusCode,
+  },
+}
+
+impl DomainEntity {
+  // the path the entity was added to, or below
+  fn parent_path_id(&self) -> &PathComponentId {
+    match self {
+      Self::PathComponent { parent_path_id, .. } | Self::PathParameter { parent_path_id, .. } => {
+        parent_path_id
+      }
+      Self::Request { path_id, .. } | Self::Response { path_id, .. } => path_id,
+    }
+  }
+}
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SpecConflict {
+  #[serde(rename_all = "camelCase")]
+  DuplicatePath {
+    absolute_path: AbsolutePathPattern,
+    path_ids: ConflictingIds,
+  },
+  #[serde(rename_all = "camelCase")]
+  AmbiguousPathParameter {
+    absolute_path: AbsolutePathPattern,
+    path_ids: ConflictingIds,
+    literal_path_ids: ConflictingIds,
+  },
+  #[serde(rename_all = "camelCase")]
+  DuplicateRequest {
+    absolute_path: AbsolutePathPattern,
+    http_method: HttpMethod,
+    http_content_type: Option<HttpContentType>,
+    request_ids: ConflictingIds,
+  },
+  #[serde(rename_all = "camelCase")]
+  DuplicateResponse {
+    absolute_path: AbsolutePathPattern,
+    http_method: HttpMethod,
+    http_status_code: HttpStatusCode,
+    http_content_type: Option<HttpContentType>,
+    response_ids: ConflictingIds,
+  },
+}
 ////////////////////////////////////////////////////////////////////////////////////////////////////
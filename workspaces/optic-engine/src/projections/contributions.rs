@@ -12,6 +12,10 @@ impl ContributionsProjection {
     serde_json::to_string(&self.ids_to_keys_to_values).expect("btree should be serializable")
   }
 
+  pub fn get(&self, id: &str, contribution_key: &str) -> Option<&String> {
+    self.ids_to_keys_to_values.get(id)?.get(contribution_key)
+  }
+
   pub fn with_contribution(&mut self, id: String, contribution_key: String, value: String) {
     self
       .ids_to_keys_to_values
@@ -30,6 +30,24 @@ pub struct QueryParametersDescriptor {
   pub shape: Option<QueryParametersShapeDescriptor>,
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub struct RequestParameterDescriptor {
+  pub location: String,
+  pub name: String,
+  pub shape: Option<ShapedRequestParameterShapeDescriptor>,
+}
+
+impl RequestParameterDescriptor {
+  pub fn is_header(&self) -> bool {
+    self.location == HEADER_PARAMETER_LOCATION
+  }
+
+  // header names are case-insensitive, so we compare them as such
+  pub fn is_named(&self, name: &str) -> bool {
+    self.name.eq_ignore_ascii_case(name)
+  }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct RequestDescriptor {
   pub body: Option<BodyDescriptor>,
@@ -46,6 +64,7 @@ pub enum Node {
   HttpStatusCode(HttpStatusCode),
   PathComponent(PathComponentId, PathComponentDescriptor),
   QueryParameters(QueryParametersId, QueryParametersDescriptor),
+  RequestParameter(RequestParameterId, RequestParameterDescriptor),
   Request(RequestId, RequestDescriptor),
   Response(ResponseId, ResponseBodyDescriptor),
 }
@@ -62,6 +81,7 @@ pub struct EndpointProjection {
   // which no longer exist or point to a different node. Compiler can't track these nodes for us. Do not delete nodes
   // without rebuilding this map.
   pub node_id_to_index: HashMap<String, petgraph::graph::NodeIndex>,
+  pub path_parameter_shapes_by_path_id: HashMap<PathComponentId, ShapeId>,
 }
 
 impl EndpointProjection {
@@ -99,9 +119,28 @@ impl EndpointProjection {
   }
 
   pub fn without_path_parameter(&mut self, path_id: PathComponentId) {
+    self.path_parameter_shapes_by_path_id.remove(&path_id);
     self.without_path_component(path_id);
   }
 
+  pub fn with_path_parameter_shape(
+    &mut self,
+    path_id: PathComponentId,
+    shape_descriptor: ShapedRequestParameterShapeDescriptor,
+  ) {
+    if shape_descriptor.is_removed {
+      self.path_parameter_shapes_by_path_id.remove(&path_id);
+    } else {
+      self
+        .path_parameter_shapes_by_path_id
+        .insert(path_id, shape_descriptor.shape_id);
+    }
+  }
+
+  pub fn get_path_parameter_shape_id(&self, path_id: &PathComponentId) -> Option<&ShapeId> {
+    self.path_parameter_shapes_by_path_id.get(path_id)
+  }
+
   fn with_path_component_node(
     &mut self,
     parent_path_id: PathComponentId,
@@ -229,6 +268,175 @@ impl EndpointProjection {
     // eventual garbage collection.
   }
 
+  pub fn with_request_parameter(
+    &mut self,
+    path_id: PathComponentId,
+    http_method: HttpMethod,
+    parameter_id: RequestParameterId,
+    parameter_location: String,
+    name: String,
+  ) {
+    let path_node_index = *self
+      .node_id_to_index
+      .get(&path_id)
+      .expect("expected path_id to have a corresponding node");
+    let method_node_index = self.ensure_method_node(path_node_index, http_method);
+    self.with_request_parameter_node(method_node_index, parameter_id, parameter_location, name);
+  }
+
+  pub fn with_request_parameter_by_request(
+    &mut self,
+    request_id: RequestId,
+    parameter_id: RequestParameterId,
+    parameter_location: String,
+    name: String,
+  ) {
+    let request_node_index = *self
+      .node_id_to_index
+      .get(&request_id)
+      .expect("expected request_id to have a corresponding node");
+    let method_node_index = self
+      .get_method_node_index_of(request_node_index)
+      .expect("expected request node to be a child of a method node");
+    self.with_request_parameter_node(method_node_index, parameter_id, parameter_location, name);
+  }
+
+  // response parameters are modelled as request parameters scoped to a status code rather than a method
+  pub fn with_response_parameter(
+    &mut self,
+    response_id: ResponseId,
+    parameter_id: RequestParameterId,
+    parameter_location: String,
+    name: String,
+  ) {
+    let response_node_index = *self
+      .node_id_to_index
+      .get(&response_id)
+      .expect("expected response_id to have a corresponding node");
+    let status_code_node_index = self
+      .get_status_code_node_index_of(response_node_index)
+      .expect("expected response node to be a child of a status code node");
+    self.with_request_parameter_node(
+      status_code_node_index,
+      parameter_id,
+      parameter_location,
+      name,
+    );
+  }
+
+  fn with_request_parameter_node(
+    &mut self,
+    parent_node_index: NodeIndex,
+    parameter_id: RequestParameterId,
+    location: String,
+    name: String,
+  ) {
+    let parameter_node = Node::RequestParameter(
+      parameter_id.clone(),
+      RequestParameterDescriptor {
+        location,
+        name,
+        shape: None,
+      },
+    );
+    let parameter_node_index = self.graph.add_node(parameter_node);
+    self
+      .graph
+      .add_edge(parameter_node_index, parent_node_index, Edge::IsChildOf);
+    self
+      .node_id_to_index
+      .insert(parameter_id, parameter_node_index);
+  }
+
+  pub fn with_request_parameter_name(&mut self, parameter_id: RequestParameterId, name: String) {
+    let parameter_descriptor = self.get_request_parameter_descriptor_mut(&parameter_id);
+    parameter_descriptor.name = name;
+  }
+
+  pub fn with_request_parameter_shape(
+    &mut self,
+    parameter_id: RequestParameterId,
+    shape_descriptor: ShapedRequestParameterShapeDescriptor,
+  ) {
+    let parameter_descriptor = self.get_request_parameter_descriptor_mut(&parameter_id);
+    parameter_descriptor.shape = Some(shape_descriptor);
+  }
+
+  pub fn without_request_parameter_shape(&mut self, parameter_id: RequestParameterId) {
+    let parameter_descriptor = self.get_request_parameter_descriptor_mut(&parameter_id);
+    parameter_descriptor.shape = None;
+  }
+
+  pub fn without_request_parameter(&mut self, parameter_id: RequestParameterId) {
+    let parameter_node_index = *self
+      .node_id_to_index
+      .get(&parameter_id)
+      .expect("expected parameter_id to have a corresponding node");
+
+    let parent_edge_index = self
+      .graph
+      .edges_directed(parameter_node_index, petgraph::Direction::Outgoing)
+      .find(|parent_edge| {
+        let parent_node_index = parent_edge.target();
+        let node = self.graph.node_weight(parent_node_index);
+        matches!(
+          node,
+          Some(Node::HttpMethod(_)) | Some(Node::HttpStatusCode(_))
+        )
+      })
+      .map(|parent_edge| parent_edge.id());
+
+    if let Some(parent_edge_index) = parent_edge_index {
+      self.graph.remove_edge(parent_edge_index); // prevents parameter to be resolved from path node
+    }
+    self.node_id_to_index.remove(&parameter_id); // prevents parameter node to be looked up by parameter id
+
+    // GOTCHA: we're not deleting the parameter node itself, as that would invalidate self.node_id_to_index
+    // as the graph indexes shift.
+  }
+
+  fn get_request_parameter_descriptor_mut(
+    &mut self,
+    parameter_id: &RequestParameterId,
+  ) -> &mut RequestParameterDescriptor {
+    let parameter_node_index = *self
+      .node_id_to_index
+      .get(parameter_id)
+      .expect("expected parameter_id to have a corresponding node");
+    let parameter_node = self.graph.node_weight_mut(parameter_node_index).unwrap();
+    match parameter_node {
+      Node::RequestParameter(_, descriptor) => descriptor,
+      _ => unreachable!("request parameter ids should point to request parameter nodes"),
+    }
+  }
+
+  fn get_method_node_index_of(&self, child_node_index: NodeIndex) -> Option<NodeIndex> {
+    self
+      .graph
+      .neighbors_directed(child_node_index, petgraph::Direction::Outgoing)
+      .find(|parent_node_index| {
+        let node = self.graph.node_weight(*parent_node_index);
+        matches!(node, Some(Node::HttpMethod(_)))
+      })
+  }
+
+  fn get_status_code_node_index_of(&self, child_node_index: NodeIndex) -> Option<NodeIndex> {
+    self
+      .graph
+      .neighbors_directed(child_node_index, petgraph::Direction::Outgoing)
+      .find(|parent_node_index| {
+        let node = self.graph.node_weight(*parent_node_index);
+        matches!(node, Some(Node::HttpStatusCode(_)))
+      })
+  }
+
+  fn get_parent_node_index_of(&self, child_node_index: NodeIndex) -> Option<NodeIndex> {
+    self
+      .graph
+      .neighbors_directed(child_node_index, petgraph::Direction::Outgoing)
+      .next()
+  }
+
   pub fn with_request(
     &mut self,
     path_id: PathComponentId,
@@ -355,6 +563,17 @@ impl EndpointProjection {
     }
   }
 
+  pub fn without_request_body(&mut self, request_id: RequestId) {
+    let request_node_index = self
+      .node_id_to_index
+      .get(&request_id)
+      .expect("expected request_id to have a corresponding node");
+    let request_node = self.graph.node_weight_mut(*request_node_index).unwrap();
+    if let Node::Request(id, body_descriptor) = request_node {
+      body_descriptor.body = None;
+    }
+  }
+
   pub fn with_response_body(
     &mut self,
     response_id: ResponseId,
@@ -377,6 +596,17 @@ impl EndpointProjection {
     }
   }
 
+  pub fn without_response_body(&mut self, response_id: ResponseId) {
+    let response_node_index = self
+      .node_id_to_index
+      .get(&response_id)
+      .expect("expected response_id to have a corresponding node");
+    let response_node = self.graph.node_weight_mut(*response_node_index).unwrap();
+    if let Node::Response(id, body_descriptor) = response_node {
+      body_descriptor.body = None;
+    }
+  }
+
   pub fn with_response(
     &mut self,
     path_id: PathComponentId,
@@ -484,6 +714,32 @@ impl EndpointProjection {
     }
   }
 
+  pub fn get_request_parameter_node_index(
+    &self,
+    parameter_id: &RequestParameterId,
+  ) -> Option<&NodeIndex> {
+    let node_index = self.node_id_to_index.get(parameter_id)?;
+    let node = self.graph.node_weight(*node_index)?;
+    if let &Node::RequestParameter(_, _) = node {
+      Some(node_index)
+    } else {
+      None
+    }
+  }
+
+  pub fn get_request_parameter_descriptor(
+    &self,
+    parameter_id: &RequestParameterId,
+  ) -> Option<&RequestParameterDescriptor> {
+    let node_index = self.node_id_to_index.get(parameter_id)?;
+    let node = self.graph.node_weight(*node_index)?;
+    if let Node::RequestParameter(_, descriptor) = node {
+      Some(descriptor)
+    } else {
+      None
+    }
+  }
+
   pub fn get_request_node_index(&self, request_id: &RequestId) -> Option<&NodeIndex> {
     let node_index = self.node_id_to_index.get(request_id)?;
     let node = self.graph.node_weight(*node_index)?;
@@ -585,16 +841,15 @@ impl EndpointProjection {
           .graph
           .neighbors_directed(http_method_node_index, petgraph::Direction::Incoming);
 
-        status_code_nodes.flat_map(move |status_code_node_index| {
-          let status_code = self
-            .graph
-            .node_weight(status_code_node_index)
-            .map(|node| match node {
-              Node::HttpStatusCode(status_code) => Some(status_code),
-              _ => None,
-            })
-            .flatten()
-            .unwrap();
+        // method nodes also hold requests and header parameters
+        let status_code_nodes = status_code_nodes.filter_map(move |node_index| {
+          match self.graph.node_weight(node_index)? {
+            Node::HttpStatusCode(status_code) => Some((node_index, status_code)),
+            _ => None,
+          }
+        });
+
+        status_code_nodes.flat_map(move |(status_code_node_index, status_code)| {
           let response_nodes = self
             .graph
             .neighbors_directed(status_code_node_index, petgraph::Direction::Incoming);
@@ -657,6 +912,113 @@ impl EndpointProjection {
     Some(matching_method)
   }
 
+  pub fn get_endpoint_header_parameter_nodes<'a>(
+    &'a self,
+    path_id: &PathComponentId,
+    method: &HttpMethod,
+  ) -> Option<impl Iterator<Item = (&'a RequestParameterId, &'a RequestParameterDescriptor)> + 'a>
+  {
+    let path_node_index = self.get_path_component_node_index(path_id)?;
+
+    let method_node_index = self
+      .graph
+      .neighbors_directed(*path_node_index, petgraph::Direction::Incoming)
+      .find(move |i| {
+        let node = self.graph.node_weight(*i).unwrap();
+        match node {
+          Node::HttpMethod(http_method) => method == http_method,
+          _ => false,
+        }
+      })?;
+
+    Some(self.get_header_parameter_nodes_of(method_node_index))
+  }
+
+  pub fn get_endpoint_response_header_parameter_nodes<'a>(
+    &'a self,
+    path_id: &PathComponentId,
+    method: &HttpMethod,
+    status_code: HttpStatusCode,
+  ) -> Option<impl Iterator<Item = (&'a RequestParameterId, &'a RequestParameterDescriptor)> + 'a>
+  {
+    let path_node_index = self.get_path_component_node_index(path_id)?;
+
+    let method_node_index = self
+      .graph
+      .neighbors_directed(*path_node_index, petgraph::Direction::Incoming)
+      .find(move |i| {
+        let node = self.graph.node_weight(*i).unwrap();
+        match node {
+          Node::HttpMethod(http_method) => method == http_method,
+          _ => false,
+        }
+      })?;
+
+    let status_code_node_index = self
+      .graph
+      .neighbors_directed(method_node_index, petgraph::Direction::Incoming)
+      .find(move |i| {
+        let node = self.graph.node_weight(*i).unwrap();
+        match node {
+          Node::HttpStatusCode(http_status_code) => status_code == *http_status_code,
+          _ => false,
+        }
+      })?;
+
+    Some(self.get_header_parameter_nodes_of(status_code_node_index))
+  }
+
+  pub fn get_request_header_parameter_nodes<'a>(
+    &'a self,
+    request_id: &RequestId,
+  ) -> Option<impl Iterator<Item = (&'a RequestParameterId, &'a RequestParameterDescriptor)> + 'a>
+  {
+    let request_node_index = self.get_request_node_index(request_id)?;
+    let method_node_index = self.get_method_node_index_of(*request_node_index)?;
+
+    Some(self.get_header_parameter_nodes_of(method_node_index))
+  }
+
+  pub fn get_response_header_parameter_nodes<'a>(
+    &'a self,
+    response_id: &ResponseId,
+  ) -> Option<impl Iterator<Item = (&'a RequestParameterId, &'a RequestParameterDescriptor)> + 'a>
+  {
+    let response_node_index = self.get_response_node_index(response_id)?;
+    let status_code_node_index = self.get_status_code_node_index_of(*response_node_index)?;
+
+    Some(self.get_header_parameter_nodes_of(status_code_node_index))
+  }
+
+  pub fn get_sibling_header_parameter_nodes<'a>(
+    &'a self,
+    parameter_id: &RequestParameterId,
+  ) -> Option<impl Iterator<Item = (&'a RequestParameterId, &'a RequestParameterDescriptor)> + 'a>
+  {
+    let parameter_node_index = self.get_request_parameter_node_index(parameter_id)?;
+    let parent_node_index = self.get_parent_node_index_of(*parameter_node_index)?;
+
+    Some(self.get_header_parameter_nodes_of(parent_node_index))
+  }
+
+  fn get_header_parameter_nodes_of<'a>(
+    &'a self,
+    parent_node_index: NodeIndex,
+  ) -> impl Iterator<Item = (&'a RequestParameterId, &'a RequestParameterDescriptor)> + 'a {
+    self
+      .graph
+      .neighbors_directed(parent_node_index, petgraph::Direction::Incoming)
+      .filter_map(move |i| {
+        let node = self.graph.node_weight(i).unwrap();
+        match node {
+          Node::RequestParameter(parameter_id, descriptor) if descriptor.is_header() => {
+            Some((parameter_id, descriptor))
+          }
+          _ => None,
+        }
+      })
+  }
+
   pub fn get_endpoint_query_parameter_node(
     &self,
     path_id: &PathComponentId,
@@ -705,6 +1067,7 @@ impl Default for EndpointProjection {
     EndpointProjection {
       graph,
       node_id_to_index,
+      path_parameter_shapes_by_path_id: HashMap::new(),
     }
   }
 }
@@ -747,6 +1110,9 @@ impl AggregateEvent<EndpointProjection> for EndpointEvent {
       EndpointEvent::PathParameterRemoved(e) => {
         aggregate.without_path_parameter(e.path_id);
       }
+      EndpointEvent::PathParameterShapeSet(e) => {
+        aggregate.with_path_parameter_shape(e.path_id, e.shape_descriptor);
+      }
       EndpointEvent::QueryParametersAdded(e) => {
         aggregate.with_query_parameters(e.path_id, e.http_method, e.query_parameters_id);
       }
@@ -756,6 +1122,66 @@ impl AggregateEvent<EndpointProjection> for EndpointEvent {
       EndpointEvent::QueryParametersRemoved(e) => {
         aggregate.without_query_parameters(e.query_parameters_id);
       }
+      // only header parameters are modelled, legacy query string parameters are superseded by QueryParameters
+      EndpointEvent::RequestParameterAdded(e)
+        if e.parameter_location == HEADER_PARAMETER_LOCATION =>
+      {
+        aggregate.with_request_parameter_by_request(
+          e.request_id,
+          e.parameter_id,
+          e.parameter_location,
+          e.name,
+        );
+      }
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e)
+        if e.parameter_location == HEADER_PARAMETER_LOCATION =>
+      {
+        aggregate.with_request_parameter(
+          e.path_id,
+          e.http_method,
+          e.parameter_id,
+          e.parameter_location,
+          e.name,
+        );
+      }
+      EndpointEvent::ResponseParameterAdded(e)
+        if e.parameter_location == HEADER_PARAMETER_LOCATION =>
+      {
+        aggregate.with_response_parameter(
+          e.response_id,
+          e.parameter_id,
+          e.parameter_location,
+          e.name,
+        );
+      }
+      EndpointEvent::RequestParameterRenamed(e)
+        if aggregate
+          .get_request_parameter_node_index(&e.parameter_id)
+          .is_some() =>
+      {
+        aggregate.with_request_parameter_name(e.parameter_id, e.name);
+      }
+      EndpointEvent::RequestParameterShapeSet(e)
+        if aggregate
+          .get_request_parameter_node_index(&e.parameter_id)
+          .is_some() =>
+      {
+        aggregate.with_request_parameter_shape(e.parameter_id, e.parameter_descriptor);
+      }
+      EndpointEvent::RequestParameterShapeUnset(e)
+        if aggregate
+          .get_request_parameter_node_index(&e.parameter_id)
+          .is_some() =>
+      {
+        aggregate.without_request_parameter_shape(e.parameter_id);
+      }
+      EndpointEvent::RequestParameterRemoved(e)
+        if aggregate
+          .get_request_parameter_node_index(&e.parameter_id)
+          .is_some() =>
+      {
+        aggregate.without_request_parameter(e.parameter_id);
+      }
       EndpointEvent::RequestAdded(e) => {
         aggregate.with_request(e.path_id, e.http_method, e.request_id);
       }
@@ -775,6 +1201,9 @@ impl AggregateEvent<EndpointProjection> for EndpointEvent {
           e.body_descriptor.shape_id,
         );
       }
+      EndpointEvent::RequestBodyUnset(e) => {
+        aggregate.without_request_body(e.request_id);
+      }
       EndpointEvent::ResponseBodySet(e) => {
         aggregate.with_response_body(
           e.response_id,
@@ -782,6 +1211,9 @@ impl AggregateEvent<EndpointProjection> for EndpointEvent {
           e.body_descriptor.shape_id,
         );
       }
+      EndpointEvent::ResponseBodyUnset(e) => {
+        aggregate.without_response_body(e.response_id);
+      }
       _ => eprintln!(
         "Ignoring applying event of type '{}' for EndpointProjection",
         self.event_type()
@@ -1,5 +1,6 @@
 use cqrs_core::{Aggregate, AggregateEvent};
 use petgraph::visit;
+use petgraph::visit::EdgeRef;
 use petgraph::{
   graph::{Graph, NodeIndex},
   Directed,
@@ -184,6 +185,15 @@ impl HistoryProjection {
   //   Some(std::iter::empty())
   //
 
+  pub fn get_parent_commit_id(&self, batch_id: &CommitId) -> Option<&CommitId> {
+    let node_index = *self.get_batch_commit_node_index(batch_id)?;
+    self
+      .graph
+      .edges_directed(node_index, petgraph::Direction::Incoming)
+      .find(|edge| matches!(edge.weight(), Edge::IsParentOf))
+      .and_then(|edge| self.get_commit_id(&edge.source()))
+  }
+
   pub fn find_last_batch_commit_index(&self) -> Option<NodeIndex> {
     let root_node_index = *self.get_batch_commit_node_index(&CommitId::from(ROOT_COMMIT_ID))?;
     let commit_graph = visit::NodeFiltered::from_fn(&self.graph, |node_index| {
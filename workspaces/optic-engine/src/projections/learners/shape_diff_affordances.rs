@@ -3,7 +3,9 @@ use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 
-use crate::interactions::{BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult};
+use crate::interactions::{
+  BodyAnalysisLocation, BodyAnalysisResult, EnumLearningConfig, InteractionDiffResult,
+};
 use crate::learn_shape::{TrailObservationsResult, TrailValues};
 use crate::shapes::JsonTrail;
 use crate::state::{TaggedInput, Tags};
@@ -12,15 +14,22 @@ use crate::state::{TaggedInput, Tags};
 pub struct LearnedShapeDiffAffordancesProjection {
   diffs_by_spec_id: HashMap<String, Vec<InteractionDiffResult>>,
   affordances_by_diff_fingerprint: HashMap<String, ShapeDiffAffordances>,
+  enum_config: EnumLearningConfig,
 }
 
 impl LearnedShapeDiffAffordancesProjection {
+  pub fn with_enum_config(mut self, enum_config: EnumLearningConfig) -> Self {
+    self.enum_config = enum_config;
+    self
+  }
+
   fn with_body_analysis_result(
     &mut self,
     (analysis, interaction_pointers): (BodyAnalysisResult, InteractionPointers),
   ) {
     let diffs_by_spec_id = &self.diffs_by_spec_id;
     let affordances_by_diff_fingerprint = &mut self.affordances_by_diff_fingerprint;
+    let enum_config = &self.enum_config;
 
     let spec_id = match analysis.body_location {
       BodyAnalysisLocation::MatchedQueryParameters {
@@ -89,7 +98,9 @@ impl LearnedShapeDiffAffordancesProjection {
 
       let affordances = affordances_by_diff_fingerprint
         .entry(fingerprint)
-        .or_insert_with(|| ShapeDiffAffordances::from(diff_json_trail.clone()));
+        .or_insert_with(|| {
+          ShapeDiffAffordances::from(diff_json_trail.clone()).with_enum_config(enum_config.clone())
+        });
 
       for trail_result in trail_results {
         affordances.push((trail_result.clone(), interaction_pointers.clone()));
@@ -135,6 +146,7 @@ impl FromIterator<InteractionDiffResult> for LearnedShapeDiffAffordancesProjecti
     Self {
       diffs_by_spec_id,
       affordances_by_diff_fingerprint: HashMap::new(),
+      enum_config: EnumLearningConfig::default(),
     }
   }
 }
@@ -184,21 +196,26 @@ pub struct ShapeDiffAffordances {
 
   #[serde(skip)]
   root_trail: JsonTrail,
+  #[serde(skip)]
+  enum_config: EnumLearningConfig,
 }
 pub type InteractionPointers = Tags;
 
 impl ShapeDiffAffordances {
+  pub fn with_enum_config(mut self, enum_config: EnumLearningConfig) -> Self {
+    self.enum_config = enum_config;
+    self
+  }
+
   pub fn into_trail_observations(self) -> (JsonTrail, TrailObservationsResult) {
     let root_shape_id = self.root_trail;
-    let values_by_trail: HashMap<_, _> = self
+    let mut trail_observation_results = TrailObservationsResult::with_enum_config(self.enum_config);
+    trail_observation_results.values_by_trail = self
       .affordances
       .into_iter()
       .map(|item| (item.trail.clone(), item))
       .collect();
 
-    let trail_observation_results: TrailObservationsResult =
-      TrailObservationsResult { values_by_trail };
-
     (root_shape_id, trail_observation_results)
   }
 }
@@ -230,6 +247,7 @@ impl From<JsonTrail> for ShapeDiffAffordances {
       affordances: vec![],
       interactions: InteractionsAffordances::default(),
       root_trail,
+      enum_config: EnumLearningConfig::default(),
     }
   }
 }
@@ -251,6 +269,7 @@ impl ShapeDiffAffordances {
       .find(|trail_affordances| trail_affordances.trail == new_trail_normalized)
     {
       current_affordances.union(trail_values);
+      current_affordances.limit_string_values(self.enum_config.max_values);
     } else {
       trail_values.normalize();
       self.affordances.push(trail_values);
@@ -638,4 +657,29 @@ mod test {
       "empty arrays are recorded as array trails"
     );
   }
+
+  #[test]
+  fn shape_diff_affordances_keep_string_values_within_the_enum_config() {
+    let trail = JsonTrail::empty().with_object_key(String::from("status"));
+    let enum_config = EnumLearningConfig {
+      max_values: 1,
+      ..EnumLearningConfig::default()
+    };
+    let mut affordances =
+      ShapeDiffAffordances::from(trail.clone()).with_enum_config(enum_config.clone());
+
+    for value in &["pending", "shipped"] {
+      let mut trail_values = TrailValues::new(&trail);
+      trail_values.was_string = true;
+      trail_values.insert_string_value(value);
+      affordances.push((trail_values, InteractionPointers::new()));
+    }
+
+    let (_, trail_observations) = affordances.into_trail_observations();
+    let status_values = trail_observations
+      .get(&trail)
+      .expect("status trail should have been observed");
+    assert!(status_values.string_values_exceeded);
+    assert!(status_values.string_values.is_empty());
+  }
 }
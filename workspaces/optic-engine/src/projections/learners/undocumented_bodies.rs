@@ -4,34 +4,49 @@ use serde::Serialize;
 use std::collections::HashMap;
 
 use crate::commands::{EndpointCommand, SpecCommand};
-use crate::interactions::{BodyAnalysisLocation, BodyAnalysisResult};
-use crate::learn_shape::TrailObservationsResult;
+use crate::interactions::{
+  AnalyzeUndocumentedBodiesConfig, BodyAnalysisLocation, BodyAnalysisResult, EnumLearningConfig,
+};
+use crate::learn_shape::{LearnedShapeReuse, TrailObservationsResult};
+use crate::queries::shape::ShapeStructureIndex;
 use crate::state::SpecIdGenerator;
 use crate::JsonTrail;
 
 #[derive(Default, Debug)]
 pub struct LearnedUndocumentedBodiesProjection {
   observations_by_location: HashMap<BodyAnalysisLocation, TrailObservationsResult>,
+  enum_config: EnumLearningConfig,
 }
 
 impl LearnedUndocumentedBodiesProjection {
+  pub fn new(config: &AnalyzeUndocumentedBodiesConfig) -> Self {
+    Self {
+      observations_by_location: HashMap::new(),
+      enum_config: config.enums.clone(),
+    }
+  }
+
   fn with_body_analysis_result(&mut self, analysis: BodyAnalysisResult) {
+    let enum_config = &self.enum_config;
     let existing_observations = self
       .observations_by_location
       .entry(analysis.body_location)
-      .or_insert_with(|| TrailObservationsResult::default());
+      .or_insert_with(|| TrailObservationsResult::with_enum_config(enum_config.clone()));
 
     existing_observations.union(analysis.trail_observations.normalized());
   }
 
+  // Learned shapes structurally equivalent to one of the reusable shapes refer to it instead of
+  // being added again
   pub fn into_endpoint_bodies(
     self,
     id_generator: &mut impl SpecIdGenerator,
+    reusable_shapes: &ShapeStructureIndex,
   ) -> impl Iterator<Item = EndpointBodies> {
     let mut endpoints_by_endpoint = HashMap::new();
     for (body_location, observations) in self.observations_by_location {
-      let (root_shape_id, body_commands) =
-        observations.into_commands(id_generator, &JsonTrail::empty());
+      let (root_shape_id, body_commands, shape_reuses) =
+        observations.into_commands_reusing(id_generator, &JsonTrail::empty(), reusable_shapes);
       let mut endpoint_body = EndpointBody::new(&body_location, root_shape_id, body_commands);
 
       endpoint_body.append_endpoint_commands(id_generator);
@@ -54,6 +69,7 @@ impl LearnedUndocumentedBodiesProjection {
         });
 
       endpoint_bodies.push(endpoint_body);
+      endpoint_bodies.reused_shapes.extend(shape_reuses);
     }
 
     endpoints_by_endpoint.into_iter().map(|(k, v)| v)
@@ -89,6 +105,7 @@ pub struct EndpointBodies {
   query_parameters: Option<EndpointQueryParameters>,
   requests: Vec<EndpointRequestBody>,
   responses: Vec<EndpointResponseBody>,
+  reused_shapes: Vec<LearnedShapeReuse>,
 }
 
 impl EndpointBodies {
@@ -100,6 +117,7 @@ impl EndpointBodies {
       query_parameters: None,
       requests: vec![],
       responses: vec![],
+      reused_shapes: vec![],
     }
   }
 
@@ -335,6 +353,7 @@ mod test {
   use crate::learn_shape::observe_body_trails;
   use crate::projections::SpecProjection;
   use crate::state::body::BodyDescriptor;
+  use crate::test_helpers::{assert_valid_commands, TestIdGenerator};
   use insta::assert_debug_snapshot;
   use serde_json::json;
 
@@ -415,7 +434,7 @@ mod test {
     }
 
     let mut endpoint_bodies = projection
-      .into_endpoint_bodies(&mut test_id_generator)
+      .into_endpoint_bodies(&mut test_id_generator, &ShapeStructureIndex::default())
       .collect::<Vec<_>>();
     assert_eq!(endpoint_bodies.len(), 1);
 
@@ -452,7 +471,7 @@ mod test {
     }
 
     let mut endpoint_bodies = projection
-      .into_endpoint_bodies(&mut test_id_generator)
+      .into_endpoint_bodies(&mut test_id_generator, &ShapeStructureIndex::default())
       .collect::<Vec<_>>();
     assert_eq!(endpoint_bodies.len(), 1);
 
@@ -486,7 +505,7 @@ mod test {
     projection.apply(analysis_result);
 
     let endpoint_bodies = projection
-      .into_endpoint_bodies(&mut test_id_generator)
+      .into_endpoint_bodies(&mut test_id_generator, &ShapeStructureIndex::default())
       .collect::<Vec<_>>();
 
     assert_debug_snapshot!(
@@ -494,35 +513,4 @@ mod test {
       endpoint_bodies
     );
   }
-
-  #[derive(Debug, Default)]
-  struct TestIdGenerator {
-    counter: usize,
-  }
-
-  impl SpecIdGenerator for TestIdGenerator {
-    fn generate_id(&mut self, prefix: &str) -> String {
-      let id = format!("test-id-{}-{}", prefix, self.counter);
-      self.counter += 1;
-      id
-    }
-  }
-
-  fn assert_valid_commands(
-    mut spec_projection: SpecProjection,
-    commands: impl IntoIterator<Item = SpecCommand>,
-  ) -> SpecProjection {
-    // let mut spec_projection = SpecProjection::default();
-    for command in commands {
-      let events = spec_projection
-        .execute(command)
-        .expect("generated commands must be valid");
-
-      for event in events {
-        spec_projection.apply(event)
-      }
-    }
-
-    spec_projection
-  }
 }
@@ -0,0 +1,417 @@
+use cqrs_core::{Aggregate, AggregateEvent, Event};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::commands::{EndpointCommand, ShapeCommand, SpecCommand};
+use crate::interactions::{HeaderAnalysisLocation, HeaderAnalysisResult};
+use crate::learn_shape::TrailObservationsResult;
+use crate::state::shape::ShapeKind;
+use crate::state::SpecIdGenerator;
+use crate::JsonTrail;
+
+#[derive(Default, Debug)]
+pub struct LearnedUndocumentedHeadersProjection {
+  // ordered, so ids are generated and commands emitted in the same order between runs
+  observations_by_location: BTreeMap<HeaderAnalysisLocation, TrailObservationsResult>,
+}
+
+impl LearnedUndocumentedHeadersProjection {
+  fn with_header_analysis_result(&mut self, analysis: HeaderAnalysisResult) {
+    let existing_observations = self
+      .observations_by_location
+      .entry(analysis.header_location)
+      .or_default();
+
+    existing_observations.union(analysis.trail_observations.normalized());
+  }
+
+  pub fn into_endpoint_headers(
+    self,
+    id_generator: &mut impl SpecIdGenerator,
+  ) -> impl Iterator<Item = EndpointHeaders> {
+    let mut headers_by_endpoint = BTreeMap::new();
+    for (header_location, observations) in self.observations_by_location {
+      let (path_id, method) = match &header_location {
+        HeaderAnalysisLocation::Request {
+          path_id, method, ..
+        } => (path_id.clone(), method.clone()),
+        HeaderAnalysisLocation::Response {
+          path_id, method, ..
+        } => (path_id.clone(), method.clone()),
+      };
+
+      let endpoint_headers = headers_by_endpoint
+        .entry((path_id, method))
+        .or_insert_with_key(|(path_id, method)| {
+          EndpointHeaders::new(path_id.clone(), method.clone())
+        });
+
+      let headers = learned_headers(observations, id_generator);
+      if headers.is_empty() {
+        continue;
+      }
+
+      match header_location {
+        HeaderAnalysisLocation::Request { request_id, .. } => {
+          let commands = headers
+            .into_iter()
+            .flat_map(|header| {
+              header.into_commands(id_generator, |parameter_id, name| {
+                EndpointCommand::add_header_parameter(parameter_id, request_id.clone(), name)
+              })
+            })
+            .collect();
+
+          endpoint_headers.request = Some(EndpointRequestHeaders {
+            request_id,
+            commands,
+          });
+        }
+        HeaderAnalysisLocation::Response {
+          status_code,
+          response_id,
+          ..
+        } => {
+          let commands = headers
+            .into_iter()
+            .flat_map(|header| {
+              header.into_commands(id_generator, |parameter_id, name| {
+                EndpointCommand::add_response_header_parameter(
+                  parameter_id,
+                  response_id.clone(),
+                  name,
+                )
+              })
+            })
+            .collect();
+
+          endpoint_headers.responses.push(EndpointResponseHeaders {
+            response_id,
+            status_code,
+            commands,
+          });
+        }
+      }
+    }
+
+    headers_by_endpoint
+      .into_values()
+      .filter(|endpoint_headers| {
+        endpoint_headers.request.is_some() || !endpoint_headers.responses.is_empty()
+      })
+  }
+}
+
+struct LearnedHeader {
+  name: String,
+  is_optional: bool,
+  shape_id: String,
+  shape_commands: Vec<SpecCommand>,
+}
+
+impl LearnedHeader {
+  fn into_commands(
+    self,
+    id_generator: &mut impl SpecIdGenerator,
+    add_header: impl FnOnce(String, String) -> EndpointCommand,
+  ) -> Vec<SpecCommand> {
+    let mut commands = self.shape_commands;
+
+    let shape_id = if self.is_optional {
+      let optional_shape_id = id_generator.shape();
+      let parameter_id = ShapeKind::OptionalKind
+        .get_parameter_descriptor()
+        .unwrap()
+        .shape_parameter_id;
+
+      commands.push(SpecCommand::from(ShapeCommand::add_shape(
+        optional_shape_id.clone(),
+        ShapeKind::OptionalKind,
+        String::from(""),
+      )));
+      commands.push(SpecCommand::from(ShapeCommand::set_parameter_shape(
+        optional_shape_id.clone(),
+        String::from(parameter_id),
+        self.shape_id,
+      )));
+      optional_shape_id
+    } else {
+      self.shape_id
+    };
+
+    let parameter_id = id_generator.request_parameter();
+    commands.push(SpecCommand::from(add_header(
+      parameter_id.clone(),
+      self.name,
+    )));
+    commands.push(SpecCommand::from(
+      EndpointCommand::set_header_parameter_shape(parameter_id, shape_id),
+    ));
+
+    commands
+  }
+}
+
+// Headers are observed as the fields of an object, one per interaction, so any header that's missing
+// from one of the observed field sets wasn't sent with every interaction.
+fn learned_headers(
+  mut observations: TrailObservationsResult,
+  id_generator: &mut impl SpecIdGenerator,
+) -> Vec<LearnedHeader> {
+  let root_trail = JsonTrail::empty();
+  let field_sets = match observations.remove(&root_trail) {
+    Some(root_values) => root_values.field_sets,
+    None => return vec![],
+  };
+  let names: BTreeSet<_> = field_sets.iter().flatten().cloned().collect();
+
+  names
+    .into_iter()
+    .filter_map(|name| {
+      let header_trail = root_trail.with_object_key(name.clone());
+      let header_observations = TrailObservationsResult::from(
+        observations
+          .values_by_trail
+          .iter()
+          .filter(|(trail, _)| **trail == header_trail || trail.is_descendant_of(&header_trail))
+          .map(|(trail, values)| (trail.clone(), values.clone()))
+          .collect::<HashMap<_, _>>(),
+      );
+
+      let (shape_id, shape_commands) =
+        header_observations.into_commands(id_generator, &header_trail);
+      let is_optional = !field_sets.iter().all(|field_set| field_set.contains(&name));
+
+      shape_id.map(|shape_id| LearnedHeader {
+        name,
+        is_optional,
+        shape_id,
+        shape_commands: shape_commands.collect(),
+      })
+    })
+    .collect()
+}
+
+impl Aggregate for LearnedUndocumentedHeadersProjection {
+  fn aggregate_type() -> &'static str {
+    "learned_undocumented_headers"
+  }
+}
+
+impl Event for HeaderAnalysisResult {
+  fn event_type(&self) -> &'static str {
+    "header_analysis_result"
+  }
+}
+
+impl AggregateEvent<LearnedUndocumentedHeadersProjection> for HeaderAnalysisResult {
+  fn apply_to(self, aggregate: &mut LearnedUndocumentedHeadersProjection) {
+    aggregate.with_header_analysis_result(self)
+  }
+}
+
+// Output structs
+// --------------
+
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointHeaders {
+  path_id: String,
+  method: String,
+  request: Option<EndpointRequestHeaders>,
+  responses: Vec<EndpointResponseHeaders>,
+}
+
+impl EndpointHeaders {
+  pub fn new(path_id: String, method: String) -> Self {
+    Self {
+      path_id,
+      method,
+
+      request: None,
+      responses: vec![],
+    }
+  }
+
+  pub fn into_commands(self) -> impl Iterator<Item = SpecCommand> {
+    let request_commands = self
+      .request
+      .into_iter()
+      .flat_map(|request| request.commands.into_iter());
+
+    let responses_commands = self
+      .responses
+      .into_iter()
+      .flat_map(|response| response.commands.into_iter());
+
+    request_commands.chain(responses_commands)
+  }
+}
+
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointRequestHeaders {
+  request_id: String,
+  commands: Vec<SpecCommand>,
+}
+
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointResponseHeaders {
+  response_id: String,
+  status_code: u16,
+  commands: Vec<SpecCommand>,
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use crate::learn_shape::observe_body_trails;
+  use crate::projections::SpecProjection;
+  use crate::state::body::BodyDescriptor;
+  use crate::test_helpers::{assert_valid_commands, TestIdGenerator};
+  use insta::assert_debug_snapshot;
+  use serde_json::json;
+
+  #[test]
+  fn undocumented_headers_learns_optional_headers_from_some_interactions() {
+    let location = HeaderAnalysisLocation::Response {
+      path_id: String::from("path_1"),
+      method: String::from("GET"),
+      status_code: 200,
+      response_id: String::from("response_1"),
+    };
+
+    let analysis_results = vec![
+      HeaderAnalysisResult {
+        header_location: location.clone(),
+        trail_observations: observe_body_trails(BodyDescriptor::from(json!({
+          "X-Request-Id": "abc",
+          "X-Rate-Limit": 100
+        }))),
+      },
+      HeaderAnalysisResult {
+        header_location: location.clone(),
+        trail_observations: observe_body_trails(BodyDescriptor::from(json!({
+          "X-Request-Id": "def",
+        }))),
+      },
+    ];
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let mut projection = LearnedUndocumentedHeadersProjection::default();
+
+    for result in analysis_results {
+      projection.apply(result);
+    }
+
+    let endpoint_headers = projection
+      .into_endpoint_headers(&mut test_id_generator)
+      .collect::<Vec<_>>();
+
+    assert_debug_snapshot!(
+      "undocumented_headers_learns_optional_headers_from_some_interactions__endpoint_headers",
+      endpoint_headers
+    );
+
+    let spec_projection = SpecProjection::from(
+      serde_json::from_value::<Vec<SpecEvent>>(json!([
+        {"PathComponentAdded": { "pathId": "path_1", "parentPathId": "root", "name": "orders" }},
+        {"RequestAdded": { "requestId": "request_1", "pathId": "path_1", "httpMethod": "GET" }},
+        {"ResponseAddedByPathAndMethod": { "responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200 }},
+      ]))
+      .expect("should be able to deserialize test events"),
+    );
+
+    assert_valid_commands(
+      spec_projection,
+      endpoint_headers
+        .into_iter()
+        .flat_map(|endpoint_headers| endpoint_headers.into_commands()),
+    );
+  }
+
+  #[test]
+  fn undocumented_headers_generates_commands_in_location_order() {
+    let response_location = |path_id: &str, status_code: u16| HeaderAnalysisLocation::Response {
+      path_id: String::from(path_id),
+      method: String::from("GET"),
+      status_code,
+      response_id: format!("response_{}_{}", path_id, status_code),
+    };
+    let locations = vec![
+      response_location("path_2", 200),
+      response_location("path_1", 404),
+      HeaderAnalysisLocation::Request {
+        path_id: String::from("path_1"),
+        method: String::from("GET"),
+        request_id: String::from("request_1"),
+      },
+      response_location("path_1", 200),
+    ];
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let mut projection = LearnedUndocumentedHeadersProjection::default();
+    for header_location in locations {
+      projection.apply(HeaderAnalysisResult {
+        header_location,
+        trail_observations: observe_body_trails(BodyDescriptor::from(json!({
+          "X-Request-Id": "abc",
+        }))),
+      });
+    }
+
+    let endpoint_headers = projection
+      .into_endpoint_headers(&mut test_id_generator)
+      .collect::<Vec<_>>();
+
+    let learned_locations = endpoint_headers
+      .iter()
+      .map(|endpoint_headers| {
+        let request_id = endpoint_headers
+          .request
+          .as_ref()
+          .map(|request| request.request_id.as_str());
+        let response_ids = endpoint_headers
+          .responses
+          .iter()
+          .map(|response| response.response_id.as_str())
+          .collect::<Vec<_>>();
+        (endpoint_headers.path_id.as_str(), request_id, response_ids)
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      learned_locations,
+      vec![
+        (
+          "path_1",
+          Some("request_1"),
+          vec!["response_path_1_200", "response_path_1_404"]
+        ),
+        ("path_2", None, vec!["response_path_2_200"]),
+      ]
+    );
+  }
+
+  #[test]
+  fn undocumented_headers_skips_locations_without_headers() {
+    let analysis_result = HeaderAnalysisResult {
+      header_location: HeaderAnalysisLocation::Request {
+        path_id: String::from("path_1"),
+        method: String::from("GET"),
+        request_id: String::from("request_1"),
+      },
+      trail_observations: observe_body_trails(BodyDescriptor::empty_object()),
+    };
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let mut projection = LearnedUndocumentedHeadersProjection::default();
+    projection.apply(analysis_result);
+
+    let endpoint_headers = projection
+      .into_endpoint_headers(&mut test_id_generator)
+      .collect::<Vec<_>>();
+    assert!(endpoint_headers.is_empty());
+  }
+}
@@ -7,19 +7,24 @@ pub mod shape;
 pub mod spec_events;
 pub mod spectacle;
 
-pub use conflicts::ConflictsProjection;
+pub use conflicts::{ConflictsProjection, SpecConflict};
 pub use contributions::ContributionsProjection;
 pub use endpoint::{EndpointProjection, ResponseBodyDescriptor};
 pub use history::{CommitId, HistoryProjection};
 pub use learners::{
   shape_diff_affordances::LearnedShapeDiffAffordancesProjection,
   undocumented_bodies::LearnedUndocumentedBodiesProjection,
+  undocumented_headers::LearnedUndocumentedHeadersProjection,
 };
 pub use shape::ShapeProjection;
-pub use spec_events::{SpecAssemblerError, SpecAssemblerProjection};
+pub use spec_events::{
+  AssembledBatch, AssembledFork, BranchConflict, BranchConflictKind, SpecAssemblerError,
+  SpecAssemblerProjection, SpecAssemblyReport,
+};
 pub use spectacle::endpoints::EndpointsProjection;
 
-use crate::events::{EndpointEvent, RfcEvent, ShapeEvent, SpecEvent};
+use crate::events::{EndpointEvent, RfcEvent, ShapeEvent, SpecEvent, WithEventContext};
+use crate::queries::history::HistoryQueries;
 use cqrs_core::{Aggregate, AggregateCommand, AggregateEvent, CommandError};
 use std::error::Error;
 
@@ -75,6 +80,62 @@ impl SpecProjection {
   ) -> crate::projections::spectacle::endpoints::SerializableGraph {
     self.spectacle_endpoints.to_serializable_graph()
   }
+
+  /// Project the spec as it was right after a batch commit, or `None` when there is no such
+  /// commit.
+  pub fn at_commit<I>(events: I, batch_id: &CommitId) -> Option<Self>
+  where
+    I: IntoIterator<Item = SpecEvent>,
+  {
+    Self::events_at_commit(events, batch_id).map(Self::from)
+  }
+
+  /// The events of the batch commits from the root up to and including a batch commit, leaving
+  /// out those of any other branch of the commit history. Events outside of a batch are kept as
+  /// long as they precede the end of the batch commit.
+  pub fn events_at_commit<I>(events: I, batch_id: &CommitId) -> Option<Vec<SpecEvent>>
+  where
+    I: IntoIterator<Item = SpecEvent>,
+  {
+    let events = events.into_iter().collect::<Vec<_>>();
+    let history = HistoryProjection::from(events.iter().cloned());
+    let ancestry = HistoryQueries::from(&history).resolve_commit_ancestry(batch_id)?;
+
+    let mut events_at_commit = vec![];
+    let mut current_batch_id: Option<CommitId> = None;
+    for event in events {
+      let (event_batch_id, is_commit_end) = match &event {
+        SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(e)) => {
+          current_batch_id = Some(e.batch_id.clone());
+          (Some(e.batch_id.clone()), false)
+        }
+        SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(e)) => {
+          current_batch_id = None;
+          (Some(e.batch_id.clone()), e.batch_id == *batch_id)
+        }
+        // events written outside of a batch can still name the batch they were part of
+        _ => (
+          current_batch_id.clone().or_else(|| {
+            event
+              .event_context()
+              .map(|context| context.client_command_batch_id.clone())
+              .filter(|id| history.get_batch_commit_node_index(id).is_some())
+          }),
+          false,
+        ),
+      };
+
+      match event_batch_id {
+        Some(event_batch_id) if !ancestry.contains(&event_batch_id) => {}
+        _ => events_at_commit.push(event),
+      }
+      if is_commit_end {
+        break;
+      }
+    }
+
+    Some(events_at_commit)
+  }
 }
 
 impl Aggregate for SpecProjection {
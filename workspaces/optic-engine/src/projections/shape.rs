@@ -3,8 +3,9 @@ use crate::queries::shape::{ChoiceOutput, ShapeQueries};
 use crate::shapes::traverser::ShapeTrailPathComponent::ObjectFieldTrail;
 use crate::shapes::ShapeTrail;
 use crate::state::shape::{
-  FieldId, FieldShapeDescriptor, ParameterShapeDescriptor, ProviderDescriptor, ShapeId, ShapeIdRef,
-  ShapeKind, ShapeKindDescriptor, ShapeParameterId, ShapeParameterIdRef, ShapeParametersDescriptor,
+  FieldId, FieldShapeDescriptor, ParameterShapeDescriptor, ProviderDescriptor,
+  ShapeConstraintDescriptor, ShapeId, ShapeIdRef, ShapeKind, ShapeKindDescriptor, ShapeParameterId,
+  ShapeParameterIdRef, ShapeParametersDescriptor,
 };
 use crate::{RfcEvent, SpecProjection};
 use cqrs_core::{Aggregate, AggregateEvent, Event};
@@ -63,8 +64,10 @@ pub enum Edge {
   IsFieldOf,
   IsParameterOf,
   HasBinding(ShapeParameterBinding),
+  HasParameterBinding(ParameterProviderBinding),
   CreatedIn,
   UpdatedIn,
+  RemovedIn,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -73,6 +76,13 @@ pub struct ShapeParameterBinding {
   pub shape_id: ShapeId,
 }
 
+// Binds a parameter to a parameter of an enclosing shape, which provides its shape in turn
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterProviderBinding {
+  pub shape_parameter_id: ShapeParameterId,
+}
+
 pub type NodeId = String;
 
 #[derive(Debug, Clone, Serialize)]
@@ -93,6 +103,8 @@ pub struct FieldNodeDescriptor {
 pub struct ShapeProjection {
   pub graph: Graph<Node, Edge>,
   pub node_id_to_index: HashMap<NodeId, petgraph::graph::NodeIndex>,
+  pub constraints_by_shape_id: HashMap<ShapeId, ShapeConstraintDescriptor>,
+  pub names_by_shape_id: HashMap<ShapeId, String>,
 }
 
 impl Default for ShapeProjection {
@@ -102,6 +114,8 @@ impl Default for ShapeProjection {
     let mut projection = ShapeProjection {
       graph,
       node_id_to_index,
+      constraints_by_shape_id: HashMap::new(),
+      names_by_shape_id: HashMap::new(),
     };
 
     add_core_shape_to_projection(&mut projection, ShapeKind::StringKind);
@@ -114,6 +128,7 @@ impl Default for ShapeProjection {
     add_core_shape_to_projection(&mut projection, ShapeKind::UnknownKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::OptionalKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::OneOfKind);
+    add_core_shape_to_projection(&mut projection, ShapeKind::MapKind);
     projection
   }
 }
@@ -190,6 +205,22 @@ impl ShapeProjection {
       eprintln!("bad implicit batch id {}", &batch_id);
     }
   }
+  pub fn with_remove_history(&mut self, batch_id: &str, removed_node_id: &str) {
+    let removed_node_index = self
+      .node_id_to_index
+      .get(removed_node_id)
+      .expect("expected removed_node_id to exist");
+
+    let batch_node_index_option = self.node_id_to_index.get(batch_id);
+
+    if let Some(batch_node_index) = batch_node_index_option {
+      self
+        .graph
+        .add_edge(*removed_node_index, *batch_node_index, Edge::RemovedIn);
+    } else {
+      eprintln!("bad implicit batch id {}", &batch_id);
+    }
+  }
   ////////////////////////////////////////////////////////////////////////////////
   pub fn with_shape_parameter(&mut self, shape_parameter_id: ShapeParameterId, shape_id: ShapeId) {
     let shape_node_index = *self.get_shape_node_index(&shape_id).unwrap();
@@ -219,7 +250,10 @@ impl ShapeProjection {
       shape_id: shape_id.clone(),
     });
     let shape_node_index = self.graph.add_node(shape_node);
-    self.node_id_to_index.insert(shape_id, shape_node_index);
+    self
+      .node_id_to_index
+      .insert(shape_id.clone(), shape_node_index);
+    self.with_shape_name(shape_id, name);
 
     let base_shape_node_index = self
       .node_id_to_index
@@ -277,6 +311,39 @@ impl ShapeProjection {
     );
   }
 
+  pub fn with_shape_constraint(
+    &mut self,
+    shape_id: ShapeId,
+    constraint_descriptor: ShapeConstraintDescriptor,
+  ) {
+    match constraint_descriptor {
+      ShapeConstraintDescriptor::NoConstraint(_) => {
+        self.constraints_by_shape_id.remove(&shape_id);
+      }
+      constraint_descriptor => {
+        self
+          .constraints_by_shape_id
+          .insert(shape_id, constraint_descriptor);
+      }
+    }
+  }
+
+  pub fn get_shape_constraint(&self, shape_id: &ShapeId) -> Option<&ShapeConstraintDescriptor> {
+    self.constraints_by_shape_id.get(shape_id)
+  }
+
+  pub fn with_shape_name(&mut self, shape_id: ShapeId, name: String) {
+    if name.is_empty() {
+      self.names_by_shape_id.remove(&shape_id);
+    } else {
+      self.names_by_shape_id.insert(shape_id, name);
+    }
+  }
+
+  pub fn get_shape_name(&self, shape_id: &ShapeId) -> Option<&String> {
+    self.names_by_shape_id.get(shape_id)
+  }
+
   pub fn get_ancestor_shape_node_index(&self, parent_node_index: &NodeIndex) -> Option<NodeIndex> {
     let mut edges = self
       .graph
@@ -295,55 +362,79 @@ impl ShapeProjection {
     &mut self,
     shape_parameter_descriptor: ParameterShapeDescriptor,
   ) {
-    match shape_parameter_descriptor {
-      ParameterShapeDescriptor::ProviderInShape(p) => {
-        let target_shape_id = match p.provider_descriptor {
-          ProviderDescriptor::ShapeProvider(provider) => provider.shape_id,
-          _ => panic!("expected specs to only use ProviderDescriptor::ShapeProvider"),
-        };
-        let shape_node_index = self
-          .node_id_to_index
-          .get(&p.shape_id)
-          .expect("expected shape_id to have a corresponding node");
-        let shape_parameter_node_index = self
-          .node_id_to_index
-          .get(&p.consuming_parameter_id)
-          .expect("expected consuming_parameter_id to have a corresponding node");
-        let mut outgoing_edges = self
+    // parameters can be bound by shapes (descending from the shape declaring the parameter) or
+    // by fields (of which the shape declares the parameter)
+    let (binder_node_index, provider_descriptor, consuming_parameter_id) =
+      match shape_parameter_descriptor {
+        ParameterShapeDescriptor::ProviderInShape(p) => (
+          *self
+            .get_shape_node_index(&p.shape_id)
+            .expect("expected shape_id to have a corresponding node"),
+          p.provider_descriptor,
+          p.consuming_parameter_id,
+        ),
+        ParameterShapeDescriptor::ProviderInField(p) => (
+          *self
+            .get_field_node_index(&p.field_id)
+            .expect("expected field_id to have a corresponding node"),
+          p.provider_descriptor,
+          p.consuming_parameter_id,
+        ),
+      };
+    let shape_parameter_node_index = *self
+      .get_shape_parameter_node_index(&consuming_parameter_id)
+      .expect("expected consuming_parameter_id to have a corresponding node");
+
+    let binding = match provider_descriptor {
+      ProviderDescriptor::ShapeProvider(provider) => {
+        Some(Edge::HasBinding(ShapeParameterBinding {
+          shape_id: provider.shape_id,
+        }))
+      }
+      ProviderDescriptor::ParameterProvider(provider) => {
+        Some(Edge::HasParameterBinding(ParameterProviderBinding {
+          shape_parameter_id: provider.shape_parameter_id,
+        }))
+      }
+      ProviderDescriptor::NoProvider(_) => None,
+    };
+
+    let existing_binding_index = self
+      .graph
+      .edges_connecting(binder_node_index, shape_parameter_node_index)
+      .find(|edge| {
+        matches!(
+          edge.weight(),
+          Edge::HasBinding(_) | Edge::HasParameterBinding(_)
+        )
+      })
+      .map(|edge| edge.id());
+
+    match (existing_binding_index, binding) {
+      (Some(edge_index), Some(binding)) => {
+        // mutate the edge to point to the new provider
+        let edge_weight = self.graph.edge_weight_mut(edge_index).unwrap();
+        *edge_weight = binding;
+      }
+      (Some(edge_index), None) => {
+        self.graph.remove_edge(edge_index);
+      }
+      (None, Some(binding)) => {
+        self
           .graph
-          .edges_connecting(*shape_node_index, *shape_parameter_node_index);
-        if let Some(existing_binding) = outgoing_edges.next() {
-          let edge_index = existing_binding.id();
-          let edge_weight = self.graph.edge_weight_mut(edge_index).unwrap();
-          // mutate the edge to point to the new shape_id
-          match edge_weight {
-            Edge::HasBinding(b) => {
-              b.shape_id = target_shape_id;
-            }
-            _ => panic!("expected edge to be a HasBinding"),
-          }
-        } else {
-          // add a binding edge
-          let binding_edge = self.graph.add_edge(
-            *shape_node_index,
-            *shape_parameter_node_index,
-            Edge::HasBinding(ShapeParameterBinding {
-              shape_id: target_shape_id,
-            }),
-          );
-        }
+          .add_edge(binder_node_index, shape_parameter_node_index, binding);
       }
-      _ => panic!("expected specs to only use ParameterShapeDescriptor::ProviderInShape"),
+      (None, None) => {}
     }
   }
 
   pub fn with_field_shape(&mut self, shape_descriptor: FieldShapeDescriptor) {
-    let field_shape = match shape_descriptor {
-      FieldShapeDescriptor::FieldShapeFromShape(field_shape) => field_shape,
-      _ => panic!("expected specs to only use FieldShapeDescriptor::FieldShapeFromShape"),
+    let field_id = match &shape_descriptor {
+      FieldShapeDescriptor::FieldShapeFromShape(field_shape) => &field_shape.field_id,
+      FieldShapeDescriptor::FieldShapeFromParameter(field_shape) => &field_shape.field_id,
     };
     let field_node_index = *self
-      .get_field_node_index(&field_shape.field_id)
+      .get_field_node_index(field_id)
       .expect("expected field to exist");
     let field_node_weight = self
       .graph
@@ -361,13 +452,24 @@ impl ShapeProjection {
 
     self.graph.remove_edge(existing_field_shape_edge_index);
 
-    let target_shape_node_index = *self
-      .get_shape_node_index(&field_shape.shape_id)
-      .expect("expected shape_id for field value to have a corresponding node");
+    let target_node_index = self.get_field_value_node_index(&shape_descriptor);
 
     self
       .graph
-      .add_edge(target_shape_node_index, field_node_index, Edge::BelongsTo);
+      .add_edge(target_node_index, field_node_index, Edge::BelongsTo);
+  }
+
+  // Fields get their value from a shape, or from a shape parameter when the shape is provided by
+  // whatever binds the parameter
+  fn get_field_value_node_index(&self, shape_descriptor: &FieldShapeDescriptor) -> NodeIndex {
+    match shape_descriptor {
+      FieldShapeDescriptor::FieldShapeFromShape(field_shape) => *self
+        .get_shape_node_index(&field_shape.shape_id)
+        .expect("expected shape_id for field value to have a corresponding node"),
+      FieldShapeDescriptor::FieldShapeFromParameter(field_shape) => *self
+        .get_shape_parameter_node_index(&field_shape.shape_parameter_id)
+        .expect("expected shape_parameter_id for field value to have a corresponding node"),
+    }
   }
 
   pub fn with_field(
@@ -381,19 +483,15 @@ impl ShapeProjection {
       .get_shape_node_index(&object_id)
       .expect("expected shape_id of field to have a corresponding node");
 
-    let field_value_shape_node_index = match shape_descriptor {
-      FieldShapeDescriptor::FieldShapeFromShape(field_shape) => {
-        assert_eq!(
-          field_shape.field_id, field_id,
-          "expect main field id of event to match one of field shape descriptor"
-        );
-
-        *self
-          .get_shape_node_index(&field_shape.shape_id)
-          .expect("expected shape_id for field value to have a corresponding node")
-      }
-      _ => panic!("expected specs to only use FieldShapeDescriptor::FieldShapeFromShape"),
+    let descriptor_field_id = match &shape_descriptor {
+      FieldShapeDescriptor::FieldShapeFromShape(field_shape) => &field_shape.field_id,
+      FieldShapeDescriptor::FieldShapeFromParameter(field_shape) => &field_shape.field_id,
     };
+    assert_eq!(
+      descriptor_field_id, &field_id,
+      "expect main field id of event to match one of field shape descriptor"
+    );
+    let field_value_shape_node_index = self.get_field_value_node_index(&shape_descriptor);
 
     let field_node = Node::Field(FieldNode {
       field_id: field_id.clone(),
@@ -438,6 +536,104 @@ impl ShapeProjection {
     // eventual garbage collection.
   }
 
+  pub fn with_field_name(&mut self, field_id: FieldId, name: String) {
+    let field_node_index = *self
+      .get_field_node_index(&field_id)
+      .expect("expected field_id to have corresponding node");
+
+    if let Some(Node::Field(field_node)) = self.graph.node_weight_mut(field_node_index) {
+      field_node.descriptor.name = name;
+    }
+  }
+
+  pub fn without_shape_parameter(&mut self, shape_parameter_id: ShapeParameterId) {
+    let shape_parameter_node_index = *self
+      .get_shape_parameter_node_index(&shape_parameter_id)
+      .expect("expected shape_parameter_id to have corresponding node");
+
+    // unbind the parameter and detach it from its shape, so it's no longer resolved from either.
+    // Edges are removed one at a time, as removing an edge shifts the indexes of others.
+    while let Some(edge_index) = self
+      .graph
+      .edges_directed(shape_parameter_node_index, petgraph::Direction::Incoming)
+      .find(|edge| {
+        matches!(
+          edge.weight(),
+          Edge::HasBinding(_) | Edge::HasParameterBinding(_)
+        )
+      })
+      .map(|edge| edge.id())
+    {
+      self.graph.remove_edge(edge_index);
+    }
+    while let Some(edge_index) = self
+      .graph
+      .edges_directed(shape_parameter_node_index, petgraph::Direction::Outgoing)
+      .find(|edge| matches!(edge.weight(), Edge::IsParameterOf))
+      .map(|edge| edge.id())
+    {
+      self.graph.remove_edge(edge_index);
+    }
+
+    self.node_id_to_index.remove(&shape_parameter_id); // prevents parameter node to be looked up by id
+  }
+
+  pub fn without_shape(&mut self, shape_id: ShapeId) {
+    let shape_node_index = *self
+      .get_shape_node_index(&shape_id)
+      .expect("expected shape_id to have corresponding node");
+
+    // drop the references the shape makes to other shapes, so they aren't found to be used by it.
+    // Edges are removed one at a time, as removing an edge shifts the indexes of others.
+    while let Some(reference_edge_index) = self
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Outgoing)
+      .find(|edge| {
+        matches!(
+          edge.weight(),
+          Edge::IsDescendantOf | Edge::HasBinding(_) | Edge::HasParameterBinding(_)
+        )
+      })
+      .map(|edge| edge.id())
+    {
+      self.graph.remove_edge(reference_edge_index);
+    }
+
+    // the fields and parameters the shape declares go along with it
+    let field_ids: Vec<_> = self
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      .filter(|edge| matches!(edge.weight(), Edge::IsFieldOf))
+      .filter_map(|edge| match self.graph.node_weight(edge.source()) {
+        Some(Node::Field(field_node)) => Some(field_node.field_id.clone()),
+        _ => None,
+      })
+      .collect();
+    for field_id in field_ids {
+      self.without_field(field_id);
+    }
+    let shape_parameter_ids: Vec<_> = self
+      .graph
+      .neighbors_directed(shape_node_index, petgraph::Direction::Incoming)
+      .filter_map(
+        |neighbour_index| match self.graph.node_weight(neighbour_index) {
+          Some(Node::ShapeParameter(parameter_node)) => Some(parameter_node.parameter_id.clone()),
+          _ => None,
+        },
+      )
+      .collect();
+    for shape_parameter_id in shape_parameter_ids {
+      self.node_id_to_index.remove(&shape_parameter_id);
+    }
+
+    self.node_id_to_index.remove(&shape_id); // prevents shape node to be looked up by shape id
+    self.constraints_by_shape_id.remove(&shape_id);
+    self.names_by_shape_id.remove(&shape_id);
+
+    // GOTCHA: like with fields, the shape node itself is kept around, as deleting it would
+    // invalidate self.node_id_to_index as the graph indexes shift.
+  }
+
   pub fn get_shape_node_index(&self, node_id: &NodeId) -> Option<&NodeIndex> {
     let node_index = self.node_id_to_index.get(node_id)?;
     let node = self.graph.node_weight(*node_index);
@@ -635,8 +831,13 @@ impl AggregateEvent<ShapeProjection> for ShapeEvent {
       ShapeEvent::ShapeParameterShapeSet(e) => {
         projection.with_shape_parameter_shape(e.shape_descriptor.clone());
         if let Some(c) = e.event_context {
-          if let ParameterShapeDescriptor::ProviderInShape(d) = &e.shape_descriptor {
-            projection.with_update_history(&c.client_command_batch_id, &d.shape_id);
+          match &e.shape_descriptor {
+            ParameterShapeDescriptor::ProviderInShape(d) => {
+              projection.with_update_history(&c.client_command_batch_id, &d.shape_id)
+            }
+            ParameterShapeDescriptor::ProviderInField(d) => {
+              projection.with_update_history(&c.client_command_batch_id, &d.field_id)
+            }
           }
         }
       }
@@ -651,14 +852,44 @@ impl AggregateEvent<ShapeProjection> for ShapeEvent {
         projection.with_field_shape(e.shape_descriptor.clone());
 
         if let Some(c) = e.event_context {
-          if let FieldShapeDescriptor::FieldShapeFromShape(d) = &e.shape_descriptor {
-            projection.with_update_history(&c.client_command_batch_id, &d.field_id);
-          }
+          let field_id = match &e.shape_descriptor {
+            FieldShapeDescriptor::FieldShapeFromShape(d) => &d.field_id,
+            FieldShapeDescriptor::FieldShapeFromParameter(d) => &d.field_id,
+          };
+          projection.with_update_history(&c.client_command_batch_id, field_id);
+        }
+      }
+      ShapeEvent::ShapeRemoved(e) => {
+        // removed nodes can no longer be looked up, so their history is tracked beforehand
+        if let Some(c) = e.event_context {
+          projection.with_remove_history(&c.client_command_batch_id, &e.shape_id);
+        }
+        projection.without_shape(e.shape_id);
+      }
+      ShapeEvent::ShapeParameterRenamed(e) => {
+        // parameter names aren't projected, only that the parameter changed
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_parameter_id);
+        }
+      }
+      ShapeEvent::ShapeParameterRemoved(e) => {
+        if let Some(c) = e.event_context {
+          projection.with_remove_history(&c.client_command_batch_id, &e.shape_parameter_id);
+        }
+        projection.without_shape_parameter(e.shape_parameter_id);
+      }
+      ShapeEvent::FieldRenamed(e) => {
+        projection.with_field_name(e.field_id.clone(), e.name);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.field_id);
         }
       }
       ShapeEvent::FieldRemoved(e) => {
+        if let Some(c) = e.event_context {
+          projection.with_remove_history(&c.client_command_batch_id, &e.field_id);
+        }
         projection.without_field(e.field_id);
-        // TODO: track removal history
       }
 
       ShapeEvent::BaseShapeSet(e) => {
@@ -668,9 +899,19 @@ impl AggregateEvent<ShapeProjection> for ShapeEvent {
           projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
         }
       }
-      x => {
-        //dbg!("skipping ShapeEvent in ShapeProjection. warning?");
-        //dbg!(&x);
+      ShapeEvent::ShapeRenamed(e) => {
+        projection.with_shape_name(e.shape_id.clone(), e.name);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
+        }
+      }
+      ShapeEvent::ShapeConstraintSet(e) => {
+        projection.with_shape_constraint(e.shape_id.clone(), e.constraint_descriptor);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
+        }
       }
     }
   }
@@ -712,4 +953,50 @@ mod test {
     let mut projection = ShapeProjection::default();
     projection.apply(event);
   }
+
+  #[test]
+  fn can_project_removal_history() {
+    let events: Vec<ShapeEvent> = serde_json::from_value(serde_json::json!([
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","name":"","eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_1","createdAt":"2021-03-04T12:00:00.000Z"}}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"id","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"$string"}},"eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_1","createdAt":"2021-03-04T12:00:00.000Z"}}},
+      {"ShapeAdded":{"shapeId":"list_shape_1","baseShapeId":"$list","name":"","eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_1","createdAt":"2021-03-04T12:00:00.000Z"}}},
+      {"ShapeParameterAdded":{"shapeParameterId":"parameter_1","shapeId":"list_shape_1","name":"","shapeDescriptor":{"ProviderInShape":{"shapeId":"list_shape_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"parameter_1"}},"eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_1","createdAt":"2021-03-04T12:00:00.000Z"}}},
+      {"FieldRemoved":{"fieldId":"field_1","eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_2","createdAt":"2021-03-05T12:00:00.000Z"}}},
+      {"ShapeParameterRemoved":{"shapeParameterId":"parameter_1","shapeId":"list_shape_1","eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_2","createdAt":"2021-03-05T12:00:00.000Z"}}},
+      {"ShapeRemoved":{"shapeId":"object_shape_1","eventContext":{"clientId":"anonymous","clientSessionId":"session_1","clientCommandBatchId":"batch_2","createdAt":"2021-03-05T12:00:00.000Z"}}},
+    ]))
+    .expect("should deserialize json shape events");
+
+    let mut projection = ShapeProjection::default();
+    projection.with_batch_commit(
+      String::from("batch_1"),
+      String::from("2021-03-04T12:00:00.000Z"),
+    );
+    projection.with_batch_commit(
+      String::from("batch_2"),
+      String::from("2021-03-05T12:00:00.000Z"),
+    );
+    for event in events {
+      projection.apply(event);
+    }
+
+    let batch_2_index = projection.node_id_to_index["batch_2"];
+    let mut removed_ids = projection
+      .graph
+      .edges_directed(batch_2_index, petgraph::Direction::Incoming)
+      .filter(|edge| matches!(edge.weight(), Edge::RemovedIn))
+      .map(|edge| match projection.graph.node_weight(edge.source()) {
+        Some(Node::Shape(node)) => node.shape_id.clone(),
+        Some(Node::Field(node)) => node.field_id.clone(),
+        Some(Node::ShapeParameter(node)) => node.parameter_id.clone(),
+        node => unreachable!("unexpected removed node {:?}", node),
+      })
+      .collect::<Vec<_>>();
+    removed_ids.sort();
+
+    assert_eq!(
+      removed_ids,
+      vec!["field_1", "object_shape_1", "parameter_1"]
+    );
+  }
 }
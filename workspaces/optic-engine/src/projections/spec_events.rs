@@ -1,10 +1,13 @@
 use crate::events::spec_chunk::{BatchChunkEvent, RootChunkEvent};
-use crate::events::SpecChunkEvent;
+use crate::events::{EndpointEvent, RfcEvent, ShapeEvent, SpecChunkEvent};
+use crate::state::shape::{FieldShapeDescriptor, ParameterShapeDescriptor, ProviderDescriptor};
 use crate::SpecEvent;
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
 use std::error::Error;
 use std::fmt;
 
-use cqrs_core::{Aggregate, AggregateEvent};
+use cqrs_core::{Aggregate, AggregateEvent, Event};
 use std::collections::HashMap;
 
 #[derive(Debug)]
@@ -42,36 +45,434 @@ impl SpecAssemblerProjection {
 
   // TODO: implement returning of an Iterator instead of Vec
   pub fn into_events(self) -> Result<Vec<SpecEvent>, SpecAssemblerError> {
+    let (events, _) = self.into_events_with_report()?;
+    Ok(events)
+  }
+
+  // Batches that fork from the same parent (commits on different git branches that were merged
+  // since) are replayed one branch after the other, ordered by when their batch commits started.
+  // Merging fails when the branches change the same parts of the spec in incompatible ways.
+  pub fn into_events_with_report(
+    self,
+  ) -> Result<(Vec<SpecEvent>, SpecAssemblyReport), SpecAssemblerError> {
     let root_chunk = self
       .root_chunk
       .ok_or_else(|| SpecAssemblerError::RootChunkRequired)?;
 
-    let mut current_chunk_id = root_chunk.last_batch_id().clone();
-    let mut chunks = vec![SpecChunkEvent::Root(root_chunk)];
+    let mut report = SpecAssemblyReport::default();
     let mut chunks_by_parent_id = self.chunks_by_parent_id;
+    let batch_chunks = assemble_batch_chain(
+      &mut chunks_by_parent_id,
+      root_chunk.last_batch_id().clone(),
+      &mut report,
+    )?;
+
+    report.batches = batch_chunks.iter().map(AssembledBatch::from).collect();
+    report.unreachable_batch_ids = chunks_by_parent_id
+      .into_values()
+      .flatten()
+      .map(|chunk| chunk.id)
+      .collect();
+    report.unreachable_batch_ids.sort();
+
+    let chunks = std::iter::once(SpecChunkEvent::Root(root_chunk))
+      .chain(batch_chunks.into_iter().map(SpecChunkEvent::Batch));
+
+    let events = chunks.flat_map(|chunk| chunk.into_events_iter());
+
+    Ok((events.collect(), report))
+  }
+}
+
+fn assemble_batch_chain(
+  chunks_by_parent_id: &mut HashMap<String, Vec<BatchChunkEvent>>,
+  parent_id: String,
+  report: &mut SpecAssemblyReport,
+) -> Result<Vec<BatchChunkEvent>, SpecAssemblerError> {
+  let mut chain = vec![];
+  let mut current_chunk_id = parent_id;
+
+  while let Some(mut children) = chunks_by_parent_id.remove(&current_chunk_id) {
+    if children.len() == 1 {
+      let child_chunk = children.pop().unwrap();
+      current_chunk_id = child_chunk.id.clone();
+      chain.push(child_chunk);
+      continue;
+    }
+
+    children.sort_by_cached_key(|chunk| (batch_started_at(chunk), chunk.name.clone()));
+
+    let mut branches = Vec::with_capacity(children.len());
+    for child_chunk in children {
+      let child_chunk_id = child_chunk.id.clone();
+      let mut branch = vec![child_chunk];
+      branch.extend(assemble_batch_chain(
+        chunks_by_parent_id,
+        child_chunk_id,
+        report,
+      )?);
+      branches.push(branch);
+    }
+
+    let conflicts = branch_conflicts(&branches);
+    if !conflicts.is_empty() {
+      return Err(SpecAssemblerError::ConflictingBranches {
+        parent_id: current_chunk_id,
+        conflicts,
+      });
+    }
+
+    report.forks.push(AssembledFork {
+      parent_id: current_chunk_id,
+      branches: branches
+        .iter()
+        .map(|branch| branch.iter().map(|chunk| chunk.id.clone()).collect())
+        .collect(),
+    });
+    chain.extend(branches.into_iter().flatten());
+    // every batch has a single parent, so after a fork the chain continues within its branches
+    break;
+  }
+
+  Ok(chain)
+}
+
+fn batch_started_at(chunk: &BatchChunkEvent) -> Option<DateTime<FixedOffset>> {
+  chunk.events.iter().find_map(|event| match event {
+    SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(batch_started)) => batch_started
+      .event_context
+      .as_ref()
+      .and_then(|context| DateTime::parse_from_rfc3339(&context.created_at).ok()),
+    _ => None,
+  })
+}
+
+fn batch_commit_message(chunk: &BatchChunkEvent) -> Option<String> {
+  chunk.events.iter().find_map(|event| match event {
+    SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(batch_started)) => {
+      Some(batch_started.commit_message.clone())
+    }
+    _ => None,
+  })
+}
 
-    loop {
-      let children = chunks_by_parent_id.remove(&current_chunk_id);
-      if let None = children {
-        break;
+// Branch conflicts
+// ----------------
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum EntityChange {
+  Added,
+  Modified {
+    aspect: String,
+    payload: serde_json::Value,
+  },
+  Removed,
+}
+
+fn branch_conflicts(branches: &[Vec<BatchChunkEvent>]) -> Vec<BranchConflict> {
+  let changes_by_branch = branches
+    .iter()
+    .map(|branch| {
+      branch
+        .iter()
+        .flat_map(|chunk| {
+          chunk
+            .events
+            .iter()
+            .filter_map(entity_change)
+            .map(move |(entity_id, change)| (entity_id, change, chunk.id.as_str()))
+        })
+        .collect::<Vec<_>>()
+    })
+    .collect::<Vec<_>>();
+
+  let mut conflicts = vec![];
+  for (i, changes) in changes_by_branch.iter().enumerate() {
+    let mut changes_by_entity: HashMap<&str, Vec<(&EntityChange, &str)>> = HashMap::new();
+    for (entity_id, change, batch_id) in changes {
+      changes_by_entity
+        .entry(entity_id.as_str())
+        .or_default()
+        .push((change, batch_id));
+    }
+
+    for other_changes in &changes_by_branch[i + 1..] {
+      for (entity_id, other_change, other_batch_id) in other_changes {
+        let entity_changes = match changes_by_entity.get(entity_id.as_str()) {
+          Some(entity_changes) => entity_changes,
+          None => continue,
+        };
+
+        for (change, batch_id) in entity_changes {
+          let kind = match (change, other_change) {
+            (EntityChange::Added, EntityChange::Added) => BranchConflictKind::AddedInBoth,
+            (
+              EntityChange::Modified { aspect, payload },
+              EntityChange::Modified {
+                aspect: other_aspect,
+                payload: other_payload,
+              },
+            ) if aspect == other_aspect && payload != other_payload => {
+              BranchConflictKind::ModifiedInBoth {
+                aspect: aspect.clone(),
+              }
+            }
+            (EntityChange::Modified { .. }, EntityChange::Removed)
+            | (EntityChange::Removed, EntityChange::Modified { .. }) => {
+              BranchConflictKind::ModifiedAndRemoved
+            }
+            _ => continue,
+          };
+
+          conflicts.push(BranchConflict {
+            entity_id: entity_id.clone(),
+            kind,
+            batch_id: String::from(*batch_id),
+            other_batch_id: String::from(*other_batch_id),
+          });
+        }
       }
-      let mut children = children.unwrap();
+    }
+  }
 
-      if children.len() != 1 {
-        // if there are multiple children to a parent
-        break; // TODO: decide whether for this projection we need to do anything more
+  conflicts
+}
+
+// Maps an event to the entity it changes. Modifications are tracked per aspect (the event type and
+// anything else that narrows down what was set), so branches can change different aspects of the same
+// entity without conflicting, as long as they don't remove it.
+pub(crate) fn entity_change(event: &SpecEvent) -> Option<(String, EntityChange)> {
+  let modified = |entity_id: &str, aspect: String| {
+    Some((
+      String::from(entity_id),
+      EntityChange::Modified {
+        aspect,
+        payload: event_payload(event),
+      },
+    ))
+  };
+  let added = |entity_id: &str| Some((String::from(entity_id), EntityChange::Added));
+  let removed = |entity_id: &str| Some((String::from(entity_id), EntityChange::Removed));
+  let event_type = String::from(event.event_type());
+
+  match event {
+    SpecEvent::EndpointEvent(event) => match event {
+      EndpointEvent::PathComponentAdded(e) => added(&e.path_id),
+      EndpointEvent::PathComponentRenamed(e) => modified(&e.path_id, event_type),
+      EndpointEvent::PathComponentRemoved(e) => removed(&e.path_id),
+      EndpointEvent::PathParameterAdded(e) => added(&e.path_id),
+      EndpointEvent::PathParameterShapeSet(e) => modified(&e.path_id, event_type),
+      EndpointEvent::PathParameterRenamed(e) => modified(&e.path_id, event_type),
+      EndpointEvent::PathParameterRemoved(e) => removed(&e.path_id),
+      EndpointEvent::QueryParametersAdded(e) => added(&e.query_parameters_id),
+      EndpointEvent::QueryParametersShapeSet(e) => modified(&e.query_parameters_id, event_type),
+      EndpointEvent::QueryParametersRemoved(e) => removed(&e.query_parameters_id),
+      EndpointEvent::RequestParameterAdded(e) => added(&e.parameter_id),
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e) => added(&e.parameter_id),
+      EndpointEvent::RequestParameterRenamed(e) => modified(&e.parameter_id, event_type),
+      EndpointEvent::RequestParameterShapeSet(e) => {
+        modified(&e.parameter_id, String::from("RequestParameterShape"))
       }
-      let child_chunk = children.pop().unwrap();
+      EndpointEvent::RequestParameterShapeUnset(e) => {
+        modified(&e.parameter_id, String::from("RequestParameterShape"))
+      }
+      EndpointEvent::RequestParameterRemoved(e) => removed(&e.parameter_id),
+      EndpointEvent::ResponseParameterAdded(e) => added(&e.parameter_id),
+      EndpointEvent::RequestAdded(e) => added(&e.request_id),
+      EndpointEvent::RequestContentTypeSet(e) => modified(&e.request_id, event_type),
+      EndpointEvent::RequestBodySet(e) => modified(&e.request_id, String::from("RequestBody")),
+      EndpointEvent::RequestBodyUnset(e) => modified(&e.request_id, String::from("RequestBody")),
+      EndpointEvent::RequestRemoved(e) => removed(&e.request_id),
+      EndpointEvent::ResponseAddedByPathAndMethod(e) => added(&e.response_id),
+      EndpointEvent::ResponseStatusCodeSet(e) => modified(&e.response_id, event_type),
+      EndpointEvent::ResponseContentTypeSet(e) => modified(&e.response_id, event_type),
+      EndpointEvent::ResponseBodySet(e) => modified(&e.response_id, String::from("ResponseBody")),
+      EndpointEvent::ResponseBodyUnset(e) => modified(&e.response_id, String::from("ResponseBody")),
+      EndpointEvent::ResponseRemoved(e) => removed(&e.response_id),
+    },
+    SpecEvent::ShapeEvent(event) => match event {
+      ShapeEvent::ShapeAdded(e) => added(&e.shape_id),
+      ShapeEvent::BaseShapeSet(e) => modified(&e.shape_id, event_type),
+      ShapeEvent::ShapeRenamed(e) => modified(&e.shape_id, event_type),
+      ShapeEvent::ShapeRemoved(e) => removed(&e.shape_id),
+      ShapeEvent::ShapeConstraintSet(e) => modified(&e.shape_id, event_type),
+      ShapeEvent::ShapeParameterAdded(e) => added(&e.shape_parameter_id),
+      ShapeEvent::ShapeParameterShapeSet(e) => match &e.shape_descriptor {
+        ParameterShapeDescriptor::ProviderInShape(descriptor) => modified(
+          &descriptor.shape_id,
+          format!("{}:{}", event_type, descriptor.consuming_parameter_id),
+        ),
+        ParameterShapeDescriptor::ProviderInField(descriptor) => modified(
+          &descriptor.field_id,
+          format!("{}:{}", event_type, descriptor.consuming_parameter_id),
+        ),
+      },
+      ShapeEvent::ShapeParameterRenamed(e) => modified(&e.shape_parameter_id, event_type),
+      ShapeEvent::ShapeParameterRemoved(e) => removed(&e.shape_parameter_id),
+      ShapeEvent::FieldAdded(e) => added(&e.field_id),
+      ShapeEvent::FieldShapeSet(e) => match &e.shape_descriptor {
+        FieldShapeDescriptor::FieldShapeFromShape(descriptor) => {
+          modified(&descriptor.field_id, event_type)
+        }
+        FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => {
+          modified(&descriptor.field_id, event_type)
+        }
+      },
+      ShapeEvent::FieldRenamed(e) => modified(&e.field_id, event_type),
+      ShapeEvent::FieldRemoved(e) => removed(&e.field_id),
+    },
+    SpecEvent::RfcEvent(event) => match event {
+      RfcEvent::ContributionAdded(e) => modified(&e.id, format!("{}:{}", event_type, e.key)),
+      RfcEvent::APINamed(_) => modified("api", event_type),
+      RfcEvent::GitStateSet(_)
+      | RfcEvent::BatchCommitStarted(_)
+      | RfcEvent::BatchCommitEnded(_) => None,
+    },
+  }
+}
 
-      current_chunk_id = child_chunk.id.clone();
-      chunks.push(SpecChunkEvent::Batch(child_chunk));
+// The event without its context, so identical changes made on both branches can be told apart from
+// conflicting ones.
+fn event_payload(event: &SpecEvent) -> serde_json::Value {
+  let mut payload = serde_json::to_value(event).expect("spec events should serialize to json");
+  if let Some(variant) = payload.as_object_mut() {
+    for (_, fields) in variant.iter_mut() {
+      if let Some(fields) = fields.as_object_mut() {
+        fields.remove("eventContext");
+      }
+    }
+  }
+  payload
+}
+
+// The entities an event builds on, besides the one it changes
+pub(crate) fn referenced_entity_ids(event: &SpecEvent) -> Vec<&str> {
+  match event {
+    SpecEvent::EndpointEvent(event) => match event {
+      EndpointEvent::PathComponentAdded(e) => vec![&e.parent_path_id],
+      EndpointEvent::PathParameterAdded(e) => vec![&e.parent_path_id],
+      EndpointEvent::PathParameterShapeSet(e) => vec![&e.shape_descriptor.shape_id],
+      EndpointEvent::QueryParametersAdded(e) => vec![&e.path_id],
+      EndpointEvent::QueryParametersShapeSet(e) => vec![&e.shape_descriptor.shape_id],
+      EndpointEvent::RequestParameterAdded(e) => vec![&e.request_id],
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e) => vec![&e.path_id],
+      EndpointEvent::ResponseParameterAdded(e) => vec![&e.response_id],
+      EndpointEvent::RequestParameterShapeSet(e) => vec![&e.parameter_descriptor.shape_id],
+      EndpointEvent::RequestAdded(e) => vec![&e.path_id],
+      EndpointEvent::RequestBodySet(e) => vec![&e.body_descriptor.shape_id],
+      EndpointEvent::ResponseAddedByPathAndMethod(e) => vec![&e.path_id],
+      EndpointEvent::ResponseBodySet(e) => vec![&e.body_descriptor.shape_id],
+      _ => vec![],
+    },
+    SpecEvent::ShapeEvent(event) => match event {
+      ShapeEvent::ShapeAdded(e) => vec![&e.base_shape_id],
+      ShapeEvent::BaseShapeSet(e) => vec![&e.base_shape_id],
+      ShapeEvent::ShapeParameterAdded(e) => vec![&e.shape_id],
+      ShapeEvent::ShapeParameterShapeSet(e) => {
+        let (mut ids, provider_descriptor) = match &e.shape_descriptor {
+          ParameterShapeDescriptor::ProviderInShape(descriptor) => (
+            vec![
+              descriptor.shape_id.as_str(),
+              &descriptor.consuming_parameter_id,
+            ],
+            &descriptor.provider_descriptor,
+          ),
+          ParameterShapeDescriptor::ProviderInField(descriptor) => (
+            vec![
+              descriptor.field_id.as_str(),
+              &descriptor.consuming_parameter_id,
+            ],
+            &descriptor.provider_descriptor,
+          ),
+        };
+        match provider_descriptor {
+          ProviderDescriptor::ShapeProvider(provider) => ids.push(&provider.shape_id),
+          ProviderDescriptor::ParameterProvider(provider) => ids.push(&provider.shape_parameter_id),
+          ProviderDescriptor::NoProvider(_) => {}
+        }
+        ids
+      }
+      ShapeEvent::FieldAdded(e) => {
+        let mut ids = vec![e.shape_id.as_str()];
+        ids.extend(field_shape_ids(&e.shape_descriptor));
+        ids
+      }
+      ShapeEvent::FieldShapeSet(e) => field_shape_ids(&e.shape_descriptor),
+      _ => vec![],
+    },
+    SpecEvent::RfcEvent(_) => vec![],
+  }
+}
+
+fn field_shape_ids(shape_descriptor: &FieldShapeDescriptor) -> Vec<&str> {
+  match shape_descriptor {
+    FieldShapeDescriptor::FieldShapeFromShape(descriptor) => vec![&descriptor.shape_id],
+    FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => {
+      vec![&descriptor.shape_parameter_id]
+    }
+  }
+}
+
+// Assembly report
+// ---------------
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecAssemblyReport {
+  pub batches: Vec<AssembledBatch>,
+  pub forks: Vec<AssembledFork>,
+  pub unreachable_batch_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssembledBatch {
+  pub id: String,
+  pub name: String,
+  pub parent_id: String,
+  pub commit_message: Option<String>,
+}
+
+impl From<&BatchChunkEvent> for AssembledBatch {
+  fn from(chunk: &BatchChunkEvent) -> Self {
+    Self {
+      id: chunk.id.clone(),
+      name: chunk.name.clone(),
+      parent_id: chunk.parent_id.clone(),
+      commit_message: batch_commit_message(chunk),
     }
+  }
+}
 
-    let events = chunks
-      .into_iter()
-      .flat_map(|chunk| chunk.into_events_iter());
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssembledFork {
+  pub parent_id: String,
+  pub branches: Vec<Vec<String>>,
+}
 
-    Ok(events.collect())
+impl fmt::Display for SpecAssemblyReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "assembled {} batch commits", self.batches.len())?;
+    for fork in &self.forks {
+      writeln!(
+        f,
+        "merged {} branches forking from '{}':",
+        fork.branches.len(),
+        fork.parent_id
+      )?;
+      for branch in &fork.branches {
+        writeln!(f, "  {}", branch.join(" -> "))?;
+      }
+    }
+    if !self.unreachable_batch_ids.is_empty() {
+      writeln!(
+        f,
+        "skipped batch commits without a known parent: {}",
+        self.unreachable_batch_ids.join(", ")
+      )?;
+    }
+    Ok(())
   }
 }
 
@@ -117,14 +518,68 @@ where
 #[derive(Debug)]
 pub enum SpecAssemblerError {
   RootChunkRequired,
+  ConflictingBranches {
+    parent_id: String,
+    conflicts: Vec<BranchConflict>,
+  },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchConflict {
+  pub entity_id: String,
+  pub kind: BranchConflictKind,
+  pub batch_id: String,
+  pub other_batch_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BranchConflictKind {
+  AddedInBoth,
+  ModifiedInBoth { aspect: String },
+  ModifiedAndRemoved,
 }
 
 impl fmt::Display for SpecAssemblerError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    let msg = match self {
-      SpecAssemblerError::RootChunkRequired => "Root chunk required to assemble events",
+    match self {
+      SpecAssemblerError::RootChunkRequired => write!(
+        f,
+        "SpecAssemblerError: Root chunk required to assemble events"
+      ),
+      SpecAssemblerError::ConflictingBranches {
+        parent_id,
+        conflicts,
+      } => {
+        write!(
+          f,
+          "SpecAssemblerError: branches forking from '{}' could not be merged",
+          parent_id
+        )?;
+        for conflict in conflicts {
+          write!(f, "\n  {}", conflict)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl fmt::Display for BranchConflict {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let change = match &self.kind {
+      BranchConflictKind::AddedInBoth => String::from("added in both"),
+      BranchConflictKind::ModifiedInBoth { aspect } => {
+        format!("changed ({}) differently in", aspect)
+      }
+      BranchConflictKind::ModifiedAndRemoved => String::from("changed and removed between"),
     };
-    write!(f, "SpecAssemblerError: {}", msg)
+    write!(
+      f,
+      "'{}' was {} '{}' and '{}'",
+      self.entity_id, change, self.batch_id, self.other_batch_id
+    )
   }
 }
 
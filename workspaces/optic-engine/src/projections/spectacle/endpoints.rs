@@ -2,7 +2,8 @@ use crate::events::{EndpointEvent, Event, ShapeEvent, SpecEvent};
 use crate::projections::endpoint::ROOT_PATH_ID;
 use crate::state::endpoint::{
   HttpContentType, HttpMethod, HttpStatusCode, PathComponentId, QueryParametersId,
-  QueryParametersShapeDescriptor, RequestId, ResponseId,
+  QueryParametersShapeDescriptor, RequestId, RequestParameterId, ResponseId,
+  ShapedRequestParameterShapeDescriptor, HEADER_PARAMETER_LOCATION,
 };
 use crate::state::shape::ShapeId;
 use crate::RfcEvent;
@@ -175,6 +176,12 @@ impl AggregateEvent<EndpointsProjection> for EndpointEvent {
           projection.with_creation_history(c.client_command_batch_id, e.body_descriptor.shape_id);
         }
       }
+      EndpointEvent::RequestBodyUnset(e) => {
+        projection.without_request_body(&e.request_id);
+        if let Some(c) = e.event_context {
+          projection.with_update_history(c.client_command_batch_id, e.request_id);
+        }
+      }
       EndpointEvent::QueryParametersAdded(e) => {
         projection.with_query_parameters(e.path_id, e.http_method, e.query_parameters_id);
       }
@@ -191,6 +198,65 @@ impl AggregateEvent<EndpointsProjection> for EndpointEvent {
           projection.with_remove_history(c.client_command_batch_id, e.query_parameters_id);
         }
       }
+      EndpointEvent::RequestParameterAdded(e) => {
+        if e.parameter_location == HEADER_PARAMETER_LOCATION {
+          let endpoint_id = projection.get_endpoint_id_from_node(&e.request_id);
+          projection.with_header_parameter(endpoint_id, e.parameter_id.clone(), e.name);
+          if let Some(c) = e.event_context {
+            projection.with_creation_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e) => {
+        if e.parameter_location == HEADER_PARAMETER_LOCATION {
+          projection.ensure_endpoint_node_index(e.path_id.clone(), e.http_method.clone());
+          let endpoint_id = get_endpoint_id(&e.path_id, &e.http_method);
+          projection.with_header_parameter(endpoint_id, e.parameter_id.clone(), e.name);
+          if let Some(c) = e.event_context {
+            projection.with_creation_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
+      EndpointEvent::ResponseParameterAdded(e) => {
+        if e.parameter_location == HEADER_PARAMETER_LOCATION {
+          projection.with_header_parameter(e.response_id, e.parameter_id.clone(), e.name);
+          if let Some(c) = e.event_context {
+            projection.with_creation_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
+      EndpointEvent::RequestParameterRenamed(e) => {
+        if projection.is_header_parameter(&e.parameter_id) {
+          projection.with_header_parameter_name(&e.parameter_id, e.name);
+          if let Some(c) = e.event_context {
+            projection.with_update_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
+      EndpointEvent::RequestParameterShapeSet(e) => {
+        if projection.is_header_parameter(&e.parameter_id) {
+          projection.with_header_parameter_shape(&e.parameter_id, Some(e.parameter_descriptor));
+          if let Some(c) = e.event_context {
+            projection.with_update_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
+      EndpointEvent::RequestParameterShapeUnset(e) => {
+        if projection.is_header_parameter(&e.parameter_id) {
+          projection.with_header_parameter_shape(&e.parameter_id, None);
+          if let Some(c) = e.event_context {
+            projection.with_update_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
+      EndpointEvent::RequestParameterRemoved(e) => {
+        if projection.is_header_parameter(&e.parameter_id) {
+          projection.without_header_parameter(&e.parameter_id);
+          if let Some(c) = e.event_context {
+            projection.with_remove_history(c.client_command_batch_id, e.parameter_id);
+          }
+        }
+      }
       EndpointEvent::ResponseBodySet(e) => {
         projection.with_response_body(
           e.response_id,
@@ -202,6 +268,12 @@ impl AggregateEvent<EndpointsProjection> for EndpointEvent {
           projection.with_creation_history(c.client_command_batch_id, e.body_descriptor.shape_id);
         }
       }
+      EndpointEvent::ResponseBodyUnset(e) => {
+        projection.without_response_body(&e.response_id);
+        if let Some(c) = e.event_context {
+          projection.with_update_history(c.client_command_batch_id, e.response_id);
+        }
+      }
       _ => eprintln!(
         "Ignoring applying event of type '{}' for '{}'",
         self.event_type(),
@@ -438,6 +510,9 @@ impl EndpointsProjection {
           Node::Response(ResponseNode {
             is_removed: true, ..
           }) => true,
+          Node::HeaderParameter(HeaderParameterNode {
+            is_removed: true, ..
+          }) => true,
           _ => false,
         }
       });
@@ -595,6 +670,15 @@ impl EndpointsProjection {
       .add_edge(node_index, request_index, Edge::IsChildOf);
   }
   ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn without_request_body(&mut self, request_id: &RequestId) {
+    let request_index = *self
+      .domain_id_to_index
+      .get(request_id)
+      .expect("expected node with domain_id $request_id to exist in the graph");
+
+    self.without_bodies_of(request_index);
+  }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
   pub fn with_query_parameters(
     &mut self,
     path_id: PathComponentId,
@@ -673,6 +757,79 @@ impl EndpointsProjection {
     }
   }
 
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn with_header_parameter(
+    &mut self,
+    parent_id: String,
+    parameter_id: RequestParameterId,
+    name: String,
+  ) {
+    // request headers are children of the endpoint, response headers of their response
+    let parent_index = *self
+      .domain_id_to_index
+      .get(&parent_id)
+      .expect("expected node to exist in graph");
+
+    let node = Node::HeaderParameter(HeaderParameterNode {
+      parameter_id: parameter_id.clone(),
+      name,
+      root_shape_id: None,
+      is_removed: false,
+    });
+    let node_index = self.graph.add_node(node);
+    self
+      .graph
+      .add_edge(node_index, parent_index, Edge::IsChildOf);
+
+    self.domain_id_to_index.insert(parameter_id, node_index);
+  }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn is_header_parameter(&self, parameter_id: &RequestParameterId) -> bool {
+    self
+      .domain_id_to_index
+      .get(parameter_id)
+      .and_then(|node_index| self.graph.node_weight(*node_index))
+      .map(|node| matches!(node, Node::HeaderParameter(_)))
+      .unwrap_or(false)
+  }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn with_header_parameter_name(&mut self, parameter_id: &RequestParameterId, name: String) {
+    let header_index = *self
+      .domain_id_to_index
+      .get(parameter_id)
+      .expect("expected node with domain_id $parameter_id to exist in the graph");
+
+    if let Some(Node::HeaderParameter(header_node)) = self.graph.node_weight_mut(header_index) {
+      header_node.name = name;
+    }
+  }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn with_header_parameter_shape(
+    &mut self,
+    parameter_id: &RequestParameterId,
+    shape_descriptor: Option<ShapedRequestParameterShapeDescriptor>,
+  ) {
+    let header_index = *self
+      .domain_id_to_index
+      .get(parameter_id)
+      .expect("expected node with domain_id $parameter_id to exist in the graph");
+
+    if let Some(Node::HeaderParameter(header_node)) = self.graph.node_weight_mut(header_index) {
+      header_node.root_shape_id = shape_descriptor.map(|descriptor| descriptor.shape_id);
+    }
+  }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn without_header_parameter(&mut self, parameter_id: &RequestParameterId) {
+    let header_index = *self
+      .domain_id_to_index
+      .get(parameter_id)
+      .expect("expected node with domain_id $parameter_id to exist in the graph");
+
+    // headers alone don't make up an endpoint, so removing one never removes the endpoint itself
+    if let Some(Node::HeaderParameter(header_node)) = self.graph.node_weight_mut(header_index) {
+      header_node.is_removed = true;
+    }
+  }
   ////////////////////////////////////////////////////////////////////////////////////////////////////
   pub fn with_response_body(
     &mut self,
@@ -764,6 +921,32 @@ impl EndpointsProjection {
       .add_edge(node_index, response_index, Edge::IsChildOf);
   }
   ////////////////////////////////////////////////////////////////////////////////////////////////////
+  pub fn without_response_body(&mut self, response_id: &ResponseId) {
+    let response_index = *self
+      .domain_id_to_index
+      .get(response_id)
+      .expect("expected node with domain_id $response_id to exist in the graph");
+
+    self.without_bodies_of(response_index);
+  }
+
+  // like other removed nodes, the body nodes of a request or response are kept around to show their
+  // history, only marked as removed
+  fn without_bodies_of(&mut self, parent_index: petgraph::graph::NodeIndex) {
+    let body_indexes = self
+      .graph
+      .edges_directed(parent_index, petgraph::Direction::Incoming)
+      .filter(|edge| matches!(self.graph.node_weight(edge.source()), Some(Node::Body(_))))
+      .map(|edge| edge.source())
+      .collect::<Vec<_>>();
+
+    for body_index in body_indexes {
+      if let Some(Node::Body(body_node)) = self.graph.node_weight_mut(body_index) {
+        body_node.is_removed = true;
+      }
+    }
+  }
+  ////////////////////////////////////////////////////////////////////////////////////////////////////
   pub fn with_batch_commit(
     &mut self,
     batch_id: String,
@@ -840,6 +1023,7 @@ pub enum Node {
   Request(RequestNode),
   Response(ResponseNode),
   QueryParameters(QueryParametersNode),
+  HeaderParameter(HeaderParameterNode),
   Body(BodyNode),
   BatchCommit(BatchCommitNode),
 }
@@ -886,6 +1070,15 @@ pub struct QueryParametersNode {
   is_removed: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderParameterNode {
+  parameter_id: RequestParameterId,
+  name: String,
+  root_shape_id: Option<ShapeId>,
+  is_removed: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResponseNode {
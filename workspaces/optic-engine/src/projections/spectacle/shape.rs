@@ -27,7 +27,14 @@ impl From<&ShapeProjection> for ShapeChoiceMapping {
           .node_weight(i)
           .expect("node should exist");
         match node {
-          Node::Shape(shape_node) => Some(shape_node),
+          // removed shapes can no longer be looked up by id
+          Node::Shape(shape_node)
+            if shape_projection
+              .get_shape_node_index(&shape_node.shape_id)
+              .is_some() =>
+          {
+            Some(shape_node)
+          }
           _ => None,
         }
       })
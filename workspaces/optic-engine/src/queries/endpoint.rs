@@ -1,19 +1,24 @@
 use crate::commands::{EndpointCommand, SpecCommand};
 use crate::events::HttpInteraction;
-use crate::projections::endpoint::{Edge, EndpointProjection, Node, ROOT_PATH_ID};
+use crate::projections::conflicts::SpecConflict;
 use crate::projections::endpoint::{
-  QueryParametersDescriptor, RequestDescriptor, ResponseBodyDescriptor,
+  BodyDescriptor, QueryParametersDescriptor, RequestDescriptor, RequestParameterDescriptor,
+  ResponseBodyDescriptor,
 };
+use crate::projections::endpoint::{Edge, EndpointProjection, Node, ROOT_PATH_ID};
+use crate::queries::shape::{MergedShapes, ShapeUsage};
 use crate::state::endpoint::{
-  HttpMethod, HttpStatusCode, PathComponentId, PathComponentIdRef, QueryParametersId, RequestId,
-  ResponseId,
+  HttpContentType, HttpMethod, HttpStatusCode, PathComponentId, PathComponentIdRef,
+  QueryParametersId, RequestId, RequestParameterId, ResponseId,
 };
-use petgraph::graph::Graph;
+use crate::state::shape::ShapeId;
+use crate::state::SpecIdGenerator;
+use petgraph::graph::{Graph, NodeIndex};
 use petgraph::visit::{
   depth_first_search, Control, DfsEvent, EdgeFilteredNeighborsDirected, Reversed,
 };
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 pub struct EndpointQueries<'a> {
   pub endpoint_projection: &'a EndpointProjection,
@@ -234,6 +239,27 @@ impl<'a> EndpointQueries<'a> {
       .get_endpoint_query_parameter_node(&path_id.to_owned(), method)
   }
 
+  pub fn resolve_endpoint_header_params(
+    &self,
+    path_id: PathComponentIdRef,
+    method: &String,
+  ) -> Option<impl Iterator<Item = (&RequestParameterId, &RequestParameterDescriptor)>> {
+    self
+      .endpoint_projection
+      .get_endpoint_header_parameter_nodes(&path_id.to_owned(), method)
+  }
+
+  pub fn resolve_endpoint_response_header_params(
+    &self,
+    path_id: PathComponentIdRef,
+    method: &String,
+    status_code: HttpStatusCode,
+  ) -> Option<impl Iterator<Item = (&RequestParameterId, &RequestParameterDescriptor)>> {
+    self
+      .endpoint_projection
+      .get_endpoint_response_header_parameter_nodes(&path_id.to_owned(), method, status_code)
+  }
+
   pub fn resolve_requests(
     &self,
     path_id: PathComponentIdRef,
@@ -400,12 +426,36 @@ impl<'a> EndpointQueries<'a> {
     let response_ids = self
       .resolve_responses(path_id, method)?
       .map(|(response_id, _)| response_id);
+    let header_param_ids = self
+      .resolve_endpoint_header_params(path_id, method)
+      .into_iter()
+      .flatten()
+      .map(|(parameter_id, _)| parameter_id);
+    let status_codes = self
+      .endpoint_projection
+      .get_response_nodes(path_id)
+      .into_iter()
+      .flatten()
+      .filter(|(response_method, _, _)| *response_method == method)
+      .map(|(_, status_code, _)| *status_code)
+      .collect::<BTreeSet<_>>();
+    let response_header_param_ids = status_codes.into_iter().flat_map(|status_code| {
+      self
+        .resolve_endpoint_response_header_params(path_id, method, status_code)
+        .into_iter()
+        .flatten()
+        .map(|(parameter_id, _)| parameter_id)
+    });
 
     let query_parameter_command = query_param_id
       .cloned()
       .map(EndpointCommand::remove_query_parameters);
     let request_commands = request_ids.cloned().map(EndpointCommand::remove_request);
     let response_commands = response_ids.cloned().map(EndpointCommand::remove_response);
+    let header_parameter_commands = header_param_ids
+      .chain(response_header_param_ids)
+      .cloned()
+      .map(EndpointCommand::remove_header_parameter);
 
     Some(DeleteEndpointCommands {
       path_id: path_id.clone(),
@@ -413,6 +463,7 @@ impl<'a> EndpointQueries<'a> {
       commands: request_commands
         .chain(response_commands)
         .chain(query_parameter_command)
+        .chain(header_parameter_commands)
         .map(SpecCommand::from)
         .collect(),
     })
@@ -434,6 +485,601 @@ impl<'a> EndpointQueries<'a> {
     Some(std::iter::once(command))
   }
 
+  // Merges every duplicated path into the one that was added first, re-creating the requests,
+  // responses, parameters and child paths the surviving path doesn't have yet, before removing the
+  // duplicates. Remaining duplicate requests and responses (same method, status code and content
+  // type) are removed, keeping the first one.
+  pub fn repair_conflicts_commands(
+    &self,
+    conflicts: &[SpecConflict],
+    id_generator: &mut impl SpecIdGenerator,
+  ) -> RepairConflictsCommands {
+    let mut repair = ConflictRepair::default();
+    let mut merged_paths = vec![];
+
+    for conflict in conflicts {
+      if let SpecConflict::DuplicatePath { path_ids, .. } = conflict {
+        let mut remaining_path_ids = path_ids
+          .iter()
+          .filter(|path_id| !repair.removed_ids.contains(*path_id));
+        let surviving_path_id = match remaining_path_ids.next() {
+          Some(path_id) => path_id.clone(),
+          None => continue,
+        };
+        let merged_path_ids = remaining_path_ids.cloned().collect::<Vec<_>>();
+        if merged_path_ids.is_empty() {
+          continue;
+        }
+
+        for merged_path_id in &merged_path_ids {
+          self.merge_path(
+            merged_path_id,
+            &surviving_path_id,
+            true,
+            id_generator,
+            &mut repair,
+          );
+        }
+
+        merged_paths.push(MergedPaths {
+          surviving_path_id,
+          merged_path_ids,
+        });
+      }
+    }
+
+    for conflict in conflicts {
+      match conflict {
+        SpecConflict::DuplicateRequest { request_ids, .. } => {
+          let duplicate_request_ids = request_ids
+            .iter()
+            .filter(|request_id| !repair.removed_ids.contains(*request_id))
+            .skip(1)
+            .cloned()
+            .collect::<Vec<_>>();
+          for request_id in duplicate_request_ids {
+            repair.remove(
+              request_id.clone(),
+              EndpointCommand::remove_request(request_id),
+            );
+          }
+        }
+        SpecConflict::DuplicateResponse { response_ids, .. } => {
+          let duplicate_response_ids = response_ids
+            .iter()
+            .filter(|response_id| !repair.removed_ids.contains(*response_id))
+            .skip(1)
+            .cloned()
+            .collect::<Vec<_>>();
+          for response_id in duplicate_response_ids {
+            repair.remove(
+              response_id.clone(),
+              EndpointCommand::remove_response(response_id),
+            );
+          }
+        }
+        _ => {}
+      }
+    }
+
+    RepairConflictsCommands {
+      merged_paths,
+      merged_shapes: vec![],
+      removed_path_ids: vec![],
+      removed_shape_ids: vec![],
+      commands: repair.commands.into_iter().map(SpecCommand::from).collect(),
+    }
+  }
+
+  // `into_exists` is false for paths that are only added by the repair itself, so the projection
+  // can't know about anything below them yet
+  fn merge_path(
+    &self,
+    from_path_id: &PathComponentId,
+    into_path_id: &PathComponentId,
+    into_exists: bool,
+    id_generator: &mut impl SpecIdGenerator,
+    repair: &mut ConflictRepair,
+  ) {
+    for method in self.resolve_path_methods(from_path_id) {
+      let endpoint = (into_path_id.clone(), method.clone());
+
+      if into_exists && !repair.requests.contains_key(&endpoint) {
+        let existing_requests = self
+          .resolve_requests(into_path_id, &method)
+          .into_iter()
+          .flatten()
+          .map(|(request_id, descriptor)| (request_id.clone(), body_content_type(&descriptor.body)))
+          .collect();
+        repair.requests.insert(endpoint.clone(), existing_requests);
+      }
+      for (request_id, descriptor) in self
+        .resolve_requests(from_path_id, &method)
+        .into_iter()
+        .flatten()
+      {
+        let content_type = body_content_type(&descriptor.body);
+        let target_requests = repair.requests.entry(endpoint.clone()).or_default();
+        if !target_requests
+          .iter()
+          .any(|(_, existing)| *existing == content_type)
+        {
+          let new_request_id = id_generator.request();
+          repair.commands.push(EndpointCommand::add_request(
+            new_request_id.clone(),
+            into_path_id.clone(),
+            method.clone(),
+          ));
+          if let Some(body) = &descriptor.body {
+            repair
+              .commands
+              .push(EndpointCommand::set_request_body_shape(
+                new_request_id.clone(),
+                body.root_shape_id.clone(),
+                body.http_content_type.clone(),
+                false,
+              ));
+          }
+          target_requests.push((new_request_id, content_type));
+        }
+        repair.remove(
+          request_id.clone(),
+          EndpointCommand::remove_request(request_id.clone()),
+        );
+      }
+
+      if into_exists
+        && !repair.query_parameters.contains(&endpoint)
+        && self
+          .resolve_endpoint_query_params(into_path_id, &method)
+          .is_some()
+      {
+        repair.query_parameters.insert(endpoint.clone());
+      }
+      if let Some((query_parameters_id, descriptor)) =
+        self.resolve_endpoint_query_params(from_path_id, &method)
+      {
+        if repair.query_parameters.insert(endpoint.clone()) {
+          let new_query_parameters_id = id_generator.query_params();
+          repair.commands.push(EndpointCommand::add_query_parameters(
+            new_query_parameters_id.clone(),
+            into_path_id.clone(),
+            method.clone(),
+          ));
+          if let Some(shape) = &descriptor.shape {
+            repair
+              .commands
+              .push(EndpointCommand::set_query_parameters_shape(
+                new_query_parameters_id,
+                shape.shape_id.clone(),
+                shape.is_removed,
+              ));
+          }
+        }
+        repair.remove(
+          query_parameters_id.clone(),
+          EndpointCommand::remove_query_parameters(query_parameters_id.clone()),
+        );
+      }
+
+      // header parameters belong to the method, but are added through one of its requests
+      let target_request_id = repair
+        .requests
+        .get(&endpoint)
+        .and_then(|requests| requests.first())
+        .map(|(request_id, _)| request_id.clone());
+      if into_exists {
+        let existing_names = self
+          .resolve_endpoint_header_params(into_path_id, &method)
+          .into_iter()
+          .flatten()
+          .map(|(_, descriptor)| descriptor.name.to_lowercase());
+        repair
+          .header_names
+          .entry(endpoint.clone())
+          .or_default()
+          .extend(existing_names);
+      }
+      for (parameter_id, descriptor) in self
+        .resolve_endpoint_header_params(from_path_id, &method)
+        .into_iter()
+        .flatten()
+      {
+        let header_names = repair.header_names.entry(endpoint.clone()).or_default();
+        if let Some(target_request_id) = &target_request_id {
+          if header_names.insert(descriptor.name.to_lowercase()) {
+            let new_parameter_id = id_generator.request_parameter();
+            repair.commands.push(EndpointCommand::add_header_parameter(
+              new_parameter_id.clone(),
+              target_request_id.clone(),
+              descriptor.name.clone(),
+            ));
+            if let Some(shape) = &descriptor.shape {
+              repair
+                .commands
+                .push(EndpointCommand::set_header_parameter_shape(
+                  new_parameter_id,
+                  shape.shape_id.clone(),
+                ));
+            }
+          }
+        }
+        repair.remove(
+          parameter_id.clone(),
+          EndpointCommand::remove_header_parameter(parameter_id.clone()),
+        );
+      }
+
+      let responses = self
+        .endpoint_projection
+        .get_response_nodes(from_path_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|(response_method, status_code, node)| match node {
+          Node::Response(response_id, descriptor) if *response_method == method => {
+            Some((*status_code, response_id, descriptor))
+          }
+          _ => None,
+        });
+      for (status_code, response_id, descriptor) in responses {
+        let status_code_endpoint = (into_path_id.clone(), method.clone(), status_code);
+        if into_exists && !repair.responses.contains_key(&status_code_endpoint) {
+          let existing_responses = self
+            .resolve_responses_by_method_and_status_code(&method, status_code, into_path_id)
+            .map(|(response_id, descriptor)| {
+              (response_id.clone(), body_content_type(&descriptor.body))
+            })
+            .collect();
+          repair
+            .responses
+            .insert(status_code_endpoint.clone(), existing_responses);
+
+          let existing_names = self
+            .resolve_endpoint_response_header_params(into_path_id, &method, status_code)
+            .into_iter()
+            .flatten()
+            .map(|(_, descriptor)| descriptor.name.to_lowercase())
+            .collect();
+          repair
+            .response_header_names
+            .insert(status_code_endpoint.clone(), existing_names);
+        }
+
+        let content_type = body_content_type(&descriptor.body);
+        let target_responses = repair
+          .responses
+          .entry(status_code_endpoint.clone())
+          .or_default();
+        if !target_responses
+          .iter()
+          .any(|(_, existing)| *existing == content_type)
+        {
+          let new_response_id = id_generator.response();
+          repair
+            .commands
+            .push(EndpointCommand::add_response_by_path_and_method(
+              new_response_id.clone(),
+              into_path_id.clone(),
+              method.clone(),
+              status_code,
+            ));
+          if let Some(body) = &descriptor.body {
+            repair
+              .commands
+              .push(EndpointCommand::set_response_body_shape(
+                new_response_id.clone(),
+                body.root_shape_id.clone(),
+                body.http_content_type.clone(),
+                false,
+              ));
+          }
+          target_responses.push((new_response_id, content_type));
+        }
+        let target_response_id = target_responses[0].0.clone();
+
+        for (parameter_id, header_descriptor) in self
+          .resolve_endpoint_response_header_params(from_path_id, &method, status_code)
+          .into_iter()
+          .flatten()
+        {
+          let header_names = repair
+            .response_header_names
+            .entry(status_code_endpoint.clone())
+            .or_default();
+          if header_names.insert(header_descriptor.name.to_lowercase()) {
+            let new_parameter_id = id_generator.request_parameter();
+            repair
+              .commands
+              .push(EndpointCommand::add_response_header_parameter(
+                new_parameter_id.clone(),
+                target_response_id.clone(),
+                header_descriptor.name.clone(),
+              ));
+            if let Some(shape) = &header_descriptor.shape {
+              repair
+                .commands
+                .push(EndpointCommand::set_header_parameter_shape(
+                  new_parameter_id,
+                  shape.shape_id.clone(),
+                ));
+            }
+          }
+          repair.remove(
+            parameter_id.clone(),
+            EndpointCommand::remove_header_parameter(parameter_id.clone()),
+          );
+        }
+
+        repair.remove(
+          response_id.clone(),
+          EndpointCommand::remove_response(response_id.clone()),
+        );
+      }
+    }
+
+    let child_paths = self
+      .endpoint_projection
+      .get_child_path_component_nodes(from_path_id)
+      .into_iter()
+      .flatten()
+      .filter_map(|node| match node {
+        Node::PathComponent(path_id, descriptor) => Some((path_id.clone(), descriptor.clone())),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    for (child_path_id, descriptor) in child_paths {
+      // parameters match any segment, so their names don't have to match to merge
+      let child_key = (
+        into_path_id.clone(),
+        descriptor.is_parameter,
+        if descriptor.is_parameter {
+          String::new()
+        } else {
+          descriptor.name.clone()
+        },
+      );
+
+      let existing_child_path_id = if into_exists {
+        self
+          .endpoint_projection
+          .get_child_path_component_nodes(into_path_id)
+          .into_iter()
+          .flatten()
+          .find_map(|node| match node {
+            Node::PathComponent(path_id, into_descriptor)
+              if into_descriptor.is_parameter == descriptor.is_parameter
+                && (descriptor.is_parameter || into_descriptor.name == descriptor.name)
+                && !repair.removed_ids.contains(path_id) =>
+            {
+              Some(path_id.clone())
+            }
+            _ => None,
+          })
+      } else {
+        None
+      };
+
+      let (into_child_path_id, into_child_exists) = match existing_child_path_id {
+        Some(path_id) => (path_id, true),
+        None => match repair.added_paths.get(&child_key) {
+          Some(path_id) => (path_id.clone(), false),
+          None => {
+            let new_path_id = id_generator.path();
+            repair.commands.push(if descriptor.is_parameter {
+              EndpointCommand::add_path_parameter(
+                new_path_id.clone(),
+                into_path_id.clone(),
+                descriptor.name.clone(),
+              )
+            } else {
+              EndpointCommand::add_path_component(
+                new_path_id.clone(),
+                into_path_id.clone(),
+                descriptor.name.clone(),
+              )
+            });
+            repair.added_paths.insert(child_key, new_path_id.clone());
+            (new_path_id, false)
+          }
+        },
+      };
+
+      // a parameter merged into one without a shape brings its own
+      if descriptor.is_parameter {
+        let into_child_shaped = into_child_exists
+          && self
+            .endpoint_projection
+            .get_path_parameter_shape_id(&into_child_path_id)
+            .is_some();
+        let child_shape_id = self
+          .endpoint_projection
+          .get_path_parameter_shape_id(&child_path_id);
+        if let Some(shape_id) = child_shape_id {
+          if !into_child_shaped
+            && repair
+              .shaped_path_parameters
+              .insert(into_child_path_id.clone())
+          {
+            repair
+              .commands
+              .push(EndpointCommand::set_path_parameter_shape(
+                into_child_path_id.clone(),
+                shape_id.clone(),
+              ));
+          }
+        }
+      }
+
+      self.merge_path(
+        &child_path_id,
+        &into_child_path_id,
+        into_child_exists,
+        id_generator,
+        repair,
+      );
+    }
+
+    let is_parameter = self
+      .endpoint_projection
+      .get_path_component_descriptor(from_path_id)
+      .map(|descriptor| descriptor.is_parameter)
+      .unwrap_or(false);
+    let remove_path_command = if is_parameter {
+      EndpointCommand::remove_path_parameter(from_path_id.clone())
+    } else {
+      EndpointCommand::remove_path_component(from_path_id.clone())
+    };
+    repair.remove(from_path_id.clone(), remove_path_command);
+  }
+
+  pub fn resolve_path_methods(&self, path_id: PathComponentIdRef) -> Vec<HttpMethod> {
+    let path_node_index = match self.graph_get_index(path_id) {
+      Some(index) => index,
+      None => return vec![],
+    };
+
+    self
+      .graph_get_children(path_node_index)
+      .filter_map(
+        |child_index| match self.endpoint_projection.graph.node_weight(child_index) {
+          Some(Node::HttpMethod(method)) => Some(method.clone()),
+          _ => None,
+        },
+      )
+      .collect()
+  }
+
+  // The shapes describing the bodies and parameters of endpoints, from which all other shapes
+  // in use can be reached
+  pub fn resolve_root_shape_ids(&self) -> HashSet<ShapeId> {
+    let graph = &self.endpoint_projection.graph;
+    let endpoint_shape_ids = graph.node_indices().filter_map(|node_index| {
+      self.resolve_node_endpoint(node_index)?; // removed, or not part of an endpoint
+
+      match graph.node_weight(node_index)? {
+        Node::Request(_, RequestDescriptor { body: Some(body) })
+        | Node::Response(_, ResponseBodyDescriptor { body: Some(body) }) => {
+          Some(body.root_shape_id.clone())
+        }
+        Node::QueryParameters(_, QueryParametersDescriptor { shape: Some(shape) })
+          if !shape.is_removed =>
+        {
+          Some(shape.shape_id.clone())
+        }
+        Node::RequestParameter(
+          _,
+          RequestParameterDescriptor {
+            shape: Some(shape), ..
+          },
+        ) if !shape.is_removed => Some(shape.shape_id.clone()),
+        _ => None,
+      }
+    });
+    let path_parameter_shape_ids = self
+      .endpoint_projection
+      .path_parameter_shapes_by_path_id
+      .iter()
+      .filter(|(path_id, _)| self.graph_get_index(path_id).is_some())
+      .map(|(_, shape_id)| shape_id.clone());
+
+    endpoint_shape_ids.chain(path_parameter_shape_ids).collect()
+  }
+
+  // The bodies, query parameters and request parameters of endpoints described by any of the shapes
+  pub fn resolve_shape_usages(&self, shape_ids: &HashSet<ShapeId>) -> Vec<ShapeUsage> {
+    let graph = &self.endpoint_projection.graph;
+    let mut usages = vec![];
+
+    for node_index in graph.node_indices() {
+      let (path_id, method) = match self.resolve_node_endpoint(node_index) {
+        Some((path_id, method)) => (path_id.clone(), method.clone()),
+        None => continue, // removed, or not part of an endpoint
+      };
+
+      match graph.node_weight(node_index) {
+        Some(Node::Request(request_id, RequestDescriptor { body: Some(body) }))
+          if shape_ids.contains(&body.root_shape_id) =>
+        {
+          usages.push(ShapeUsage::RequestBody {
+            request_id: request_id.clone(),
+            path_id,
+            method,
+            content_type: body.http_content_type.clone(),
+          })
+        }
+        Some(Node::Response(response_id, ResponseBodyDescriptor { body: Some(body) }))
+          if shape_ids.contains(&body.root_shape_id) =>
+        {
+          let status_code = graph
+            .neighbors_directed(node_index, petgraph::Direction::Outgoing)
+            .find_map(|parent_index| match graph.node_weight(parent_index) {
+              Some(Node::HttpStatusCode(status_code)) => Some(*status_code),
+              _ => None,
+            })
+            .expect("responses should be children of a status code");
+          usages.push(ShapeUsage::ResponseBody {
+            response_id: response_id.clone(),
+            path_id,
+            method,
+            status_code,
+            content_type: body.http_content_type.clone(),
+          })
+        }
+        Some(Node::QueryParameters(query_parameters_id, descriptor)) => {
+          if let Some(shape) = &descriptor.shape {
+            if !shape.is_removed && shape_ids.contains(&shape.shape_id) {
+              usages.push(ShapeUsage::QueryParameters {
+                query_parameters_id: query_parameters_id.clone(),
+                path_id,
+                method,
+              })
+            }
+          }
+        }
+        Some(Node::RequestParameter(parameter_id, descriptor)) => {
+          if let Some(shape) = &descriptor.shape {
+            if !shape.is_removed && shape_ids.contains(&shape.shape_id) {
+              usages.push(ShapeUsage::RequestParameter {
+                parameter_id: parameter_id.clone(),
+                location: descriptor.location.clone(),
+                name: descriptor.name.clone(),
+                path_id,
+                method,
+              })
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+
+    usages
+  }
+
+  // The path and method of the endpoint a node belongs to, following its parents up to the method
+  fn resolve_node_endpoint(
+    &self,
+    node_index: NodeIndex,
+  ) -> Option<(&PathComponentId, &HttpMethod)> {
+    let graph = &self.endpoint_projection.graph;
+    let mut current_index = node_index;
+    loop {
+      let parent_index = graph
+        .neighbors_directed(current_index, petgraph::Direction::Outgoing)
+        .next()?;
+      if let Some(Node::HttpMethod(method)) = graph.node_weight(parent_index) {
+        let path_index = graph
+          .neighbors_directed(parent_index, petgraph::Direction::Outgoing)
+          .next()?;
+        return match graph.node_weight(path_index) {
+          Some(Node::PathComponent(path_id, _)) => Some((path_id, method)),
+          _ => None,
+        };
+      }
+      current_index = parent_index;
+    }
+  }
+
   fn graph_get_index(&self, node_id: &str) -> Option<&petgraph::graph::NodeIndex> {
     self.endpoint_projection.node_id_to_index.get(node_id)
   }
@@ -469,11 +1115,59 @@ pub struct DeleteEndpointCommands {
   commands: Vec<SpecCommand>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairConflictsCommands {
+  pub merged_paths: Vec<MergedPaths>,
+  // only repaired by `repair_spec_conflicts`, which can look beyond endpoints
+  pub merged_shapes: Vec<MergedShapes>,
+  pub removed_path_ids: Vec<PathComponentId>,
+  pub removed_shape_ids: Vec<ShapeId>,
+  pub commands: Vec<SpecCommand>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedPaths {
+  pub surviving_path_id: PathComponentId,
+  pub merged_path_ids: Vec<PathComponentId>,
+}
+
+type RepairEndpoint = (PathComponentId, HttpMethod);
+type RepairStatusCode = (PathComponentId, HttpMethod, HttpStatusCode);
+
+// What the surviving paths will look like once the repair commands have been applied
+#[derive(Default)]
+struct ConflictRepair {
+  commands: Vec<EndpointCommand>,
+  removed_ids: HashSet<String>,
+  added_paths: HashMap<(PathComponentId, bool, String), PathComponentId>,
+  requests: HashMap<RepairEndpoint, Vec<(RequestId, Option<HttpContentType>)>>,
+  responses: HashMap<RepairStatusCode, Vec<(ResponseId, Option<HttpContentType>)>>,
+  query_parameters: HashSet<RepairEndpoint>,
+  header_names: HashMap<RepairEndpoint, HashSet<String>>,
+  response_header_names: HashMap<RepairStatusCode, HashSet<String>>,
+  shaped_path_parameters: HashSet<PathComponentId>,
+}
+
+impl ConflictRepair {
+  fn remove(&mut self, id: String, command: EndpointCommand) {
+    if self.removed_ids.insert(id) {
+      self.commands.push(command);
+    }
+  }
+}
+
+fn body_content_type(body: &Option<BodyDescriptor>) -> Option<HttpContentType> {
+  body.as_ref().map(|body| body.http_content_type.clone())
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
   use crate::events::SpecEvent;
   use crate::projections::SpecProjection;
+  use crate::test_helpers::{assert_valid_commands, TestIdGenerator};
   use crate::Aggregate;
   use insta::assert_debug_snapshot;
   use petgraph::dot::Dot;
@@ -630,6 +1324,10 @@ mod test {
 
       {"QueryParametersAdded": {"queryParametersId": "query_1", "httpMethod": "GET", "pathId": "path_2"}},
       {"QueryParametersShapeSet": {"queryParametersId": "query_1", "shapeDescriptor":{"shapeId":"shape_Ba53AWXhVW","isRemoved":false}}},
+
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_1", "parameterLocation": "header", "name": "X-Trace-Id"}},
+      {"ResponseParameterAdded": {"parameterId": "header_2", "responseId": "response_1", "parameterLocation": "header", "name": "X-RateLimit-Remaining"}},
+      {"ResponseParameterAdded": {"parameterId": "header_3", "responseId": "response_2", "parameterLocation": "header", "name": "Location"}},
     ]))
     .expect("should be able to deserialize test events");
 
@@ -657,12 +1355,27 @@ mod test {
       .resolve_responses(&subject_path, &subject_method)
       .unwrap()
       .collect::<Vec<_>>();
+    // removed responses detach their status codes, so look up header parameters by id instead
+    let remaining_header_parameter_ids = ["header_1", "header_2", "header_3"]
+      .iter()
+      .map(|parameter_id| String::from(*parameter_id))
+      .filter(|parameter_id| {
+        updated_spec
+          .endpoint()
+          .get_request_parameter_descriptor(parameter_id)
+          .is_some()
+      })
+      .collect::<Vec<_>>();
 
     // dbg!(Dot::with_config(&updated_spec.endpoint().graph, &[]));
 
     assert!(remaining_query_parameters.is_none());
     assert_eq!(remaining_requests.len(), 0);
     assert_eq!(remaining_responses.len(), 0);
+    assert_eq!(
+      remaining_header_parameter_ids,
+      vec![String::from("header_3")]
+    );
   }
 
   #[test]
@@ -708,21 +1421,86 @@ mod test {
     assert_eq!(remaining_paths.len(), 0);
   }
 
-  fn assert_valid_commands(
-    mut spec_projection: SpecProjection,
-    commands: impl IntoIterator<Item = SpecCommand>,
-  ) -> SpecProjection {
-    // let mut spec_projection = SpecProjection::default();
-    for command in commands {
-      let events = spec_projection
-        .execute(command)
-        .expect("generated commands must be valid");
+  #[test]
+  pub fn can_generate_repair_conflicts_commands() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": { "pathId": "path_1", "parentPathId": "root", "name": "posts" }},
+      {"PathComponentAdded": { "pathId": "path_2", "parentPathId": "root", "name": "posts" }},
+      {"PathParameterAdded": { "pathId": "path_3", "parentPathId": "path_2", "name": "postId" }},
+      {"ShapeAdded": { "shapeId": "shape_1", "baseShapeId": "$string", "name": "" }},
+      {"PathParameterShapeSet": { "pathId": "path_3", "shapeDescriptor": { "shapeId": "shape_1", "isRemoved": false }}},
+
+      {"RequestAdded": { "requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200 }},
+
+      {"RequestAdded": { "requestId": "request_2", "pathId": "path_2", "httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_2", "httpMethod": "GET", "httpStatusCode": 200 }},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_3", "pathId": "path_2", "httpMethod": "GET", "httpStatusCode": 404 }},
+      {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_2", "parameterLocation": "header", "name": "X-Api-Key"}},
+      {"RequestParameterShapeSet": {"parameterId": "header_1", "parameterDescriptor": {"shapeId": "shape_1", "isRemoved": false}}},
+
+      {"RequestAdded": { "requestId": "request_3", "pathId": "path_2", "httpMethod": "POST"}},
+      {"RequestBodySet": { "requestId": "request_3", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_4", "pathId": "path_2", "httpMethod": "POST", "httpStatusCode": 201 }},
+      {"QueryParametersAdded": {"queryParametersId": "query_1", "httpMethod": "POST", "pathId": "path_2"}},
+      {"QueryParametersShapeSet": {"queryParametersId": "query_1", "shapeDescriptor":{"shapeId":"shape_1","isRemoved":false}}},
+
+      {"RequestAdded": { "requestId": "request_4", "pathId": "path_3", "httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_5", "pathId": "path_3", "httpMethod": "GET", "httpStatusCode": 200 }},
+    ]))
+    .expect("should be able to deserialize test events");
 
-      for event in events {
-        spec_projection.apply(event)
-      }
-    }
+    let spec_projection = SpecProjection::from(events);
+    let conflicts = spec_projection.conflicts().spec_conflicts();
+    assert!(!conflicts.is_empty());
+
+    let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+    let mut id_generator = TestIdGenerator::default();
+    let repair_commands = endpoint_queries.repair_conflicts_commands(&conflicts, &mut id_generator);
+
+    assert_eq!(repair_commands.merged_paths.len(), 1);
+    assert_eq!(repair_commands.merged_paths[0].surviving_path_id, "path_1");
+    assert_eq!(
+      repair_commands.merged_paths[0].merged_path_ids,
+      vec![String::from("path_2")]
+    );
 
-    spec_projection
+    let updated_spec = assert_valid_commands(spec_projection, repair_commands.commands);
+    assert!(updated_spec.conflicts().spec_conflicts().is_empty());
+
+    let updated_queries = EndpointQueries::new(updated_spec.endpoint());
+    assert_eq!(updated_queries.resolve_path("/posts"), Some("path_1"));
+
+    let get_responses = updated_queries
+      .resolve_responses(&String::from("path_1"), &String::from("GET"))
+      .unwrap()
+      .count();
+    assert_eq!(get_responses, 2);
+    let get_headers = updated_queries
+      .resolve_endpoint_header_params(&String::from("path_1"), &String::from("GET"))
+      .unwrap()
+      .count();
+    assert_eq!(get_headers, 1);
+    assert!(updated_queries
+      .resolve_endpoint_query_params(&String::from("path_1"), &String::from("POST"))
+      .is_some());
+
+    let post_id_path = updated_queries
+      .resolve_path("/posts/1")
+      .expect("child paths of merged paths should be moved to the surviving path");
+    assert_ne!(post_id_path, "path_3");
+    assert_eq!(
+      updated_spec
+        .endpoint()
+        .get_path_parameter_shape_id(&String::from(post_id_path))
+        .map(String::as_str),
+      Some("shape_1"),
+      "moved path parameters should keep their shapes"
+    );
+    let child_requests = updated_queries
+      .resolve_requests(&String::from(post_id_path), &String::from("GET"))
+      .unwrap()
+      .count();
+    assert_eq!(child_requests, 1);
   }
 }
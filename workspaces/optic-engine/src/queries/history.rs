@@ -1,4 +1,5 @@
 use crate::projections::history::{CommitId, HistoryProjection, ROOT_COMMIT_ID};
+use std::collections::HashSet;
 
 pub struct HistoryQueries<'a> {
   history_projection: &'a HistoryProjection,
@@ -25,4 +26,22 @@ impl<'a> HistoryQueries<'a> {
 
     commit_id.unwrap_or_else(|| CommitId::from(ROOT_COMMIT_ID))
   }
+
+  // the batch commit itself, along with every commit from the root leading up to it
+  pub fn resolve_commit_ancestry(&self, batch_id: &CommitId) -> Option<HashSet<CommitId>> {
+    self
+      .history_projection
+      .get_batch_commit_node_index(batch_id)?;
+
+    let mut ancestry = HashSet::new();
+    let mut commit_id = Some(batch_id);
+    while let Some(id) = commit_id {
+      if !ancestry.insert(id.clone()) {
+        break;
+      }
+      commit_id = self.history_projection.get_parent_commit_id(id);
+    }
+
+    Some(ancestry)
+  }
 }
@@ -1,11 +1,22 @@
-use crate::commands::{shape as shape_commands, ShapeCommand};
+use crate::commands::{shape as shape_commands, EndpointCommand, ShapeCommand, SpecCommand};
 use crate::projections::shape::{CoreShapeNode, Edge, Node};
 use crate::projections::shape::{FieldNode, FieldNodeDescriptor, ShapeNode, ShapeProjection};
+use crate::queries::endpoint::EndpointQueries;
 use crate::shapes::traverser::{ShapeTrail, ShapeTrailPathComponent};
-use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeParameterId};
+use crate::state::endpoint::{
+  HttpContentType, HttpMethod, HttpStatusCode, PathComponentId, QueryParametersId, RequestId,
+  RequestParameterId, ResponseId, HEADER_PARAMETER_LOCATION,
+};
+use crate::state::shape::{
+  FieldId, ParameterShapeDescriptor, ProviderDescriptor, ProviderInField,
+  ShapeConstraintDescriptor, ShapeId, ShapeKind, ShapeParameterId, ShapeProvider,
+};
 use crate::state::SpecIdGenerator;
+use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
-use std::collections::BTreeSet;
+use serde::Serialize;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::iter::FromIterator;
 
 pub struct ShapeQueries<'a> {
@@ -49,7 +60,23 @@ impl<'a> ShapeQueries<'a> {
     }
 
     let current_node_index = parent_node_index.unwrap();
-    let core_shape_nodes = projection.get_core_shape_nodes(&current_node_index);
+    let shape_id = match projection.graph.node_weight(*current_node_index) {
+      Some(Node::Shape(shape_node)) => &shape_node.shape_id,
+      _ => return vec![],
+    };
+    // shapes descending from other shapes take the core shapes of their oldest ancestor
+    let core_shape_node_index = self
+      .resolve_shape_lineage(shape_id)
+      .into_iter()
+      .rev()
+      .find(|node_index| {
+        matches!(
+          projection.get_node_by_index(node_index),
+          Some(Node::Shape(_))
+        )
+      })
+      .unwrap_or(*current_node_index);
+    let core_shape_nodes = projection.get_core_shape_nodes(&core_shape_node_index);
     if let None = core_shape_nodes {
       return vec![];
     }
@@ -70,8 +97,8 @@ impl<'a> ShapeQueries<'a> {
               .get_parameter_descriptor()
               .unwrap()
               .shape_parameter_id;
-            let item_shape_id =
-              self.resolve_parameter_to_shape(&shape_id, &String::from(nullable_parameter_id));
+            let item_shape_id = self
+              .resolve_trail_parameter_to_shape(shape_trail, &String::from(nullable_parameter_id));
             let trail = shape_trail
               .with_component(ShapeTrailPathComponent::NullableTrail {
                 shape_id: shape_id.clone(),
@@ -88,6 +115,7 @@ impl<'a> ShapeQueries<'a> {
               }],
               shape_id: shape_id.clone(),
               core_shape_kind: core_shape_node.descriptor.kind.clone(),
+              constraint: None,
             }];
             output.append(&mut self.list_trail_choices(&trail));
             output
@@ -99,8 +127,8 @@ impl<'a> ShapeQueries<'a> {
               .get_parameter_descriptor()
               .unwrap()
               .shape_parameter_id;
-            let item_shape_id =
-              self.resolve_parameter_to_shape(&shape_id, &String::from(optional_parameter_id));
+            let item_shape_id = self
+              .resolve_trail_parameter_to_shape(shape_trail, &String::from(optional_parameter_id));
             let trail = shape_trail
               .with_component(ShapeTrailPathComponent::OptionalTrail {
                 shape_id: shape_id.clone(),
@@ -116,6 +144,7 @@ impl<'a> ShapeQueries<'a> {
               }],
               shape_id: shape_id.clone(),
               core_shape_kind: core_shape_node.descriptor.kind.clone(),
+              constraint: None,
             }];
             output.append(&mut self.list_trail_choices(&trail));
             output
@@ -143,6 +172,7 @@ impl<'a> ShapeQueries<'a> {
             additional_components: vec![],
             shape_id: shape_id.clone(),
             core_shape_kind: core_shape_node.descriptor.kind.clone(),
+            constraint: self.resolve_shape_constraint(&shape_id).cloned(),
           }],
         };
         trails
@@ -154,26 +184,27 @@ impl<'a> ShapeQueries<'a> {
 
   pub fn resolve_to_core_shape(&self, shape_id: &ShapeId) -> &ShapeKind {
     //@TODO: use petgraph::visit::EdgeFiltered, etc.
-    //@GOTCHA: this does not support multiple levels of ancestors
-    let shape_node_index = self
+    let mut shape_node_index = *self
       .shape_projection
       .get_shape_node_index(shape_id)
       .expect("shape node to exist for shape id");
-    let core_shape_node_index = self
-      .shape_projection
-      .get_ancestor_shape_node_index(shape_node_index)
-      .unwrap();
-    // eprintln!(
-    //   "{:?} -( IsDescendantOf )-> {:?}",
-    //   shape_node_index, core_shape_node_index
-    // );
-    match self
-      .shape_projection
-      .graph
-      .node_weight(core_shape_node_index)
-    {
-      Some(Node::CoreShape(core_shape_node)) => &core_shape_node.descriptor.kind,
-      _ => unreachable!("all shapes should resolve to a core shape"),
+
+    // shapes can descend from other shapes, so follow the ancestors until we reach a core shape
+    loop {
+      match self.shape_projection.graph.node_weight(shape_node_index) {
+        Some(Node::CoreShape(core_shape_node)) => return &core_shape_node.descriptor.kind,
+        Some(Node::Shape(_)) => {
+          let ancestor_node_index = self
+            .shape_projection
+            .get_ancestor_shape_node_index(&shape_node_index)
+            .unwrap();
+          if ancestor_node_index == shape_node_index {
+            unreachable!("all shapes should resolve to a core shape");
+          }
+          shape_node_index = ancestor_node_index;
+        }
+        _ => unreachable!("all shapes should resolve to a core shape"),
+      }
     }
   }
 
@@ -197,6 +228,20 @@ impl<'a> ShapeQueries<'a> {
         },
         _ => unreachable!("should only receive ListItemTrail relative to ListKind"),
       },
+      ShapeKind::MapKind => match path_component {
+        ShapeTrailPathComponent::MapTrail { shape_id } => ResolvedTrail {
+          shape_id: parent.shape_id.clone(),
+          core_shape_kind: &ShapeKind::MapKind,
+        },
+        ShapeTrailPathComponent::MapValueTrail {
+          map_shape_id,
+          value_shape_id,
+        } => ResolvedTrail {
+          shape_id: value_shape_id.clone(),
+          core_shape_kind: self.resolve_to_core_shape(value_shape_id),
+        },
+        _ => unreachable!("should only receive MapTrail or MapValueTrail relative to MapKind"),
+      },
       ShapeKind::OneOfKind => match path_component {
         ShapeTrailPathComponent::OneOfTrail { shape_id } => ResolvedTrail {
           shape_id: parent.shape_id.clone(),
@@ -286,37 +331,93 @@ impl<'a> ShapeQueries<'a> {
     &self,
     shape_id: &ShapeId,
     shape_parameter_id: &ShapeParameterId,
+  ) -> ShapeId {
+    self.resolve_trail_parameter_to_shape(&ShapeTrail::new(shape_id.clone()), shape_parameter_id)
+  }
+
+  // Resolves a parameter for the shape at the end of the trail. Bindings are looked up from the
+  // innermost scope outwards: the field leading to a shape first, then the shape and the shapes
+  // it descends from. Bindings to another parameter continue the lookup for that parameter in
+  // the enclosing scopes. Parameters left unbound describe values we know nothing about.
+  pub fn resolve_trail_parameter_to_shape(
+    &self,
+    shape_trail: &ShapeTrail,
+    shape_parameter_id: &ShapeParameterId,
   ) -> ShapeId {
     let projection = &self.shape_projection;
+    let unknown_shape_id = || String::from(ShapeKind::UnknownKind.get_descriptor().base_shape_id);
 
-    let shape_node_index = projection
-      .get_shape_node_index(shape_id)
-      .expect("shape id to resolve parameter for must exist");
+    let mut shape_parameter_node_index =
+      match projection.get_shape_parameter_node_index(shape_parameter_id) {
+        Some(node_index) => *node_index,
+        None => return unknown_shape_id(),
+      };
 
-    let shape_parameter_node_index = projection
-      .get_shape_parameter_node_index(shape_parameter_id)
-      .unwrap_or_else(|| {
-        panic!(
-          "shape parameter id '{}' to resolve parameter for must exist",
-          shape_parameter_id
-        )
-      });
+    'scopes: for (shape_id, field_id) in trail_scopes(shape_trail).into_iter().rev() {
+      let field_node_index = field_id
+        .and_then(|field_id| projection.get_field_node_index(field_id))
+        .copied();
+      let binder_node_indexes = field_node_index
+        .into_iter()
+        .chain(self.resolve_shape_lineage(shape_id));
+
+      for binder_node_index in binder_node_indexes {
+        let binding = projection
+          .graph
+          .edges_connecting(binder_node_index, shape_parameter_node_index)
+          .find_map(|edge| match edge.weight() {
+            binding @ Edge::HasBinding(_) | binding @ Edge::HasParameterBinding(_) => Some(binding),
+            _ => None,
+          });
 
-    // @REFACTOR: move this to a method on the projection, we shouldn't have
-    // to reach into projection internals
-    let mut outgoing_edges = projection
-      .graph
-      .edges_connecting(*shape_node_index, *shape_parameter_node_index);
-
-    let existing_binding = outgoing_edges
-      .next()
-      .expect("expected a parameter binding to exist");
-    let edge_index = existing_binding.id();
-    let edge_weight = projection.graph.edge_weight(edge_index).unwrap();
-    match edge_weight {
-      Edge::HasBinding(b) => b.shape_id.clone(),
-      _ => unreachable!("expected edge to be a HasBinding"),
+        match binding {
+          Some(Edge::HasBinding(b)) => return b.shape_id.clone(),
+          Some(Edge::HasParameterBinding(b)) => {
+            shape_parameter_node_index =
+              match projection.get_shape_parameter_node_index(&b.shape_parameter_id) {
+                Some(node_index) => *node_index,
+                None => return unknown_shape_id(),
+              };
+            continue 'scopes;
+          }
+          _ => {}
+        }
+      }
+    }
+
+    unknown_shape_id()
+  }
+
+  // Shapes inherit the constraint of the shapes they descend from, unless they set their own
+  pub fn resolve_shape_constraint(&self, shape_id: &ShapeId) -> Option<&ShapeConstraintDescriptor> {
+    let projection = &self.shape_projection;
+    self
+      .resolve_shape_lineage(shape_id)
+      .into_iter()
+      .find_map(
+        |node_index| match projection.get_node_by_index(&node_index) {
+          Some(Node::Shape(shape_node)) => projection.get_shape_constraint(&shape_node.shape_id),
+          _ => None,
+        },
+      )
+  }
+
+  // The node of the shape, followed by those of the shapes it descends from
+  fn resolve_shape_lineage(&self, shape_id: &ShapeId) -> Vec<NodeIndex> {
+    let projection = &self.shape_projection;
+    let mut lineage = vec![];
+    let mut next_node_index = projection.get_shape_node_index(shape_id).copied();
+
+    while let Some(node_index) = next_node_index.take() {
+      lineage.push(node_index);
+      if let Some(Node::Shape(_)) = projection.get_node_by_index(&node_index) {
+        next_node_index = projection
+          .get_ancestor_shape_node_index(&node_index)
+          .filter(|ancestor_node_index| *ancestor_node_index != node_index);
+      }
     }
+
+    lineage
   }
 
   pub fn resolve_parameters_to_shapes(
@@ -356,13 +457,20 @@ impl<'a> ShapeQueries<'a> {
   pub fn resolve_field_id(&self, shape_id: &ShapeId, field_name: &String) -> Option<FieldId> {
     let projection = &self.shape_projection;
 
-    let shape_node_index = *projection
-      .get_shape_node_index(shape_id)
-      .expect("shape id to which field belongs should exist");
+    assert!(
+      projection.get_shape_node_index(shape_id).is_some(),
+      "shape id to which field belongs should exist"
+    );
 
-    projection
-      .graph
-      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+    // fields are inherited from the shapes a shape descends from
+    self
+      .resolve_shape_lineage(shape_id)
+      .into_iter()
+      .flat_map(|shape_node_index| {
+        projection
+          .graph
+          .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      })
       .find_map(|edge| match edge.weight() {
         Edge::IsFieldOf => match projection.graph.node_weight(edge.source()) {
           Some(Node::Field(ref field_node)) => {
@@ -415,19 +523,94 @@ impl<'a> ShapeQueries<'a> {
       })
   }
 
+  pub fn resolve_field_shape_parameter(&self, field_id: &FieldId) -> Option<ShapeParameterId> {
+    let projection = &self.shape_projection;
+
+    let field_node_index = *projection.get_field_node_index(field_id)?;
+
+    projection
+      .graph
+      .edges_directed(field_node_index, petgraph::Direction::Incoming)
+      .find_map(|edge| match edge.weight() {
+        Edge::BelongsTo => match projection.graph.node_weight(edge.source()) {
+          Some(Node::ShapeParameter(ref parameter_node)) => {
+            Some(parameter_node.parameter_id.clone())
+          }
+          _ => None,
+        },
+        _ => None,
+      })
+  }
+
+  // The shape of a field of the object at the end of the trail, resolving the shape parameter
+  // it might get its shape from
+  pub fn resolve_trail_field_shape(&self, shape_trail: &ShapeTrail, field_id: &FieldId) -> ShapeId {
+    match self.resolve_field_shape_node(field_id) {
+      Some(field_shape_id) => field_shape_id,
+      None => {
+        let shape_parameter_id = self.resolve_field_shape_parameter(field_id).expect(
+          "field node should have an edge to a shape or shape parameter describing its value",
+        );
+        self.resolve_trail_parameter_to_shape(shape_trail, &shape_parameter_id)
+      }
+    }
+  }
+
+  // The shape a (non-core) shape descends from, which holds the definition it shares
+  pub fn resolve_base_shape_id(&self, shape_id: &ShapeId) -> Option<ShapeId> {
+    let projection = self.shape_projection;
+    let shape_node_index = projection.get_shape_node_index(shape_id)?;
+    let base_node_index = projection.get_ancestor_shape_node_index(shape_node_index)?;
+
+    match projection.get_node_by_index(&base_node_index)? {
+      Node::Shape(base_shape) if base_shape.shape_id != *shape_id => {
+        Some(base_shape.shape_id.clone())
+      }
+      _ => None,
+    }
+  }
+
+  // Whether the shape, or any shape it descends from, declares parameters, making what it
+  // describes depend on how they're bound where it's used
+  pub fn declares_shape_parameters(&self, shape_id: &ShapeId) -> bool {
+    let projection = &self.shape_projection;
+    self
+      .resolve_shape_lineage(shape_id)
+      .into_iter()
+      .filter(|node_index| {
+        matches!(
+          projection.get_node_by_index(node_index),
+          Some(Node::Shape(_))
+        )
+      })
+      .any(|node_index| {
+        projection
+          .graph
+          .edges_directed(node_index, petgraph::Direction::Incoming)
+          .any(|edge| matches!(edge.weight(), Edge::IsParameterOf))
+      })
+  }
+
   pub fn resolve_shape_field_id_and_names(
     &self,
     shape_id: &ShapeId,
   ) -> impl Iterator<Item = (&FieldId, &String)> {
     let projection = &self.shape_projection;
 
-    let object_node_index = &projection
-      .get_shape_node_index(shape_id)
-      .expect("shape id for which to find field nodes should exist");
+    assert!(
+      projection.get_shape_node_index(shape_id).is_some(),
+      "shape id for which to find field nodes should exist"
+    );
 
-    projection
-      .get_shape_field_nodes(object_node_index)
-      .unwrap()
+    self
+      .resolve_shape_lineage(shape_id)
+      .into_iter()
+      .flat_map(move |shape_node_index| {
+        projection
+          .get_shape_field_nodes(&shape_node_index)
+          .into_iter()
+          .flatten()
+      })
       .map(|field_node| {
         let FieldNode {
           field_id,
@@ -452,6 +635,9 @@ impl<'a> ShapeQueries<'a> {
             ShapeKind::ListKind => {
               unimplemented!("resolving of shape trail by list shape")
             }
+            // map values are bound through the map's parameter rather than owned by it, so
+            // walking up we never know which value a trail through a map should lead to
+            ShapeKind::MapKind => return None,
             ShapeKind::ObjectKind => {
               let shape_id = shape_node.shape_id.clone();
               trail_components.push(ShapeTrailPathComponent::ObjectTrail { shape_id });
@@ -473,10 +659,7 @@ impl<'a> ShapeQueries<'a> {
               root_shape_id = None;
             }
 
-            ShapeKind::AnyKind
-            | ShapeKind::MapKind
-            | ShapeKind::IdentifierKind
-            | ShapeKind::ReferenceKind => {
+            ShapeKind::AnyKind | ShapeKind::IdentifierKind | ShapeKind::ReferenceKind => {
               unimplemented!("resolving of shape trail by complex typed shapes")
             }
           }
@@ -496,29 +679,662 @@ impl<'a> ShapeQueries<'a> {
             _ => unreachable!("field nodes should be owned by their objects shape node"),
           };
 
-          trail_components.push(ShapeTrailPathComponent::ObjectFieldTrail {
-            field_id,
-            field_shape_id,
-            parent_object_shape_id,
-          });
+          trail_components.push(ShapeTrailPathComponent::ObjectFieldTrail {
+            field_id,
+            field_shape_id,
+            parent_object_shape_id,
+          });
+
+          next_node = owner_node
+        }
+        Node::ShapeParameter(parameter_node) => {
+          unimplemented!("resolving of shape trail by shape parameter");
+          // TODO: consider supporting list items here
+
+          // next_node = self.shape_projection.get_owner_node(&current_node_index);
+        }
+        Node::BatchCommit(_) | Node::CoreShape(_) => {}
+      };
+    }
+
+    trail_components.reverse();
+    root_shape_id.map(move |root_shape_id| ShapeTrail {
+      root_shape_id,
+      path: trail_components,
+    })
+  }
+
+  // Describes the shape as a JSON Schema (draft 2020-12), with recursive shapes as `$defs`
+  pub fn json_schema(&self, root_shape_id: &ShapeId) -> JsonValue {
+    let mut builder = JsonSchemaBuilder {
+      shape_queries: self,
+      visiting_shape_ids: vec![],
+      recursive_shape_ids: HashSet::new(),
+      definitions: JsonMap::new(),
+    };
+
+    let root_schema = builder.shape_schema(root_shape_id);
+    let mut schema = JsonMap::new();
+    schema.insert(
+      String::from("$schema"),
+      JsonValue::from(JSON_SCHEMA_DIALECT),
+    );
+    if let JsonValue::Object(root_schema) = root_schema {
+      schema.extend(root_schema);
+    }
+    if !builder.definitions.is_empty() {
+      schema.insert(
+        String::from("$defs"),
+        JsonValue::Object(builder.definitions),
+      );
+    }
+
+    JsonValue::Object(schema)
+  }
+
+  // A hash of the structure of a shape, equal for all shapes describing the same values
+  pub fn shape_structural_hash(&self, shape_id: &ShapeId) -> StructuralHash {
+    self.structural_hash_helper(shape_id, &mut vec![])
+  }
+
+  fn structural_hash_helper(
+    &self,
+    shape_id: &ShapeId,
+    visiting_shape_ids: &mut Vec<ShapeId>,
+  ) -> StructuralHash {
+    // recursive shapes are only described up to where they recurse
+    if visiting_shape_ids.contains(shape_id) {
+      return RECURSIVE_STRUCTURAL_HASH;
+    }
+    visiting_shape_ids.push(shape_id.clone());
+
+    let core_shape_kind = self.resolve_to_core_shape(shape_id).clone();
+    let structure = match core_shape_kind {
+      ShapeKind::ObjectKind => {
+        let shape_trail = ShapeTrail::new(shape_id.clone());
+        let fields = self
+          .resolve_shape_field_id_and_names(shape_id)
+          .map(|(field_id, name)| {
+            let field_shape_id = self.resolve_trail_field_shape(&shape_trail, field_id);
+            (name.clone(), field_shape_id)
+          })
+          .collect::<Vec<_>>();
+
+        ShapeStructure::object(
+          fields
+            .into_iter()
+            .map(|(name, field_shape_id)| {
+              let field_hash = self.structural_hash_helper(&field_shape_id, visiting_shape_ids);
+              (name, field_hash)
+            })
+            .collect(),
+        )
+      }
+      ShapeKind::OneOfKind => ShapeStructure::one_of(
+        self
+          .resolve_parameters_to_shapes(shape_id)
+          .into_iter()
+          .map(|(_, branch_shape_id)| {
+            self.structural_hash_helper(&branch_shape_id, visiting_shape_ids)
+          })
+          .collect(),
+      ),
+      kind => match kind.get_parameter_descriptor() {
+        Some(parameter_descriptor) => {
+          let parameter_shape_id = self.resolve_parameter_to_shape(
+            shape_id,
+            &String::from(parameter_descriptor.shape_parameter_id),
+          );
+          let parameter_hash = self.structural_hash_helper(&parameter_shape_id, visiting_shape_ids);
+          ShapeStructure::parameterized(kind, parameter_hash)
+        }
+        None => {
+          let constraint = self.resolve_shape_constraint(shape_id).cloned();
+          ShapeStructure::primitive(kind, constraint)
+        }
+      },
+    };
+
+    visiting_shape_ids.pop();
+    structure.structural_hash()
+  }
+
+  // Indexes the shapes that have been given a name by their structure, so equivalent shapes can
+  // refer to them rather than repeating their definition
+  pub fn named_shape_structures(&self) -> ShapeStructureIndex {
+    let mut named_shapes = self
+      .shape_projection
+      .names_by_shape_id
+      .iter()
+      .collect::<Vec<_>>();
+    named_shapes.sort();
+
+    let mut index = ShapeStructureIndex::default();
+    for (shape_id, name) in named_shapes {
+      let structural_hash = self.shape_structural_hash(shape_id);
+      index
+        .shapes_by_hash
+        .entry(structural_hash)
+        .or_insert_with(|| ReusableShape {
+          shape_id: shape_id.clone(),
+          name: name.clone(),
+        });
+    }
+
+    index
+  }
+
+  // The shapes that have been given a name, with everywhere they're used
+  pub fn named_shapes(&self, endpoint_queries: &EndpointQueries) -> Vec<NamedShape> {
+    let mut named_shapes = self
+      .shape_projection
+      .names_by_shape_id
+      .iter()
+      .map(|(shape_id, name)| NamedShape {
+        shape_id: shape_id.clone(),
+        name: name.clone(),
+        kind: String::from(
+          self
+            .resolve_to_core_shape(shape_id)
+            .get_descriptor()
+            .base_shape_id,
+        ),
+        usages: self.shape_usages(shape_id, endpoint_queries),
+      })
+      .collect::<Vec<_>>();
+    named_shapes.sort_by(|a, b| (&a.name, &a.shape_id).cmp(&(&b.name, &b.shape_id)));
+
+    named_shapes
+  }
+
+  // Everywhere a shape is used, found by following the references to it backwards. Optionals,
+  // nullables and one-ofs of the shape are looked through, as their usages use the shape as well.
+  pub fn shape_usages(
+    &self,
+    shape_id: &ShapeId,
+    endpoint_queries: &EndpointQueries,
+  ) -> Vec<ShapeUsage> {
+    let projection = &self.shape_projection;
+    let mut usages = vec![];
+    let mut using_shape_ids = HashSet::new();
+    let mut unvisited_shape_ids = vec![shape_id.clone()];
+
+    while let Some(shape_id) = unvisited_shape_ids.pop() {
+      let shape_node_index = match projection.get_shape_node_index(&shape_id) {
+        Some(node_index) => *node_index,
+        None => continue,
+      };
+      if !using_shape_ids.insert(shape_id.clone()) {
+        continue;
+      }
+
+      for edge in projection
+        .graph
+        .edges_directed(shape_node_index, petgraph::Direction::Outgoing)
+      {
+        if let (Edge::BelongsTo, Some(usage)) = (edge.weight(), self.field_usage(edge.target())) {
+          usages.push(usage);
+        }
+      }
+
+      for edge in projection
+        .graph
+        .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      {
+        if let (Edge::IsDescendantOf, Some(Node::Shape(descendant_node))) =
+          (edge.weight(), projection.get_node_by_index(&edge.source()))
+        {
+          usages.push(ShapeUsage::BaseShape {
+            shape_id: descendant_node.shape_id.clone(),
+          });
+        }
+      }
+
+      // parameter bindings refer to the shapes they bind by id
+      let bindings = projection.graph.edge_references().filter(
+        |edge| matches!(edge.weight(), Edge::HasBinding(binding) if binding.shape_id == shape_id),
+      );
+      for binding in bindings {
+        let binder_shape_id = match projection.get_node_by_index(&binding.source()) {
+          Some(Node::Field(_)) => {
+            usages.extend(self.field_usage(binding.source()));
+            continue;
+          }
+          Some(Node::Shape(binder_node)) => binder_node.shape_id.clone(),
+          _ => continue,
+        };
+        match self.resolve_to_core_shape(&binder_shape_id) {
+          ShapeKind::ListKind => usages.push(ShapeUsage::ListItem {
+            list_shape_id: binder_shape_id,
+          }),
+          ShapeKind::MapKind => usages.push(ShapeUsage::MapValue {
+            map_shape_id: binder_shape_id,
+          }),
+          ShapeKind::OptionalKind | ShapeKind::NullableKind | ShapeKind::OneOfKind => {
+            unvisited_shape_ids.push(binder_shape_id)
+          }
+          _ => {
+            let shape_parameter_id = match projection.get_node_by_index(&binding.target()) {
+              Some(Node::ShapeParameter(parameter_node)) => parameter_node.parameter_id.clone(),
+              _ => continue,
+            };
+            usages.push(ShapeUsage::ShapeParameter {
+              shape_id: binder_shape_id,
+              shape_parameter_id,
+            })
+          }
+        }
+      }
+    }
+
+    usages.extend(endpoint_queries.resolve_shape_usages(&using_shape_ids));
+    usages
+  }
+
+  // The usage by a field, as long as it's still a field of an object
+  fn field_usage(&self, field_node_index: NodeIndex) -> Option<ShapeUsage> {
+    let projection = &self.shape_projection;
+    let field_node = match projection.get_node_by_index(&field_node_index)? {
+      Node::Field(field_node) => field_node,
+      _ => return None,
+    };
+    let object_shape_id = projection
+      .graph
+      .edges_directed(field_node_index, petgraph::Direction::Outgoing)
+      .find(|edge| matches!(edge.weight(), Edge::IsFieldOf))
+      .and_then(|edge| match projection.get_node_by_index(&edge.target()) {
+        Some(Node::Shape(object_node)) => Some(object_node.shape_id.clone()),
+        _ => None,
+      })?;
+
+    Some(ShapeUsage::Field {
+      field_id: field_node.field_id.clone(),
+      field_name: field_node.descriptor.name.clone(),
+      object_shape_id,
+    })
+  }
+
+  // The shapes that can be reached from the given ones, through base shapes, fields and
+  // parameter bindings
+  pub fn reachable_shape_ids(
+    &self,
+    root_shape_ids: impl IntoIterator<Item = ShapeId>,
+  ) -> HashSet<ShapeId> {
+    let mut reachable_shape_ids = HashSet::new();
+    let mut unvisited_shape_ids: Vec<_> = root_shape_ids.into_iter().collect();
+
+    while let Some(shape_id) = unvisited_shape_ids.pop() {
+      let shape_node_index = match self.shape_projection.get_shape_node_index(&shape_id) {
+        Some(node_index) => *node_index,
+        None => continue,
+      };
+      if reachable_shape_ids.insert(shape_id) {
+        unvisited_shape_ids.extend(self.referenced_shape_ids(shape_node_index));
+      }
+    }
+
+    reachable_shape_ids
+  }
+
+  // Commands removing the shapes no body or parameter of an endpoint can reach any more, along with
+  // their fields. Shapes are removed before the shapes they refer to.
+  pub fn remove_unreachable_shapes_commands(
+    &self,
+    endpoint_queries: &EndpointQueries,
+  ) -> impl Iterator<Item = ShapeCommand> {
+    let projection = &self.shape_projection;
+    let reachable_shape_ids = self.reachable_shape_ids(endpoint_queries.resolve_root_shape_ids());
+
+    let unreachable_shapes: BTreeMap<ShapeId, NodeIndex> = projection
+      .graph
+      .node_indices()
+      .filter_map(
+        |node_index| match projection.get_node_by_index(&node_index) {
+          Some(Node::Shape(shape_node)) => Some((shape_node.shape_id.clone(), node_index)),
+          _ => None,
+        },
+      )
+      .filter(|(shape_id, node_index)| {
+        // removed shapes can no longer be looked up by id
+        projection.get_shape_node_index(shape_id) == Some(node_index)
+          && !reachable_shape_ids.contains(shape_id)
+      })
+      .collect();
+
+    let mut removal_order = vec![];
+    let mut visited_shape_ids = HashSet::new();
+    for shape_id in unreachable_shapes.keys() {
+      self.unreachable_removal_order(
+        shape_id,
+        &unreachable_shapes,
+        &mut visited_shape_ids,
+        &mut removal_order,
+      );
+    }
+    removal_order.reverse(); // referring shapes come first
+
+    let remove_field_commands = removal_order
+      .iter()
+      .flat_map(|shape_id| {
+        let shape_node_index = unreachable_shapes[shape_id];
+        projection
+          .graph
+          .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+          .filter(|edge| matches!(edge.weight(), Edge::IsFieldOf))
+          .filter_map(|edge| match projection.get_node_by_index(&edge.source()) {
+            Some(Node::Field(field_node)) => {
+              Some(ShapeCommand::remove_field(field_node.field_id.clone()))
+            }
+            _ => None,
+          })
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    let remove_shape_commands = removal_order.into_iter().map(ShapeCommand::remove_shape);
+
+    remove_field_commands
+      .into_iter()
+      .chain(remove_shape_commands)
+  }
+
+  // Merges every group of structurally equivalent shapes in use into the one that was added first,
+  // by pointing whatever refers to the others at it instead. References from within merged shapes
+  // are left alone, as those shapes become unreachable themselves. Named shapes are told apart by
+  // their names on purpose, and primitives aren't worth sharing, so neither are merged.
+  pub fn merge_duplicate_shapes_commands(
+    &self,
+    endpoint_queries: &EndpointQueries,
+  ) -> (Vec<MergedShapes>, Vec<SpecCommand>) {
+    let projection = &self.shape_projection;
+    let reachable_shape_ids = self.reachable_shape_ids(endpoint_queries.resolve_root_shape_ids());
+
+    let mut duplicate_groups: Vec<Vec<ShapeId>> = vec![];
+    let mut group_by_hash: HashMap<StructuralHash, usize> = HashMap::new();
+    for node_index in projection.graph.node_indices() {
+      let shape_id = match projection.get_node_by_index(&node_index) {
+        Some(Node::Shape(shape_node)) => &shape_node.shape_id,
+        _ => continue,
+      };
+      if projection.get_shape_node_index(shape_id) != Some(&node_index)
+        || !reachable_shape_ids.contains(shape_id)
+        || projection.get_shape_name(shape_id).is_some()
+        || !matches!(
+          self.resolve_to_core_shape(shape_id),
+          ShapeKind::ObjectKind
+            | ShapeKind::ListKind
+            | ShapeKind::MapKind
+            | ShapeKind::OneOfKind
+            | ShapeKind::OptionalKind
+            | ShapeKind::NullableKind
+        )
+      {
+        continue;
+      }
+
+      let structural_hash = self.shape_structural_hash(shape_id);
+      let group_index = *group_by_hash.entry(structural_hash).or_insert_with(|| {
+        duplicate_groups.push(vec![]);
+        duplicate_groups.len() - 1
+      });
+      duplicate_groups[group_index].push(shape_id.clone());
+    }
+
+    let merged_shapes = duplicate_groups
+      .into_iter()
+      .filter(|shape_ids| shape_ids.len() > 1)
+      .map(|mut shape_ids| MergedShapes {
+        surviving_shape_id: shape_ids.remove(0),
+        merged_shape_ids: shape_ids,
+      })
+      .collect::<Vec<_>>();
+    let merged_shape_ids = merged_shapes
+      .iter()
+      .flat_map(|merged| merged.merged_shape_ids.iter())
+      .collect::<HashSet<_>>();
+
+    let mut commands = vec![];
+    for merged in &merged_shapes {
+      let surviving_shape_id = &merged.surviving_shape_id;
+      for merged_shape_id in &merged.merged_shape_ids {
+        for usage in self.direct_shape_usages(merged_shape_id) {
+          let command = match usage {
+            ShapeUsage::Field {
+              field_id,
+              object_shape_id,
+              ..
+            } if !merged_shape_ids.contains(&object_shape_id) => {
+              ShapeCommand::set_field_shape(field_id, surviving_shape_id.clone())
+            }
+            ShapeUsage::BaseShape { shape_id } if !merged_shape_ids.contains(&shape_id) => {
+              ShapeCommand::set_base_shape(shape_id, surviving_shape_id.clone())
+            }
+            ShapeUsage::ShapeParameter {
+              shape_id,
+              shape_parameter_id,
+            } if !merged_shape_ids.contains(&shape_id) => ShapeCommand::set_parameter_shape(
+              shape_id,
+              shape_parameter_id,
+              surviving_shape_id.clone(),
+            ),
+            _ => continue,
+          };
+          commands.push(SpecCommand::from(command));
+        }
+
+        for (field_id, object_shape_id, shape_parameter_id) in
+          self.field_bindings_of(merged_shape_id)
+        {
+          if merged_shape_ids.contains(&object_shape_id) {
+            continue;
+          }
+          commands.push(SpecCommand::from(ShapeCommand::SetParameterShape(
+            shape_commands::SetParameterShape {
+              shape_descriptor: ParameterShapeDescriptor::ProviderInField(ProviderInField {
+                field_id,
+                consuming_parameter_id: shape_parameter_id,
+                provider_descriptor: ProviderDescriptor::ShapeProvider(ShapeProvider {
+                  shape_id: surviving_shape_id.clone(),
+                }),
+              }),
+            },
+          )));
+        }
+
+        let endpoint_usages =
+          endpoint_queries.resolve_shape_usages(&HashSet::from_iter(vec![merged_shape_id.clone()]));
+        for usage in endpoint_usages {
+          let command = match usage {
+            ShapeUsage::RequestBody {
+              request_id,
+              content_type,
+              ..
+            } => EndpointCommand::set_request_body_shape(
+              request_id,
+              surviving_shape_id.clone(),
+              content_type,
+              false,
+            ),
+            ShapeUsage::ResponseBody {
+              response_id,
+              content_type,
+              ..
+            } => EndpointCommand::set_response_body_shape(
+              response_id,
+              surviving_shape_id.clone(),
+              content_type,
+              false,
+            ),
+            ShapeUsage::QueryParameters {
+              query_parameters_id,
+              ..
+            } => EndpointCommand::set_query_parameters_shape(
+              query_parameters_id,
+              surviving_shape_id.clone(),
+              false,
+            ),
+            ShapeUsage::RequestParameter {
+              parameter_id,
+              location,
+              ..
+            } if location == HEADER_PARAMETER_LOCATION => {
+              EndpointCommand::set_header_parameter_shape(parameter_id, surviving_shape_id.clone())
+            }
+            _ => continue,
+          };
+          commands.push(SpecCommand::from(command));
+        }
+      }
+    }
+
+    (merged_shapes, commands)
+  }
+
+  // The shapes and fields directly referring to a shape, without looking through the optionals,
+  // nullables and one-ofs wrapping it like `shape_usages` does. Fields binding a parameter to the
+  // shape are left to `field_bindings_of`.
+  fn direct_shape_usages(&self, shape_id: &ShapeId) -> Vec<ShapeUsage> {
+    let projection = &self.shape_projection;
+    let shape_node_index = match projection.get_shape_node_index(shape_id) {
+      Some(node_index) => *node_index,
+      None => return vec![],
+    };
+    let mut usages = vec![];
+
+    for edge in projection
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Outgoing)
+    {
+      if let (Edge::BelongsTo, Some(usage)) = (edge.weight(), self.field_usage(edge.target())) {
+        usages.push(usage);
+      }
+    }
+
+    for edge in projection
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+    {
+      if let (Edge::IsDescendantOf, Some(Node::Shape(descendant_node))) =
+        (edge.weight(), projection.get_node_by_index(&edge.source()))
+      {
+        usages.push(ShapeUsage::BaseShape {
+          shape_id: descendant_node.shape_id.clone(),
+        });
+      }
+    }
+
+    let bindings = projection.graph.edge_references().filter(
+      |edge| matches!(edge.weight(), Edge::HasBinding(binding) if binding.shape_id == *shape_id),
+    );
+    for binding in bindings {
+      if let (Some(Node::Shape(binder_node)), Some(Node::ShapeParameter(parameter_node))) = (
+        projection.get_node_by_index(&binding.source()),
+        projection.get_node_by_index(&binding.target()),
+      ) {
+        usages.push(ShapeUsage::ShapeParameter {
+          shape_id: binder_node.shape_id.clone(),
+          shape_parameter_id: parameter_node.parameter_id.clone(),
+        });
+      }
+    }
+
+    usages
+  }
+
+  // The fields binding a parameter of their object's shape to the shape, with that object shape
+  // and the bound parameter
+  fn field_bindings_of(&self, shape_id: &ShapeId) -> Vec<(FieldId, ShapeId, ShapeParameterId)> {
+    let projection = &self.shape_projection;
+    projection
+      .graph
+      .edge_references()
+      .filter(
+        |edge| matches!(edge.weight(), Edge::HasBinding(binding) if binding.shape_id == *shape_id),
+      )
+      .filter_map(|binding| {
+        let parameter_id = match projection.get_node_by_index(&binding.target()) {
+          Some(Node::ShapeParameter(parameter_node)) => parameter_node.parameter_id.clone(),
+          _ => return None,
+        };
+        match self.field_usage(binding.source())? {
+          ShapeUsage::Field {
+            field_id,
+            object_shape_id,
+            ..
+          } => Some((field_id, object_shape_id, parameter_id)),
+          _ => None,
+        }
+      })
+      .collect()
+  }
+
+  // Depth-first post-order of the unreachable shapes, so shapes come after the shapes referring to
+  // them once reversed
+  fn unreachable_removal_order(
+    &self,
+    shape_id: &ShapeId,
+    unreachable_shapes: &BTreeMap<ShapeId, NodeIndex>,
+    visited_shape_ids: &mut HashSet<ShapeId>,
+    removal_order: &mut Vec<ShapeId>,
+  ) {
+    let shape_node_index = match unreachable_shapes.get(shape_id) {
+      Some(node_index) => *node_index,
+      None => return,
+    };
+    if !visited_shape_ids.insert(shape_id.clone()) {
+      return;
+    }
+
+    for referenced_shape_id in self.referenced_shape_ids(shape_node_index) {
+      self.unreachable_removal_order(
+        &referenced_shape_id,
+        unreachable_shapes,
+        visited_shape_ids,
+        removal_order,
+      );
+    }
+    removal_order.push(shape_id.clone());
+  }
+
+  // The shapes a shape refers to: its base shape, the shapes of its fields and the shapes bound to
+  // parameters by it or its fields
+  fn referenced_shape_ids(&self, shape_node_index: NodeIndex) -> Vec<ShapeId> {
+    let projection = &self.shape_projection;
+    let graph = &projection.graph;
+    let mut referenced_shape_ids = vec![];
+    let mut binder_node_indices = vec![shape_node_index];
+
+    for edge in graph.edges_directed(shape_node_index, petgraph::Direction::Outgoing) {
+      if let (Edge::IsDescendantOf, Some(Node::Shape(base_shape_node))) =
+        (edge.weight(), projection.get_node_by_index(&edge.target()))
+      {
+        referenced_shape_ids.push(base_shape_node.shape_id.clone());
+      }
+    }
 
-          next_node = owner_node
+    let field_node_indices = graph
+      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      .filter(|edge| matches!(edge.weight(), Edge::IsFieldOf))
+      .map(|edge| edge.source());
+    for field_node_index in field_node_indices {
+      binder_node_indices.push(field_node_index);
+      for edge in graph.edges_directed(field_node_index, petgraph::Direction::Incoming) {
+        if let (Edge::BelongsTo, Some(Node::Shape(field_shape_node))) =
+          (edge.weight(), projection.get_node_by_index(&edge.source()))
+        {
+          referenced_shape_ids.push(field_shape_node.shape_id.clone());
         }
-        Node::ShapeParameter(parameter_node) => {
-          unimplemented!("resolving of shape trail by shape parameter");
-          // TODO: consider supporting list items here
+      }
+    }
 
-          // next_node = self.shape_projection.get_owner_node(&current_node_index);
+    for binder_node_index in binder_node_indices {
+      for edge in graph.edges_directed(binder_node_index, petgraph::Direction::Outgoing) {
+        if let Edge::HasBinding(binding) = edge.weight() {
+          referenced_shape_ids.push(binding.shape_id.clone());
         }
-        Node::BatchCommit(_) | Node::CoreShape(_) => {}
-      };
+      }
     }
 
-    trail_components.reverse();
-    root_shape_id.map(move |root_shape_id| ShapeTrail {
-      root_shape_id,
-      path: trail_components,
-    })
+    referenced_shape_ids
   }
 
   pub fn remove_field_commands(
@@ -628,6 +1444,7 @@ pub struct ChoiceOutput {
   pub additional_components: Vec<ShapeTrailPathComponent>,
   pub shape_id: ShapeId,
   pub core_shape_kind: ShapeKind,
+  pub constraint: Option<ShapeConstraintDescriptor>,
 }
 
 impl ChoiceOutput {
@@ -642,6 +1459,377 @@ impl ChoiceOutput {
   }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedShapes {
+  pub surviving_shape_id: ShapeId,
+  pub merged_shape_ids: Vec<ShapeId>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedShape {
+  pub shape_id: ShapeId,
+  pub name: String,
+  pub kind: String,
+  pub usages: Vec<ShapeUsage>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub enum ShapeUsage {
+  #[serde(rename_all = "camelCase")]
+  Field {
+    field_id: FieldId,
+    field_name: String,
+    object_shape_id: ShapeId,
+  },
+  #[serde(rename_all = "camelCase")]
+  ListItem { list_shape_id: ShapeId },
+  #[serde(rename_all = "camelCase")]
+  MapValue { map_shape_id: ShapeId },
+  #[serde(rename_all = "camelCase")]
+  ShapeParameter {
+    shape_id: ShapeId,
+    shape_parameter_id: ShapeParameterId,
+  },
+  // a shape descending from the shape
+  #[serde(rename_all = "camelCase")]
+  BaseShape { shape_id: ShapeId },
+  #[serde(rename_all = "camelCase")]
+  RequestBody {
+    request_id: RequestId,
+    path_id: PathComponentId,
+    method: HttpMethod,
+    content_type: HttpContentType,
+  },
+  #[serde(rename_all = "camelCase")]
+  ResponseBody {
+    response_id: ResponseId,
+    path_id: PathComponentId,
+    method: HttpMethod,
+    status_code: HttpStatusCode,
+    content_type: HttpContentType,
+  },
+  #[serde(rename_all = "camelCase")]
+  QueryParameters {
+    query_parameters_id: QueryParametersId,
+    path_id: PathComponentId,
+    method: HttpMethod,
+  },
+  #[serde(rename_all = "camelCase")]
+  RequestParameter {
+    parameter_id: RequestParameterId,
+    location: String,
+    name: String,
+    path_id: PathComponentId,
+    method: HttpMethod,
+  },
+}
+
+pub type StructuralHash = u64;
+
+const RECURSIVE_STRUCTURAL_HASH: StructuralHash = 0;
+
+// A canonical description of a shape, describing the shapes it's composed of by their structural
+// hashes rather than their ids
+#[derive(Debug, Serialize)]
+pub struct ShapeStructure {
+  kind: ShapeKind,
+  constraint: Option<ShapeConstraintDescriptor>,
+  fields: BTreeMap<String, StructuralHash>,
+  parameters: Vec<StructuralHash>,
+}
+
+impl ShapeStructure {
+  pub fn primitive(kind: ShapeKind, constraint: Option<ShapeConstraintDescriptor>) -> Self {
+    Self {
+      kind,
+      constraint,
+      fields: BTreeMap::new(),
+      parameters: vec![],
+    }
+  }
+
+  pub fn object(fields: BTreeMap<String, StructuralHash>) -> Self {
+    Self {
+      kind: ShapeKind::ObjectKind,
+      constraint: None,
+      fields,
+      parameters: vec![],
+    }
+  }
+
+  pub fn parameterized(kind: ShapeKind, parameter_hash: StructuralHash) -> Self {
+    Self {
+      kind,
+      constraint: None,
+      fields: BTreeMap::new(),
+      parameters: vec![parameter_hash],
+    }
+  }
+
+  // the order of the branches doesn't change which values a one-of describes
+  pub fn one_of(mut branch_hashes: Vec<StructuralHash>) -> Self {
+    branch_hashes.sort_unstable();
+    branch_hashes.dedup();
+    Self {
+      kind: ShapeKind::OneOfKind,
+      constraint: None,
+      fields: BTreeMap::new(),
+      parameters: branch_hashes,
+    }
+  }
+
+  pub fn structural_hash(&self) -> StructuralHash {
+    let canonical_json =
+      serde_json::to_vec(self).expect("shape structure should be serializable to json");
+    seahash::hash(&canonical_json)
+  }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReusableShape {
+  pub shape_id: ShapeId,
+  pub name: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ShapeStructureIndex {
+  shapes_by_hash: HashMap<StructuralHash, ReusableShape>,
+}
+
+impl ShapeStructureIndex {
+  pub fn get(&self, structural_hash: StructuralHash) -> Option<&ReusableShape> {
+    self.shapes_by_hash.get(&structural_hash)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.shapes_by_hash.is_empty()
+  }
+}
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// The shapes a trail passes through, each with the field that lead to it
+fn trail_scopes(shape_trail: &ShapeTrail) -> Vec<(&ShapeId, Option<&FieldId>)> {
+  let mut scopes = vec![(&shape_trail.root_shape_id, None)];
+  scopes.extend(
+    shape_trail
+      .path
+      .iter()
+      .filter_map(|component| match component {
+        ShapeTrailPathComponent::ObjectFieldTrail {
+          field_id,
+          field_shape_id,
+          ..
+        } => Some((field_shape_id, Some(field_id))),
+        ShapeTrailPathComponent::ListItemTrail { item_shape_id, .. }
+        | ShapeTrailPathComponent::OneOfItemTrail { item_shape_id, .. } => {
+          Some((item_shape_id, None))
+        }
+        ShapeTrailPathComponent::MapValueTrail { value_shape_id, .. } => {
+          Some((value_shape_id, None))
+        }
+        ShapeTrailPathComponent::OptionalItemTrail { inner_shape_id, .. }
+        | ShapeTrailPathComponent::NullableItemTrail { inner_shape_id, .. } => {
+          Some((inner_shape_id, None))
+        }
+        _ => None,
+      }),
+  );
+  scopes
+}
+
+struct JsonSchemaBuilder<'q, 'a> {
+  shape_queries: &'q ShapeQueries<'a>,
+  visiting_shape_ids: Vec<ShapeId>,
+  recursive_shape_ids: HashSet<ShapeId>,
+  definitions: JsonMap<String, JsonValue>,
+}
+
+impl<'q, 'a> JsonSchemaBuilder<'q, 'a> {
+  fn shape_schema(&mut self, shape_id: &ShapeId) -> JsonValue {
+    self.choices_schema(&ShapeTrail::new(shape_id.clone())).0
+  }
+
+  // Combines the choices of the shape at the end of the trail into a single schema, also
+  // returning whether the shape is optional. The trail is what parameters are resolved through.
+  fn choices_schema(&mut self, shape_trail: &ShapeTrail) -> (JsonValue, bool) {
+    let choices = self.shape_queries.list_trail_choices(shape_trail);
+
+    let mut is_optional = false;
+    let mut is_nullable = false;
+    let mut schemas: Vec<JsonValue> = vec![];
+    for choice in choices {
+      let schema = match choice.core_shape_kind {
+        ShapeKind::OptionalKind => {
+          is_optional = true;
+          continue;
+        }
+        ShapeKind::NullableKind => {
+          is_nullable = true;
+          continue;
+        }
+        ShapeKind::ObjectKind | ShapeKind::ListKind | ShapeKind::MapKind => {
+          self.definition_schema(&choice)
+        }
+        ShapeKind::StringKind => Self::constrained_schema(json!({ "type": "string" }), &choice),
+        ShapeKind::NumberKind => Self::constrained_schema(json!({ "type": "number" }), &choice),
+        ShapeKind::BooleanKind => json!({ "type": "boolean" }),
+        _ => json!({}),
+      };
+      if !schemas.contains(&schema) {
+        schemas.push(schema);
+      }
+    }
+
+    // unknown shapes have no choices, and anything goes when any of the choices allows it
+    if schemas.is_empty() || schemas.iter().any(|schema| schema == &json!({})) {
+      return (json!({}), is_optional);
+    }
+
+    let schema = if schemas.len() == 1 {
+      let mut schema = schemas.remove(0);
+      if is_nullable {
+        match schema.get("type").cloned() {
+          Some(JsonValue::String(json_type)) => {
+            schema["type"] = json!([json_type, "null"]);
+            schema
+          }
+          _ => json!({ "oneOf": [schema, { "type": "null" }] }),
+        }
+      } else {
+        schema
+      }
+    } else {
+      if is_nullable {
+        schemas.push(json!({ "type": "null" }));
+      }
+      json!({ "oneOf": schemas })
+    };
+
+    (schema, is_optional)
+  }
+
+  fn constrained_schema(mut schema: JsonValue, choice: &ChoiceOutput) -> JsonValue {
+    match &choice.constraint {
+      Some(ShapeConstraintDescriptor::FormatConstraint(constraint)) => {
+        schema["format"] = json!(constraint.format.name());
+      }
+      Some(ShapeConstraintDescriptor::EnumConstraint(constraint)) => {
+        schema["enum"] = json!(constraint.values);
+      }
+      Some(ShapeConstraintDescriptor::IntegerConstraint(_)) => {
+        schema["type"] = json!("integer");
+      }
+      Some(ShapeConstraintDescriptor::NoConstraint(_)) | None => {}
+    }
+    schema
+  }
+
+  // Objects, lists and maps can (indirectly) contain themselves, in which case they're described once
+  // in `$defs` and referenced from everywhere they occur
+  fn definition_schema(&mut self, choice: &ChoiceOutput) -> JsonValue {
+    // shapes descending from another (non-core) shape share its definition, unless it declares
+    // parameters, which the descending shape might bind
+    let mut shape_id = choice.shape_id.clone();
+    while let Some(base_shape_id) = self.shape_queries.resolve_base_shape_id(&shape_id) {
+      if self.shape_queries.declares_shape_parameters(&base_shape_id) {
+        break;
+      }
+      shape_id = base_shape_id;
+    }
+    let shape_trail = choice.shape_trail();
+
+    if self.visiting_shape_ids.contains(&shape_id) {
+      self.recursive_shape_ids.insert(shape_id.clone());
+      return Self::definition_reference(&shape_id);
+    }
+    if self.definitions.contains_key(&shape_id) {
+      return Self::definition_reference(&shape_id);
+    }
+
+    self.visiting_shape_ids.push(shape_id.clone());
+    let schema = match choice.core_shape_kind {
+      ShapeKind::ObjectKind => {
+        let fields = self
+          .shape_queries
+          .resolve_shape_field_id_and_names(&shape_id)
+          .map(|(field_id, name)| (field_id.clone(), name.clone()))
+          .collect::<Vec<_>>();
+
+        let mut properties = JsonMap::new();
+        let mut required = BTreeSet::new();
+        for (field_id, name) in fields {
+          let field_shape_id = self
+            .shape_queries
+            .resolve_trail_field_shape(&shape_trail, &field_id);
+          let field_trail = shape_trail.with_component(ShapeTrailPathComponent::ObjectFieldTrail {
+            field_id,
+            field_shape_id,
+            parent_object_shape_id: choice.shape_id.clone(),
+          });
+          let (field_schema, is_optional) = self.choices_schema(&field_trail);
+          if !is_optional {
+            required.insert(name.clone());
+          }
+          properties.insert(name, field_schema);
+        }
+
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+          schema["required"] = json!(required);
+        }
+        schema
+      }
+      _ => {
+        let parameter_id = String::from(
+          choice
+            .core_shape_kind
+            .get_parameter_descriptor()
+            .expect("expected $list and $map to have a parameter descriptor")
+            .shape_parameter_id,
+        );
+        let parameter_shape_id = self
+          .shape_queries
+          .resolve_trail_parameter_to_shape(&shape_trail, &parameter_id);
+        let parameter_trail = match choice.core_shape_kind {
+          ShapeKind::MapKind => {
+            shape_trail.with_component(ShapeTrailPathComponent::MapValueTrail {
+              map_shape_id: choice.shape_id.clone(),
+              value_shape_id: parameter_shape_id,
+            })
+          }
+          _ => shape_trail.with_component(ShapeTrailPathComponent::ListItemTrail {
+            list_shape_id: choice.shape_id.clone(),
+            item_shape_id: parameter_shape_id,
+          }),
+        };
+        let parameter_schema = self.choices_schema(&parameter_trail).0;
+        match choice.core_shape_kind {
+          ShapeKind::MapKind => {
+            json!({ "type": "object", "additionalProperties": parameter_schema })
+          }
+          _ => json!({ "type": "array", "items": parameter_schema }),
+        }
+      }
+    };
+    self.visiting_shape_ids.pop();
+
+    if self.recursive_shape_ids.contains(&shape_id) {
+      self.definitions.insert(shape_id.clone(), schema);
+      Self::definition_reference(&shape_id)
+    } else {
+      schema
+    }
+  }
+
+  fn definition_reference(shape_id: &ShapeId) -> JsonValue {
+    json!({ "$ref": format!("#/$defs/{}", shape_id) })
+  }
+}
+
 #[derive(Clone, Debug)]
 struct ShapePrototype {
   id: ShapeId,
@@ -699,9 +1887,9 @@ mod test {
   use crate::commands::SpecCommand;
   use crate::events::SpecEvent;
   use crate::projections::SpecProjection;
+  use crate::test_helpers::assert_valid_commands;
   use crate::Aggregate;
-  use insta::assert_debug_snapshot;
-  use serde_json::json;
+  use insta::{assert_debug_snapshot, assert_json_snapshot};
 
   #[test]
   pub fn can_generate_remove_field_commands() {
@@ -1031,6 +2219,12 @@ mod test {
       { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "one_of_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "number_shape_1"}},"consumingParameterId": "one_of_param_2" }}}},
       { "FieldAdded": { "fieldId": "field_5", "shapeId": "object_shape_1", "name": "nestedObject", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_5", "shapeId": "one_of_shape_1"}} }},
 
+      // map of numbers
+      { "ShapeAdded": { "shapeId": "number_shape_2", "baseShapeId": "$number", "name": "" }},
+      { "ShapeAdded": { "shapeId": "map_shape_1", "baseShapeId": "$map", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "map_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "number_shape_2"}},"consumingParameterId": "$mapValue" }}}},
+      { "FieldAdded": { "fieldId": "field_6", "shapeId": "object_shape_1", "name": "scores", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_6", "shapeId": "map_shape_1"}} }},
+
     ]))
     .expect("should be able to deserialize test events");
 
@@ -1053,6 +2247,9 @@ mod test {
       &nested_field_trail
     );
 
+    let map_shape_trail = shape_queries.resolve_shape_trail(&"map_shape_1".to_owned());
+    assert!(map_shape_trail.is_none());
+
     // UNIMPLEMENTED
     // let optional_shape_trail = shape_queries.resolve_shape_trail(&"optional_shape_1".to_owned());
     // assert_debug_snapshot!(
@@ -1075,22 +2272,118 @@ mod test {
     // );
   }
 
-  fn assert_valid_commands(
-    mut spec_projection: SpecProjection,
-    commands: impl IntoIterator<Item = SpecCommand>,
-  ) -> SpecProjection {
-    // let mut spec_projection = SpecProjection::default();
-    for command in commands {
-      let events = spec_projection
-        .execute(command)
-        .expect("generated commands must be valid");
+  #[test]
+  pub fn can_describe_shapes_as_json_schema() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "comment_shape_1", "baseShapeId": "$object", "name": "Comment" }},
 
-      for event in events {
-        spec_projection.apply(event)
-      }
-    }
+      // required string field
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "comment_shape_1", "name": "body", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "string_shape_1"}} }},
+
+      // optional nullable number field
+      { "ShapeAdded": { "shapeId": "number_shape_1", "baseShapeId": "$number", "name": "" }},
+      { "ShapeAdded": { "shapeId": "nullable_shape_1", "baseShapeId": "$nullable", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "nullable_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "number_shape_1"}},"consumingParameterId": "$nullableInner" }}}},
+      { "ShapeAdded": { "shapeId": "optional_shape_1", "baseShapeId": "$optional", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "optional_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "nullable_shape_1"}},"consumingParameterId": "$optionalInner" }}}},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "comment_shape_1", "name": "score", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "optional_shape_1"}} }},
+
+      // one of string or boolean
+      { "ShapeAdded": { "shapeId": "boolean_shape_1", "baseShapeId": "$boolean", "name": "" }},
+      { "ShapeAdded": { "shapeId": "one_of_shape_1", "baseShapeId": "$oneOf", "name": "" }},
+      { "ShapeParameterAdded": { "shapeId": "one_of_shape_1", "shapeParameterId": "one_of_param_1", "name": "", "shapeDescriptor": { "ProviderInShape": {"shapeId": "one_of_shape_1","providerDescriptor": { "NoProvider": {} },"consumingParameterId": "one_of_param_1"}}}},
+      { "ShapeParameterAdded": { "shapeId": "one_of_shape_1", "shapeParameterId": "one_of_param_2", "name": "", "shapeDescriptor": { "ProviderInShape": {"shapeId": "one_of_shape_1","providerDescriptor": { "NoProvider": {} },"consumingParameterId": "one_of_param_2"}}}},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "one_of_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "string_shape_1"}},"consumingParameterId": "one_of_param_1" }}}},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "one_of_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "boolean_shape_1"}},"consumingParameterId": "one_of_param_2" }}}},
+      { "FieldAdded": { "fieldId": "field_3", "shapeId": "comment_shape_1", "name": "approved", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_3", "shapeId": "one_of_shape_1"}} }},
+
+      // recursive list of replies
+      { "ShapeAdded": { "shapeId": "list_shape_1", "baseShapeId": "$list", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "list_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "comment_shape_1"}},"consumingParameterId": "$listItem" }}}},
+      { "FieldAdded": { "fieldId": "field_4", "shapeId": "comment_shape_1", "name": "replies", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_4", "shapeId": "list_shape_1"}} }},
+    ]))
+    .expect("should be able to deserialize test events");
+
+    let spec_projection = SpecProjection::from(events);
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+
+    let json_schema = shape_queries.json_schema(&String::from("list_shape_1"));
+    assert_json_snapshot!("can_describe_shapes_as_json_schema__schema", json_schema);
+  }
+
+  #[test]
+  pub fn can_describe_constrained_shapes_as_json_schema() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "event_shape_1", "baseShapeId": "$object", "name": "Event" }},
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "ShapeConstraintSet": { "shapeId": "string_shape_1", "constraintDescriptor": { "FormatConstraint": { "format": "date-time" }}}},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "event_shape_1", "name": "at", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "string_shape_1"}} }},
+      { "ShapeAdded": { "shapeId": "string_shape_2", "baseShapeId": "$string", "name": "" }},
+      { "ShapeConstraintSet": { "shapeId": "string_shape_2", "constraintDescriptor": { "EnumConstraint": { "values": ["created", "deleted"] }}}},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "event_shape_1", "name": "kind", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "string_shape_2"}} }},
+      { "ShapeAdded": { "shapeId": "number_shape_1", "baseShapeId": "$number", "name": "" }},
+      { "ShapeConstraintSet": { "shapeId": "number_shape_1", "constraintDescriptor": { "IntegerConstraint": {}}}},
+      { "FieldAdded": { "fieldId": "field_3", "shapeId": "event_shape_1", "name": "count", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_3", "shapeId": "number_shape_1"}} }},
+    ]))
+    .expect("should be able to deserialize test events");
+
+    let spec_projection = SpecProjection::from(events);
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+
+    let json_schema = shape_queries.json_schema(&String::from("event_shape_1"));
+    let properties = &json_schema["properties"];
+    assert_eq!(
+      properties["at"],
+      json!({ "type": "string", "format": "date-time" })
+    );
+    assert_eq!(
+      properties["kind"],
+      json!({ "type": "string", "enum": ["created", "deleted"] })
+    );
+    assert_eq!(properties["count"], json!({ "type": "integer" }));
+  }
 
-    spec_projection
+  #[test]
+  pub fn can_describe_generic_shapes_as_json_schema() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "user_shape_1", "baseShapeId": "$object", "name": "User" }},
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "user_shape_1", "name": "name", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "string_shape_1"}} }},
+
+      // Page<T> { items: T[], first: T }
+      { "ShapeAdded": { "shapeId": "page_shape_1", "baseShapeId": "$object", "parameters": { "DynamicParameterList": { "shapeParameterIds": [] }}, "name": "Page" }},
+      { "ShapeParameterAdded": { "shapeParameterId": "page_item_1", "shapeId": "page_shape_1", "name": "T", "shapeDescriptor": { "ProviderInShape": { "shapeId": "page_shape_1", "providerDescriptor": { "NoProvider": {} }, "consumingParameterId": "page_item_1" }}}},
+      { "ShapeAdded": { "shapeId": "list_shape_1", "baseShapeId": "$list", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "list_shape_1", "providerDescriptor": { "ParameterProvider": { "shapeParameterId": "page_item_1" }}, "consumingParameterId": "$listItem" }}}},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "page_shape_1", "name": "items", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "list_shape_1"}} }},
+      { "FieldAdded": { "fieldId": "field_3", "shapeId": "page_shape_1", "name": "first", "shapeDescriptor": { "FieldShapeFromParameter": { "fieldId": "field_3", "shapeParameterId": "page_item_1"}} }},
+
+      // Page<User>
+      { "ShapeAdded": { "shapeId": "user_page_shape_1", "baseShapeId": "page_shape_1", "parameters": { "DynamicParameterList": { "shapeParameterIds": [] }}, "name": "UserPage" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "user_page_shape_1", "providerDescriptor": { "ShapeProvider": { "shapeId": "user_shape_1" }}, "consumingParameterId": "page_item_1" }}}},
+    ]))
+    .expect("should be able to deserialize test events");
+
+    let spec_projection = SpecProjection::from(events);
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+
+    let user_schema = json!({
+      "type": "object",
+      "properties": { "name": { "type": "string" } },
+      "required": ["name"],
+    });
+    let json_schema = shape_queries.json_schema(&String::from("user_page_shape_1"));
+    assert_eq!(
+      json_schema["properties"]["items"],
+      json!({ "type": "array", "items": user_schema })
+    );
+    assert_eq!(json_schema["properties"]["first"], user_schema);
+    assert_eq!(json_schema["required"], json!(["first", "items"]));
+
+    // unbound parameters describe values we know nothing about
+    let json_schema = shape_queries.json_schema(&String::from("page_shape_1"));
+    assert_eq!(json_schema["properties"]["first"], json!({}));
   }
 
   #[derive(Debug, Default)]
@@ -1103,4 +2396,176 @@ mod test {
       format!("{}{}", prefix, self.next_id.to_string())
     }
   }
+
+  #[test]
+  pub fn can_index_named_shapes_by_structure() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "ShapeAdded": { "shapeId": "number_shape_1", "baseShapeId": "$number", "name": "" }},
+      { "ShapeAdded": { "shapeId": "optional_shape_1", "baseShapeId": "$optional", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "optional_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "string_shape_1"}},"consumingParameterId": "$optionalInner" }}}},
+
+      // named user
+      { "ShapeAdded": { "shapeId": "user_shape_1", "baseShapeId": "$object", "name": "User" }},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "user_shape_1", "name": "id", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "string_shape_1"}} }},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "user_shape_1", "name": "nickname", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "optional_shape_1"}} }},
+
+      // the same structure, with fields added in another order and other shapes for them
+      { "ShapeAdded": { "shapeId": "string_shape_2", "baseShapeId": "$string", "name": "" }},
+      { "ShapeAdded": { "shapeId": "optional_shape_2", "baseShapeId": "$optional", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "optional_shape_2","providerDescriptor": {"ShapeProvider": {"shapeId": "string_shape_2"}},"consumingParameterId": "$optionalInner" }}}},
+      { "ShapeAdded": { "shapeId": "author_shape_1", "baseShapeId": "$object", "name": "" }},
+      { "FieldAdded": { "fieldId": "field_3", "shapeId": "author_shape_1", "name": "nickname", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_3", "shapeId": "optional_shape_2"}} }},
+      { "FieldAdded": { "fieldId": "field_4", "shapeId": "author_shape_1", "name": "id", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_4", "shapeId": "string_shape_2"}} }},
+
+      // a different structure
+      { "ShapeAdded": { "shapeId": "account_shape_1", "baseShapeId": "$object", "name": "Account" }},
+      { "FieldAdded": { "fieldId": "field_5", "shapeId": "account_shape_1", "name": "id", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_5", "shapeId": "number_shape_1"}} }},
+      { "FieldAdded": { "fieldId": "field_6", "shapeId": "account_shape_1", "name": "nickname", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_6", "shapeId": "optional_shape_1"}} }},
+    ]))
+    .expect("should be able to deserialize test events");
+
+    let spec_projection = SpecProjection::from(events);
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+
+    let user_hash = shape_queries.shape_structural_hash(&String::from("user_shape_1"));
+    let author_hash = shape_queries.shape_structural_hash(&String::from("author_shape_1"));
+    let account_hash = shape_queries.shape_structural_hash(&String::from("account_shape_1"));
+    assert_eq!(user_hash, author_hash);
+    assert_ne!(user_hash, account_hash);
+
+    let named_shapes = shape_queries.named_shape_structures();
+    let reusable_shape = named_shapes
+      .get(author_hash)
+      .expect("author should be equivalent to a named shape");
+    assert_eq!(reusable_shape.shape_id, "user_shape_1");
+    assert_eq!(reusable_shape.name, "User");
+    assert_eq!(
+      named_shapes.get(account_hash).unwrap().shape_id,
+      "account_shape_1"
+    );
+  }
+
+  #[test]
+  pub fn can_list_named_shapes_with_their_usages() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "ShapeAdded": { "shapeId": "user_shape_1", "baseShapeId": "$object", "name": "User" }},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "user_shape_1", "name": "id", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "string_shape_1"}} }},
+
+      // post with an optional author
+      { "ShapeAdded": { "shapeId": "post_shape_1", "baseShapeId": "$object", "name": "" }},
+      { "ShapeRenamed": { "shapeId": "post_shape_1", "name": "Post" }},
+      { "ShapeAdded": { "shapeId": "optional_shape_1", "baseShapeId": "$optional", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "optional_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "user_shape_1"}},"consumingParameterId": "$optionalInner" }}}},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "post_shape_1", "name": "author", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "optional_shape_1"}} }},
+      { "FieldAdded": { "fieldId": "field_3", "shapeId": "post_shape_1", "name": "editor", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_3", "shapeId": "user_shape_1"}} }},
+      { "FieldRemoved": { "fieldId": "field_3" }},
+
+      // list of users
+      { "ShapeAdded": { "shapeId": "list_shape_1", "baseShapeId": "$list", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "list_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "user_shape_1"}},"consumingParameterId": "$listItem" }}}},
+
+      { "PathComponentAdded": { "pathId": "path_1", "parentPathId": "root", "name": "users" }},
+      { "ResponseAddedByPathAndMethod": { "responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200 }},
+      { "ResponseBodySet": { "responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "list_shape_1", "isRemoved": false }}},
+      { "RequestAdded": { "requestId": "request_1", "pathId": "path_1", "httpMethod": "POST" }},
+      { "RequestBodySet": { "requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "user_shape_1", "isRemoved": false }}},
+
+      { "PathComponentAdded": { "pathId": "path_2", "parentPathId": "root", "name": "posts" }},
+      { "ResponseAddedByPathAndMethod": { "responseId": "response_2", "pathId": "path_2", "httpMethod": "POST", "httpStatusCode": 201 }},
+      { "ResponseBodySet": { "responseId": "response_2", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "post_shape_1", "isRemoved": false }}},
+    ]))
+    .expect("should be able to deserialize test events");
+
+    let spec_projection = SpecProjection::from(events);
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+    let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+
+    let named_shapes = shape_queries.named_shapes(&endpoint_queries);
+    assert_json_snapshot!(
+      "can_list_named_shapes_with_their_usages__named_shapes",
+      named_shapes
+    );
+  }
+
+  #[test]
+  pub fn can_generate_commands_to_remove_unreachable_shapes() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      { "ShapeAdded": { "shapeId": "string_shape_1", "baseShapeId": "$string", "name": "" }},
+      { "ShapeAdded": { "shapeId": "number_shape_1", "baseShapeId": "$number", "name": "" }},
+      { "ShapeAdded": { "shapeId": "order_shape_1", "baseShapeId": "$object", "name": "" }},
+      { "FieldAdded": { "fieldId": "field_1", "shapeId": "order_shape_1", "name": "total", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_1", "shapeId": "number_shape_1"}} }},
+
+      // a field that has since been removed, leaving its shape behind
+      { "ShapeAdded": { "shapeId": "string_shape_2", "baseShapeId": "$string", "name": "" }},
+      { "FieldAdded": { "fieldId": "field_2", "shapeId": "order_shape_1", "name": "note", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_2", "shapeId": "string_shape_2"}} }},
+      { "FieldRemoved": { "fieldId": "field_2" }},
+
+      // a previously learned body, replaced by the order
+      { "ShapeAdded": { "shapeId": "string_shape_3", "baseShapeId": "$string", "name": "" }},
+      { "ShapeAdded": { "shapeId": "item_shape_1", "baseShapeId": "$object", "name": "Item" }},
+      { "FieldAdded": { "fieldId": "field_3", "shapeId": "item_shape_1", "name": "sku", "shapeDescriptor": { "FieldShapeFromShape": { "fieldId": "field_3", "shapeId": "string_shape_3"}} }},
+      { "ShapeAdded": { "shapeId": "list_shape_1", "baseShapeId": "$list", "name": "" }},
+      { "ShapeParameterShapeSet": { "shapeDescriptor": { "ProviderInShape": { "shapeId": "list_shape_1","providerDescriptor": {"ShapeProvider": {"shapeId": "item_shape_1"}},"consumingParameterId": "$listItem" }}}},
+      { "ShapeConstraintSet": { "shapeId": "string_shape_3", "constraintDescriptor": { "FormatConstraint": { "format": "uuid" }}}},
+
+      { "PathComponentAdded": { "pathId": "path_1", "parentPathId": "root", "name": "orders" }},
+      { "PathParameterAdded": { "pathId": "path_2", "parentPathId": "path_1", "name": "orderId" }},
+      { "ShapeAdded": { "shapeId": "string_shape_4", "baseShapeId": "$string", "name": "" }},
+      { "PathParameterShapeSet": { "pathId": "path_2", "shapeDescriptor": { "shapeId": "string_shape_4", "isRemoved": false }}},
+      { "ResponseAddedByPathAndMethod": { "responseId": "response_1", "pathId": "path_2", "httpMethod": "GET", "httpStatusCode": 200 }},
+      { "ResponseBodySet": { "responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "list_shape_1", "isRemoved": false }}},
+      { "ResponseBodySet": { "responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "order_shape_1", "isRemoved": false }}},
+    ]))
+    .expect("should be able to deserialize test events");
+
+    let spec_projection = SpecProjection::from(events);
+    let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+
+    let commands = shape_queries
+      .remove_unreachable_shapes_commands(&endpoint_queries)
+      .collect::<Vec<_>>();
+    assert_debug_snapshot!(
+      "can_generate_commands_to_remove_unreachable_shapes__commands",
+      &commands
+    );
+
+    let mut shape_projection = spec_projection.shape().clone();
+    for command in commands {
+      let events = shape_projection
+        .execute(command)
+        .expect("generated commands should be valid");
+      for event in events {
+        shape_projection.apply(event);
+      }
+    }
+
+    let shape_queries = ShapeQueries::new(&shape_projection);
+    assert_eq!(
+      shape_queries
+        .remove_unreachable_shapes_commands(&endpoint_queries)
+        .count(),
+      0
+    );
+    assert!(shape_projection
+      .get_shape_name(&String::from("item_shape_1"))
+      .is_none());
+    assert!(shape_projection
+      .get_shape_constraint(&String::from("string_shape_3"))
+      .is_none());
+    assert!(shape_projection
+      .get_shape_node_index(&String::from("string_shape_4"))
+      .is_some());
+    assert_eq!(
+      shape_queries.json_schema(&String::from("order_shape_1")),
+      json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": { "total": { "type": "number" }},
+        "required": ["total"]
+      })
+    );
+  }
 }
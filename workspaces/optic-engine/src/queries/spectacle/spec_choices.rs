@@ -49,12 +49,21 @@ pub struct ArrayChoice {
   item_shape_id: ShapeId,
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MapChoice {
+  json_type: JsonType,
+  shape_id: ShapeId,
+  value_shape_id: ShapeId,
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(tag = "type")]
 pub enum ShapeChoice {
   Primitive(PrimitiveChoice),
   Object(ObjectChoice),
   Array(ArrayChoice),
+  Map(MapChoice),
   Any,
   Unknown,
 }
@@ -65,6 +74,7 @@ impl ShapeChoice {
       ShapeChoice::Primitive(choice) => Some(&choice.json_type),
       ShapeChoice::Object(choice) => Some(&choice.json_type),
       ShapeChoice::Array(choice) => Some(&choice.json_type),
+      ShapeChoice::Map(choice) => Some(&choice.json_type),
       ShapeChoice::Any => None,
       ShapeChoice::Unknown => None,
     }
@@ -99,12 +109,11 @@ impl<'a> ShapeChoiceQueries<'a> {
       .into_iter()
       .map(move |choice| match choice.core_shape_kind {
         ShapeKind::ObjectKind => {
+          let object_trail = choice.shape_trail();
           let object_fields = queries.resolve_shape_field_id_and_names(&choice.shape_id);
           let fields = object_fields
             .map(|(field_id, name)| {
-              let field_shape_id = queries
-                .resolve_field_shape_node(field_id)
-                .expect("expected field shape to resolve");
+              let field_shape_id = queries.resolve_trail_field_shape(&object_trail, field_id);
 
               ObjectFieldChoice {
                 field_id: field_id.clone(),
@@ -130,7 +139,7 @@ impl<'a> ShapeChoiceQueries<'a> {
               .shape_parameter_id,
           );
           let list_item_shape_id =
-            queries.resolve_parameter_to_shape(&choice.shape_id, shape_parameter_id);
+            queries.resolve_trail_parameter_to_shape(&choice.shape_trail(), shape_parameter_id);
           let output = ArrayChoice {
             shape_id: choice.shape_id.clone(),
             json_type: JsonType::Array,
@@ -138,7 +147,22 @@ impl<'a> ShapeChoiceQueries<'a> {
           };
           ShapeChoice::Array(output)
         }
-        ShapeKind::MapKind => unimplemented!(),
+        ShapeKind::MapKind => {
+          let shape_parameter_id = &String::from(
+            choice
+              .core_shape_kind
+              .get_parameter_descriptor()
+              .expect("expected $map to have a parameter descriptor")
+              .shape_parameter_id,
+          );
+          let map_value_shape_id =
+            queries.resolve_trail_parameter_to_shape(&choice.shape_trail(), shape_parameter_id);
+          ShapeChoice::Map(MapChoice {
+            shape_id: choice.shape_id.clone(),
+            json_type: JsonType::Object,
+            value_shape_id: map_value_shape_id,
+          })
+        }
         ShapeKind::OneOfKind => unreachable!(),
         ShapeKind::AnyKind => ShapeChoice::Any,
         ShapeKind::UnknownKind => ShapeChoice::Unknown,
@@ -196,6 +220,7 @@ mod test {
   use crate::commands::SpecCommand;
   use crate::events::SpecEvent;
   use crate::projections::SpecProjection;
+  use crate::test_helpers::assert_valid_commands;
   use crate::Aggregate;
   use insta::assert_debug_snapshot;
   use serde_json::json;
@@ -242,24 +267,6 @@ mod test {
     );
   }
 
-  fn assert_valid_commands(
-    mut spec_projection: SpecProjection,
-    commands: impl IntoIterator<Item = SpecCommand>,
-  ) -> SpecProjection {
-    // let mut spec_projection = SpecProjection::default();
-    for command in commands {
-      let events = spec_projection
-        .execute(command)
-        .expect("generated commands must be valid");
-
-      for event in events {
-        spec_projection.apply(event)
-      }
-    }
-
-    spec_projection
-  }
-
   #[derive(Debug, Default)]
   struct SequentialIdGenerator {
     next_id: u32,
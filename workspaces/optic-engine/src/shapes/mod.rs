@@ -9,7 +9,7 @@ use crate::projections::shape::ShapeProjection;
 use crate::queries::shape::ShapeQueries;
 use crate::state::shape::ShapeId;
 use crate::InteractionDiffResult;
-pub use result::ShapeDiffResult;
+pub use result::{ObservedValue, ShapeDiffResult};
 use std::collections::HashMap;
 pub use traverser::{JsonTrail, JsonTrailPathComponent, ShapeTrail, ShapeTrailPathComponent};
 use visitors::BodyVisitors;
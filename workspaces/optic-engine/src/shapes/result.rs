@@ -1,4 +1,5 @@
 use crate::shapes::{JsonTrail, ShapeTrail};
+use crate::state::shape::{ShapeConstraintDescriptor, StringFormat};
 use seahash::hash;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
@@ -16,6 +17,35 @@ pub enum ShapeDiffResult {
     json_trail: JsonTrail,
     shape_trail: ShapeTrail,
   },
+  #[serde(rename_all = "camelCase")]
+  UnmatchedConstraint {
+    json_trail: JsonTrail,
+    shape_trail: ShapeTrail,
+    expected: ShapeConstraintDescriptor,
+    observed: ObservedValue,
+  },
+}
+
+/// What was observed of a value that didn't satisfy the constraint of its shape
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Hash)]
+pub enum ObservedValue {
+  #[serde(rename_all = "camelCase")]
+  String {
+    value: Option<String>,
+    format: Option<StringFormat>,
+  },
+}
+
+impl ObservedValue {
+  // Different values of the same kind make for the same diff
+  fn normalized(&self) -> Self {
+    match self {
+      ObservedValue::String { format, .. } => ObservedValue::String {
+        value: None,
+        format: *format,
+      },
+    }
+  }
 }
 
 impl ShapeDiffResult {
@@ -35,6 +65,17 @@ impl ShapeDiffResult {
         json_trail: json_trail.normalized(),
         shape_trail: shape_trail.clone(),
       },
+      ShapeDiffResult::UnmatchedConstraint {
+        json_trail,
+        shape_trail,
+        expected,
+        observed,
+      } => ShapeDiffResult::UnmatchedConstraint {
+        json_trail: json_trail.normalized(),
+        shape_trail: shape_trail.clone(),
+        expected: expected.clone(),
+        observed: observed.normalized(),
+      },
     }
   }
   pub fn fingerprint(&self) -> String {
@@ -64,6 +105,18 @@ impl Hash for ShapeDiffResult {
         Hash::hash(&json_trail.normalized(), hash_state);
         Hash::hash(shape_trail, hash_state);
       }
+      ShapeDiffResult::UnmatchedConstraint {
+        json_trail,
+        shape_trail,
+        expected,
+        observed,
+      } => {
+        Hash::hash(&core::mem::discriminant(self), hash_state);
+        Hash::hash(&json_trail.normalized(), hash_state);
+        Hash::hash(shape_trail, hash_state);
+        Hash::hash(expected, hash_state);
+        Hash::hash(&observed.normalized(), hash_state);
+      }
     }
   }
 }
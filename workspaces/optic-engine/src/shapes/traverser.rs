@@ -2,9 +2,10 @@ use super::visitors::{
   BodyArrayVisitor, BodyObjectKeyVisitor, BodyObjectVisitor, BodyPrimitiveVisitor, BodyVisitors,
 };
 use crate::queries::shape::{ChoiceOutput, ShapeQueries};
-use crate::state::body::BodyDescriptor;
+use crate::state::body::{BodyDescriptor, ObjectDescriptor};
 use crate::state::shape::{
-  FieldId, FieldShapeDescriptor, FieldShapeFromShape, ShapeId, ShapeKind, ShapeParameterId,
+  FieldId, FieldShapeDescriptor, FieldShapeFromShape, ShapeConstraintDescriptor, ShapeId,
+  ShapeKind, ShapeParameterId,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -60,8 +61,8 @@ impl<'a> Traverser<'a> {
           .iter()
           .flat_map(move |choice| {
             if let ShapeKind::ListKind = &choice.core_shape_kind {
-              let item_shape_id = self.shape_queries.resolve_parameter_to_shape(
-                &choice.shape_id,
+              let item_shape_id = self.shape_queries.resolve_trail_parameter_to_shape(
+                &choice.shape_trail(),
                 &String::from(
                   choice
                     .core_shape_kind
@@ -123,9 +124,12 @@ impl<'a> Traverser<'a> {
           BodyDescriptor::Object(fields) => fields,
           _ => unreachable!("expect body to be an object"),
         };
+        let matching_choices = self.fitting_object_choices(&object, matching_choices);
 
+        // maps allow for any key, so only objects have their keys visited
         let object_key_choices = matching_choices
           .iter()
+          .filter(|choice| matches!(choice.core_shape_kind, ShapeKind::ObjectKind))
           .map(|choice| {
             if let ShapeKind::ObjectKind = &choice.core_shape_kind {
               // - find field node by key in object's field node edges
@@ -139,8 +143,7 @@ impl<'a> Traverser<'a> {
                   .map(|(field_id, field_name)| {
                     let field_shape_id = self
                       .shape_queries
-                      .resolve_field_shape_node(&field_id)
-                      .unwrap();
+                      .resolve_trail_field_shape(&choice.shape_trail(), field_id);
                     let field_core_shape_kind =
                       self.shape_queries.resolve_to_core_shape(&field_shape_id);
                     (
@@ -183,8 +186,7 @@ impl<'a> Traverser<'a> {
                 let field_id = field_id_option.unwrap();
                 let field_shape_id = self
                   .shape_queries
-                  .resolve_field_shape_node(&field_id)
-                  .expect("field node should have an edge to a shape node describing its value");
+                  .resolve_trail_field_shape(&choice.shape_trail(), &field_id);
                 //dbg!("shape-traverser: field_shape_id", &field_shape_id);
 
                 let field_trail =
@@ -196,8 +198,28 @@ impl<'a> Traverser<'a> {
                       parent_object_shape_id: choice.shape_id.clone(),
                     });
                 self.shape_queries.list_trail_choices(&field_trail)
+              } else if let ShapeKind::MapKind = &choice.core_shape_kind {
+                // every value of a map shares the same shape
+                let value_shape_id = self.shape_queries.resolve_trail_parameter_to_shape(
+                  &choice.shape_trail(),
+                  &String::from(
+                    choice
+                      .core_shape_kind
+                      .get_parameter_descriptor()
+                      .unwrap()
+                      .shape_parameter_id,
+                  ),
+                );
+                let value_trail =
+                  choice
+                    .shape_trail()
+                    .with_component(ShapeTrailPathComponent::MapValueTrail {
+                      map_shape_id: choice.shape_id.clone(),
+                      value_shape_id,
+                    });
+                self.shape_queries.list_trail_choices(&value_trail)
               } else {
-                unreachable!("should only contain choices of object kind");
+                unreachable!("should only contain choices of object or map kind");
               }
             })
             .collect::<Vec<ChoiceOutput>>();
@@ -225,6 +247,85 @@ impl<'a> Traverser<'a> {
       }
     }
   }
+
+  // An object body can only be one of several object choices (like the branches of a
+  // discriminated union), so when some of them fit it, the others aren't compared against it
+  fn fitting_object_choices(
+    &self,
+    object: &ObjectDescriptor,
+    choices: Vec<ChoiceOutput>,
+  ) -> Vec<ChoiceOutput> {
+    let is_object_choice =
+      |choice: &ChoiceOutput| matches!(choice.core_shape_kind, ShapeKind::ObjectKind);
+    if choices
+      .iter()
+      .filter(|choice| is_object_choice(choice))
+      .count()
+      < 2
+    {
+      return choices;
+    }
+
+    // narrow by discriminating values first, so a body with a known discriminator is only
+    // compared against its own variant, even when it's missing some of its fields
+    [false, true]
+      .iter()
+      .fold(choices, |choices, &requires_all_fields| {
+        let fitting_choices = choices
+          .iter()
+          .filter(|choice| {
+            !is_object_choice(choice)
+              || self.object_fits_choice(object, choice, requires_all_fields)
+          })
+          .cloned()
+          .collect::<Vec<_>>();
+
+        if fitting_choices.iter().any(is_object_choice) {
+          fitting_choices
+        } else {
+          choices
+        }
+      })
+  }
+
+  // Whether strings of enumerated fields of the choice are one of their values and, when required,
+  // whether the object has all required fields of the choice
+  fn object_fits_choice(
+    &self,
+    object: &ObjectDescriptor,
+    choice: &ChoiceOutput,
+    requires_all_fields: bool,
+  ) -> bool {
+    let shape_trail = choice.shape_trail();
+    self
+      .shape_queries
+      .resolve_shape_field_id_and_names(&choice.shape_id)
+      .all(|(field_id, field_name)| {
+        let field_shape_id = self
+          .shape_queries
+          .resolve_trail_field_shape(&shape_trail, field_id);
+
+        match object.get(field_name) {
+          None => {
+            !requires_all_fields
+              || matches!(
+                self.shape_queries.resolve_to_core_shape(&field_shape_id),
+                ShapeKind::OptionalKind
+              )
+          }
+          Some(BodyDescriptor::String(string)) => {
+            match self.shape_queries.resolve_shape_constraint(&field_shape_id) {
+              Some(ShapeConstraintDescriptor::EnumConstraint(constraint)) => string
+                .values()
+                .iter()
+                .all(|value| constraint.values.contains(value)),
+              _ => true,
+            }
+          }
+          Some(_) => true,
+        }
+      })
+  }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Hash)]
@@ -245,6 +346,13 @@ pub enum ShapeTrailPathComponent {
     item_shape_id: ShapeId,
   },
   #[serde(rename_all = "camelCase")]
+  MapTrail { shape_id: ShapeId },
+  #[serde(rename_all = "camelCase")]
+  MapValueTrail {
+    map_shape_id: ShapeId,
+    value_shape_id: ShapeId,
+  },
+  #[serde(rename_all = "camelCase")]
   OneOfTrail { shape_id: ShapeId },
   #[serde(rename_all = "camelCase")]
   OneOfItemTrail {
@@ -366,6 +474,23 @@ impl JsonTrail {
     }
   }
 
+  // Swaps the object key component at the given depth, if there is one, for the object itself,
+  // standing for any of its keys
+  pub(crate) fn with_object_at(&self, depth: usize) -> Option<Self> {
+    match self.path.get(depth) {
+      Some(JsonTrailPathComponent::JsonObjectKey { .. }) => {
+        let mut new_trail = self.clone();
+        new_trail.path[depth] = JsonTrailPathComponent::JsonObject {};
+        Some(new_trail)
+      }
+      _ => None,
+    }
+  }
+
+  pub fn depth(&self) -> usize {
+    self.path.len()
+  }
+
   pub fn pop(&mut self) -> Option<JsonTrailPathComponent> {
     self.path.pop()
   }
@@ -3,10 +3,10 @@ use super::{
   BodyVisitors, VisitorResults,
 };
 use crate::queries::shape::ChoiceOutput;
-use crate::shapes::ShapeDiffResult;
 use crate::shapes::{JsonTrail, JsonTrailPathComponent, ShapeTrail, ShapeTrailPathComponent};
+use crate::shapes::{ObservedValue, ShapeDiffResult};
 use crate::state::body::BodyDescriptor;
-use crate::state::shape::{FieldId, ShapeId, ShapeKind};
+use crate::state::shape::{FieldId, ShapeConstraintDescriptor, ShapeId, ShapeKind};
 use serde_json::Value as JsonValue;
 
 pub struct DiffVisitors {
@@ -95,11 +95,17 @@ impl BodyPrimitiveVisitor<ShapeDiffResult> for DiffPrimitiveVisitor {
           ShapeKind::BooleanKind => true,
           _ => false,
         },
-        BodyDescriptor::Number => match choice.core_shape_kind {
-          ShapeKind::NumberKind => true,
+        BodyDescriptor::Number(number) => match choice.core_shape_kind {
+          // integers are told apart from other numbers, as clients represent them differently
+          ShapeKind::NumberKind => match &choice.constraint {
+            Some(ShapeConstraintDescriptor::IntegerConstraint(_)) => {
+              number.is_integer() != Some(false)
+            }
+            _ => true,
+          },
           _ => false,
         },
-        BodyDescriptor::String => match choice.core_shape_kind {
+        BodyDescriptor::String(_) => match choice.core_shape_kind {
           ShapeKind::StringKind => true,
           _ => false,
         },
@@ -119,7 +125,62 @@ impl BodyPrimitiveVisitor<ShapeDiffResult> for DiffPrimitiveVisitor {
             shape_trail: choice.shape_trail(),
           });
         });
+    } else {
+      let unsatisfied_constraints = matched
+        .iter()
+        .map(|&choice| {
+          let observed = choice
+            .constraint
+            .as_ref()
+            .and_then(|constraint| unsatisfied_constraint(&body, constraint));
+          (choice, observed)
+        })
+        .collect::<Vec<_>>();
+
+      // the value is of the right kind, but not one any of the shapes allow for
+      if unsatisfied_constraints
+        .iter()
+        .all(|(_, observed)| observed.is_some())
+      {
+        for (choice, observed) in unsatisfied_constraints {
+          self.results.push(ShapeDiffResult::UnmatchedConstraint {
+            json_trail: json_trail.clone(),
+            shape_trail: choice.shape_trail(),
+            expected: choice.constraint.clone().unwrap(),
+            observed: observed.unwrap(),
+          });
+        }
+      }
+    }
+  }
+}
+
+// Values are only held against what is known about them: shape hashes don't carry the values
+// they were computed from, and only describe formats when the capturing agent supports it
+fn unsatisfied_constraint(
+  body: &BodyDescriptor,
+  constraint: &ShapeConstraintDescriptor,
+) -> Option<ObservedValue> {
+  match (constraint, body) {
+    (ShapeConstraintDescriptor::FormatConstraint(constraint), BodyDescriptor::String(string))
+      if string.is_described() && string.format() != Some(constraint.format) =>
+    {
+      Some(ObservedValue::String {
+        value: string.values().first().cloned(),
+        format: string.format(),
+      })
+    }
+    (ShapeConstraintDescriptor::EnumConstraint(constraint), BodyDescriptor::String(string)) => {
+      string
+        .values()
+        .iter()
+        .find(|value| !constraint.values.contains(value))
+        .map(|value| ObservedValue::String {
+          value: Some(value.clone()),
+          format: string.format(),
+        })
     }
+    _ => None,
   }
 }
 
@@ -222,7 +283,7 @@ impl BodyObjectVisitor<ShapeDiffResult> for DiffObjectVisitor {
     let (matched, unmatched): (Vec<&ChoiceOutput>, Vec<&ChoiceOutput>) =
       trail_choices.into_iter().partition(|choice| match body {
         BodyDescriptor::Object(_) => match choice.core_shape_kind {
-          ShapeKind::ObjectKind => true,
+          ShapeKind::ObjectKind | ShapeKind::MapKind => true,
           _ => false,
         },
         _ => unreachable!("should only call object visitor for object body types"),
@@ -0,0 +1,109 @@
+use crate::events::{EventContext, RfcEvent, SpecEvent, WithEventContext};
+use crate::projections::spec_events::{entity_change, referenced_entity_ids, EntityChange};
+use crate::projections::CommitId;
+use cqrs_core::Event;
+use serde::Serialize;
+
+/// List, in order, the events that touched the entity with `entity_id` (a path, request, response,
+/// shape, field or any other spec entity), along with the batch commit they were made in and who
+/// made them.
+pub fn blame<I>(events: I, entity_id: &str) -> Blame
+where
+  I: IntoIterator<Item = SpecEvent>,
+{
+  let mut entries = vec![];
+  let mut current_batch: Option<(CommitId, String, Option<EventContext>)> = None;
+
+  for event in events {
+    match &event {
+      SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(e)) => {
+        current_batch = Some((
+          e.batch_id.clone(),
+          e.commit_message.clone(),
+          e.event_context.clone(),
+        ));
+        continue;
+      }
+      SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(_)) => {
+        current_batch = None;
+        continue;
+      }
+      _ => {}
+    }
+
+    let change = match entity_change(&event) {
+      Some((changed_id, change)) if changed_id == entity_id => BlameChange::from(change),
+      _ if referenced_entity_ids(&event).contains(&entity_id) => BlameChange::Referenced,
+      _ => continue,
+    };
+
+    // events recorded outside of batch commits, or by older clients, might not carry their own
+    // context, in which case the one of the batch commit they're part of is the best we know
+    let event_context = event.event_context().or_else(|| {
+      current_batch
+        .as_ref()
+        .and_then(|(_, _, context)| context.as_ref())
+    });
+
+    entries.push(BlameEntry {
+      change,
+      event_type: String::from(event.event_type()),
+      batch_id: current_batch
+        .as_ref()
+        .map(|(batch_id, _, _)| batch_id.clone()),
+      commit_message: current_batch
+        .as_ref()
+        .map(|(_, commit_message, _)| commit_message.clone()),
+      client_id: event_context.map(|context| context.client_id.clone()),
+      client_session_id: event_context.map(|context| context.client_session_id.clone()),
+      created_at: event_context.map(|context| context.created_at.clone()),
+      event,
+    });
+  }
+
+  Blame {
+    entity_id: String::from(entity_id),
+    entries,
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blame {
+  pub entity_id: String,
+  pub entries: Vec<BlameEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlameEntry {
+  pub change: BlameChange,
+  pub event_type: String,
+  pub batch_id: Option<CommitId>,
+  pub commit_message: Option<String>,
+  pub client_id: Option<String>,
+  pub client_session_id: Option<String>,
+  pub created_at: Option<String>,
+  pub event: SpecEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlameChange {
+  Added,
+  Modified,
+  Removed,
+  /// The event changed another entity, building on this one (like a request added to a path, or a
+  /// field added to a shape)
+  Referenced,
+}
+
+impl From<EntityChange> for BlameChange {
+  fn from(change: EntityChange) -> Self {
+    match change {
+      EntityChange::Added => BlameChange::Added,
+      EntityChange::Modified { .. } => BlameChange::Modified,
+      EntityChange::Removed => BlameChange::Removed,
+    }
+  }
+}
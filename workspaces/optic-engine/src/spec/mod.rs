@@ -7,6 +7,14 @@ use crate::projections::SpecProjection;
 use crate::queries::EndpointQueries;
 use cqrs_core::Aggregate;
 
+mod blame;
+mod repair;
+mod revert;
+
+pub use blame::{blame, Blame, BlameChange, BlameEntry};
+pub use repair::repair_spec_conflicts;
+pub use revert::{revert_batch, revert_batch_commands, RevertError};
+
 /// Create an interface that allows commands to be applied to a spec as part of a single
 /// batch. Will produce events enclosed in `BatchCommitStarted` and `BatchCommitEnded`.
 pub fn append_batch(
@@ -70,6 +78,10 @@ impl AppendedBatch {
     Ok(())
   }
 
+  pub fn spec_projection(&self) -> &SpecProjection {
+    self.command_handler.spec_projection()
+  }
+
   pub fn commit(self) -> Vec<SpecEvent> {
     let mut new_events = self.new_events;
     let end_event = self
@@ -0,0 +1,78 @@
+use super::append_batch;
+use crate::commands::{CommandContext, ShapeCommand, SpecCommand, SpecCommandError};
+use crate::projections::SpecProjection;
+use crate::queries::endpoint::{EndpointQueries, RepairConflictsCommands};
+use crate::queries::shape::ShapeQueries;
+use crate::state::SpecIdGenerator;
+
+/// Generate the commands repairing a spec: duplicated paths, requests and responses are merged as
+/// by `EndpointQueries::repair_conflicts_commands`, after which structurally duplicated shapes are
+/// merged, and the paths and shapes left orphaned by it all are removed. Every step looks at the spec
+/// as the steps before it left it, so the commands are applied as they're generated, failing when
+/// one of them can't be.
+pub fn repair_spec_conflicts(
+  spec_projection: &SpecProjection,
+  id_generator: &mut impl SpecIdGenerator,
+) -> Result<RepairConflictsCommands, Box<SpecCommandError>> {
+  let conflicts = spec_projection.conflicts().spec_conflicts();
+  let mut repair = EndpointQueries::new(spec_projection.endpoint())
+    .repair_conflicts_commands(&conflicts, id_generator);
+
+  let mut batch = append_batch(
+    spec_projection.clone(),
+    String::new(),
+    CommandContext::default(),
+  );
+  for command in &repair.commands {
+    batch.with_command(command.clone())?;
+  }
+
+  let (merged_shapes, merge_shape_commands) = {
+    let repaired_projection = batch.spec_projection();
+    ShapeQueries::new(repaired_projection.shape())
+      .merge_duplicate_shapes_commands(&EndpointQueries::new(repaired_projection.endpoint()))
+  };
+  for command in merge_shape_commands {
+    batch.with_command(command.clone())?;
+    repair.commands.push(command);
+  }
+  repair.merged_shapes = merged_shapes;
+
+  // unused paths come after their unused child paths, so they can be removed in order
+  let remove_path_commands = {
+    let endpoint_queries = EndpointQueries::new(batch.spec_projection().endpoint());
+    let unused_path_ids = endpoint_queries.resolve_unused_paths().collect::<Vec<_>>();
+    let commands = unused_path_ids
+      .iter()
+      .flat_map(|path_id| {
+        endpoint_queries
+          .delete_path_commands(path_id)
+          .expect("unused path should exist")
+      })
+      .map(SpecCommand::from)
+      .collect::<Vec<_>>();
+    repair.removed_path_ids = unused_path_ids;
+    commands
+  };
+  for command in remove_path_commands {
+    batch.with_command(command.clone())?;
+    repair.commands.push(command);
+  }
+
+  let remove_shape_commands = {
+    let repaired_projection = batch.spec_projection();
+    ShapeQueries::new(repaired_projection.shape())
+      .remove_unreachable_shapes_commands(&EndpointQueries::new(repaired_projection.endpoint()))
+      .map(SpecCommand::from)
+      .collect::<Vec<_>>()
+  };
+  for command in remove_shape_commands {
+    if let SpecCommand::ShapeCommand(ShapeCommand::RemoveShape(remove_shape)) = &command {
+      repair.removed_shape_ids.push(remove_shape.shape_id.clone());
+    }
+    batch.with_command(command.clone())?;
+    repair.commands.push(command);
+  }
+
+  Ok(repair)
+}
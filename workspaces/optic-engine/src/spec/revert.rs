@@ -0,0 +1,646 @@
+use super::append_batch;
+use crate::commands::{
+  endpoint as endpoint_commands, shape as shape_commands, CommandContext, EndpointCommand,
+  RfcCommand, ShapeCommand, SpecCommand, SpecCommandError,
+};
+use crate::events::{EndpointEvent, Event, RfcEvent, ShapeEvent, SpecEvent};
+use crate::projections::spec_events::{entity_change, referenced_entity_ids, EntityChange};
+use crate::projections::{CommitId, SpecProjection};
+use crate::state::endpoint::{
+  QueryParametersShapeDescriptor, RequestId, RequestParameterId, ResponseId,
+  ShapedRequestParameterShapeDescriptor, HEADER_PARAMETER_LOCATION,
+};
+use crate::state::shape::{
+  FieldId, FieldShapeDescriptor, NoConstraint, NoProvider, ParameterShapeDescriptor,
+  ProviderDescriptor, ShapeConstraintDescriptor,
+};
+use std::collections::HashSet;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RevertError {
+  #[error("could not find completed batch commit '{0}'")]
+  UnknownBatchCommit(CommitId),
+  #[error("event of type '{0}' can not be reverted")]
+  Irreversible(&'static str),
+  #[error("'{entity_id}' was changed by the reverted batch, but is depended on by later batch commit '{batch_id}'")]
+  DependedOn {
+    entity_id: String,
+    batch_id: CommitId,
+  },
+  #[error("command reverting the batch could not be applied: {0:?}")]
+  Command(Box<SpecCommandError>),
+}
+
+/// Append a batch commit to the spec, undoing the changes made by an earlier batch commit.
+pub fn revert_batch(
+  events: Vec<SpecEvent>,
+  batch_id: &CommitId,
+  batch_command_context: CommandContext,
+) -> Result<Vec<SpecEvent>, RevertError> {
+  let (commit_message, commands) = {
+    let reverted_batch = RevertedBatch::from_events(&events, batch_id)?;
+    (
+      reverted_batch.commit_message.clone(),
+      reverted_batch.inverse_commands()?,
+    )
+  };
+
+  let mut batch = append_batch(
+    SpecProjection::from(events),
+    format!(
+      "Revert \"{}\"\n\nThis reverts batch commit {}.",
+      commit_message
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default(),
+      batch_id
+    ),
+    batch_command_context,
+  );
+  for command in commands {
+    batch
+      .with_command(command)
+      .map_err(|err| RevertError::Command(Box::new(err)))?;
+  }
+
+  Ok(batch.commit())
+}
+
+/// The commands undoing the changes made by a batch commit, in the order they should be applied.
+/// Fails when a batch commit made after it changes or builds on anything the batch changed.
+pub fn revert_batch_commands(
+  events: &[SpecEvent],
+  batch_id: &CommitId,
+) -> Result<Vec<SpecCommand>, RevertError> {
+  RevertedBatch::from_events(events, batch_id)?.inverse_commands()
+}
+
+struct RevertedBatch<'a> {
+  commit_message: String,
+  // the events up to and including the batch commit, leaving out those of other branches
+  events_at_commit: Vec<SpecEvent>,
+  batch_range: std::ops::Range<usize>,
+  later_events: &'a [SpecEvent],
+}
+
+impl<'a> RevertedBatch<'a> {
+  fn from_events(events: &'a [SpecEvent], batch_id: &CommitId) -> Result<Self, RevertError> {
+    let unknown_batch_commit = || RevertError::UnknownBatchCommit(batch_id.clone());
+
+    let events_at_commit = SpecProjection::events_at_commit(events.iter().cloned(), batch_id)
+      .ok_or_else(unknown_batch_commit)?;
+    let (batch_start, commit_message) = events_at_commit
+      .iter()
+      .enumerate()
+      .find_map(|(index, event)| match event {
+        SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(e)) if e.batch_id == *batch_id => {
+          Some((index, e.commit_message.clone()))
+        }
+        _ => None,
+      })
+      .ok_or_else(unknown_batch_commit)?;
+    let batch_end = match events_at_commit.last() {
+      Some(SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(e))) if e.batch_id == *batch_id => {
+        events_at_commit.len() - 1
+      }
+      _ => return Err(unknown_batch_commit()),
+    };
+
+    let later_events_start = events
+      .iter()
+      .position(|event| {
+        matches!(event, SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(e)) if e.batch_id == *batch_id)
+      })
+      .map(|index| index + 1)
+      .ok_or_else(unknown_batch_commit)?;
+
+    Ok(Self {
+      commit_message,
+      events_at_commit,
+      batch_range: (batch_start + 1)..batch_end,
+      later_events: &events[later_events_start..],
+    })
+  }
+
+  fn inverse_commands(&self) -> Result<Vec<SpecCommand>, RevertError> {
+    self.validate_later_events()?;
+
+    let mut commands = vec![];
+    let mut shape_removals = vec![];
+    for index in self.batch_range.clone().rev() {
+      let event = &self.events_at_commit[index];
+      let prior_events = &self.events_at_commit[..index];
+
+      match inverse_command(event, prior_events)? {
+        // shapes can only be removed once no longer used by endpoints or other shapes
+        Some(command @ SpecCommand::ShapeCommand(ShapeCommand::RemoveShape(_))) => {
+          shape_removals.push(command)
+        }
+        Some(command) => commands.push(command),
+        None => {}
+      }
+    }
+    commands.append(&mut shape_removals);
+
+    Ok(commands)
+  }
+
+  // Later batch commits can't change what the batch changed, nor build on what it added, as
+  // reverting the batch would undo or orphan their changes.
+  fn validate_later_events(&self) -> Result<(), RevertError> {
+    let mut changed_entity_ids = HashSet::new();
+    let mut added_entity_ids = HashSet::new();
+    for event in &self.events_at_commit[self.batch_range.clone()] {
+      if let Some((entity_id, change)) = entity_change(event) {
+        if matches!(change, EntityChange::Added) {
+          added_entity_ids.insert(entity_id.clone());
+        }
+        changed_entity_ids.insert(entity_id);
+      }
+    }
+
+    let mut later_batch_id = None;
+    for event in self.later_events {
+      if let SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(e)) = event {
+        later_batch_id = Some(e.batch_id.clone());
+      }
+
+      let changed_entity_id = entity_change(event)
+        .map(|(entity_id, _)| entity_id)
+        .filter(|entity_id| changed_entity_ids.contains(entity_id));
+      let referenced_entity_id = referenced_entity_ids(event)
+        .into_iter()
+        .find(|entity_id| added_entity_ids.contains(*entity_id))
+        .map(String::from);
+
+      if let Some(entity_id) = changed_entity_id.or(referenced_entity_id) {
+        return Err(RevertError::DependedOn {
+          entity_id,
+          batch_id: later_batch_id.unwrap_or_else(|| String::from("unknown-batch")),
+        });
+      }
+    }
+
+    Ok(())
+  }
+}
+
+// Maps an event to the command restoring what it changed, looking up the state it replaced in the
+// events preceding it.
+fn inverse_command(
+  event: &SpecEvent,
+  prior_events: &[SpecEvent],
+) -> Result<Option<SpecCommand>, RevertError> {
+  let irreversible = || RevertError::Irreversible(event.event_type());
+
+  let command = match event {
+    SpecEvent::EndpointEvent(event) => match event {
+      EndpointEvent::PathComponentAdded(e) => {
+        EndpointCommand::remove_path_component(e.path_id.clone()).into()
+      }
+      EndpointEvent::PathComponentRenamed(e) => {
+        let name = latest(prior_events, |event| match event {
+          SpecEvent::EndpointEvent(EndpointEvent::PathComponentAdded(prior))
+            if prior.path_id == e.path_id =>
+          {
+            Some(prior.name.clone())
+          }
+          SpecEvent::EndpointEvent(EndpointEvent::PathComponentRenamed(prior))
+            if prior.path_id == e.path_id =>
+          {
+            Some(prior.name.clone())
+          }
+          _ => None,
+        })
+        .ok_or_else(irreversible)?;
+
+        EndpointCommand::RenamePathComponent(endpoint_commands::RenamePathComponent {
+          path_id: e.path_id.clone(),
+          name,
+        })
+        .into()
+      }
+
+      EndpointEvent::PathParameterAdded(e) => {
+        EndpointCommand::remove_path_parameter(e.path_id.clone()).into()
+      }
+      EndpointEvent::PathParameterShapeSet(e) => {
+        let shape_descriptor = latest(prior_events, |event| match event {
+          SpecEvent::EndpointEvent(EndpointEvent::PathParameterShapeSet(prior))
+            if prior.path_id == e.path_id =>
+          {
+            Some(prior.shape_descriptor.clone())
+          }
+          _ => None,
+        })
+        .unwrap_or_else(|| ShapedRequestParameterShapeDescriptor {
+          shape_id: e.shape_descriptor.shape_id.clone(),
+          is_removed: true,
+        });
+
+        EndpointCommand::SetPathParameterShape(endpoint_commands::SetPathParameterShape {
+          path_id: e.path_id.clone(),
+          shaped_request_parameter_shape_descriptor: shape_descriptor,
+        })
+        .into()
+      }
+      EndpointEvent::PathParameterRenamed(e) => {
+        let name = latest(prior_events, |event| match event {
+          SpecEvent::EndpointEvent(EndpointEvent::PathParameterAdded(prior))
+            if prior.path_id == e.path_id =>
+          {
+            Some(prior.name.clone())
+          }
+          SpecEvent::EndpointEvent(EndpointEvent::PathParameterRenamed(prior))
+            if prior.path_id == e.path_id =>
+          {
+            Some(prior.name.clone())
+          }
+          _ => None,
+        })
+        .ok_or_else(irreversible)?;
+
+        EndpointCommand::RenamePathParameter(endpoint_commands::RenamePathParameter {
+          path_id: e.path_id.clone(),
+          name,
+        })
+        .into()
+      }
+
+      EndpointEvent::QueryParametersAdded(e) => {
+        EndpointCommand::remove_query_parameters(e.query_parameters_id.clone()).into()
+      }
+      EndpointEvent::QueryParametersShapeSet(e) => {
+        let shape_descriptor = latest(prior_events, |event| match event {
+          SpecEvent::EndpointEvent(EndpointEvent::QueryParametersShapeSet(prior))
+            if prior.query_parameters_id == e.query_parameters_id =>
+          {
+            Some(prior.shape_descriptor.clone())
+          }
+          _ => None,
+        })
+        .unwrap_or_else(|| QueryParametersShapeDescriptor {
+          shape_id: e.shape_descriptor.shape_id.clone(),
+          is_removed: true,
+        });
+
+        EndpointCommand::set_query_parameters_shape(
+          e.query_parameters_id.clone(),
+          shape_descriptor.shape_id,
+          shape_descriptor.is_removed,
+        )
+        .into()
+      }
+
+      EndpointEvent::RequestParameterAdded(e)
+        if e.parameter_location == HEADER_PARAMETER_LOCATION =>
+      {
+        EndpointCommand::remove_header_parameter(e.parameter_id.clone()).into()
+      }
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e)
+        if e.parameter_location == HEADER_PARAMETER_LOCATION =>
+      {
+        EndpointCommand::remove_header_parameter(e.parameter_id.clone()).into()
+      }
+      EndpointEvent::ResponseParameterAdded(e)
+        if e.parameter_location == HEADER_PARAMETER_LOCATION =>
+      {
+        EndpointCommand::remove_header_parameter(e.parameter_id.clone()).into()
+      }
+      EndpointEvent::RequestParameterRenamed(e) => {
+        let name =
+          latest(prior_events, |event| match event {
+            SpecEvent::EndpointEvent(EndpointEvent::RequestParameterAdded(prior))
+              if prior.parameter_id == e.parameter_id =>
+            {
+              Some(prior.name.clone())
+            }
+            SpecEvent::EndpointEvent(EndpointEvent::RequestParameterAddedByPathAndMethod(
+              prior,
+            )) if prior.parameter_id == e.parameter_id => Some(prior.name.clone()),
+            SpecEvent::EndpointEvent(EndpointEvent::ResponseParameterAdded(prior))
+              if prior.parameter_id == e.parameter_id =>
+            {
+              Some(prior.name.clone())
+            }
+            SpecEvent::EndpointEvent(EndpointEvent::RequestParameterRenamed(prior))
+              if prior.parameter_id == e.parameter_id =>
+            {
+              Some(prior.name.clone())
+            }
+            _ => None,
+          })
+          .ok_or_else(irreversible)?;
+
+        EndpointCommand::rename_header_parameter(e.parameter_id.clone(), name).into()
+      }
+      EndpointEvent::RequestParameterShapeSet(e) => {
+        previous_header_parameter_shape(&e.parameter_id, prior_events)
+      }
+      EndpointEvent::RequestParameterShapeUnset(e) => {
+        previous_header_parameter_shape(&e.parameter_id, prior_events)
+      }
+
+      EndpointEvent::RequestAdded(e) => {
+        EndpointCommand::remove_request(e.request_id.clone()).into()
+      }
+      EndpointEvent::RequestBodySet(e) => previous_request_body(&e.request_id, prior_events),
+      EndpointEvent::RequestBodyUnset(e) => previous_request_body(&e.request_id, prior_events),
+
+      EndpointEvent::ResponseAddedByPathAndMethod(e) => {
+        EndpointCommand::remove_response(e.response_id.clone()).into()
+      }
+      EndpointEvent::ResponseBodySet(e) => previous_response_body(&e.response_id, prior_events),
+      EndpointEvent::ResponseBodyUnset(e) => previous_response_body(&e.response_id, prior_events),
+
+      // removed entities can't be restored with their ids, nor can the legacy content type and
+      // status code setting be undone
+      _ => return Err(irreversible()),
+    },
+
+    SpecEvent::ShapeEvent(event) => match event {
+      ShapeEvent::ShapeAdded(e) => ShapeCommand::remove_shape(e.shape_id.clone()).into(),
+      ShapeEvent::BaseShapeSet(e) => {
+        let base_shape_id = latest(prior_events, |event| match event {
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeAdded(prior)) if prior.shape_id == e.shape_id => {
+            Some(prior.base_shape_id.clone())
+          }
+          SpecEvent::ShapeEvent(ShapeEvent::BaseShapeSet(prior))
+            if prior.shape_id == e.shape_id =>
+          {
+            Some(prior.base_shape_id.clone())
+          }
+          _ => None,
+        })
+        .ok_or_else(irreversible)?;
+
+        ShapeCommand::set_base_shape(e.shape_id.clone(), base_shape_id).into()
+      }
+      ShapeEvent::ShapeRenamed(e) => {
+        let name = latest(prior_events, |event| match event {
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeAdded(prior)) if prior.shape_id == e.shape_id => {
+            Some(prior.name.clone())
+          }
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeRenamed(prior))
+            if prior.shape_id == e.shape_id =>
+          {
+            Some(prior.name.clone())
+          }
+          _ => None,
+        })
+        .ok_or_else(irreversible)?;
+
+        ShapeCommand::rename_shape(e.shape_id.clone(), name).into()
+      }
+      ShapeEvent::ShapeConstraintSet(e) => {
+        let constraint_descriptor = latest(prior_events, |event| match event {
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeConstraintSet(prior))
+            if prior.shape_id == e.shape_id =>
+          {
+            Some(prior.constraint_descriptor.clone())
+          }
+          _ => None,
+        })
+        .unwrap_or(ShapeConstraintDescriptor::NoConstraint(NoConstraint {}));
+
+        ShapeCommand::set_shape_constraint(e.shape_id.clone(), constraint_descriptor).into()
+      }
+
+      ShapeEvent::ShapeParameterAdded(e) => {
+        ShapeCommand::remove_shape_parameter(e.shape_parameter_id.clone()).into()
+      }
+      ShapeEvent::ShapeParameterShapeSet(e) => {
+        let binding = parameter_binding(&e.shape_descriptor);
+        let shape_descriptor = latest(prior_events, |event| match event {
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeParameterShapeSet(prior))
+            if parameter_binding(&prior.shape_descriptor) == binding =>
+          {
+            Some(prior.shape_descriptor.clone())
+          }
+          _ => None,
+        })
+        .unwrap_or_else(|| unbound_parameter(&e.shape_descriptor));
+
+        ShapeCommand::SetParameterShape(shape_commands::SetParameterShape { shape_descriptor })
+          .into()
+      }
+      ShapeEvent::ShapeParameterRenamed(e) => {
+        let name = latest(prior_events, |event| match event {
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeParameterAdded(prior))
+            if prior.shape_parameter_id == e.shape_parameter_id =>
+          {
+            Some(prior.name.clone())
+          }
+          SpecEvent::ShapeEvent(ShapeEvent::ShapeParameterRenamed(prior))
+            if prior.shape_parameter_id == e.shape_parameter_id =>
+          {
+            Some(prior.name.clone())
+          }
+          _ => None,
+        })
+        .ok_or_else(irreversible)?;
+
+        ShapeCommand::rename_shape_parameter(e.shape_parameter_id.clone(), name).into()
+      }
+
+      ShapeEvent::FieldAdded(e) => ShapeCommand::remove_field(e.field_id.clone()).into(),
+      ShapeEvent::FieldShapeSet(e) => {
+        let shape_descriptor = previous_field_shape(field_id_of(&e.shape_descriptor), prior_events)
+          .ok_or_else(irreversible)?;
+
+        ShapeCommand::SetFieldShape(shape_commands::SetFieldShape { shape_descriptor }).into()
+      }
+      ShapeEvent::FieldRenamed(e) => {
+        let name = previous_field_name(&e.field_id, prior_events).ok_or_else(irreversible)?;
+
+        ShapeCommand::rename_field(e.field_id.clone(), name).into()
+      }
+      // field ids are released upon removal, so removed fields can be added again
+      ShapeEvent::FieldRemoved(e) => {
+        let shape_id = latest(prior_events, |event| match event {
+          SpecEvent::ShapeEvent(ShapeEvent::FieldAdded(prior)) if prior.field_id == e.field_id => {
+            Some(prior.shape_id.clone())
+          }
+          _ => None,
+        })
+        .ok_or_else(irreversible)?;
+        let name = previous_field_name(&e.field_id, prior_events).ok_or_else(irreversible)?;
+        let shape_descriptor =
+          previous_field_shape(&e.field_id, prior_events).ok_or_else(irreversible)?;
+
+        ShapeCommand::AddField(shape_commands::AddField {
+          field_id: e.field_id.clone(),
+          shape_id,
+          name,
+          shape_descriptor,
+        })
+        .into()
+      }
+
+      // removed shapes and shape parameters can't be restored with their ids
+      ShapeEvent::ShapeRemoved(_) | ShapeEvent::ShapeParameterRemoved(_) => {
+        return Err(irreversible())
+      }
+    },
+
+    SpecEvent::RfcEvent(event) => match event {
+      RfcEvent::ContributionAdded(e) => {
+        let value = latest(prior_events, |event| match event {
+          SpecEvent::RfcEvent(RfcEvent::ContributionAdded(prior))
+            if prior.id == e.id && prior.key == e.key =>
+          {
+            Some(prior.value.clone())
+          }
+          _ => None,
+        })
+        .unwrap_or_default();
+
+        RfcCommand::add_contribution(e.id.clone(), e.key.clone(), value).into()
+      }
+      RfcEvent::BatchCommitStarted(_) | RfcEvent::BatchCommitEnded(_) => return Ok(None),
+      RfcEvent::APINamed(_) | RfcEvent::GitStateSet(_) => return Err(irreversible()),
+    },
+  };
+
+  Ok(Some(command))
+}
+
+// The most recent value found in the events preceding the reverted one
+fn latest<T>(prior_events: &[SpecEvent], value: impl Fn(&SpecEvent) -> Option<T>) -> Option<T> {
+  prior_events.iter().rev().find_map(value)
+}
+
+fn previous_field_name(field_id: &FieldId, prior_events: &[SpecEvent]) -> Option<String> {
+  latest(prior_events, |event| match event {
+    SpecEvent::ShapeEvent(ShapeEvent::FieldAdded(prior)) if prior.field_id == *field_id => {
+      Some(prior.name.clone())
+    }
+    SpecEvent::ShapeEvent(ShapeEvent::FieldRenamed(prior)) if prior.field_id == *field_id => {
+      Some(prior.name.clone())
+    }
+    _ => None,
+  })
+}
+
+fn previous_field_shape(
+  field_id: &FieldId,
+  prior_events: &[SpecEvent],
+) -> Option<FieldShapeDescriptor> {
+  latest(prior_events, |event| match event {
+    SpecEvent::ShapeEvent(ShapeEvent::FieldAdded(prior)) if prior.field_id == *field_id => {
+      Some(prior.shape_descriptor.clone())
+    }
+    SpecEvent::ShapeEvent(ShapeEvent::FieldShapeSet(prior))
+      if field_id_of(&prior.shape_descriptor) == field_id =>
+    {
+      Some(prior.shape_descriptor.clone())
+    }
+    _ => None,
+  })
+}
+
+fn previous_header_parameter_shape(
+  parameter_id: &RequestParameterId,
+  prior_events: &[SpecEvent],
+) -> SpecCommand {
+  latest(prior_events, |event| match event {
+    SpecEvent::EndpointEvent(EndpointEvent::RequestParameterShapeSet(prior))
+      if prior.parameter_id == *parameter_id =>
+    {
+      Some(EndpointCommand::set_header_parameter_shape(
+        parameter_id.clone(),
+        prior.parameter_descriptor.shape_id.clone(),
+      ))
+    }
+    SpecEvent::EndpointEvent(EndpointEvent::RequestParameterShapeUnset(prior))
+      if prior.parameter_id == *parameter_id =>
+    {
+      Some(EndpointCommand::unset_header_parameter_shape(
+        parameter_id.clone(),
+      ))
+    }
+    _ => None,
+  })
+  .unwrap_or_else(|| EndpointCommand::unset_header_parameter_shape(parameter_id.clone()))
+  .into()
+}
+
+fn previous_request_body(request_id: &RequestId, prior_events: &[SpecEvent]) -> SpecCommand {
+  latest(prior_events, |event| match event {
+    SpecEvent::EndpointEvent(EndpointEvent::RequestBodySet(prior))
+      if prior.request_id == *request_id =>
+    {
+      Some(EndpointCommand::SetRequestBodyShape(
+        endpoint_commands::SetRequestBodyShape {
+          request_id: request_id.clone(),
+          body_descriptor: prior.body_descriptor.clone(),
+        },
+      ))
+    }
+    SpecEvent::EndpointEvent(EndpointEvent::RequestBodyUnset(prior))
+      if prior.request_id == *request_id =>
+    {
+      Some(EndpointCommand::unset_request_body_shape(
+        request_id.clone(),
+      ))
+    }
+    _ => None,
+  })
+  .unwrap_or_else(|| EndpointCommand::unset_request_body_shape(request_id.clone()))
+  .into()
+}
+
+fn previous_response_body(response_id: &ResponseId, prior_events: &[SpecEvent]) -> SpecCommand {
+  latest(prior_events, |event| match event {
+    SpecEvent::EndpointEvent(EndpointEvent::ResponseBodySet(prior))
+      if prior.response_id == *response_id =>
+    {
+      Some(EndpointCommand::SetResponseBodyShape(
+        endpoint_commands::SetResponseBodyShape {
+          response_id: response_id.clone(),
+          body_descriptor: prior.body_descriptor.clone(),
+        },
+      ))
+    }
+    SpecEvent::EndpointEvent(EndpointEvent::ResponseBodyUnset(prior))
+      if prior.response_id == *response_id =>
+    {
+      Some(EndpointCommand::unset_response_body_shape(
+        response_id.clone(),
+      ))
+    }
+    _ => None,
+  })
+  .unwrap_or_else(|| EndpointCommand::unset_response_body_shape(response_id.clone()))
+  .into()
+}
+
+fn field_id_of(shape_descriptor: &FieldShapeDescriptor) -> &FieldId {
+  match shape_descriptor {
+    FieldShapeDescriptor::FieldShapeFromShape(descriptor) => &descriptor.field_id,
+    FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => &descriptor.field_id,
+  }
+}
+
+// The shape or field binding the parameter, and the parameter being bound
+fn parameter_binding(shape_descriptor: &ParameterShapeDescriptor) -> (&String, &String) {
+  match shape_descriptor {
+    ParameterShapeDescriptor::ProviderInShape(descriptor) => {
+      (&descriptor.shape_id, &descriptor.consuming_parameter_id)
+    }
+    ParameterShapeDescriptor::ProviderInField(descriptor) => {
+      (&descriptor.field_id, &descriptor.consuming_parameter_id)
+    }
+  }
+}
+
+fn unbound_parameter(shape_descriptor: &ParameterShapeDescriptor) -> ParameterShapeDescriptor {
+  let mut shape_descriptor = shape_descriptor.clone();
+  let provider_descriptor = match &mut shape_descriptor {
+    ParameterShapeDescriptor::ProviderInShape(descriptor) => &mut descriptor.provider_descriptor,
+    ParameterShapeDescriptor::ProviderInField(descriptor) => &mut descriptor.provider_descriptor,
+  };
+  *provider_descriptor = ProviderDescriptor::NoProvider(NoProvider {});
+  shape_descriptor
+}
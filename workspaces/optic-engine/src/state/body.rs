@@ -1,16 +1,19 @@
 use crate::shapehash;
+use crate::shapes::JsonTrail;
+use crate::state::shape::StringFormat;
 use serde::de::value;
 use serde_json::map::Map as JsonMap;
 use serde_json::Value as JsonValue;
 use serde_urlencoded;
 use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
 
 #[derive(PartialEq, Clone, Debug, Hash, Eq)]
 pub enum BodyDescriptor {
   Object(ObjectDescriptor),
   Array(ItemsDescriptor),
-  String,
-  Number,
+  String(StringDescriptor),
+  Number(NumberDescriptor),
   Boolean,
   Null,
 }
@@ -19,6 +22,259 @@ impl BodyDescriptor {
   pub fn empty_object() -> Self {
     Self::Object(ObjectDescriptor::from(std::iter::empty()))
   }
+
+  // Merges the values of an equally shaped body into this one
+  fn merge_values(&mut self, other: BodyDescriptor) {
+    match (self, other) {
+      (BodyDescriptor::String(string), BodyDescriptor::String(other_string)) => {
+        string.merge_values(other_string)
+      }
+      (BodyDescriptor::Object(object), BodyDescriptor::Object(other_object)) => {
+        for (FieldDescriptor(_, body), FieldDescriptor(_, other_body)) in
+          object.fields.iter_mut().zip(other_object.fields)
+        {
+          body.merge_values(*other_body);
+        }
+      }
+      (BodyDescriptor::Array(items), BodyDescriptor::Array(other_items)) => {
+        for ((item, _), (other_item, _)) in
+          items.unique_items.iter_mut().zip(*other_items.unique_items)
+        {
+          item.merge_values(other_item);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  /// Leaves out the values at the trails for which the predicate holds: fields are removed from
+  /// objects and arrays are left without items. Nothing remains when the root itself is removed.
+  pub fn without_trails(self, removes_trail: impl Fn(&JsonTrail) -> bool) -> Option<Self> {
+    let root_trail = JsonTrail::empty();
+    if removes_trail(&root_trail) {
+      None
+    } else {
+      Some(self.without_nested_trails(&root_trail, &removes_trail))
+    }
+  }
+
+  fn without_nested_trails(
+    self,
+    trail: &JsonTrail,
+    removes_trail: &impl Fn(&JsonTrail) -> bool,
+  ) -> Self {
+    match self {
+      BodyDescriptor::Object(object) => {
+        let fields = object.entries().filter_map(|(key, body)| {
+          let field_trail = trail.with_object_key(key.clone());
+          if removes_trail(&field_trail) {
+            None
+          } else {
+            Some((key, body.without_nested_trails(&field_trail, removes_trail)))
+          }
+        });
+        BodyDescriptor::Object(ObjectDescriptor::from(fields))
+      }
+      BodyDescriptor::Array(items) => {
+        let unique_items = items
+          .into_unique()
+          .filter_map(|(item, indexes)| {
+            let item_trail = trail.with_array_item(indexes[0] as u32);
+            if removes_trail(&item_trail) {
+              None
+            } else {
+              Some((
+                item.without_nested_trails(&item_trail, removes_trail),
+                indexes,
+              ))
+            }
+          })
+          .collect();
+        BodyDescriptor::Array(ItemsDescriptor {
+          unique_items: Box::new(unique_items),
+        })
+      }
+      body => body,
+    }
+  }
+
+  /// Describes a number captured as text, like the values of headers. Only text written the way
+  /// JSON numbers are is one, so values like `NaN`, `inf` or `+1` remain strings.
+  pub fn number_from_text(text: &str) -> Option<Self> {
+    if text.trim() != text {
+      return None;
+    }
+    let number = serde_json::from_str::<serde_json::Number>(text).ok()?;
+
+    Some(Self::Number(NumberDescriptor::from(&number)))
+  }
+}
+
+// Rules of shape hashes describing the values they were computed from
+const FORMAT_RULE_PREFIX: &str = "format:";
+const INTEGER_RULE: &str = "number:integer";
+const DECIMAL_RULE: &str = "number:decimal";
+
+// The most distinct values a string keeps, well beyond how many an enum would have
+const MAX_STRING_VALUES: usize = 64;
+
+// Strings are told apart by their format rather than their value, so array items of the same shape
+// are merged, holding the values of all of them
+#[derive(Clone, Debug, Default)]
+pub struct StringDescriptor {
+  values: Vec<String>,
+  values_exceeded: bool,
+  format: Option<StringFormat>,
+}
+
+impl StringDescriptor {
+  /// The distinct values observed, in the order they were observed, up to the most that are kept
+  pub fn values(&self) -> &[String] {
+    &self.values
+  }
+
+  /// Whether more distinct values were observed than were kept
+  pub fn values_exceeded(&self) -> bool {
+    self.values_exceeded
+  }
+
+  pub fn format(&self) -> Option<StringFormat> {
+    self.format
+  }
+
+  /// Whether anything is known about the string, which isn't the case for shape hashes without rules
+  pub fn is_described(&self) -> bool {
+    !self.values.is_empty() || self.format.is_some()
+  }
+
+  fn merge_values(&mut self, other: StringDescriptor) {
+    self.values_exceeded = self.values_exceeded || other.values_exceeded;
+    for value in other.values {
+      if self.values_exceeded {
+        break;
+      }
+      if !self.values.contains(&value) {
+        if self.values.len() < MAX_STRING_VALUES {
+          self.values.push(value);
+        } else {
+          self.values_exceeded = true;
+        }
+      }
+    }
+  }
+
+  fn from_rules(rules: &[String]) -> Self {
+    let format = rules.iter().find_map(|rule| {
+      let name = rule.strip_prefix(FORMAT_RULE_PREFIX)?;
+      StringFormat::from_name(name)
+    });
+
+    Self {
+      values: vec![],
+      values_exceeded: false,
+      format,
+    }
+  }
+}
+
+impl PartialEq for StringDescriptor {
+  fn eq(&self, other: &Self) -> bool {
+    self.format == other.format && self.values.is_empty() == other.values.is_empty()
+  }
+}
+
+impl Eq for StringDescriptor {}
+
+impl Hash for StringDescriptor {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.format.hash(state);
+    self.values.is_empty().hash(state);
+  }
+}
+
+impl From<String> for StringDescriptor {
+  fn from(value: String) -> Self {
+    let format = detect_string_format(&value);
+    Self {
+      values: vec![value],
+      values_exceeded: false,
+      format,
+    }
+  }
+}
+
+fn detect_string_format(value: &str) -> Option<StringFormat> {
+  if chrono::DateTime::parse_from_rfc3339(value).is_ok() {
+    Some(StringFormat::DateTime)
+  } else if value.len() == 36 && uuid::Uuid::parse_str(value).is_ok() {
+    Some(StringFormat::Uuid) // only the hyphenated form, as plain hex could be any kind of hash
+  } else if is_email(value) {
+    Some(StringFormat::Email)
+  } else if is_uri(value) {
+    Some(StringFormat::Uri)
+  } else {
+    None
+  }
+}
+
+fn is_email(value: &str) -> bool {
+  let mut parts = value.split('@');
+  match (parts.next(), parts.next(), parts.next()) {
+    (Some(local), Some(domain), None) => {
+      !local.is_empty()
+        && !value.contains(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+    }
+    _ => false,
+  }
+}
+
+// Only URIs with an authority, like `https://example.com`, as the generic syntax matches far too
+// much free text
+fn is_uri(value: &str) -> bool {
+  match value.find("://") {
+    Some(scheme_end) => {
+      let (scheme, rest) = value.split_at(scheme_end);
+      scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-')
+        && rest.len() > 3
+        && !rest.contains(char::is_whitespace)
+    }
+    None => false,
+  }
+}
+
+#[derive(PartialEq, Clone, Debug, Hash, Eq, Default)]
+pub struct NumberDescriptor {
+  integer: Option<bool>,
+}
+
+impl NumberDescriptor {
+  /// Whether the number was integral, if known
+  pub fn is_integer(&self) -> Option<bool> {
+    self.integer
+  }
+
+  fn from_rules(rules: &[String]) -> Self {
+    let integer = rules.iter().find_map(|rule| match rule.as_str() {
+      INTEGER_RULE => Some(true),
+      DECIMAL_RULE => Some(false),
+      _ => None,
+    });
+
+    Self { integer }
+  }
+}
+
+impl From<&serde_json::Number> for NumberDescriptor {
+  fn from(number: &serde_json::Number) -> Self {
+    Self {
+      integer: Some(number.is_i64() || number.is_u64()),
+    }
+  }
 }
 
 #[derive(PartialEq, Clone, Debug, Hash, Eq)]
@@ -40,6 +296,20 @@ impl ObjectDescriptor {
     self.fields.iter().map(|FieldDescriptor(key, body)| key)
   }
 
+  pub fn fields(&self) -> impl Iterator<Item = (&String, &BodyDescriptor)> {
+    self
+      .fields
+      .iter()
+      .map(|FieldDescriptor(key, body)| (key, body.as_ref()))
+  }
+
+  pub fn get(&self, key: &str) -> Option<&BodyDescriptor> {
+    self
+      .fields()
+      .find(|(field_key, _)| *field_key == key)
+      .map(|(_, body)| body)
+  }
+
   pub fn entries(self) -> impl Iterator<Item = (String, BodyDescriptor)> {
     self
       .fields
@@ -71,21 +341,22 @@ where
   T: Iterator<Item = BodyDescriptor>,
 {
   fn from(all_items: T) -> Self {
-    let mut unique_items = all_items
-      .enumerate()
-      .fold(HashMap::new(), |mut indexes_by_unique_items, (i, item)| {
-        {
-          let items = indexes_by_unique_items.entry(item).or_insert(vec![]);
-          items.push(i);
-        };
-
-        indexes_by_unique_items
-      })
-      .into_iter()
-      .map(|(item, indexes)| (item.clone(), indexes))
-      .collect::<Vec<_>>();
-
-    unique_items.sort_by_key(|(_, indexes)| *indexes.first().unwrap());
+    let mut unique_items: Vec<(BodyDescriptor, Vec<usize>)> = vec![];
+    let mut unique_item_positions: HashMap<BodyDescriptor, usize> = HashMap::new();
+
+    for (i, item) in all_items.enumerate() {
+      match unique_item_positions.get(&item) {
+        Some(position) => {
+          let (unique_item, indexes) = &mut unique_items[*position];
+          unique_item.merge_values(item);
+          indexes.push(i);
+        }
+        None => {
+          unique_item_positions.insert(item.clone(), unique_items.len());
+          unique_items.push((item, vec![i]));
+        }
+      }
+    }
 
     Self {
       unique_items: Box::new(unique_items),
@@ -141,8 +412,12 @@ impl From<shapehash::ShapeDescriptor> for BodyDescriptor {
       }
       shapehash::ShapeDescriptor_PrimitiveType::BOOLEAN => BodyDescriptor::Boolean,
       shapehash::ShapeDescriptor_PrimitiveType::NULL => BodyDescriptor::Null,
-      shapehash::ShapeDescriptor_PrimitiveType::NUMBER => BodyDescriptor::Number,
-      shapehash::ShapeDescriptor_PrimitiveType::STRING => BodyDescriptor::String,
+      shapehash::ShapeDescriptor_PrimitiveType::NUMBER => BodyDescriptor::Number(
+        NumberDescriptor::from_rules(shape_hash_descriptor.get_rules()),
+      ),
+      shapehash::ShapeDescriptor_PrimitiveType::STRING => BodyDescriptor::String(
+        StringDescriptor::from_rules(shape_hash_descriptor.get_rules()),
+      ),
     }
   }
 }
@@ -166,21 +441,21 @@ impl From<JsonValue> for BodyDescriptor {
       }
       JsonValue::Bool(_) => BodyDescriptor::Boolean,
       JsonValue::Null => BodyDescriptor::Null,
-      JsonValue::Number(_) => BodyDescriptor::Number,
-      JsonValue::String(_) => BodyDescriptor::String,
+      JsonValue::Number(number) => BodyDescriptor::Number(NumberDescriptor::from(&number)),
+      JsonValue::String(string) => BodyDescriptor::from(string),
     }
   }
 }
 
 impl From<String> for BodyDescriptor {
   fn from(string: String) -> Self {
-    BodyDescriptor::String
+    BodyDescriptor::String(StringDescriptor::from(string))
   }
 }
 
 impl From<&String> for BodyDescriptor {
-  fn from(str: &String) -> Self {
-    BodyDescriptor::String
+  fn from(string: &String) -> Self {
+    BodyDescriptor::from(string.clone())
   }
 }
 
@@ -207,10 +482,10 @@ impl From<ParsedQueryString> for BodyDescriptor {
 
     let fields = values_by_key.into_iter().map(|(key, values)| {
       let value_descriptor = if values.len() == 1 {
-        BodyDescriptor::String
+        BodyDescriptor::from(values.into_iter().next().unwrap())
       } else {
-        let item_descriptor = ItemsDescriptor::from(BodyDescriptor::String); // we only ever expect strings with this parser
-        BodyDescriptor::Array(item_descriptor)
+        let item_descriptors = values.into_iter().map(BodyDescriptor::from); // we only ever expect strings with this parser
+        BodyDescriptor::Array(ItemsDescriptor::from(item_descriptors))
       };
 
       (key, value_descriptor)
@@ -220,6 +495,91 @@ impl From<ParsedQueryString> for BodyDescriptor {
   }
 }
 
+#[derive(Debug, Default)]
+pub struct ParsedHeaders {
+  entries: Vec<(String, String)>,
+}
+
+impl ParsedHeaders {
+  /// Accepts both a map of names to (lists of) values and a list of name / value pairs. Entries
+  /// without a value that can be read as text are skipped.
+  pub fn from_json(json: JsonValue) -> Option<Self> {
+    let mut entries = vec![];
+
+    match json {
+      JsonValue::Object(fields) => {
+        for (name, value) in fields {
+          match value {
+            JsonValue::Array(values) => {
+              for value in values {
+                if let Some(value) = Self::header_value(value) {
+                  entries.push((name.clone(), value));
+                }
+              }
+            }
+            value => {
+              if let Some(value) = Self::header_value(value) {
+                entries.push((name, value));
+              }
+            }
+          }
+        }
+      }
+      JsonValue::Array(pairs) => {
+        for pair in pairs {
+          let name = pair.get("name").and_then(JsonValue::as_str);
+          let value = pair.get("value").cloned().and_then(Self::header_value);
+          if let (Some(name), Some(value)) = (name, value) {
+            entries.push((name.to_owned(), value));
+          }
+        }
+      }
+      _ => return None,
+    }
+
+    Some(Self { entries })
+  }
+
+  fn header_value(value: JsonValue) -> Option<String> {
+    match value {
+      JsonValue::String(value) => Some(value),
+      JsonValue::Number(value) => Some(value.to_string()),
+      JsonValue::Bool(value) => Some(value.to_string()),
+      _ => None,
+    }
+  }
+
+  /// Unique header names, in the casing they were first observed with
+  pub fn names(&self) -> impl Iterator<Item = &String> {
+    let mut seen: Vec<&String> = vec![];
+    for (name, _) in &self.entries {
+      if !seen
+        .iter()
+        .any(|seen_name| seen_name.eq_ignore_ascii_case(name))
+      {
+        seen.push(name);
+      }
+    }
+    seen.into_iter()
+  }
+
+  pub fn contains(&self, name: &str) -> bool {
+    self
+      .entries
+      .iter()
+      .any(|(entry_name, _)| entry_name.eq_ignore_ascii_case(name))
+  }
+
+  pub fn values(&self, name: &str) -> Vec<&String> {
+    self
+      .entries
+      .iter()
+      .filter(|(entry_name, _)| entry_name.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value)
+      .collect()
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -242,4 +602,218 @@ mod test {
       body_descriptor
     );
   }
+
+  #[test]
+  fn headers_can_be_parsed_from_json() {
+    let from_map = ParsedHeaders::from_json(serde_json::json!({
+      "Content-Type": "application/json",
+      "X-RateLimit-Remaining": 99,
+      "Set-Cookie": ["a=1", "b=2"]
+    }))
+    .expect("should be able to parse a map of headers");
+
+    assert_debug_snapshot!("headers_can_be_parsed_from_json__from_map", &from_map);
+    assert!(from_map.contains("x-ratelimit-remaining"));
+    assert_eq!(from_map.values("set-cookie"), vec!["a=1", "b=2"]);
+    assert!(from_map.values("X-Request-Id").is_empty());
+
+    let from_pairs = ParsedHeaders::from_json(serde_json::json!([
+      { "name": "Accept", "value": "*/*" },
+      { "name": "accept", "value": "text/plain" }
+    ]))
+    .expect("should be able to parse a list of header pairs");
+
+    assert_eq!(from_pairs.names().collect::<Vec<_>>(), vec!["Accept"]);
+
+    let with_unreadable_values = ParsedHeaders::from_json(serde_json::json!({
+      "Accept": "*/*",
+      "X-Empty": null,
+      "X-Nested": { "value": "1" },
+      "Set-Cookie": ["a=1", null]
+    }))
+    .expect("should be able to parse headers with some unreadable values");
+    assert_eq!(
+      with_unreadable_values.names().collect::<Vec<_>>(),
+      vec!["Accept", "Set-Cookie"]
+    );
+    assert_eq!(with_unreadable_values.values("set-cookie"), vec!["a=1"]);
+
+    let with_unreadable_pairs = ParsedHeaders::from_json(serde_json::json!([
+      { "name": "Accept", "value": "*/*" },
+      { "name": "X-Empty", "value": null },
+      { "value": "nameless" }
+    ]))
+    .expect("should be able to parse header pairs with some unreadable ones");
+    assert_eq!(
+      with_unreadable_pairs.names().collect::<Vec<_>>(),
+      vec!["Accept"]
+    );
+
+    assert!(ParsedHeaders::from_json(serde_json::json!("Accept: */*")).is_none());
+  }
+
+  #[test]
+  fn bodies_can_leave_out_trails() {
+    let body = BodyDescriptor::from(serde_json::json!({
+      "id": 1,
+      "meta": { "requestedAt": "2021-03-04T12:00:00.000Z" },
+      "items": [{ "etag": "a", "name": "first" }, { "etag": "b", "name": "second" }],
+      "tags": ["a", "b"]
+    }));
+    let meta_trail = JsonTrail::empty().with_object_key(String::from("meta"));
+    let etag_trail = JsonTrail::empty()
+      .with_object_key(String::from("items"))
+      .with_array_item(0)
+      .with_object_key(String::from("etag"));
+    let tag_trail = JsonTrail::empty()
+      .with_object_key(String::from("tags"))
+      .with_array_item(0);
+
+    let filtered_body = body
+      .clone()
+      .without_trails(|trail| {
+        let trail = trail.normalized();
+        trail == meta_trail || trail == etag_trail || trail == tag_trail
+      })
+      .expect("root should remain when it isn't left out");
+
+    assert_eq!(
+      filtered_body,
+      BodyDescriptor::from(serde_json::json!({
+        "id": 1,
+        "items": [{ "name": "first" }, { "name": "second" }],
+        "tags": []
+      }))
+    );
+    assert!(body
+      .without_trails(|trail| *trail == JsonTrail::empty())
+      .is_none());
+  }
+
+  #[test]
+  fn strings_are_described_by_their_format() {
+    let formats = vec![
+      "2021-03-04T12:00:00.000Z",
+      "0b9dd9c7-7b1c-4a37-9a1e-4a4f4e4c3a2b",
+      "someone@example.com",
+      "https://example.com/path?query=1",
+      "just some text",
+      "2021-03-04",
+    ]
+    .into_iter()
+    .map(|value| StringDescriptor::from(String::from(value)).format())
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+      formats,
+      vec![
+        Some(StringFormat::DateTime),
+        Some(StringFormat::Uuid),
+        Some(StringFormat::Email),
+        Some(StringFormat::Uri),
+        None,
+        None
+      ]
+    );
+
+    let items = match BodyDescriptor::from(serde_json::json!(["a", "b", "a", 1, 2])) {
+      BodyDescriptor::Array(items) => items,
+      _ => unreachable!("json arrays are described as arrays"),
+    };
+    let unique_items = items.into_unique().collect::<Vec<_>>();
+    assert_eq!(
+      unique_items.len(),
+      2,
+      "strings without format and integers should be unique regardless of their values"
+    );
+    match &unique_items[0].0 {
+      BodyDescriptor::String(string) => assert_eq!(string.values(), ["a", "b"]),
+      _ => unreachable!("first unique item should be the strings"),
+    }
+  }
+
+  #[test]
+  fn strings_keep_a_limited_number_of_values() {
+    let values = (0..MAX_STRING_VALUES * 2)
+      .map(|n| format!("value-{}", n))
+      .collect::<Vec<_>>();
+    let items = match BodyDescriptor::from(serde_json::json!(values)) {
+      BodyDescriptor::Array(items) => items,
+      _ => unreachable!("json arrays are described as arrays"),
+    };
+
+    match items.into_unique().next() {
+      Some((BodyDescriptor::String(string), indexes)) => {
+        assert_eq!(indexes.len(), MAX_STRING_VALUES * 2);
+        assert_eq!(string.values(), &values[..MAX_STRING_VALUES]);
+        assert!(string.values_exceeded());
+      }
+      _ => unreachable!("strings without format should be a single unique item"),
+    }
+  }
+
+  #[test]
+  fn numbers_can_be_described_from_text() {
+    let integers = vec!["0", "-12", "18446744073709551615"];
+    for text in integers {
+      assert_eq!(
+        BodyDescriptor::number_from_text(text),
+        Some(BodyDescriptor::Number(NumberDescriptor {
+          integer: Some(true)
+        })),
+        "{} should be an integer",
+        text
+      );
+    }
+
+    let decimals = vec!["1.5", "-0.25", "2e3"];
+    for text in decimals {
+      assert_eq!(
+        BodyDescriptor::number_from_text(text),
+        Some(BodyDescriptor::Number(NumberDescriptor {
+          integer: Some(false)
+        })),
+        "{} should be a decimal",
+        text
+      );
+    }
+
+    let not_numbers = vec![
+      "NaN", "nan", "inf", "-inf", "infinity", "+1", ".5", "1.", "01", "1e999", " 1", "0x10", "",
+    ];
+    for text in not_numbers {
+      assert_eq!(
+        BodyDescriptor::number_from_text(text),
+        None,
+        "{} should not be a number",
+        text
+      );
+    }
+  }
+
+  #[test]
+  fn shape_hash_rules_can_be_parsed_to_body_descriptor() {
+    let mut string_hash = shapehash::ShapeDescriptor::new();
+    string_hash.set_field_type(shapehash::ShapeDescriptor_PrimitiveType::STRING);
+    string_hash
+      .mut_rules()
+      .push(String::from("format:date-time"));
+
+    match BodyDescriptor::from(string_hash) {
+      BodyDescriptor::String(string) => {
+        assert_eq!(string.format(), Some(StringFormat::DateTime));
+        assert!(string.values().is_empty());
+      }
+      _ => unreachable!("string hashes are described as strings"),
+    }
+
+    let mut number_hash = shapehash::ShapeDescriptor::new();
+    number_hash.set_field_type(shapehash::ShapeDescriptor_PrimitiveType::NUMBER);
+    number_hash.mut_rules().push(String::from("number:decimal"));
+
+    match BodyDescriptor::from(number_hash) {
+      BodyDescriptor::Number(number) => assert_eq!(number.is_integer(), Some(false)),
+      _ => unreachable!("number hashes are described as numbers"),
+    }
+  }
 }
@@ -11,6 +11,8 @@ pub type HttpMethod = String;
 pub type HttpStatusCode = u16;
 pub type HttpContentType = String;
 
+pub const HEADER_PARAMETER_LOCATION: &str = "header";
+
 #[derive(Debug, Deserialize, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ShapedBodyDescriptor {
@@ -13,6 +13,10 @@ pub trait SpecIdGenerator {
     self.generate_id("field_")
   }
 
+  fn path(&mut self) -> String {
+    self.generate_id("path_")
+  }
+
   fn query_params(&mut self) -> String {
     self.generate_id("query_params_")
   }
@@ -25,6 +29,10 @@ pub trait SpecIdGenerator {
     self.generate_id("response_")
   }
 
+  fn request_parameter(&mut self) -> String {
+    self.generate_id("request_parameter_")
+  }
+
   fn shape(&mut self) -> String {
     self.generate_id("shape_")
   }
@@ -59,7 +59,11 @@ pub enum ParameterShapeDescriptor {
 }
 #[derive(Debug, Deserialize, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct ProviderInField {}
+pub struct ProviderInField {
+  pub field_id: FieldId,
+  pub provider_descriptor: ProviderDescriptor,
+  pub consuming_parameter_id: ShapeParameterId,
+}
 
 #[derive(Debug, Deserialize, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -84,7 +88,9 @@ impl Default for ProviderDescriptor {
 
 #[derive(Debug, Deserialize, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct ParameterProvider {}
+pub struct ParameterProvider {
+  pub shape_parameter_id: ShapeParameterId,
+}
 
 #[derive(Debug, Deserialize, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -96,6 +102,78 @@ pub struct ShapeProvider {
 #[serde(rename_all = "camelCase")]
 pub struct NoProvider {}
 ////////////////////////////////////////////////////////////////////////////////
+// variant names are the tags constraints are serialized with in events, like other descriptors'
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Hash)]
+pub enum ShapeConstraintDescriptor {
+  FormatConstraint(FormatConstraint),
+  EnumConstraint(EnumConstraint),
+  IntegerConstraint(IntegerConstraint),
+  NoConstraint(NoConstraint),
+}
+
+impl ShapeConstraintDescriptor {
+  /// The kind of core shape the constrained values must have
+  pub fn constrained_kind(&self) -> Option<ShapeKind> {
+    match self {
+      Self::FormatConstraint(_) | Self::EnumConstraint(_) => Some(ShapeKind::StringKind),
+      Self::IntegerConstraint(_) => Some(ShapeKind::NumberKind),
+      Self::NoConstraint(_) => None,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatConstraint {
+  pub format: StringFormat,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumConstraint {
+  pub values: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegerConstraint {}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct NoConstraint {}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Copy, Hash, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum StringFormat {
+  DateTime,
+  Uuid,
+  Email,
+  Uri,
+}
+
+impl StringFormat {
+  /// Name of the format, as used by JSON Schema and OpenAPI
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::DateTime => "date-time",
+      Self::Uuid => "uuid",
+      Self::Email => "email",
+      Self::Uri => "uri",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "date-time" => Some(Self::DateTime),
+      "uuid" => Some(Self::Uuid),
+      "email" => Some(Self::Email),
+      "uri" => Some(Self::Uri),
+      _ => None,
+    }
+  }
+}
+////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Clone, Debug, Serialize, Ord, PartialOrd, Eq, PartialEq)]
 pub enum ShapeKind {
@@ -177,6 +255,9 @@ impl ShapeKind {
       Self::ListKind => Some(ShapeKindParameterDescriptor {
         shape_parameter_id: "$listItem",
       }),
+      Self::MapKind => Some(ShapeKindParameterDescriptor {
+        shape_parameter_id: "$mapValue",
+      }),
       Self::NullableKind => Some(ShapeKindParameterDescriptor {
         shape_parameter_id: "$nullableInner",
       }),
@@ -0,0 +1,36 @@
+use crate::commands::SpecCommand;
+use crate::projections::SpecProjection;
+use crate::state::SpecIdGenerator;
+use crate::Aggregate;
+
+/// Generates ids that are predictable between test runs, so they can be snapshotted
+#[derive(Debug, Default)]
+pub(crate) struct TestIdGenerator {
+  counter: usize,
+}
+
+impl SpecIdGenerator for TestIdGenerator {
+  fn generate_id(&mut self, prefix: &str) -> String {
+    let id = format!("test-id-{}-{}", prefix, self.counter);
+    self.counter += 1;
+    id
+  }
+}
+
+/// Executes and applies the commands in order, panicking at the first that isn't valid
+pub(crate) fn assert_valid_commands(
+  mut spec_projection: SpecProjection,
+  commands: impl IntoIterator<Item = SpecCommand>,
+) -> SpecProjection {
+  for command in commands {
+    let events = spec_projection
+      .execute(command)
+      .expect("generated commands must be valid");
+
+    for event in events {
+      spec_projection.apply(event)
+    }
+  }
+
+  spec_projection
+}
@@ -1,7 +1,8 @@
 use futures::sink::SinkExt;
 use insta::assert_debug_snapshot;
 use optic_engine::{
-  diff_interaction, streams, DiffInteractionConfig, HttpInteraction, SpecEvent, SpecProjection,
+  diff_interaction, streams, DiffInteractionConfig, HttpInteraction, InteractionDiffResult,
+  SpecEvent, SpecProjection,
 };
 use petgraph::dot::Dot;
 use serde_json::json;
@@ -331,3 +332,274 @@ fn can_handle_no_request_and_response_body() {
   );
   assert_eq!(results.len(), 0);
 }
+
+#[test]
+fn can_yield_header_diffs() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"xyz"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","name":""}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","name":""}},
+      {"ShapeAdded":{"shapeId":"optional_shape_1","baseShapeId":"$optional","name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"optional_shape_1","providerDescriptor":{"ShapeProvider":{"shapeId":"string_shape_1"}},"consumingParameterId":"$optionalInner"}}}},
+      {"RequestParameterAdded":{"parameterId":"header_1","requestId":"request_1","parameterLocation":"header","name":"X-Api-Key"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_1","parameterDescriptor":{"shapeId":"string_shape_1","isRemoved":false}}},
+      {"RequestParameterAdded":{"parameterId":"header_2","requestId":"request_1","parameterLocation":"header","name":"X-Trace-Id"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_2","parameterDescriptor":{"shapeId":"optional_shape_1","isRemoved":false}}},
+      {"ResponseParameterAdded":{"parameterId":"header_3","responseId":"response_1","parameterLocation":"header","name":"X-RateLimit-Remaining"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_3","parameterDescriptor":{"shapeId":"string_shape_1","isRemoved":false}}},
+      {"ResponseParameterAdded":{"parameterId":"header_4","responseId":"response_1","parameterLocation":"header","name":"X-Version"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_4","parameterDescriptor":{"shapeId":"number_shape_1","isRemoved":false}}},
+    ]),
+  )
+  .expect("should be able to deserialize header events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let interaction_with_headers =
+    |request_headers: serde_json::Value, response_headers: serde_json::Value| {
+      serde_json::from_value::<HttpInteraction>(json!({
+      "uuid": "5",
+      "request": {
+        "host": "localhost",
+        "method": "GET",
+        "path": "/xyz",
+        "query": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+        "headers": { "asJsonString": request_headers.to_string(), "asText": null, "asShapeHashBytes": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+        }
+      },
+      "response": {
+        "statusCode": 200,
+        "headers": { "asJsonString": response_headers.to_string(), "asText": null, "asShapeHashBytes": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+        }
+      },
+      "tags": []
+    }))
+    .expect("example http interaction should deserialize")
+    };
+
+  let compliant_interaction = interaction_with_headers(
+    json!({ "Host": "localhost", "x-api-key": "secret" }),
+    json!({ "Content-Length": "0", "X-RateLimit-Remaining": "99", "X-Version": "2" }),
+  );
+  let compliant_results = diff_interaction(
+    &spec_projection,
+    compliant_interaction,
+    &DiffInteractionConfig::default(),
+  );
+  assert_eq!(compliant_results.len(), 0);
+
+  let non_compliant_interaction = interaction_with_headers(
+    json!([
+      { "name": "X-Request-Id", "value": "abc" },
+      { "name": "X-Trace-Id", "value": "abc" },
+      { "name": "X-Trace-Id", "value": "def" }
+    ]),
+    json!({ "X-Version": "v2", "X-Powered-By": "optic" }),
+  );
+  let results = diff_interaction(
+    &spec_projection,
+    non_compliant_interaction,
+    &DiffInteractionConfig::default(),
+  );
+  let fingerprints = results
+    .iter()
+    .map(|result| result.fingerprint())
+    .collect::<Vec<_>>();
+
+  assert_debug_snapshot!("can_yield_header_diffs__results", results);
+  assert_debug_snapshot!("can_yield_header_diffs__fingerprints", fingerprints);
+}
+
+#[test]
+fn only_reports_undocumented_headers_where_headers_are_documented() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"xyz"}},
+    {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+    {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+    {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","name":""}},
+    {"ResponseParameterAdded":{"parameterId":"header_1","responseId":"response_1","parameterLocation":"header","name":"X-Version"}},
+    {"RequestParameterShapeSet":{"parameterId":"header_1","parameterDescriptor":{"shapeId":"string_shape_1","isRemoved":false}}},
+  ]))
+  .expect("should be able to deserialize header events as spec events");
+  let spec_projection = SpecProjection::from(events);
+
+  let interaction: HttpInteraction = serde_json::from_value(json!({
+    "uuid": "5",
+    "request": {
+      "host": "localhost",
+      "method": "GET",
+      "path": "/xyz",
+      "query": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+      "headers": {
+        "asJsonString": json!({ "Authorization": "Bearer secret", "Referer": "http://localhost" }).to_string(),
+        "asText": null,
+        "asShapeHashBytes": null
+      },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+      }
+    },
+    "response": {
+      "statusCode": 200,
+      "headers": {
+        "asJsonString": json!({ "X-Version": "2", "ETag": "abc" }).to_string(),
+        "asText": null,
+        "asShapeHashBytes": null
+      },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+      }
+    },
+    "tags": []
+  }))
+  .expect("example http interaction should deserialize");
+
+  let results = diff_interaction(
+    &spec_projection,
+    interaction,
+    &DiffInteractionConfig::default(),
+  );
+
+  assert_eq!(
+    results.len(),
+    1,
+    "only the undocumented response header is reported"
+  );
+  assert!(matches!(
+    &results[0],
+    InteractionDiffResult::UnmatchedResponseHeader(_)
+  ));
+}
+
+#[test]
+fn can_configure_what_gets_diffed() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"xyz"}},
+    {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+    {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+    {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","name":""}},
+    {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","name":""}},
+    {"FieldAdded": {"fieldId": "field_1","shapeId": "object_shape_1","name": "id","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1","shapeId": "string_shape_1"}}}},
+    {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "object_shape_1","isRemoved": false}}},
+  ]))
+  .expect("should be able to deserialize spec events");
+  let spec_projection = SpecProjection::from(events);
+
+  let interaction = |path: &str, status_code: u16, content_type: &str, body: serde_json::Value| {
+    serde_json::from_value::<HttpInteraction>(json!({
+      "uuid": "5",
+      "request": {
+        "host": "localhost",
+        "method": "GET",
+        "path": path,
+        "query": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+        "headers": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+        }
+      },
+      "response": {
+        "statusCode": status_code,
+        "headers": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+        "body": {
+          "contentType": content_type,
+          "value": { "asJsonString": body.to_string(), "asText": null, "asShapeHashBytes": null }
+        }
+      },
+      "tags": []
+    }))
+    .expect("example http interaction should deserialize")
+  };
+  let count_diffs = |interaction: HttpInteraction, config: serde_json::Value| {
+    let config: DiffInteractionConfig =
+      serde_json::from_value(config).expect("example config should deserialize");
+    diff_interaction(&spec_projection, interaction, &config).len()
+  };
+
+  // unknown paths are reported, unless ignored
+  assert_eq!(
+    count_diffs(
+      interaction("/health", 200, "application/json", json!({})),
+      json!({})
+    ),
+    1
+  );
+  assert_eq!(
+    count_diffs(
+      interaction("/health", 200, "application/json", json!({})),
+      json!({ "ignore": [{ "path": "/health/**" }, { "path": "/health" }] })
+    ),
+    0
+  );
+  assert_eq!(
+    count_diffs(
+      interaction("/health", 200, "application/json", json!({})),
+      json!({ "ignore": [{ "path": { "regex": "^/heal" }, "methods": ["POST"] }] })
+    ),
+    1
+  );
+
+  // status codes and content types can be skipped
+  assert_eq!(
+    count_diffs(
+      interaction("/xyz", 503, "application/json", json!({})),
+      json!({})
+    ),
+    1
+  );
+  assert_eq!(
+    count_diffs(
+      interaction("/xyz", 503, "application/json", json!({})),
+      json!({ "skipStatusCodes": ["5xx"] })
+    ),
+    0
+  );
+  assert_eq!(
+    count_diffs(
+      interaction(
+        "/xyz",
+        200,
+        "text/html; charset=utf-8",
+        json!("<html></html>")
+      ),
+      json!({ "skipContentTypes": ["text/*"] })
+    ),
+    0
+  );
+
+  // shape diffs can be ignored by trail, or extra keys dropped altogether
+  let noisy_body = json!({ "id": 1, "requestedAt": "now" });
+  assert_eq!(
+    count_diffs(
+      interaction("/xyz", 200, "application/json", noisy_body.clone()),
+      json!({})
+    ),
+    2
+  );
+  assert_eq!(
+    count_diffs(
+      interaction("/xyz", 200, "application/json", noisy_body.clone()),
+      json!({ "ignoreTrails": ["$.id"] })
+    ),
+    1
+  );
+  assert_eq!(
+    count_diffs(
+      interaction("/xyz", 200, "application/json", noisy_body),
+      json!({ "lenient": true })
+    ),
+    1
+  );
+}
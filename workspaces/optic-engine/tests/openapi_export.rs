@@ -0,0 +1,181 @@
+use insta::assert_json_snapshot;
+use optic_engine::{export_openapi, SpecEvent, SpecProjection};
+use serde_json::json;
+
+#[test]
+pub fn exports_endpoints_shapes_and_contributions() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "posts"}},
+    {"PathParameterAdded": {"pathId": "path_2", "parentPathId": "path_1", "name": "postId"}},
+    {"ContributionAdded": {"id": "path_2", "key": "description", "value": "the id of the post"}},
+
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "number_1", "baseShapeId": "$number", "name": ""}},
+    {"ShapeAdded": {"shapeId": "boolean_1", "baseShapeId": "$boolean", "name": ""}},
+    {"ShapeAdded": {"shapeId": "optional_1", "baseShapeId": "$optional", "name": ""}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "optional_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "number_1"}}, "consumingParameterId": "$optionalInner"}}}},
+    {"ShapeAdded": {"shapeId": "nullable_1", "baseShapeId": "$nullable", "name": ""}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "nullable_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "string_1"}}, "consumingParameterId": "$nullableInner"}}}},
+    {"ShapeAdded": {"shapeId": "list_1", "baseShapeId": "$list", "name": ""}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "list_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "string_1"}}, "consumingParameterId": "$listItem"}}}},
+    {"ShapeAdded": {"shapeId": "one_of_1", "baseShapeId": "$oneOf", "name": ""}},
+    {"ShapeParameterAdded": {"shapeParameterId": "one_of_parameter_1", "shapeId": "one_of_1", "name": "", "shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_1", "providerDescriptor": {"NoProvider": {}}, "consumingParameterId": "one_of_parameter_1"}}}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "string_1"}}, "consumingParameterId": "one_of_parameter_1"}}}},
+    {"ShapeParameterAdded": {"shapeParameterId": "one_of_parameter_2", "shapeId": "one_of_1", "name": "", "shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_1", "providerDescriptor": {"NoProvider": {}}, "consumingParameterId": "one_of_parameter_2"}}}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "boolean_1"}}, "consumingParameterId": "one_of_parameter_2"}}}},
+
+    {"ShapeAdded": {"shapeId": "post_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "post_1", "name": "title", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "post_1", "name": "rating", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "optional_1"}}}},
+    {"FieldAdded": {"fieldId": "field_3", "shapeId": "post_1", "name": "subtitle", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_3", "shapeId": "nullable_1"}}}},
+    {"FieldAdded": {"fieldId": "field_4", "shapeId": "post_1", "name": "tags", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_4", "shapeId": "list_1"}}}},
+    {"FieldAdded": {"fieldId": "field_5", "shapeId": "post_1", "name": "published", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_5", "shapeId": "one_of_1"}}}},
+    {"ContributionAdded": {"id": "field_1", "key": "description", "value": "the title of the post"}},
+
+    {"ShapeAdded": {"shapeId": "query_shape_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_6", "shapeId": "query_shape_1", "name": "limit", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_6", "shapeId": "optional_1"}}}},
+
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"QueryParametersAdded": {"queryParametersId": "query_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"QueryParametersShapeSet": {"queryParametersId": "query_1", "shapeDescriptor": {"shapeId": "query_shape_1", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "post_1", "isRemoved": false}}},
+    {"ContributionAdded": {"id": "path_1.GET", "key": "purpose", "value": "List posts"}},
+    {"ContributionAdded": {"id": "response_1", "key": "description", "value": "the posts"}},
+
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_2", "httpMethod": "PUT"}},
+    {"RequestBodySet": {"requestId": "request_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "post_1", "isRemoved": false}}},
+    {"RequestParameterAdded": {"parameterId": "header_1", "requestId": "request_2", "parameterLocation": "header", "name": "X-Api-Key"}},
+    {"RequestParameterShapeSet": {"parameterId": "header_1", "parameterDescriptor": {"shapeId": "string_1", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_2", "httpMethod": "PUT", "httpStatusCode": 204}},
+    {"ResponseParameterAdded": {"parameterId": "header_2", "responseId": "response_2", "parameterLocation": "header", "name": "X-Rate-Limit"}},
+    {"RequestParameterShapeSet": {"parameterId": "header_2", "parameterDescriptor": {"shapeId": "optional_1", "isRemoved": false}}},
+    {"ContributionAdded": {"id": "path_2.PUT", "key": "purpose", "value": "Update a post"}},
+    {"ContributionAdded": {"id": "path_2.PUT", "key": "description", "value": "Replaces the post entirely"}},
+  ]))
+  .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let openapi = export_openapi(&spec_projection);
+
+  assert_json_snapshot!(
+    "exports_endpoints_shapes_and_contributions__openapi",
+    openapi
+  );
+}
+
+#[test]
+pub fn exports_bodies_of_shapes_descending_from_another_as_its_component() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "admins"}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": "User"}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}}},
+    {"ShapeAdded": {"shapeId": "admin_1", "baseShapeId": "user_1", "name": "Admin"}},
+
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "admin_1", "isRemoved": false}}},
+  ]))
+  .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let openapi = export_openapi(&spec_projection);
+
+  let schema = openapi.paths["/admins"].operations["get"].responses["200"].content
+    ["application/json"]
+    .schema
+    .as_ref()
+    .expect("response body should have a schema");
+  assert_eq!(
+    schema.reference.as_deref(),
+    Some("#/components/schemas/user_1")
+  );
+  assert_eq!(
+    openapi.components.schemas.keys().collect::<Vec<_>>(),
+    vec!["user_1"]
+  );
+}
+
+#[test]
+pub fn exports_generic_shapes_with_their_bound_parameters() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "users"}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": "User"}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}}},
+
+    // Page<T> { items: T[], first: T }
+    {"ShapeAdded": {"shapeId": "page_1", "baseShapeId": "$object", "parameters": {"DynamicParameterList": {"shapeParameterIds": []}}, "name": "Page"}},
+    {"ShapeParameterAdded": {"shapeParameterId": "page_item_1", "shapeId": "page_1", "name": "T", "shapeDescriptor": {"ProviderInShape": {"shapeId": "page_1", "providerDescriptor": {"NoProvider": {}}, "consumingParameterId": "page_item_1"}}}},
+    {"ShapeAdded": {"shapeId": "list_1", "baseShapeId": "$list", "name": ""}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "list_1", "providerDescriptor": {"ParameterProvider": {"shapeParameterId": "page_item_1"}}, "consumingParameterId": "$listItem"}}}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "page_1", "name": "items", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "list_1"}}}},
+    {"FieldAdded": {"fieldId": "field_3", "shapeId": "page_1", "name": "first", "shapeDescriptor": {"FieldShapeFromParameter": {"fieldId": "field_3", "shapeParameterId": "page_item_1"}}}},
+
+    // Page<User> as a shape of its own
+    {"ShapeAdded": {"shapeId": "user_page_1", "baseShapeId": "page_1", "parameters": {"DynamicParameterList": {"shapeParameterIds": []}}, "name": "UserPage"}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "user_page_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "user_1"}}, "consumingParameterId": "page_item_1"}}}},
+
+    // { data: Page<User> }, bound by the field
+    {"ShapeAdded": {"shapeId": "envelope_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_4", "shapeId": "envelope_1", "name": "data", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_4", "shapeId": "page_1"}}}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInField": {"fieldId": "field_4", "providerDescriptor": {"ShapeProvider": {"shapeId": "user_1"}}, "consumingParameterId": "page_item_1"}}}},
+
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_page_1", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 206}},
+    {"ResponseBodySet": {"responseId": "response_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "envelope_1", "isRemoved": false}}},
+  ]))
+  .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let openapi = export_openapi(&spec_projection);
+
+  let user_schema = json!({
+    "type": "object",
+    "properties": {"name": {"type": "string"}},
+    "required": ["name"],
+  });
+  let page_schema = json!({
+    "type": "object",
+    "properties": {
+      "items": {"type": "array", "items": user_schema},
+      "first": user_schema,
+    },
+    "required": ["first", "items"],
+  });
+  let schemas = serde_json::to_value(&openapi.components.schemas).unwrap();
+  assert_eq!(schemas["user_page_1"], page_schema);
+  assert_eq!(schemas["envelope_1"]["properties"]["data"], page_schema);
+}
+
+#[test]
+pub fn exports_every_documented_path_of_a_spec() {
+  let events = SpecEvent::from_file("tests/fixtures/ergast-example-spec.json")
+    .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let openapi = export_openapi(&spec_projection);
+
+  assert!(openapi
+    .paths
+    .contains_key("/api/f1/{season}/{round}/results"));
+  let operation = &openapi.paths["/api/f1/{season}/{round}/results"].operations["get"];
+  assert_eq!(
+    operation.summary.as_deref(),
+    Some("Get results for Round of Season")
+  );
+  assert_eq!(
+    openapi.paths["/api/f1/{season}/{round}/results"]
+      .parameters
+      .len(),
+    2
+  );
+
+  let serialized = serde_json::to_value(&openapi).expect("openapi should serialize");
+  let roundtripped: optic_engine::openapi::OpenApi =
+    serde_json::from_value(serialized).expect("exported openapi should deserialize");
+  assert_eq!(roundtripped, openapi);
+}
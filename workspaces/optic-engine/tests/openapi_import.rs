@@ -0,0 +1,168 @@
+use insta::assert_json_snapshot;
+use optic_engine::openapi::OpenApi;
+use optic_engine::{
+  export_openapi, import_openapi, Aggregate, SpecCommand, SpecEvent, SpecIdGenerator,
+  SpecProjection,
+};
+use serde_json::json;
+use std::convert::TryFrom;
+
+#[test]
+pub fn imports_endpoints_schemas_and_descriptions() {
+  let document = OpenApi::try_from(json!({
+    "openapi": "3.0.3",
+    "info": { "title": "Posts", "version": "1.0.0" },
+    "paths": {
+      "/posts": {
+        "get": {
+          "summary": "List posts",
+          "parameters": [
+            { "name": "limit", "in": "query", "schema": { "type": "integer" }, "description": "page size" },
+            { "$ref": "#/components/parameters/ApiKey" },
+            { "name": "session", "in": "cookie", "schema": { "type": "string" } }
+          ],
+          "responses": {
+            "200": {
+              "description": "the posts",
+              "headers": { "X-Rate-Limit": { "schema": { "type": "integer" } } },
+              "content": {
+                "application/json": {
+                  "schema": { "type": "array", "items": { "$ref": "#/components/schemas/Post" } }
+                }
+              }
+            },
+            "default": { "description": "an error" }
+          }
+        }
+      },
+      "/posts/{postId}": {
+        "parameters": [
+          { "name": "postId", "in": "path", "required": true, "schema": { "type": "string" }, "description": "the id of the post" }
+        ],
+        "put": {
+          "summary": "Update a post",
+          "description": "Replaces the post entirely",
+          "requestBody": {
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Post" } } }
+          },
+          "responses": { "204": { "$ref": "#/components/responses/NoContent" } }
+        }
+      }
+    },
+    "components": {
+      "parameters": {
+        "ApiKey": { "name": "X-Api-Key", "in": "header", "required": true, "schema": { "type": "string" } }
+      },
+      "responses": {
+        "NoContent": { "description": "the post was updated" }
+      },
+      "schemas": {
+        "Post": {
+          "type": "object",
+          "description": "a blog post",
+          "required": ["title", "tags", "subtitle", "published", "replies"],
+          "properties": {
+            "title": { "type": "string", "description": "the title of the post", "pattern": "^[A-Z]" },
+            "subtitle": { "type": "string", "nullable": true },
+            "rating": { "type": "number" },
+            "status": { "type": "string", "enum": ["draft", "live"] },
+            "createdAt": { "type": "string", "format": "date-time" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "published": { "oneOf": [{ "type": "string" }, { "type": "boolean" }] },
+            "replies": { "type": "array", "items": { "$ref": "#/components/schemas/Post" } },
+            "author": { "allOf": [{ "type": "object" }, { "type": "object" }] }
+          }
+        }
+      }
+    }
+  }))
+  .expect("document should parse");
+
+  let mut id_generator = SequentialIdGenerator::default();
+  let import = import_openapi(&SpecProjection::default(), &document, &mut id_generator);
+
+  assert_json_snapshot!(
+    "imports_endpoints_schemas_and_descriptions__unsupported",
+    import.unsupported
+  );
+
+  let spec_projection = assert_valid_commands(SpecProjection::default(), import.commands);
+  assert_json_snapshot!(
+    "imports_endpoints_schemas_and_descriptions__exported",
+    export_openapi(&spec_projection)
+  );
+}
+
+#[test]
+pub fn imports_exported_specs_into_equivalent_specs() {
+  let events = SpecEvent::from_file("tests/fixtures/ergast-example-spec.json")
+    .expect("should be able to deserialize events");
+  let exported = export_openapi(&SpecProjection::from(events));
+
+  let mut id_generator = SequentialIdGenerator::default();
+  let import = import_openapi(&SpecProjection::default(), &exported, &mut id_generator);
+  assert!(import.unsupported.is_empty());
+
+  let spec_projection = assert_valid_commands(SpecProjection::default(), import.commands);
+  let reexported = export_openapi(&spec_projection);
+
+  assert_eq!(
+    reexported.paths.keys().collect::<Vec<_>>(),
+    exported.paths.keys().collect::<Vec<_>>()
+  );
+  for (path, path_item) in &exported.paths {
+    let reexported_path_item = &reexported.paths[path];
+    assert_eq!(reexported_path_item.parameters, path_item.parameters);
+    assert_eq!(
+      reexported_path_item.operations.keys().collect::<Vec<_>>(),
+      path_item.operations.keys().collect::<Vec<_>>()
+    );
+    for (method, operation) in &path_item.operations {
+      let reexported_operation = &reexported_path_item.operations[method];
+      assert_eq!(reexported_operation.summary, operation.summary);
+      assert_eq!(
+        reexported_operation.responses.keys().collect::<Vec<_>>(),
+        operation.responses.keys().collect::<Vec<_>>()
+      );
+    }
+  }
+}
+
+#[test]
+pub fn rejects_documents_of_other_versions() {
+  let result = OpenApi::try_from(json!({
+    "swagger": "2.0",
+    "info": { "title": "Posts", "version": "1.0.0" },
+    "paths": {}
+  }));
+
+  assert!(result.is_err());
+}
+
+#[derive(Debug, Default)]
+struct SequentialIdGenerator {
+  next_id: u32,
+}
+impl SpecIdGenerator for SequentialIdGenerator {
+  fn generate_id(&mut self, prefix: &str) -> String {
+    self.next_id += 1;
+    format!("{}{}", prefix, self.next_id)
+  }
+}
+
+fn assert_valid_commands(
+  mut spec_projection: SpecProjection,
+  commands: impl IntoIterator<Item = SpecCommand>,
+) -> SpecProjection {
+  for command in commands {
+    let events = spec_projection
+      .execute(command)
+      .expect("generated commands must be valid");
+
+    for event in events {
+      spec_projection.apply(event)
+    }
+  }
+
+  spec_projection
+}
@@ -548,3 +548,288 @@ fn can_handle_base_shape_changes() {
   assert_eq!(results.len(), 0);
   assert_debug_snapshot!("can_handle_base_shape_changes__fingerprints", fingerprints);
 }
+
+#[test]
+fn can_match_map_json_with_any_keys() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"map_1","baseShapeId":"$map","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"map_1","providerDescriptor":{"ShapeProvider":{"shapeId":"number_shape_1"}},"consumingParameterId":"$mapValue"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+
+  assert_debug_snapshot!(
+    "can_match_map_json_with_any_keys__shape_projection_graph",
+    Dot::with_config(&shape_projection.graph, &[])
+  );
+  let map_body = json!({
+    "homer": 39,
+    "marge": 36,
+    "bart": 10
+  });
+  let shape_id = String::from("map_1");
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(map_body)),
+    &shape_id,
+  );
+
+  assert_eq!(
+    results.len(),
+    0,
+    "map values matching the value shape should not yield any results, whatever their keys"
+  );
+}
+
+#[test]
+fn can_yield_unmatched_shape_for_mismatched_map_values() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"map_1","baseShapeId":"$map","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"map_1","providerDescriptor":{"ShapeProvider":{"shapeId":"number_shape_1"}},"consumingParameterId":"$mapValue"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+
+  let map_body = json!({
+    "homer": 39,
+    "marge": "thirty-six",
+    "bart": 10
+  });
+  let shape_id = String::from("map_1");
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(map_body)),
+    &shape_id,
+  );
+  let fingerprints = results
+    .iter()
+    .map(|result| result.fingerprint())
+    .collect::<Vec<_>>();
+
+  assert_eq!(results.len(), 1);
+  assert_debug_snapshot!(
+    "can_yield_unmatched_shape_for_mismatched_map_values__results",
+    results
+  );
+  assert_debug_snapshot!(
+    "can_yield_unmatched_shape_for_mismatched_map_values__fingerprints",
+    fingerprints
+  );
+}
+
+#[test]
+fn can_diff_shapes_binding_generic_parameters() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"page_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Page"}},
+      {"ShapeParameterAdded":{"shapeParameterId":"page_item_1","shapeId":"page_1","name":"T","shapeDescriptor":{"ProviderInShape":{"shapeId":"page_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"page_item_1"}}}},
+      {"ShapeAdded":{"shapeId":"list_1","baseShapeId":"$list","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"list_1","providerDescriptor":{"ParameterProvider":{"shapeParameterId":"page_item_1"}},"consumingParameterId":"$listItem"}}}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"page_1","name":"items","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"list_1"}}}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"page_1","name":"total","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"number_shape_1"}}}},
+      {"FieldAdded":{"fieldId":"field_3","shapeId":"page_1","name":"first","shapeDescriptor":{"FieldShapeFromParameter":{"fieldId":"field_3","shapeParameterId":"page_item_1"}}}},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"page_of_strings_1","baseShapeId":"page_1","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"PageOfStrings"}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"page_of_strings_1","providerDescriptor":{"ShapeProvider":{"shapeId":"string_shape_1"}},"consumingParameterId":"page_item_1"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("page_of_strings_1");
+
+  let matching_body = json!({
+    "items": ["a", "b", "c"],
+    "total": 3,
+    "first": "a"
+  });
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(matching_body)),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "fields and list items of the bound parameter should match the provided shape"
+  );
+
+  let mismatching_body = json!({
+    "items": ["a", 2, "c"],
+    "total": 3,
+    "first": false
+  });
+  let mismatching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(mismatching_body)),
+    &shape_id,
+  );
+  assert_eq!(mismatching_results.len(), 2);
+  assert_debug_snapshot!(
+    "can_diff_shapes_binding_generic_parameters__mismatching_results",
+    mismatching_results
+  );
+}
+
+#[test]
+fn can_diff_generic_parameters_bound_through_fields() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"page_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Page"}},
+      {"ShapeParameterAdded":{"shapeParameterId":"page_item_1","shapeId":"page_1","name":"T","shapeDescriptor":{"ProviderInShape":{"shapeId":"page_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"page_item_1"}}}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"page_1","name":"first","shapeDescriptor":{"FieldShapeFromParameter":{"fieldId":"field_1","shapeParameterId":"page_item_1"}}}},
+      {"ShapeAdded":{"shapeId":"response_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Response"}},
+      {"ShapeParameterAdded":{"shapeParameterId":"response_data_1","shapeId":"response_1","name":"U","shapeDescriptor":{"ProviderInShape":{"shapeId":"response_1","providerDescriptor":{"NoProvider":{}},"consumingParameterId":"response_data_1"}}}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"response_1","name":"data","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"page_1"}}}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInField":{"fieldId":"field_2","providerDescriptor":{"ParameterProvider":{"shapeParameterId":"response_data_1"}},"consumingParameterId":"page_item_1"}}}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"response_of_numbers_1","baseShapeId":"response_1","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"ResponseOfNumbers"}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"response_of_numbers_1","providerDescriptor":{"ShapeProvider":{"shapeId":"number_shape_1"}},"consumingParameterId":"response_data_1"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("response_of_numbers_1");
+
+  let matching_body = json!({
+    "data": { "first": 1 }
+  });
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(matching_body)),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "parameters forwarded through fields should resolve against the enclosing binding"
+  );
+
+  let mismatching_body = json!({
+    "data": { "first": "one" }
+  });
+  let mismatching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(mismatching_body)),
+    &shape_id,
+  );
+  assert_eq!(mismatching_results.len(), 1);
+  assert_debug_snapshot!(
+    "can_diff_generic_parameters_bound_through_fields__mismatching_results",
+    mismatching_results
+  );
+}
+
+#[test]
+fn can_diff_string_format_constraints() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+    {"ShapeAdded":{"shapeId":"example_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+    {"ShapeConstraintSet":{"shapeId":"example_shape_1","constraintDescriptor":{"FormatConstraint":{"format":"uuid"}}}}
+  ]),
+  ).expect("should be able to deserialize shape constraint events as spec events");
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("example_shape_1");
+
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(json!(
+      "0b9dd9c7-7b1c-4a37-9a1e-4a4f4e4c3a2b"
+    ))),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "strings of the documented format should not yield any results"
+  );
+
+  let mismatching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(json!("2021-03-04T12:00:00Z"))),
+    &shape_id,
+  );
+  assert_eq!(mismatching_results.len(), 1);
+  assert_debug_snapshot!(
+    "can_diff_string_format_constraints__mismatching_results",
+    mismatching_results
+  );
+}
+
+#[test]
+fn can_diff_enum_constraints() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+    {"ShapeAdded":{"shapeId":"list_1","baseShapeId":"$list","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+    {"ShapeAdded":{"shapeId":"status_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+    {"ShapeConstraintSet":{"shapeId":"status_1","constraintDescriptor":{"EnumConstraint":{"values":["active","archived"]}}}},
+    {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"list_1","providerDescriptor":{"ShapeProvider":{"shapeId":"status_1"}},"consumingParameterId":"$listItem"}}}},
+  ]),
+  ).expect("should be able to deserialize shape constraint events as spec events");
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("list_1");
+
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(json!([
+      "active", "archived", "active"
+    ]))),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "enumerated values should not yield any results"
+  );
+
+  let mismatching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(json!(["active", "deleted", "purged"]))),
+    &shape_id,
+  );
+  assert_eq!(
+    mismatching_results.len(),
+    1,
+    "values of equally shaped array items should be checked together"
+  );
+  assert_debug_snapshot!(
+    "can_diff_enum_constraints__mismatching_results",
+    mismatching_results
+  );
+}
+
+#[test]
+fn can_diff_integer_constraints() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+    {"ShapeAdded":{"shapeId":"example_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+    {"ShapeConstraintSet":{"shapeId":"example_shape_1","constraintDescriptor":{"IntegerConstraint":{}}}}
+  ]),
+  ).expect("should be able to deserialize shape constraint events as spec events");
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("example_shape_1");
+
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(json!(42))),
+    &shape_id,
+  );
+  assert_eq!(matching_results.len(), 0);
+
+  let mismatching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(json!(4.2))),
+    &shape_id,
+  );
+  assert_eq!(mismatching_results.len(), 1);
+  assert_debug_snapshot!(
+    "can_diff_integer_constraints__mismatching_results",
+    mismatching_results
+  );
+}
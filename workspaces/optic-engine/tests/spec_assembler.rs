@@ -1,5 +1,5 @@
 use insta::assert_debug_snapshot;
-use optic_engine::{SpecAssemblerProjection, SpecChunkEvent, SpecEvent};
+use optic_engine::{SpecAssemblerError, SpecAssemblerProjection, SpecChunkEvent, SpecEvent};
 use serde_json::json;
 
 #[test]
@@ -82,3 +82,157 @@ pub fn can_assemble_spec_events_from_serialized_chunks() {
     assembled_events
   );
 }
+
+#[test]
+pub fn can_assemble_spec_events_from_forked_batch_chunks() {
+  let spec_chunk_events = spec_chunks(vec![
+    (
+      "specification.json",
+      true,
+      json!([
+        {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      ]),
+    ),
+    (
+      "0001.json",
+      false,
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-1", "parentId": "root", "commitMessage": "Add GET /todos", "eventContext": event_context("2021-03-01T10:00:00.000Z") }},
+        {"RequestAdded": { "requestId": "request_1","pathId": "path_1","httpMethod": "GET" }},
+        {"BatchCommitEnded": { "batchId": "batch-1" }}
+      ]),
+    ),
+    (
+      "0003-other-branch.json",
+      false,
+      // committed on another branch, later than batch-2
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-3", "parentId": "batch-1", "commitMessage": "Add 404 response", "eventContext": event_context("2021-03-02T09:00:00.000+01:00") }},
+        {"ResponseAddedByPathAndMethod": { "responseId": "response_2", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 404 }},
+        {"BatchCommitEnded": { "batchId": "batch-3" }}
+      ]),
+    ),
+    (
+      "0002.json",
+      false,
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-2", "parentId": "batch-1", "commitMessage": "Add 200 response", "eventContext": event_context("2021-03-02T07:30:00.000Z") }},
+        {"ResponseAddedByPathAndMethod": { "responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200 }},
+        {"BatchCommitEnded": { "batchId": "batch-2" }}
+      ]),
+    ),
+    (
+      "0004.json",
+      false,
+      // continues from the tip of the merged spec
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-4", "parentId": "batch-3", "commitMessage": "Rename todos", "eventContext": event_context("2021-03-03T10:00:00.000Z") }},
+        {"PathComponentRenamed": { "pathId": "path_1", "name": "tasks" }},
+        {"BatchCommitEnded": { "batchId": "batch-4" }}
+      ]),
+    ),
+  ]);
+
+  let (assembled_events, report) = SpecAssemblerProjection::from(spec_chunk_events)
+    .into_events_with_report()
+    .expect("branches without conflicting changes should merge");
+
+  let assembled_batch_ids = report
+    .batches
+    .iter()
+    .map(|batch| batch.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    assembled_batch_ids,
+    vec!["batch-1", "batch-2", "batch-3", "batch-4"]
+  );
+  assert_eq!(report.forks.len(), 1);
+  assert_eq!(report.forks[0].parent_id, "batch-1");
+  assert_eq!(
+    report.forks[0].branches,
+    vec![
+      vec![String::from("batch-2")],
+      vec![String::from("batch-3"), String::from("batch-4")]
+    ]
+  );
+  assert!(report.unreachable_batch_ids.is_empty());
+  assert_eq!(assembled_events.len(), 13);
+}
+
+#[test]
+pub fn assembling_conflicting_branches_is_an_error() {
+  let spec_chunk_events = spec_chunks(vec![
+    (
+      "specification.json",
+      true,
+      json!([
+        {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      ]),
+    ),
+    (
+      "0001.json",
+      false,
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-1", "parentId": "root", "commitMessage": "Rename to tasks" }},
+        {"PathComponentRenamed": { "pathId": "path_1", "name": "tasks" }},
+        {"BatchCommitEnded": { "batchId": "batch-1" }}
+      ]),
+    ),
+    (
+      "0002.json",
+      false,
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-2", "parentId": "root", "commitMessage": "Rename to items" }},
+        {"PathComponentRenamed": { "pathId": "path_1", "name": "items" }},
+        {"BatchCommitEnded": { "batchId": "batch-2" }}
+      ]),
+    ),
+    (
+      "0003.json",
+      false,
+      json!([
+        {"BatchCommitStarted": {"batchId": "batch-3", "parentId": "root", "commitMessage": "Also rename to tasks" }},
+        {"PathComponentRenamed": { "pathId": "path_1", "name": "tasks" }},
+        {"BatchCommitEnded": { "batchId": "batch-3" }}
+      ]),
+    ),
+  ]);
+
+  let result = SpecAssemblerProjection::from(spec_chunk_events).into_events_with_report();
+
+  match result {
+    Err(SpecAssemblerError::ConflictingBranches {
+      parent_id,
+      conflicts,
+    }) => {
+      assert_eq!(parent_id, "root");
+      // identical renames on batch-1 and batch-3 don't conflict
+      assert_eq!(conflicts.len(), 2);
+      assert!(conflicts
+        .iter()
+        .all(|conflict| conflict.entity_id == "path_1"));
+    }
+    other => panic!("expected conflicting branches, got {:?}", other),
+  }
+}
+
+fn spec_chunks(raw_chunks: Vec<(&str, bool, serde_json::Value)>) -> Vec<SpecChunkEvent> {
+  raw_chunks
+    .into_iter()
+    .map(|(file_name, is_root, events_json)| {
+      let events: Vec<SpecEvent> =
+        serde_json::from_value(events_json).expect("example events should be valid spec events");
+
+      SpecChunkEvent::from((String::from(file_name), is_root, events))
+    })
+    .collect()
+}
+
+fn event_context(created_at: &str) -> serde_json::Value {
+  json!({
+    "clientId": "anonymous",
+    "clientSessionId": "session-1",
+    "clientCommandBatchId": "command-batch-1",
+    "createdAt": created_at
+  })
+}
@@ -0,0 +1,64 @@
+use optic_engine::{SpecEvent, SpecProjection};
+use serde_json::json;
+
+#[test]
+pub fn spec_can_be_projected_at_any_batch_commit() {
+  let events = branched_spec_events();
+
+  let path_names_at = |batch_id: &str| {
+    SpecProjection::events_at_commit(events.clone(), &String::from(batch_id))
+      .expect("batch commit should exist")
+      .into_iter()
+      .filter_map(|event| match serde_json::to_value(event).unwrap() {
+        serde_json::Value::Object(event) => event
+          .get("PathComponentAdded")
+          .map(|added| added["name"].as_str().unwrap().to_owned()),
+        _ => None,
+      })
+      .collect::<Vec<_>>()
+  };
+
+  assert_eq!(path_names_at("batch_1"), vec!["users"]);
+  assert_eq!(path_names_at("batch_2"), vec!["users", "posts"]);
+  // commits of other branches are left out
+  assert_eq!(path_names_at("batch_3"), vec!["users", "comments"]);
+  assert_eq!(path_names_at("batch_4"), vec!["users", "comments", "tags"]);
+
+  let spec_projection = SpecProjection::at_commit(events.clone(), &String::from("batch_3"))
+    .expect("batch commit should exist");
+  let history = spec_projection.history();
+  assert!(history
+    .get_batch_commit_node_index(&String::from("batch_3"))
+    .is_some());
+  assert!(history
+    .get_batch_commit_node_index(&String::from("batch_2"))
+    .is_none());
+  assert!(history
+    .get_batch_commit_node_index(&String::from("batch_4"))
+    .is_none());
+
+  assert!(SpecProjection::at_commit(events, &String::from("not-a-batch")).is_none());
+}
+
+fn branched_spec_events() -> Vec<SpecEvent> {
+  let context = |batch_id: &str| json!({"clientId": "anonymous", "clientSessionId": "session_1", "clientCommandBatchId": batch_id, "createdAt": "2021-03-01T10:00:00Z"});
+
+  serde_json::from_value(json!([
+    {"BatchCommitStarted": {"batchId": "batch_1", "commitMessage": "add users", "parentId": "root", "eventContext": context("batch_1")}},
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "users"}},
+    {"BatchCommitEnded": {"batchId": "batch_1"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_2", "commitMessage": "add posts", "parentId": "batch_1", "eventContext": context("batch_2")}},
+    {"PathComponentAdded": {"pathId": "path_2", "parentPathId": "root", "name": "posts"}},
+    {"BatchCommitEnded": {"batchId": "batch_2"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_3", "commitMessage": "add comments", "parentId": "batch_1", "eventContext": context("batch_3")}},
+    {"PathComponentAdded": {"pathId": "path_3", "parentPathId": "root", "name": "comments"}},
+    {"BatchCommitEnded": {"batchId": "batch_3"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_4", "commitMessage": "add tags", "parentId": "batch_3", "eventContext": context("batch_4")}},
+    {"PathComponentAdded": {"pathId": "path_4", "parentPathId": "root", "name": "tags"}},
+    {"BatchCommitEnded": {"batchId": "batch_4"}},
+  ]))
+  .expect("should be able to deserialize events")
+}
@@ -0,0 +1,90 @@
+use insta::assert_json_snapshot;
+use optic_engine::{blame, BlameChange, SpecEvent};
+use serde_json::json;
+
+#[test]
+pub fn blame_lists_events_touching_entity_with_their_commits() {
+  let events = users_spec_events();
+
+  let field_blame = blame(events.clone(), "field_2");
+  assert_json_snapshot!(
+    "blame_lists_events_touching_entity_with_their_commits__field",
+    field_blame
+  );
+
+  let path_blame = blame(events.clone(), "path_1");
+  let path_changes = path_blame
+    .entries
+    .iter()
+    .map(|entry| (entry.event_type.as_str(), entry.batch_id.as_deref()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    path_changes,
+    vec![
+      ("PathComponentAdded", Some("batch_1")),
+      ("RequestAdded", Some("batch_1")),
+      ("ResponseAddedByPathAndMethod", Some("batch_1")),
+      ("ResponseAddedByPathAndMethod", Some("batch_2")),
+      ("RequestAdded", Some("batch_3")),
+      ("ResponseAddedByPathAndMethod", Some("batch_3")),
+    ]
+  );
+  assert_eq!(path_blame.entries[0].change, BlameChange::Added);
+  assert!(path_blame.entries[1..]
+    .iter()
+    .all(|entry| entry.change == BlameChange::Referenced));
+
+  assert!(blame(events, "not-an-entity").entries.is_empty());
+}
+
+#[test]
+pub fn blame_prefers_context_of_event_over_its_batch_commit() {
+  let mut events = users_spec_events();
+  events.extend(
+    serde_json::from_value::<Vec<SpecEvent>>(json!([
+      {"BatchCommitStarted": {"batchId": "batch_4", "commitMessage": "Rename name", "parentId": "batch_3", "eventContext": context("batch_4", "anonymous")}},
+      {"FieldRenamed": {"fieldId": "field_2", "name": "displayName", "eventContext": context("batch_4", "reviewer")}},
+      {"BatchCommitEnded": {"batchId": "batch_4"}},
+    ]))
+    .unwrap(),
+  );
+
+  let field_blame = blame(events, "field_2");
+  let last_entry = field_blame.entries.last().unwrap();
+  assert_eq!(last_entry.batch_id.as_deref(), Some("batch_4"));
+  assert_eq!(last_entry.commit_message.as_deref(), Some("Rename name"));
+  assert_eq!(last_entry.client_id.as_deref(), Some("reviewer"));
+}
+
+fn context(batch_id: &str, client_id: &str) -> serde_json::Value {
+  json!({"clientId": client_id, "clientSessionId": "session_1", "clientCommandBatchId": batch_id, "createdAt": "2021-03-01T10:00:00Z"})
+}
+
+fn users_spec_events() -> Vec<SpecEvent> {
+  serde_json::from_value(json!([
+    {"BatchCommitStarted": {"batchId": "batch_1", "commitMessage": "Document listing users", "parentId": "root", "eventContext": context("batch_1", "anonymous")}},
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "users"}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "number_1", "baseShapeId": "$number", "name": ""}},
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "id", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "number_1"}}}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "string_1"}}}},
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}}},
+    {"BatchCommitEnded": {"batchId": "batch_1"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_2", "commitMessage": "Rework users", "parentId": "batch_1", "eventContext": context("batch_2", "anonymous")}},
+    {"FieldRenamed": {"fieldId": "field_2", "name": "fullName"}},
+    {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "number_1"}}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 404}},
+    {"BatchCommitEnded": {"batchId": "batch_2"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_3", "commitMessage": "Document creating users", "parentId": "batch_2", "eventContext": context("batch_3", "anonymous")}},
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_1", "httpMethod": "POST"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_3", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 201}},
+    {"FieldRemoved": {"fieldId": "field_2"}},
+    {"BatchCommitEnded": {"batchId": "batch_3"}},
+  ]))
+  .expect("should be able to deserialize events")
+}
@@ -0,0 +1,75 @@
+use insta::{assert_json_snapshot, assert_snapshot};
+use optic_engine::{changelog, SpecEvent};
+use serde_json::json;
+
+#[test]
+pub fn changelog_lists_changes_per_batch_commit() {
+  let events = users_spec_events();
+
+  let changelog = changelog(events.clone(), None, None).expect("batch commits should exist");
+
+  assert_eq!(changelog.commits.len(), 3);
+  assert_json_snapshot!(
+    "changelog_lists_changes_per_batch_commit__changelog",
+    changelog
+  );
+  assert_snapshot!(
+    "changelog_lists_changes_per_batch_commit__markdown",
+    changelog.to_markdown()
+  );
+
+  let since_first_commit = changelog_since(&events, "batch_1", None);
+  assert_eq!(
+    since_first_commit,
+    vec![String::from("batch_2"), String::from("batch_3")]
+  );
+  let first_two_commits = changelog_since(&events, "root", Some("batch_2"));
+  assert_eq!(
+    first_two_commits,
+    vec![String::from("batch_1"), String::from("batch_2")]
+  );
+
+  assert!(optic_engine::changelog(events, Some(&String::from("not-a-batch")), None).is_none());
+}
+
+fn changelog_since(events: &[SpecEvent], from: &str, to: Option<&str>) -> Vec<String> {
+  let to = to.map(String::from);
+  changelog(events.to_vec(), Some(&String::from(from)), to.as_ref())
+    .expect("batch commits should exist")
+    .commits
+    .into_iter()
+    .map(|commit| commit.batch_id)
+    .collect()
+}
+
+fn users_spec_events() -> Vec<SpecEvent> {
+  let context = |batch_id: &str| json!({"clientId": "anonymous", "clientSessionId": "session_1", "clientCommandBatchId": batch_id, "createdAt": "2021-03-01T10:00:00Z"});
+
+  serde_json::from_value(json!([
+    {"BatchCommitStarted": {"batchId": "batch_1", "commitMessage": "Document listing users", "parentId": "root", "eventContext": context("batch_1")}},
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "users"}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "number_1", "baseShapeId": "$number", "name": ""}},
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "id", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "number_1"}}}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "string_1"}}}},
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}}},
+    {"BatchCommitEnded": {"batchId": "batch_1"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_2", "commitMessage": "Rework users", "parentId": "batch_1", "eventContext": context("batch_2")}},
+    {"FieldRenamed": {"fieldId": "field_2", "name": "fullName"}},
+    {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}}},
+    {"FieldAdded": {"fieldId": "field_3", "shapeId": "user_1", "name": "email", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_3", "shapeId": "string_1"}}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 404}},
+    {"BatchCommitEnded": {"batchId": "batch_2"}},
+
+    {"BatchCommitStarted": {"batchId": "batch_3", "commitMessage": "Document creating users", "parentId": "batch_2", "eventContext": context("batch_3")}},
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_1", "httpMethod": "POST"}},
+    {"RequestBodySet": {"requestId": "request_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_3", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 201}},
+    {"BatchCommitEnded": {"batchId": "batch_3"}},
+  ]))
+  .expect("should be able to deserialize events")
+}
@@ -0,0 +1,189 @@
+use insta::assert_json_snapshot;
+use optic_engine::compare::{ChangeKind, ChangeLocation};
+use optic_engine::{compare_specs, SpecEvent, SpecProjection};
+use serde_json::json;
+
+#[test]
+pub fn classifies_changes_between_spec_versions() {
+  let before_events = users_spec_events();
+  let mut after_events = before_events.clone();
+  after_events.extend(
+    serde_json::from_value::<Vec<SpecEvent>>(json!([
+      {"ShapeAdded": {"shapeId": "integer_1", "baseShapeId": "$number", "name": ""}},
+      {"ShapeConstraintSet": {"shapeId": "integer_1", "constraintDescriptor": {"IntegerConstraint": {}}}},
+
+      // user name turns optional, both in responses and the request to create one
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "optional_string_1"}}}},
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_3", "shapeId": "optional_string_1"}}}},
+      // ids and ages narrowed to integers
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "integer_1"}}}},
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_4", "shapeId": "integer_1"}}}},
+
+      // listing users requires paging
+      {"ShapeAdded": {"shapeId": "query_shape_1", "baseShapeId": "$object", "name": ""}},
+      {"FieldAdded": {"fieldId": "field_5", "shapeId": "query_shape_1", "name": "page", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_5", "shapeId": "number_1"}}}},
+      {"QueryParametersAdded": {"queryParametersId": "query_1", "pathId": "path_1", "httpMethod": "GET"}},
+      {"QueryParametersShapeSet": {"queryParametersId": "query_1", "shapeDescriptor": {"shapeId": "query_shape_1", "isRemoved": false}}},
+
+      {"ResponseRemoved": {"responseId": "response_2"}},
+      {"RequestRemoved": {"requestId": "request_3"}},
+      {"ResponseRemoved": {"responseId": "response_4"}},
+    ]))
+    .expect("should be able to deserialize events"),
+  );
+
+  let before = SpecProjection::from(before_events);
+  let after = SpecProjection::from(after_events);
+
+  let comparison = compare_specs(&before, &after);
+  assert!(comparison.is_breaking());
+
+  let field_made_optional = |location: &ChangeLocation| {
+    comparison
+      .changes
+      .iter()
+      .find(|change| {
+        change.location == *location
+          && change.trail == vec![String::from("name")]
+          && change.change == ChangeKind::FieldMadeOptional
+      })
+      .expect("name field should have been made optional")
+  };
+  assert!(
+    field_made_optional(&ChangeLocation::ResponseBody {
+      status_code: String::from("200"),
+      content_type: String::from("application/json"),
+    })
+    .is_breaking,
+    "optional fields in responses break clients"
+  );
+  assert!(
+    !field_made_optional(&ChangeLocation::RequestBody {
+      content_type: Some(String::from("application/json")),
+    })
+    .is_breaking,
+    "optional fields in requests are compatible"
+  );
+
+  assert_json_snapshot!(
+    "classifies_changes_between_spec_versions__comparison",
+    comparison
+  );
+}
+
+#[test]
+pub fn comparing_a_spec_to_itself_finds_no_changes() {
+  let events = SpecEvent::from_file("tests/fixtures/ergast-example-spec.json")
+    .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let comparison = compare_specs(&spec_projection, &spec_projection);
+
+  assert!(comparison.changes.is_empty());
+  assert!(!comparison.is_breaking());
+}
+
+#[test]
+pub fn compares_recursive_shapes() {
+  let before_events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "tree"}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "node_1", "baseShapeId": "$object", "name": ""}},
+    {"ShapeAdded": {"shapeId": "list_1", "baseShapeId": "$list", "name": ""}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "list_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "node_1"}}, "consumingParameterId": "$listItem"}}}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "node_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "node_1", "name": "children", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "list_1"}}}},
+
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "node_1", "isRemoved": false}}},
+  ]))
+  .expect("should be able to deserialize events");
+  let mut after_events = before_events.clone();
+  after_events.extend(
+    serde_json::from_value::<Vec<SpecEvent>>(json!([
+      {"FieldAdded": {"fieldId": "field_3", "shapeId": "node_1", "name": "label", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_3", "shapeId": "string_1"}}}},
+    ]))
+    .expect("should be able to deserialize events"),
+  );
+
+  let comparison = compare_specs(
+    &SpecProjection::from(before_events),
+    &SpecProjection::from(after_events),
+  );
+
+  assert_eq!(comparison.changes.len(), 1);
+  assert_eq!(comparison.changes[0].trail, vec![String::from("label")]);
+  assert_eq!(
+    comparison.changes[0].change,
+    ChangeKind::FieldAdded { required: true }
+  );
+  assert!(!comparison.is_breaking());
+}
+
+#[test]
+pub fn compares_shapes_behind_bodies_descending_from_them() {
+  let before_events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "admins"}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "number_1", "baseShapeId": "$number", "name": ""}},
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": "User"}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}}},
+    {"ShapeAdded": {"shapeId": "admin_1", "baseShapeId": "user_1", "name": "Admin"}},
+
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "admin_1", "isRemoved": false}}},
+  ]))
+  .expect("should be able to deserialize events");
+  let mut after_events = before_events.clone();
+  after_events.extend(
+    serde_json::from_value::<Vec<SpecEvent>>(json!([
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "number_1"}}}},
+    ]))
+    .expect("should be able to deserialize events"),
+  );
+
+  let comparison = compare_specs(
+    &SpecProjection::from(before_events),
+    &SpecProjection::from(after_events),
+  );
+
+  assert_eq!(comparison.changes.len(), 1);
+  assert_eq!(comparison.changes[0].trail, vec![String::from("name")]);
+  assert!(comparison.is_breaking());
+}
+
+fn users_spec_events() -> Vec<SpecEvent> {
+  serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "users"}},
+    {"PathParameterAdded": {"pathId": "path_2", "parentPathId": "path_1", "name": "userId"}},
+    {"PathComponentAdded": {"pathId": "path_3", "parentPathId": "root", "name": "posts"}},
+
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": ""}},
+    {"ShapeAdded": {"shapeId": "number_1", "baseShapeId": "$number", "name": ""}},
+    {"ShapeAdded": {"shapeId": "optional_string_1", "baseShapeId": "$optional", "name": ""}},
+    {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "optional_string_1", "providerDescriptor": {"ShapeProvider": {"shapeId": "string_1"}}, "consumingParameterId": "$optionalInner"}}}},
+
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "id", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "number_1"}}}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "string_1"}}}},
+    {"ShapeAdded": {"shapeId": "new_user_1", "baseShapeId": "$object", "name": ""}},
+    {"FieldAdded": {"fieldId": "field_3", "shapeId": "new_user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_3", "shapeId": "string_1"}}}},
+    {"FieldAdded": {"fieldId": "field_4", "shapeId": "new_user_1", "name": "age", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_4", "shapeId": "number_1"}}}},
+
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 404}},
+
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_1", "httpMethod": "POST"}},
+    {"RequestBodySet": {"requestId": "request_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "new_user_1", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_3", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 201}},
+    {"ResponseBodySet": {"responseId": "response_3", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}}},
+
+    {"RequestAdded": {"requestId": "request_3", "pathId": "path_3", "httpMethod": "GET"}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_4", "pathId": "path_3", "httpMethod": "GET", "httpStatusCode": 200}},
+  ]))
+  .expect("should be able to deserialize events")
+}
@@ -0,0 +1,178 @@
+use insta::assert_json_snapshot;
+use optic_engine::{Aggregate, SpecConflict, SpecEvent, SpecProjection};
+use serde_json::json;
+
+#[test]
+pub fn reports_duplicated_paths_and_endpoints() {
+  let events = SpecEvent::from_file(
+    "tests/fixtures/domain-conflict-scenarios/events-20e9bda8afc0279258c5ac2c01b82c437aa1976e1eba23cb92096f7434d9316c.json",
+  )
+  .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  assert_json_snapshot!(
+    "reports_duplicated_paths_and_endpoints__conflicts",
+    spec_projection.conflicts().spec_conflicts()
+  );
+}
+
+#[test]
+pub fn reports_path_parameters_ambiguous_with_literal_paths() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "posts"}},
+    {"PathComponentAdded": {"pathId": "path_2", "parentPathId": "path_1", "name": "latest"}},
+    {"PathParameterAdded": {"pathId": "path_3", "parentPathId": "path_1", "name": "postId"}},
+    {"PathComponentAdded": {"pathId": "path_4", "parentPathId": "path_3", "name": "comments"}},
+  ]))
+  .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let conflicts = spec_projection.conflicts().spec_conflicts();
+  assert_eq!(conflicts.len(), 1);
+  match &conflicts[0] {
+    SpecConflict::AmbiguousPathParameter {
+      absolute_path,
+      path_ids,
+      literal_path_ids,
+    } => {
+      assert_eq!(absolute_path, "/posts/{}");
+      assert_eq!(path_ids, &vec![String::from("path_3")]);
+      assert_eq!(literal_path_ids, &vec![String::from("path_2")]);
+    }
+    conflict => panic!("expected ambiguous path parameter, got {:?}", conflict),
+  }
+}
+
+#[test]
+pub fn requests_and_responses_conflict_only_with_the_same_content_type() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "posts"}},
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_1", "httpMethod": "POST"}},
+    {"RequestAdded": {"requestId": "request_3", "pathId": "path_1", "httpMethod": "POST"}},
+    {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false}}},
+    {"RequestBodySet": {"requestId": "request_2", "bodyDescriptor": {"httpContentType": "text/plain", "shapeId": "shape_2", "isRemoved": false}}},
+    {"RequestBodySet": {"requestId": "request_3", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "shape_3", "isRemoved": false}}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 201}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 201}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "shape_4", "isRemoved": false}}},
+    {"ResponseBodySet": {"responseId": "response_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "shape_5", "isRemoved": false}}},
+    {"ResponseRemoved": {"responseId": "response_2"}},
+  ]))
+  .expect("should be able to deserialize events");
+  let spec_projection = SpecProjection::from(events);
+
+  let conflicts = spec_projection.conflicts().spec_conflicts();
+  assert_eq!(conflicts.len(), 1);
+  match &conflicts[0] {
+    SpecConflict::DuplicateRequest {
+      absolute_path,
+      http_method,
+      http_content_type,
+      request_ids,
+    } => {
+      assert_eq!(absolute_path, "/posts");
+      assert_eq!(http_method, "POST");
+      assert_eq!(http_content_type.as_deref(), Some("application/json"));
+      assert_eq!(
+        request_ids,
+        &vec![String::from("request_1"), String::from("request_3")]
+      );
+    }
+    conflict => panic!("expected duplicate request, got {:?}", conflict),
+  }
+}
+
+#[test]
+pub fn renaming_paths_updates_their_conflicts() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "posts"}},
+    {"PathParameterAdded": {"pathId": "path_2", "parentPathId": "path_1", "name": "postId"}},
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_2", "httpMethod": "GET"}},
+    {"PathComponentAdded": {"pathId": "path_3", "parentPathId": "root", "name": "articles"}},
+    {"PathParameterAdded": {"pathId": "path_4", "parentPathId": "path_3", "name": "articleId"}},
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_4", "httpMethod": "GET"}},
+    {"RequestBodySet": {"requestId": "request_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false}}},
+    {"RequestAdded": {"requestId": "request_3", "pathId": "path_4", "httpMethod": "GET"}},
+  ]))
+  .expect("should be able to deserialize events");
+  let mut spec_projection = SpecProjection::from(events);
+  assert!(spec_projection.conflicts().spec_conflicts().is_empty());
+
+  let rename = |path_id: &str, name: &str| -> SpecEvent {
+    serde_json::from_value(json!({"PathComponentRenamed": {"pathId": path_id, "name": name}}))
+      .expect("should be able to deserialize rename event")
+  };
+
+  // renaming a path into a duplicate reports it, and everything below it
+  spec_projection.apply(rename("path_3", "posts"));
+  let conflicts = serde_json::to_value(spec_projection.conflicts().spec_conflicts()).unwrap();
+  assert_eq!(
+    conflicts,
+    json!([
+      { "type": "duplicatePath", "absolutePath": "/posts", "pathIds": ["path_1", "path_3"] },
+      { "type": "duplicatePath", "absolutePath": "/posts/{}", "pathIds": ["path_2", "path_4"] },
+      {
+        "type": "duplicateRequest",
+        "absolutePath": "/posts/{}",
+        "httpMethod": "GET",
+        "httpContentType": null,
+        "requestIds": ["request_1", "request_3"]
+      },
+    ])
+  );
+
+  // renaming the duplicate away resolves the conflicts
+  spec_projection.apply(rename("path_3", "articles"));
+  assert!(spec_projection.conflicts().spec_conflicts().is_empty());
+}
+
+#[test]
+pub fn renaming_path_parameters_keeps_their_conflicts() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "posts"}},
+    {"PathParameterAdded": {"pathId": "path_2", "parentPathId": "path_1", "name": "postId"}},
+    {"PathParameterAdded": {"pathId": "path_3", "parentPathId": "path_1", "name": "id"}},
+    {"PathComponentAdded": {"pathId": "path_4", "parentPathId": "path_3", "name": "comments"}},
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_4", "httpMethod": "GET"}},
+  ]))
+  .expect("should be able to deserialize events");
+  let mut spec_projection = SpecProjection::from(events);
+
+  let conflicts_before =
+    serde_json::to_value(spec_projection.conflicts().spec_conflicts()).unwrap();
+  assert_eq!(
+    conflicts_before,
+    json!([
+      { "type": "duplicatePath", "absolutePath": "/posts/{}", "pathIds": ["path_2", "path_3"] },
+    ])
+  );
+
+  // parameters match any name, so renaming one into or away from another name changes nothing
+  let renamed: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathParameterRenamed": {"pathId": "path_3", "name": "postId"}},
+    {"PathParameterRenamed": {"pathId": "path_2", "name": "slug"}},
+    {"PathComponentAdded": {"pathId": "path_5", "parentPathId": "path_2", "name": "comments"}},
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_5", "httpMethod": "GET"}},
+  ]))
+  .expect("should be able to deserialize rename events");
+  for event in renamed {
+    spec_projection.apply(event);
+  }
+
+  let conflicts_after = serde_json::to_value(spec_projection.conflicts().spec_conflicts()).unwrap();
+  assert_eq!(
+    conflicts_after,
+    json!([
+      { "type": "duplicatePath", "absolutePath": "/posts/{}", "pathIds": ["path_2", "path_3"] },
+      { "type": "duplicatePath", "absolutePath": "/posts/{}/comments", "pathIds": ["path_4", "path_5"] },
+      {
+        "type": "duplicateRequest",
+        "absolutePath": "/posts/{}/comments",
+        "httpMethod": "GET",
+        "httpContentType": null,
+        "requestIds": ["request_1", "request_2"]
+      },
+    ])
+  );
+}
@@ -0,0 +1,98 @@
+use insta::assert_json_snapshot;
+use optic_engine::{
+  export_openapi, repair_spec_conflicts, Aggregate, SpecCommand, SpecEvent, SpecIdGenerator,
+  SpecProjection,
+};
+use serde_json::Value;
+
+#[test]
+pub fn repairing_duplicated_nodes_merges_shapes_and_removes_orphans() {
+  let events = SpecEvent::from_file(
+    std::env::current_dir()
+      .unwrap()
+      .join("tests/fixtures/events-deleted-duplicated-nodes-spec.json")
+      .to_str()
+      .unwrap(),
+  )
+  .expect("duplicated nodes spec should deserialize");
+  let spec_projection = SpecProjection::from(events);
+
+  let repair = repair_spec_conflicts(&spec_projection, &mut SequentialIdGenerator::default())
+    .expect("conflicts should be repairable");
+  assert_json_snapshot!(
+    "repairing_duplicated_nodes_merges_shapes_and_removes_orphans__repair",
+    repair
+  );
+
+  let repaired_projection = assert_valid_commands(spec_projection.clone(), repair.commands);
+  // merged shapes are exported under the id of the shape they were merged into
+  assert_eq!(
+    exported_paths(&repaired_projection),
+    exported_paths(&spec_projection),
+    "repair should not change what the spec describes"
+  );
+
+  let repeated_repair =
+    repair_spec_conflicts(&repaired_projection, &mut SequentialIdGenerator::default())
+      .expect("conflicts should be repairable");
+  assert!(repeated_repair.commands.is_empty());
+}
+
+// The exported paths, with the schemas they refer to inlined
+fn exported_paths(spec_projection: &SpecProjection) -> Value {
+  let document = serde_json::to_value(export_openapi(spec_projection)).unwrap();
+  let schemas = document["components"]["schemas"].clone();
+  inline_references(document["paths"].clone(), &schemas)
+}
+
+fn inline_references(value: Value, schemas: &Value) -> Value {
+  match value {
+    Value::Object(object) => match object.get("$ref").and_then(Value::as_str) {
+      Some(reference) => {
+        let schema_id = reference.trim_start_matches("#/components/schemas/");
+        inline_references(schemas[schema_id].clone(), schemas)
+      }
+      None => Value::Object(
+        object
+          .into_iter()
+          .map(|(key, value)| (key, inline_references(value, schemas)))
+          .collect(),
+      ),
+    },
+    Value::Array(items) => Value::Array(
+      items
+        .into_iter()
+        .map(|item| inline_references(item, schemas))
+        .collect(),
+    ),
+    value => value,
+  }
+}
+
+#[derive(Debug, Default)]
+struct SequentialIdGenerator {
+  next_id: u32,
+}
+impl SpecIdGenerator for SequentialIdGenerator {
+  fn generate_id(&mut self, prefix: &str) -> String {
+    self.next_id += 1;
+    format!("{}{}", prefix, self.next_id)
+  }
+}
+
+fn assert_valid_commands(
+  mut spec_projection: SpecProjection,
+  commands: impl IntoIterator<Item = SpecCommand>,
+) -> SpecProjection {
+  for command in commands {
+    let events = spec_projection
+      .execute(command)
+      .expect("generated commands must be valid");
+
+    for event in events {
+      spec_projection.apply(event)
+    }
+  }
+
+  spec_projection
+}
@@ -0,0 +1,184 @@
+use insta::assert_json_snapshot;
+use optic_engine::{
+  export_openapi, revert_batch, revert_batch_commands, CommandContext, RevertError, RfcEvent,
+  SpecEvent, SpecProjection,
+};
+use serde_json::json;
+
+#[test]
+pub fn reverting_batch_commit_restores_spec_before_it() {
+  let events = users_spec_events();
+
+  let commands =
+    revert_batch_commands(&events, &String::from("batch_2")).expect("batch_2 should be revertable");
+  assert_json_snapshot!(
+    "reverting_batch_commit_restores_spec_before_it__commands",
+    commands
+  );
+
+  let mut reverted_events = events.clone();
+  reverted_events.extend(
+    revert_batch(events.clone(), &String::from("batch_2"), revert_context())
+      .expect("batch_2 should be revertable"),
+  );
+
+  // the batch commits themselves don't matter to the exported spec, leaving them out avoids batch_3
+  // missing its parent
+  let without_batch_2 = events
+    .iter()
+    .filter(|event| !is_batch_commit_event(event) && !is_event_of_batch(event, "batch_2"))
+    .cloned()
+    .collect::<Vec<_>>();
+  assert_eq!(
+    serde_json::to_value(export_openapi(&SpecProjection::from(reverted_events))).unwrap(),
+    serde_json::to_value(export_openapi(&SpecProjection::from(without_batch_2))).unwrap(),
+  );
+}
+
+#[test]
+pub fn reverting_batch_commit_depended_on_by_later_commits_fails() {
+  let mut events = users_spec_events();
+  events.extend(
+    serde_json::from_value::<Vec<SpecEvent>>(json!([
+      {"BatchCommitStarted": {"batchId": "batch_4", "commitMessage": "Rename email", "parentId": "batch_3", "eventContext": context("batch_4")}},
+      {"FieldRenamed": {"fieldId": "field_3", "name": "emailAddress"}},
+      {"BatchCommitEnded": {"batchId": "batch_4"}},
+    ]))
+    .unwrap(),
+  );
+
+  let result = revert_batch_commands(&events, &String::from("batch_2"));
+  assert!(matches!(
+    result,
+    Err(RevertError::DependedOn { ref entity_id, ref batch_id })
+      if entity_id == "field_3" && batch_id == "batch_4"
+  ));
+
+  // batches nothing depends on can still be reverted
+  assert!(revert_batch_commands(&events, &String::from("batch_3")).is_ok());
+
+  assert!(matches!(
+    revert_batch_commands(&events, &String::from("not-a-batch")),
+    Err(RevertError::UnknownBatchCommit(_))
+  ));
+}
+
+#[test]
+pub fn reverting_batch_commit_unsets_bodies_it_set() {
+  let mut events = users_spec_events();
+  events.extend(
+    serde_json::from_value::<Vec<SpecEvent>>(json!([
+      {"BatchCommitStarted": {"batchId": "batch_4", "commitMessage": "Document bodies", "parentId": "batch_3", "eventContext": context("batch_4")}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "error_1", "isRemoved": false}, "eventContext": context("batch_4")}},
+      {"ResponseBodySet": {"responseId": "response_3", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}, "eventContext": context("batch_4")}},
+      {"BatchCommitEnded": {"batchId": "batch_4", "eventContext": context("batch_4")}},
+    ]))
+    .unwrap(),
+  );
+  let spec_projection = SpecProjection::from(events.clone());
+  assert_eq!(live_bodies(&spec_projection, "request_1").len(), 1);
+  assert_eq!(live_bodies(&spec_projection, "response_3").len(), 1);
+
+  let mut reverted_events = events.clone();
+  reverted_events.extend(
+    revert_batch(events, &String::from("batch_4"), revert_context())
+      .expect("batch_4 should be revertable"),
+  );
+  let reverted_projection = SpecProjection::from(reverted_events);
+
+  let operations = &export_openapi(&reverted_projection).paths["/users"].operations;
+  assert!(operations["get"].request_body.is_none());
+  assert!(operations["post"].responses["201"].content.is_empty());
+
+  assert!(live_bodies(&reverted_projection, "request_1").is_empty());
+  assert!(live_bodies(&reverted_projection, "response_3").is_empty());
+}
+
+// The bodies of a request or response in the spectacle projection that haven't been removed
+fn live_bodies(spec_projection: &SpecProjection, parent_id: &str) -> Vec<serde_json::Value> {
+  let graph = serde_json::to_value(
+    spec_projection
+      .spectacle_endpoints()
+      .to_serializable_graph(),
+  )
+  .unwrap();
+  let parent_index = graph["nodeIndexToId"]
+    .as_object()
+    .unwrap()
+    .iter()
+    .find(|(_, id)| *id == parent_id)
+    .map(|(index, _)| index.parse::<u64>().unwrap())
+    .expect("parent should be in the spectacle projection");
+
+  graph["edges"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .filter(|edge| edge[1] == parent_index)
+    .map(|edge| &graph["nodes"][edge[0].as_u64().unwrap() as usize])
+    .filter(|node| node["type"] == "Body" && node["data"]["isRemoved"] == false)
+    .cloned()
+    .collect()
+}
+
+fn is_batch_commit_event(event: &SpecEvent) -> bool {
+  matches!(
+    event,
+    SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(_))
+      | SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(_))
+  )
+}
+
+fn is_event_of_batch(event: &SpecEvent, batch_id: &str) -> bool {
+  let event_json = serde_json::to_value(event).unwrap();
+  let (_, event_fields) = event_json.as_object().unwrap().iter().next().unwrap();
+  event_fields["eventContext"]["clientCommandBatchId"] == batch_id
+}
+
+fn revert_context() -> CommandContext {
+  CommandContext::new(
+    String::from("revert_batch_2"),
+    String::from("anonymous"),
+    String::from("session_1"),
+    chrono::Utc::now(),
+  )
+}
+
+fn context(batch_id: &str) -> serde_json::Value {
+  json!({"clientId": "anonymous", "clientSessionId": "session_1", "clientCommandBatchId": batch_id, "createdAt": "2021-03-01T10:00:00Z"})
+}
+
+fn users_spec_events() -> Vec<SpecEvent> {
+  serde_json::from_value(json!([
+    {"BatchCommitStarted": {"batchId": "batch_1", "commitMessage": "Document listing users", "parentId": "root", "eventContext": context("batch_1")}},
+    {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "users", "eventContext": context("batch_1")}},
+    {"ShapeAdded": {"shapeId": "string_1", "baseShapeId": "$string", "name": "", "eventContext": context("batch_1")}},
+    {"ShapeAdded": {"shapeId": "number_1", "baseShapeId": "$number", "name": "", "eventContext": context("batch_1")}},
+    {"ShapeAdded": {"shapeId": "user_1", "baseShapeId": "$object", "name": "", "eventContext": context("batch_1")}},
+    {"FieldAdded": {"fieldId": "field_1", "shapeId": "user_1", "name": "id", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "number_1"}}, "eventContext": context("batch_1")}},
+    {"FieldAdded": {"fieldId": "field_2", "shapeId": "user_1", "name": "name", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_2", "shapeId": "string_1"}}, "eventContext": context("batch_1")}},
+    {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET", "eventContext": context("batch_1")}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200, "eventContext": context("batch_1")}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}, "eventContext": context("batch_1")}},
+    {"BatchCommitEnded": {"batchId": "batch_1", "eventContext": context("batch_1")}},
+
+    {"BatchCommitStarted": {"batchId": "batch_2", "commitMessage": "Rework users", "parentId": "batch_1", "eventContext": context("batch_2")}},
+    {"FieldRenamed": {"fieldId": "field_2", "name": "fullName", "eventContext": context("batch_2")}},
+    {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_1", "shapeId": "string_1"}}, "eventContext": context("batch_2")}},
+    {"ShapeAdded": {"shapeId": "string_2", "baseShapeId": "$string", "name": "", "eventContext": context("batch_2")}},
+    {"FieldAdded": {"fieldId": "field_3", "shapeId": "user_1", "name": "email", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_3", "shapeId": "string_2"}}, "eventContext": context("batch_2")}},
+    {"ShapeAdded": {"shapeId": "error_1", "baseShapeId": "$object", "name": "", "eventContext": context("batch_2")}},
+    {"FieldAdded": {"fieldId": "field_4", "shapeId": "error_1", "name": "message", "shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "field_4", "shapeId": "string_1"}}, "eventContext": context("batch_2")}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_2", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 404, "eventContext": context("batch_2")}},
+    {"ResponseBodySet": {"responseId": "response_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "error_1", "isRemoved": false}, "eventContext": context("batch_2")}},
+    {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": {"httpContentType": "application/vnd.users+json", "shapeId": "user_1", "isRemoved": false}, "eventContext": context("batch_2")}},
+    {"BatchCommitEnded": {"batchId": "batch_2", "eventContext": context("batch_2")}},
+
+    {"BatchCommitStarted": {"batchId": "batch_3", "commitMessage": "Document creating users", "parentId": "batch_2", "eventContext": context("batch_3")}},
+    {"RequestAdded": {"requestId": "request_2", "pathId": "path_1", "httpMethod": "POST", "eventContext": context("batch_3")}},
+    {"RequestBodySet": {"requestId": "request_2", "bodyDescriptor": {"httpContentType": "application/json", "shapeId": "user_1", "isRemoved": false}, "eventContext": context("batch_3")}},
+    {"ResponseAddedByPathAndMethod": {"responseId": "response_3", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 201, "eventContext": context("batch_3")}},
+    {"BatchCommitEnded": {"batchId": "batch_3", "eventContext": context("batch_3")}},
+  ]))
+  .expect("should be able to deserialize events")
+}
@@ -6,8 +6,8 @@ use tokio::fs::read_to_string;
 
 use optic_engine::{
   analyze_undocumented_bodies, Aggregate, AnalyzeUndocumentedBodiesConfig, EndpointCommand,
-  HttpInteraction, LearnedUndocumentedBodiesProjection, SpecCommand, SpecEvent, SpecIdGenerator,
-  SpecProjection,
+  HttpInteraction, LearnedUndocumentedBodiesProjection, ShapeStructureIndex, SpecCommand,
+  SpecEvent, SpecIdGenerator, SpecProjection,
 };
 
 #[tokio::main]
@@ -31,7 +31,7 @@ async fn get_request_with_query_params() {
   let mut id_generator = SequentialIdGenerator { next_id: 1093 }; // <3 primes
 
   let endpoint_bodies = learned_undocumented_bodies
-    .into_endpoint_bodies(&mut id_generator)
+    .into_endpoint_bodies(&mut id_generator, &ShapeStructureIndex::default())
     .next()
     .expect("an endpoint should have been learned for");
 
@@ -80,7 +80,7 @@ async fn get_request_with_object_query_params() {
   let mut id_generator = SequentialIdGenerator { next_id: 1093 }; // <3 primes
 
   let endpoint_bodies = learned_undocumented_bodies
-    .into_endpoint_bodies(&mut id_generator)
+    .into_endpoint_bodies(&mut id_generator, &ShapeStructureIndex::default())
     .next()
     .expect("an endpoint should have been learned for");
 
@@ -112,7 +112,7 @@ async fn requests_with_and_without_query_params() {
   let mut id_generator = SequentialIdGenerator { next_id: 1093 }; // <3 primes
 
   let endpoint_bodies = learned_undocumented_bodies
-    .into_endpoint_bodies(&mut id_generator)
+    .into_endpoint_bodies(&mut id_generator, &ShapeStructureIndex::default())
     .next()
     .expect("an endpoint should have been learned for");
 
@@ -127,6 +127,24 @@ async fn requests_with_and_without_query_params() {
   // dbg!(Dot::with_config(&_updated_spec.shape().graph, &[]));
 }
 
+#[tokio::main]
+#[test]
+async fn ignored_interactions_are_not_analyzed() {
+  let mut capture = DebugCapture::from_name("get-request-with-query-params.json").await;
+
+  let spec = SpecProjection::from(capture.events);
+  let interaction = capture.session.samples.remove(0);
+
+  let learner_config: AnalyzeUndocumentedBodiesConfig = serde_json::from_value(
+    serde_json::json!({ "ignore": [{ "path": "/**", "methods": [interaction.request.method] }] }),
+  )
+  .expect("example config should deserialize");
+
+  let results = analyze_undocumented_bodies(&spec, interaction, &learner_config);
+
+  assert_eq!(results.count(), 0);
+}
+
 #[derive(Deserialize, Debug)]
 struct DebugCapture {
   events: Vec<SpecEvent>,
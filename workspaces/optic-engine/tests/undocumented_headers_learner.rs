@@ -0,0 +1,191 @@
+use serde_json::json;
+
+use optic_engine::{
+  analyze_undocumented_headers, diff_interaction, Aggregate, AnalyzeUndocumentedBodiesConfig,
+  DiffInteractionConfig, EndpointCommand, HttpInteraction, InteractionFilters,
+  LearnedUndocumentedHeadersProjection, SpecCommand, SpecEvent, SpecIdGenerator, SpecProjection,
+};
+
+#[test]
+fn learns_request_and_response_headers_until_interactions_are_compliant() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"xyz"}},
+    {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+    {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+    {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","name":""}},
+    {"RequestParameterAdded":{"parameterId":"header_1","requestId":"request_1","parameterLocation":"header","name":"X-Api-Key"}},
+    {"RequestParameterShapeSet":{"parameterId":"header_1","parameterDescriptor":{"shapeId":"string_shape_1","isRemoved":false}}},
+  ]))
+  .expect("should be able to deserialize header events as spec events");
+  let spec = SpecProjection::from(events);
+
+  let interactions = vec![
+    interaction_with_headers(
+      json!({ "Host": "localhost", "X-Api-Key": "secret", "X-Request-Id": "abc" }),
+      json!({ "Content-Length": "0", "X-RateLimit-Remaining": "99", "X-Cache": "true" }),
+    ),
+    interaction_with_headers(
+      json!({ "Host": "localhost", "X-Api-Key": "secret" }),
+      json!({ "X-RateLimit-Remaining": "98", "Vary": ["Origin", "Accept"] }),
+    ),
+  ];
+
+  let mut learned_undocumented_headers = LearnedUndocumentedHeadersProjection::default();
+  for interaction in interactions.iter().cloned() {
+    for result in analyze_undocumented_headers(&spec, interaction, &InteractionFilters::default()) {
+      learned_undocumented_headers.apply(result)
+    }
+  }
+
+  let mut id_generator = SequentialIdGenerator { next_id: 1093 }; // <3 primes
+
+  let commands = learned_undocumented_headers
+    .into_endpoint_headers(&mut id_generator)
+    .flat_map(|endpoint_headers| endpoint_headers.into_commands())
+    .collect::<Vec<_>>();
+
+  let learned_header_names = commands
+    .iter()
+    .filter_map(|command| match command {
+      SpecCommand::EndpointCommand(EndpointCommand::AddHeaderParameter(command)) => {
+        Some(command.name.as_str())
+      }
+      SpecCommand::EndpointCommand(EndpointCommand::AddResponseHeaderParameter(command)) => {
+        Some(command.name.as_str())
+      }
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(learned_header_names.len(), 4);
+  for name in &["X-Request-Id", "X-RateLimit-Remaining", "X-Cache", "Vary"] {
+    assert!(learned_header_names.contains(name));
+  }
+
+  let updated_spec = assert_valid_commands(spec, commands);
+
+  for interaction in interactions {
+    let results = diff_interaction(
+      &updated_spec,
+      interaction,
+      &DiffInteractionConfig::default(),
+    );
+    assert_eq!(results.len(), 0);
+  }
+}
+
+#[test]
+fn ignored_interactions_are_not_analyzed_for_headers() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"xyz"}},
+    {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+  ]))
+  .expect("should be able to deserialize spec events");
+  let spec = SpecProjection::from(events);
+  let interaction = interaction_with_headers(json!({ "X-Request-Id": "abc" }), json!({}));
+
+  let learner_config: AnalyzeUndocumentedBodiesConfig =
+    serde_json::from_value(json!({ "ignore": [{ "path": "/xyz" }] }))
+      .expect("example config should deserialize");
+
+  let results = analyze_undocumented_headers(&spec, interaction.clone(), &learner_config.filters);
+  assert_eq!(results.count(), 0);
+
+  let results = analyze_undocumented_headers(&spec, interaction, &InteractionFilters::default());
+  assert_eq!(results.count(), 1);
+}
+
+#[test]
+fn header_values_are_not_kept_or_learned_as_enums() {
+  let events: Vec<SpecEvent> = serde_json::from_value(json!([
+    {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"xyz"}},
+    {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+    {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+  ]))
+  .expect("should be able to deserialize spec events");
+  let spec = SpecProjection::from(events);
+
+  let mut learned_undocumented_headers = LearnedUndocumentedHeadersProjection::default();
+  for n in 0..12 {
+    let interaction = interaction_with_headers(
+      json!({ "Authorization": "Bearer secret-token", "X-Api-Key": "secret-key" }),
+      json!({ "X-Cache": if n % 2 == 0 { "HIT" } else { "MISS" } }),
+    );
+    for result in analyze_undocumented_headers(&spec, interaction, &InteractionFilters::default()) {
+      assert!(result
+        .trail_observations
+        .values()
+        .all(|trail_values| trail_values.string_values.is_empty()));
+      learned_undocumented_headers.apply(result)
+    }
+  }
+
+  let mut id_generator = SequentialIdGenerator::default();
+  let commands = learned_undocumented_headers
+    .into_endpoint_headers(&mut id_generator)
+    .flat_map(|endpoint_headers| endpoint_headers.into_commands())
+    .collect::<Vec<_>>();
+
+  let commands_json = serde_json::to_string(&commands).unwrap();
+  assert!(!commands_json.contains("EnumConstraint"));
+  assert!(!commands_json.contains("secret"));
+  assert!(!commands_json.contains("HIT"));
+  assert_valid_commands(spec, commands);
+}
+
+fn interaction_with_headers(
+  request_headers: serde_json::Value,
+  response_headers: serde_json::Value,
+) -> HttpInteraction {
+  serde_json::from_value::<HttpInteraction>(json!({
+    "uuid": "5",
+    "request": {
+      "host": "localhost",
+      "method": "GET",
+      "path": "/xyz",
+      "query": { "asJsonString": null, "asText": null, "asShapeHashBytes": null },
+      "headers": { "asJsonString": request_headers.to_string(), "asText": null, "asShapeHashBytes": null },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+      }
+    },
+    "response": {
+      "statusCode": 200,
+      "headers": { "asJsonString": response_headers.to_string(), "asText": null, "asShapeHashBytes": null },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "asShapeHashBytes": null }
+      }
+    },
+    "tags": []
+  }))
+  .expect("example http interaction should deserialize")
+}
+
+#[derive(Debug, Default)]
+struct SequentialIdGenerator {
+  next_id: u32,
+}
+impl SpecIdGenerator for SequentialIdGenerator {
+  fn generate_id(&mut self, prefix: &str) -> String {
+    self.next_id += 1;
+    format!("{}{}", prefix, self.next_id)
+  }
+}
+
+fn assert_valid_commands(
+  mut spec_projection: SpecProjection,
+  commands: impl IntoIterator<Item = SpecCommand>,
+) -> SpecProjection {
+  for command in commands {
+    let events = spec_projection
+      .execute(command)
+      .expect("generated commands must be valid");
+
+    for event in events {
+      spec_projection.apply(event)
+    }
+  }
+
+  spec_projection
+}
@@ -32,6 +32,13 @@ export interface IOpticEngine {
     id_generator_strategy: string
   ): string;
 
+  affordances_to_commands_reusing(
+    spec: any,
+    json_affordances_json: string,
+    json_trail_json: string,
+    id_generator_strategy: string
+  ): string;
+
   get_shape_viewer_projection(spec: any): string;
 
   get_contributions_projection(spec: any): string;
@@ -184,4 +184,6 @@ export type CurrentSpecContext = {
   domainIds: DomainIdGenerator;
   idGeneratorStrategy: 'sequential' | 'random';
   opticEngine: IOpticEngine;
+  // the engine's projection of the spec, of which learned shapes reuse the named shapes
+  spec: any;
 };
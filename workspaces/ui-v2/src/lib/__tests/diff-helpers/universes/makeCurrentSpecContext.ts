@@ -41,5 +41,6 @@ export async function makeCurrentSpecContext(
     domainIds: newDeterministicIdGenerator(),
     idGeneratorStrategy: 'sequential',
     opticEngine,
+    spec: opticEngine.spec_from_events(JSON.stringify(events)),
   };
 }
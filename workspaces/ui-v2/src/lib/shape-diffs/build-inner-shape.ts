@@ -55,7 +55,8 @@ export function builderInnerShapeFromChoices(
       );
 
       const [commands, newShapeId] = JSON.parse(
-        currentSpecContext.opticEngine.affordances_to_commands(
+        currentSpecContext.opticEngine.affordances_to_commands_reusing(
+          currentSpecContext.spec,
           JSON.stringify(filterToTarget),
           JSON.stringify(actual.jsonTrail),
           currentSpecContext.idGeneratorStrategy
@@ -32,6 +32,8 @@ import { useCapturesService } from '<src>/hooks/useCapturesHook';
 import { IOpticDiffService } from '../../../../spectacle/build';
 import { useAnalytics } from '<src>/contexts/analytics';
 import { useSpectacleContext } from '<src>/contexts/spectacle-provider';
+import { useSpecRepository } from '<src>/contexts/SpecRepositoryContext';
+import { useOpticEngine } from '<src>/hooks/useOpticEngine';
 
 export function DiffReviewPages(props: any) {
   const { match } = props;
@@ -42,6 +44,9 @@ export function DiffReviewPages(props: any) {
   const [diffService, setDiffService] = useState<IOpticDiffService | null>(
     null
   );
+  const specRepository = useSpecRepository();
+  const opticEngine = useOpticEngine();
+  const [spec, setSpec] = useState<any>(null);
 
   const dispatch = useAppDispatch();
 
@@ -72,6 +77,12 @@ export function DiffReviewPages(props: any) {
       } catch (e) {}
     })();
   }, [dispatch, capturesService, boundaryId, analytics]);
+  useEffect(() => {
+    (async () => {
+      const events = await specRepository.listEvents();
+      setSpec(opticEngine.spec_from_events(JSON.stringify(events)));
+    })();
+  }, [specRepository, opticEngine, boundaryId]);
   const diffState = useAppSelector((state) => state.diff.state);
   const filteredEndpoints = useMemo(
     () => selectors.filterRemovedItems(endpointsState.data?.endpoints || []),
@@ -96,7 +107,8 @@ export function DiffReviewPages(props: any) {
     diffState.loading ||
     endpointsState.loading ||
     pathsState.loading ||
-    !diffService
+    !diffService ||
+    !spec
   ) {
     return (
       <PageLayout>
@@ -110,6 +122,7 @@ export function DiffReviewPages(props: any) {
 
   return (
     <SharedDiffStore
+      spec={spec}
       diffService={diffService}
       diffs={diffState.data.diffs}
       diffTrails={diffState.data.trails}
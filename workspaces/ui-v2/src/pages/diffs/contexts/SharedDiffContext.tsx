@@ -75,6 +75,7 @@ type ISharedDiffContext = {
 };
 
 type SharedDiffStoreProps = {
+  spec: any;
   endpoints: IEndpoint[];
   captureId: string;
   allPaths: IPath[];
@@ -96,8 +97,9 @@ export const SharedDiffStore: FC<SharedDiffStoreProps> = (props) => {
       domainIds: newRandomIdGenerator(),
       idGeneratorStrategy: 'random',
       opticEngine,
+      spec: props.spec,
     }),
-    [opticEngine, props.allPaths, props.endpoints]
+    [opticEngine, props.allPaths, props.endpoints, props.spec]
   );
 
   const [state, send]: any = useMachine(() =>